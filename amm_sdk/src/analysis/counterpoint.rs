@@ -0,0 +1,549 @@
+use super::events::{collect_timeslice_events, merge_ties, NoteEvent};
+use crate::context::{Key, TimeSignature, TimeSignatureType};
+use crate::note::{Duration, DurationType, Rational};
+use crate::structure::Staff;
+use amm_internal::amm_prelude::*;
+
+/// Represents a species of strict two-voice counterpoint.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Species {
+  /// One note against each note of the cantus firmus.
+  #[default]
+  First,
+  /// Two notes against each note of the cantus firmus.
+  Second,
+  /// Four notes against each note of the cantus firmus.
+  Third,
+  /// Syncopated notes tied across each note of the cantus firmus, forming suspensions.
+  Fourth,
+  /// A free mixture of the first four species (florid counterpoint).
+  Fifth,
+}
+
+impl Species {
+  /// Returns the number of counterpoint notes expected against each cantus firmus note,
+  /// or `None` if the rhythm of the species is not fixed.
+  const fn notes_per_cantus_note(self) -> Option<usize> {
+    match self {
+      Self::First => Some(1),
+      Self::Second => Some(2),
+      Self::Third => Some(4),
+      Self::Fourth | Self::Fifth => None,
+    }
+  }
+}
+
+/// Represents a set of rules used to validate species counterpoint.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum CounterpointRules {
+  /// The strict rules from Fux's "Gradus ad Parnassum" (1725), which forbid major sixth
+  /// and descending minor sixth leaps, forbid neighbor tones in second species, and
+  /// forbid any perfect interval approached by similar motion.
+  #[default]
+  Fux,
+  /// The relaxed rules taught in most modern counterpoint textbooks, which allow all
+  /// sixth leaps and neighbor tones, and only forbid perfect intervals approached by
+  /// similar motion when the upper voice leaps.
+  Modern,
+}
+
+/// Represents a type of counterpoint error.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum CounterpointIssue {
+  /// The number of counterpoint notes against a cantus firmus note does not match the species.
+  RhythmMismatch,
+  /// The counterpoint does not begin on a perfect consonance (or begins a fifth below the cantus firmus).
+  InvalidStart,
+  /// A dissonance is attacked at the same time as a cantus firmus note.
+  DissonantDownbeat,
+  /// A dissonance on a weak beat is not a permitted passing tone, neighbor tone, or cambiata.
+  InvalidDissonance,
+  /// A dissonant suspension is not prepared by a consonance or does not resolve down by step to a consonance.
+  InvalidSuspension,
+  /// Consecutive downbeats form the same perfect interval.
+  ParallelPerfects,
+  /// A perfect interval is approached by similar motion.
+  DirectPerfects,
+  /// The voices form a unison anywhere other than the first or last note.
+  Unison,
+  /// The counterpoint moves by a forbidden melodic interval.
+  ForbiddenLeap,
+  /// The counterpoint repeats a note in a species which does not allow repeated notes.
+  RepeatedNote,
+  /// The counterpoint does not end with a stepwise approach from a sixth or third into an octave or unison.
+  InvalidCadence,
+}
+
+/// Represents a single error found while validating a line of counterpoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CounterpointDiagnostic {
+  /// The type of error.
+  pub issue: CounterpointIssue,
+  /// The beat at which the error occurs, where a quarter note represents one beat.
+  pub start_beat: Rational,
+  /// The identifiers of the counterpoint and cantus firmus notes involved in the error.
+  pub note_ids: Vec<usize>,
+}
+
+/// Represents a validator which checks a line of counterpoint against a cantus firmus
+/// according to the rules of a single species.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CounterpointValidator {
+  species: Species,
+  rules: CounterpointRules,
+}
+
+impl CounterpointValidator {
+  /// Creates a new validator for the given species and rule set.
+  #[must_use]
+  pub const fn new(species: Species, rules: CounterpointRules) -> Self {
+    Self { species, rules }
+  }
+
+  /// Checks the given counterpoint against the given cantus firmus, where both staves are
+  /// written in the specified key, and returns all errors found ordered by the beat at
+  /// which they occur.
+  ///
+  /// Tied notes are treated as a single sustained note, so a suspension may be written
+  /// either as one note spanning the downbeat or as two tied notes.
+  #[must_use]
+  pub fn validate(&self, cantus_firmus: &Staff, counterpoint: &Staff, key: Key) -> Vec<CounterpointDiagnostic> {
+    let beat_base = Duration::new(DurationType::Quarter, 0);
+    let collect = |staff: &Staff| -> Vec<NoteEvent> {
+      merge_ties(
+        collect_timeslice_events(
+          staff.iter_timeslices(),
+          key,
+          TimeSignature::new(TimeSignatureType::CommonTime),
+          beat_base,
+        )
+        .into_iter()
+        .filter(|event| !event.is_grace && event.beats.is_positive())
+        .collect(),
+      )
+    };
+    let (cantus, line) = (collect(cantus_firmus), collect(counterpoint));
+    let mut diagnostics = Vec::new();
+    if !cantus.is_empty() && !line.is_empty() {
+      self.check_rhythm(&cantus, &line, &mut diagnostics);
+      check_boundaries(&cantus, &line, &mut diagnostics);
+      self.check_downbeats(&cantus, &line, &mut diagnostics);
+      self.check_notes(&cantus, &line, &mut diagnostics);
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.start_beat, diagnostic.issue));
+    diagnostics
+  }
+
+  fn check_rhythm(self, cantus: &[NoteEvent], line: &[NoteEvent], diagnostics: &mut Vec<CounterpointDiagnostic>) {
+    let Some(notes_per_cantus_note) = self.species.notes_per_cantus_note() else {
+      return;
+    };
+    for (index, cantus_note) in cantus.iter().enumerate() {
+      let notes: Vec<&NoteEvent> = line
+        .iter()
+        .filter(|note| note.start_beat >= cantus_note.start_beat && note.start_beat < cantus_note.end_beat())
+        .collect();
+      let is_valid = match index {
+        _ if index + 1 == cantus.len() => notes.len() == 1,
+        0 => (1..=notes_per_cantus_note).contains(&notes.len()),
+        _ => notes.len() == notes_per_cantus_note,
+      };
+      if !is_valid {
+        diagnostics.push(CounterpointDiagnostic {
+          issue: CounterpointIssue::RhythmMismatch,
+          start_beat: cantus_note.start_beat,
+          note_ids: core::iter::once(cantus_note.note_id)
+            .chain(notes.iter().map(|note| note.note_id))
+            .collect(),
+        });
+      }
+    }
+  }
+
+  fn check_downbeats(self, cantus: &[NoteEvent], line: &[NoteEvent], diagnostics: &mut Vec<CounterpointDiagnostic>) {
+    let downbeats: Vec<(&NoteEvent, Option<&NoteEvent>)> = cantus
+      .iter()
+      .map(|cantus_note| (cantus_note, sounding_at(line, cantus_note.start_beat)))
+      .collect();
+    for index in 1..downbeats.len() {
+      let (cantus_note, Some(note)) = downbeats[index] else {
+        continue;
+      };
+      let interval = note.midi_number.abs_diff(cantus_note.midi_number);
+      if interval == 0 && index + 1 < downbeats.len() {
+        diagnostics.push(diagnostic(
+          CounterpointIssue::Unison,
+          cantus_note.start_beat,
+          note,
+          cantus_note,
+        ));
+      }
+      if note.start_beat < cantus_note.start_beat && !is_consonant(interval) {
+        let is_prepared = sounding_at(cantus, note.start_beat)
+          .is_some_and(|preparation| is_consonant(note.midi_number.abs_diff(preparation.midi_number)));
+        let is_resolved = next_note(line, note).is_some_and(|resolution| {
+          (1..=2).contains(&(i16::from(note.midi_number) - i16::from(resolution.midi_number)))
+            && sounding_at(cantus, resolution.start_beat)
+              .is_some_and(|cantus_note| is_consonant(resolution.midi_number.abs_diff(cantus_note.midi_number)))
+        });
+        if !matches!(self.species, Species::Fourth | Species::Fifth) || !is_prepared || !is_resolved {
+          diagnostics.push(diagnostic(
+            CounterpointIssue::InvalidSuspension,
+            cantus_note.start_beat,
+            note,
+            cantus_note,
+          ));
+        }
+      }
+      let (previous_cantus_note, Some(previous_note)) = downbeats[index - 1] else {
+        continue;
+      };
+      let motion = i16::from(note.midi_number) - i16::from(previous_note.midi_number);
+      let cantus_motion = i16::from(cantus_note.midi_number) - i16::from(previous_cantus_note.midi_number);
+      if motion == 0 || motion.signum() != cantus_motion.signum() || !is_perfect(interval) {
+        continue;
+      }
+      let previous_interval = previous_note.midi_number.abs_diff(previous_cantus_note.midi_number);
+      let upper_motion = if note.midi_number >= cantus_note.midi_number {
+        motion
+      } else {
+        cantus_motion
+      };
+      if previous_interval % 12 == interval % 12 {
+        diagnostics.push(diagnostic(
+          CounterpointIssue::ParallelPerfects,
+          cantus_note.start_beat,
+          note,
+          cantus_note,
+        ));
+      } else if self.rules == CounterpointRules::Fux || upper_motion.abs() > 2 {
+        diagnostics.push(diagnostic(
+          CounterpointIssue::DirectPerfects,
+          cantus_note.start_beat,
+          note,
+          cantus_note,
+        ));
+      }
+    }
+  }
+
+  fn check_notes(self, cantus: &[NoteEvent], line: &[NoteEvent], diagnostics: &mut Vec<CounterpointDiagnostic>) {
+    for (index, note) in line.iter().enumerate() {
+      let Some(cantus_note) = sounding_at(cantus, note.start_beat) else {
+        continue;
+      };
+      let motion_from = |from: Option<&NoteEvent>, to: Option<&NoteEvent>| {
+        from
+          .zip(to)
+          .map(|(from, to)| i16::from(to.midi_number) - i16::from(from.midi_number))
+      };
+      let (previous, next) = (
+        index.checked_sub(1).map(|previous| &line[previous]),
+        line.get(index + 1),
+      );
+      let (approach, departure) = (motion_from(previous, Some(note)), motion_from(Some(note), next));
+      match approach {
+        Some(0) if matches!(self.species, Species::Second | Species::Third) => diagnostics.push(diagnostic(
+          CounterpointIssue::RepeatedNote,
+          note.start_beat,
+          note,
+          cantus_note,
+        )),
+        Some(motion) if !self.is_allowed_leap(motion) => diagnostics.push(diagnostic(
+          CounterpointIssue::ForbiddenLeap,
+          note.start_beat,
+          note,
+          cantus_note,
+        )),
+        _ => (),
+      }
+      if is_consonant(note.midi_number.abs_diff(cantus_note.midi_number)) {
+        continue;
+      }
+      let is_step = |motion: Option<i16>| motion.is_some_and(|motion| (1..=2).contains(&motion.abs()));
+      let is_passing =
+        is_step(approach) && is_step(departure) && approach.map(i16::signum) == departure.map(i16::signum);
+      let is_neighbor = is_step(approach) && is_step(departure) && !is_passing;
+      let is_cambiata = matches!(self.species, Species::Third | Species::Fifth)
+        && approach.is_some_and(|motion| (-2..=-1).contains(&motion))
+        && departure.is_some_and(|motion| (-4..=-3).contains(&motion))
+        && motion_from(next, line.get(index + 2)).is_some_and(|motion| (1..=2).contains(&motion));
+      let is_allowed = match self.species {
+        _ if note.start_beat == cantus_note.start_beat => false,
+        Species::First | Species::Fourth => false,
+        Species::Second => is_passing || (is_neighbor && self.rules == CounterpointRules::Modern),
+        Species::Third | Species::Fifth => is_passing || is_neighbor || is_cambiata,
+      };
+      if !is_allowed {
+        let issue = if note.start_beat == cantus_note.start_beat {
+          CounterpointIssue::DissonantDownbeat
+        } else {
+          CounterpointIssue::InvalidDissonance
+        };
+        diagnostics.push(diagnostic(issue, note.start_beat, note, cantus_note));
+      }
+    }
+  }
+
+  fn is_allowed_leap(self, motion: i16) -> bool {
+    match motion.unsigned_abs() {
+      0..=5 | 7 | 12 => true,
+      8 => motion > 0 || self.rules == CounterpointRules::Modern,
+      9 => self.rules == CounterpointRules::Modern,
+      _ => false,
+    }
+  }
+}
+
+/// Checks that the counterpoint starts on a perfect consonance and ends with a proper cadence.
+fn check_boundaries(cantus: &[NoteEvent], line: &[NoteEvent], diagnostics: &mut Vec<CounterpointDiagnostic>) {
+  let first = &line[0];
+  if let Some(cantus_note) = sounding_at(cantus, first.start_beat) {
+    let interval = first.midi_number.abs_diff(cantus_note.midi_number);
+    if !is_perfect(interval) || (first.midi_number < cantus_note.midi_number && interval % 12 != 0) {
+      diagnostics.push(diagnostic(
+        CounterpointIssue::InvalidStart,
+        first.start_beat,
+        first,
+        cantus_note,
+      ));
+    }
+  }
+  let (last, last_cantus_note) = (&line[line.len() - 1], &cantus[cantus.len() - 1]);
+  let is_final = sounding_at(cantus, last.start_beat)
+    .is_some_and(|cantus_note| cantus_note.note_id == last_cantus_note.note_id)
+    && last.midi_number.abs_diff(last_cantus_note.midi_number) % 12 == 0;
+  let is_approached_properly = line.len() > 1 && {
+    let penultimate = &line[line.len() - 2];
+    let motion = i16::from(last.midi_number) - i16::from(penultimate.midi_number);
+    sounding_at(cantus, penultimate.start_beat).is_some_and(|cantus_note| {
+      let interval = penultimate.midi_number.abs_diff(cantus_note.midi_number) % 12;
+      let expected = if penultimate.midi_number >= cantus_note.midi_number {
+        9
+      } else {
+        3
+      };
+      let cantus_motion = i16::from(last_cantus_note.midi_number) - i16::from(cantus_note.midi_number);
+      (1..=2).contains(&motion.abs()) && interval == expected && (motion.abs() == 1 || cantus_motion.abs() == 1)
+    })
+  };
+  if !is_final || !is_approached_properly {
+    diagnostics.push(diagnostic(
+      CounterpointIssue::InvalidCadence,
+      last.start_beat,
+      last,
+      last_cantus_note,
+    ));
+  }
+}
+
+fn diagnostic(
+  issue: CounterpointIssue,
+  start_beat: Rational,
+  note: &NoteEvent,
+  cantus_note: &NoteEvent,
+) -> CounterpointDiagnostic {
+  CounterpointDiagnostic {
+    issue,
+    start_beat,
+    note_ids: vec![note.note_id, cantus_note.note_id],
+  }
+}
+
+/// Returns the note sounding at the given beat within a monophonic line.
+fn sounding_at(line: &[NoteEvent], beat: Rational) -> Option<&NoteEvent> {
+  let index = line.partition_point(|note| note.start_beat <= beat);
+  index
+    .checked_sub(1)
+    .map(|index| &line[index])
+    .filter(|note| note.end_beat() > beat)
+}
+
+/// Returns the note which follows the given note within a monophonic line.
+fn next_note<'a>(line: &'a [NoteEvent], note: &NoteEvent) -> Option<&'a NoteEvent> {
+  line.iter().find(|next| next.start_beat > note.start_beat)
+}
+
+/// Returns whether the given number of semitones forms a consonance in two-voice counterpoint.
+fn is_consonant(semitones: u8) -> bool {
+  matches!(semitones % 12, 0 | 3 | 4 | 7 | 8 | 9)
+}
+
+/// Returns whether the given number of semitones forms a perfect consonance.
+fn is_perfect(semitones: u8) -> bool {
+  matches!(semitones % 12, 0 | 7)
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for CounterpointIssue {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::RhythmMismatch => "Rhythm Mismatch",
+        Self::InvalidStart => "Invalid Start",
+        Self::DissonantDownbeat => "Dissonant Downbeat",
+        Self::InvalidDissonance => "Invalid Dissonance",
+        Self::InvalidSuspension => "Invalid Suspension",
+        Self::ParallelPerfects => "Parallel Perfect Intervals",
+        Self::DirectPerfects => "Direct Perfect Intervals",
+        Self::Unison => "Unison",
+        Self::ForbiddenLeap => "Forbidden Leap",
+        Self::RepeatedNote => "Repeated Note",
+        Self::InvalidCadence => "Invalid Cadence",
+      }
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for CounterpointDiagnostic {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{} at beat {}", self.issue, self.start_beat)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+  use crate::modification::NoteModificationType;
+  use crate::note::{Pitch, PitchName};
+
+  fn staff(notes: &[(PitchName, u8, DurationType)]) -> Staff {
+    let mut staff = Staff::new("Staff");
+    for (name, octave, duration) in notes {
+      let pitch = if *name == PitchName::Rest {
+        Pitch::new_rest()
+      } else {
+        Pitch::new(*name, *octave)
+      };
+      staff.add_note(pitch, Duration::new(*duration, 0), None);
+    }
+    staff
+  }
+
+  fn cantus_firmus(names: &[PitchName]) -> Staff {
+    staff(
+      &names
+        .iter()
+        .map(|name| (*name, 4, DurationType::Whole))
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  fn issues(diagnostics: &[CounterpointDiagnostic]) -> Vec<(CounterpointIssue, Rational)> {
+    diagnostics
+      .iter()
+      .map(|diagnostic| (diagnostic.issue, diagnostic.start_beat))
+      .collect()
+  }
+
+  #[test]
+  fn test_first_species() {
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let cantus = cantus_firmus(&[PitchName::C, PitchName::D, PitchName::E, PitchName::D, PitchName::C]);
+    let validator = CounterpointValidator::new(Species::First, CounterpointRules::Fux);
+    let line = staff(&[
+      (PitchName::G, 4, DurationType::Whole),
+      (PitchName::F, 4, DurationType::Whole),
+      (PitchName::G, 4, DurationType::Whole),
+      (PitchName::B, 4, DurationType::Whole),
+      (PitchName::C, 5, DurationType::Whole),
+    ]);
+    assert!(validator.validate(&cantus, &line, key).is_empty());
+    let line = staff(&[
+      (PitchName::E, 5, DurationType::Whole),
+      (PitchName::A, 4, DurationType::Whole),
+      (PitchName::B, 4, DurationType::Whole),
+      (PitchName::F, 4, DurationType::Whole),
+      (PitchName::C, 4, DurationType::Whole),
+    ]);
+    assert_eq!(
+      issues(&validator.validate(&cantus, &line, key)),
+      [
+        (CounterpointIssue::InvalidStart, Rational::ZERO),
+        (CounterpointIssue::ParallelPerfects, Rational::from(8_u8)),
+        (CounterpointIssue::ForbiddenLeap, Rational::from(12_u8)),
+        (CounterpointIssue::DirectPerfects, Rational::from(16_u8)),
+        (CounterpointIssue::InvalidCadence, Rational::from(16_u8)),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_dissonance_treatment() {
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let cantus = cantus_firmus(&[PitchName::C, PitchName::C, PitchName::C]);
+    let line = staff(&[
+      (PitchName::E, 4, DurationType::Half),
+      (PitchName::F, 4, DurationType::Half),
+      (PitchName::E, 4, DurationType::Half),
+      (PitchName::F, 4, DurationType::Half),
+      (PitchName::G, 4, DurationType::Whole),
+    ]);
+    let dissonances = |rules: CounterpointRules| -> Vec<(CounterpointIssue, Rational)> {
+      issues(&CounterpointValidator::new(Species::Second, rules).validate(&cantus, &line, key))
+        .into_iter()
+        .filter(|(issue, _)| *issue == CounterpointIssue::InvalidDissonance)
+        .collect()
+    };
+    assert_eq!(
+      dissonances(CounterpointRules::Fux),
+      [(CounterpointIssue::InvalidDissonance, Rational::from(2_u8))]
+    );
+    assert!(dissonances(CounterpointRules::Modern).is_empty());
+
+    let cantus = cantus_firmus(&[PitchName::C, PitchName::D, PitchName::C]);
+    let suspensions = |resolution: PitchName| -> Vec<(CounterpointIssue, Rational)> {
+      let line = staff(&[
+        (PitchName::Rest, 4, DurationType::Half),
+        (PitchName::G, 4, DurationType::Whole),
+        (resolution, 4, DurationType::Half),
+        (PitchName::C, 5, DurationType::Whole),
+      ]);
+      issues(&CounterpointValidator::new(Species::Fourth, CounterpointRules::Fux).validate(&cantus, &line, key))
+        .into_iter()
+        .filter(|(issue, _)| *issue == CounterpointIssue::InvalidSuspension)
+        .collect()
+    };
+    assert!(suspensions(PitchName::F).is_empty());
+    assert_eq!(
+      suspensions(PitchName::A),
+      [(CounterpointIssue::InvalidSuspension, Rational::from(4_u8))]
+    );
+  }
+
+  #[test]
+  fn test_tied_suspension() {
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let cantus = cantus_firmus(&[PitchName::C, PitchName::D, PitchName::C]);
+    let validator = CounterpointValidator::new(Species::Fourth, CounterpointRules::Fux);
+    let suspension_issues = |tied: bool| -> Vec<(CounterpointIssue, Rational)> {
+      let mut line = staff(&[(PitchName::Rest, 4, DurationType::Half)]);
+      let half = Duration::new(DurationType::Half, 0);
+      let preparation = line.add_note(Pitch::new(PitchName::G, 4), half, None);
+      if tied {
+        preparation.add_modification(NoteModificationType::Tie);
+      }
+      line.add_note(Pitch::new(PitchName::G, 4), half, None);
+      line.add_note(Pitch::new(PitchName::F, 4), half, None);
+      line.add_note(Pitch::new(PitchName::C, 5), Duration::new(DurationType::Whole, 0), None);
+      issues(&validator.validate(&cantus, &line, key))
+        .into_iter()
+        .filter(|(issue, _)| {
+          matches!(
+            issue,
+            CounterpointIssue::DissonantDownbeat | CounterpointIssue::InvalidSuspension
+          )
+        })
+        .collect()
+    };
+    assert!(suspension_issues(true).is_empty());
+    assert_eq!(
+      suspension_issues(false),
+      [(CounterpointIssue::DissonantDownbeat, Rational::from(4_u8))]
+    );
+  }
+}
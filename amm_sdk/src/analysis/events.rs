@@ -0,0 +1,300 @@
+use crate::context::{Key, TimeSignature};
+use crate::modification::NoteModificationType;
+use crate::note::{Accidental, Duration, Pitch, Rational};
+use crate::structure::{
+  ChordContent, MultiVoiceContent, Part, PartContent, Phrase, PhraseContent, SectionContent, StaffContent,
+};
+use crate::temporal::{AccidentalResolver, Timeslice};
+use crate::Composition;
+use alloc::collections::BTreeMap;
+use amm_internal::amm_prelude::*;
+
+/// Represents a single pitched note, along with its position within a part
+/// and the key in which it was written.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct NoteEvent {
+  /// The beat at which the note starts.
+  pub start_beat: Rational,
+  /// The duration of the note in beats.
+  pub beats: Rational,
+  /// The locally unique identifier of the note.
+  pub note_id: usize,
+  /// Whether the note is a grace note, which does not take up any metric time.
+  pub is_grace: bool,
+  /// Whether the note is tied to the following note of the same pitch.
+  pub is_tied: bool,
+  /// The written pitch of the note.
+  pub pitch: Pitch,
+  /// The accidental applied to the note when played, taking the key signature into account.
+  pub accidental: Accidental,
+  /// The MIDI number of the note.
+  pub midi_number: u8,
+  /// The key in which the note was written.
+  pub key: Key,
+}
+
+impl NoteEvent {
+  /// Returns the pitch class of the note, where 0 represents C and 11 represents B.
+  #[must_use]
+  pub const fn pitch_class(&self) -> u8 {
+    self.midi_number % 12
+  }
+
+  /// Returns the beat at which the note stops sounding.
+  #[must_use]
+  pub fn end_beat(&self) -> Rational {
+    self.start_beat + self.beats
+  }
+}
+
+/// Returns every pitched note in the given part in playback order, following any
+/// key and time signature changes that occur after the specified starting values.
+///
+/// Parts stored in written pitch are converted to sounding pitch, in which case the
+/// starting key is taken to be the sounding key.
+pub(crate) fn collect_note_events(
+  part: &Part,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+  beat_base: Duration,
+) -> Vec<NoteEvent> {
+  if part.is_written_pitch() {
+    let mut sounding_part = part.clone();
+    sounding_part.transpose_to_sounding_pitch(part.written_key(starting_key), starting_time_signature);
+    collect_timeslice_events(
+      sounding_part.iter_timeslices(),
+      starting_key,
+      starting_time_signature,
+      beat_base,
+    )
+  } else {
+    collect_timeslice_events(part.iter_timeslices(), starting_key, starting_time_signature, beat_base)
+  }
+}
+
+/// Returns every pitched note in the given sequence of timeslices, following any key
+/// and time signature changes that occur after the specified starting values.
+///
+/// Accidentals are resolved as they would be played, carrying over within each measure.
+pub(crate) fn collect_timeslice_events(
+  timeslices: impl Iterator<Item = Timeslice>,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+  beat_base: Duration,
+) -> Vec<NoteEvent> {
+  let (mut current_beat, mut events) = (Rational::ZERO, Vec::new());
+  let mut resolver = AccidentalResolver::new(timeslices, starting_key, starting_time_signature);
+  while let Some(timeslice) = resolver.next() {
+    let key = resolver.current_key();
+    timeslice
+      .content
+      .iter()
+      .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+      .for_each(|content| {
+        events.push(NoteEvent {
+          start_beat: current_beat,
+          beats: content.get_beats(&beat_base),
+          note_id: content.note.note_id,
+          is_grace: content.note.is_grace_note(),
+          is_tied: content
+            .note
+            .iter_modifications()
+            .any(|modification| modification.r#type == NoteModificationType::Tie),
+          pitch: content.note.pitch,
+          accidental: content.sounding_accidental(Some(key)),
+          midi_number: content.midi_number(Some(key)),
+          key,
+        });
+      });
+    current_beat += timeslice.get_beats(&beat_base);
+  }
+  events
+}
+
+/// Merges every note which is tied to a following note of the same pitch into a single
+/// event spanning both, so that tie continuations are not treated as new attacks.
+pub(crate) fn merge_ties(events: Vec<NoteEvent>) -> Vec<NoteEvent> {
+  let (mut merged, mut open_ties) = (Vec::<NoteEvent>::with_capacity(events.len()), BTreeMap::new());
+  for event in events {
+    let tied_index = open_ties
+      .remove(&event.midi_number)
+      .filter(|index: &usize| merged[*index].end_beat() == event.start_beat);
+    let index = if let Some(index) = tied_index {
+      merged[index].beats += event.beats;
+      merged[index].is_tied = event.is_tied;
+      index
+    } else {
+      merged.push(event);
+      merged.len() - 1
+    };
+    if event.is_tied {
+      open_ties.insert(event.midi_number, index);
+    }
+  }
+  merged
+}
+
+/// Represents a single monophonic line of music extracted from a staff.
+#[derive(Clone, Debug)]
+pub(crate) struct VoiceLine {
+  /// The non-overlapping notes in the voice, ordered by start time.
+  pub events: Vec<NoteEvent>,
+}
+
+impl VoiceLine {
+  /// Returns the note sounding at the given beat, if any.
+  pub fn sounding_at(&self, beat: Rational) -> Option<&NoteEvent> {
+    let index = self.events.partition_point(|event| event.start_beat <= beat);
+    index
+      .checked_sub(1)
+      .map(|index| &self.events[index])
+      .filter(|event| event.end_beat() > beat)
+  }
+
+  /// Returns the note which follows the note with the given identifier, if any.
+  pub fn next_after(&self, note_id: usize) -> Option<&NoteEvent> {
+    let index = self.events.iter().position(|event| event.note_id == note_id)?;
+    self.events.get(index + 1)
+  }
+
+  fn average_midi_number(&self) -> f64 {
+    let total: f64 = self.events.iter().map(|event| f64::from(event.midi_number)).sum();
+    total / f64::from(u32::try_from(self.events.len().max(1)).unwrap_or(u32::MAX))
+  }
+}
+
+/// Separates every staff in the given composition into monophonic voices, ordered
+/// from the highest to the lowest average pitch.
+///
+/// Notes within the same phrase of a `MultiVoice` are kept in the same voice where
+/// possible, while simultaneous notes in chords are distributed across voices by pitch.
+/// Grace notes are ignored.
+pub(crate) fn collect_voices(composition: &Composition) -> Vec<VoiceLine> {
+  let beat_base = composition.get_tempo().base_note;
+  let mut voices = Vec::new();
+  for part in composition {
+    let mut phrase_indices = BTreeMap::new();
+    part.iter().for_each(|PartContent::Section(section)| {
+      section
+        .iter()
+        .for_each(|content| index_section_phrases(content, &mut phrase_indices));
+    });
+    for staff_part in part.extract_staves_as_parts() {
+      let events: Vec<NoteEvent> = collect_note_events(
+        &staff_part,
+        *composition.get_starting_key(),
+        *composition.get_starting_time_signature(),
+        beat_base,
+      )
+      .into_iter()
+      .filter(|event| !event.is_grace && event.beats.is_positive())
+      .collect();
+      voices.extend(
+        separate_voices(&events, &phrase_indices)
+          .into_iter()
+          .map(|events| VoiceLine { events }),
+      );
+    }
+  }
+  voices.sort_by(|a, b| b.average_midi_number().total_cmp(&a.average_midi_number()));
+  voices
+}
+
+fn index_section_phrases(content: &SectionContent, phrase_indices: &mut BTreeMap<usize, usize>) {
+  match content {
+    SectionContent::Staff(staff) => staff.iter().for_each(|content| match content {
+      StaffContent::MultiVoice(multivoice) => multivoice
+        .iter()
+        .enumerate()
+        .for_each(|(index, MultiVoiceContent::Phrase(phrase))| index_phrase_notes(phrase, index, phrase_indices)),
+      StaffContent::Phrase(phrase) => index_nested_phrases(phrase, phrase_indices),
+      _ => (),
+    }),
+    SectionContent::Section(section) => section
+      .iter()
+      .for_each(|content| index_section_phrases(content, phrase_indices)),
+  }
+}
+
+fn index_nested_phrases(phrase: &Phrase, phrase_indices: &mut BTreeMap<usize, usize>) {
+  phrase.iter().for_each(|content| match content {
+    PhraseContent::MultiVoice(multivoice) => multivoice
+      .iter()
+      .enumerate()
+      .for_each(|(index, MultiVoiceContent::Phrase(phrase))| index_phrase_notes(phrase, index, phrase_indices)),
+    PhraseContent::Phrase(phrase) => index_nested_phrases(phrase, phrase_indices),
+    _ => (),
+  });
+}
+
+fn index_phrase_notes(phrase: &Phrase, index: usize, phrase_indices: &mut BTreeMap<usize, usize>) {
+  phrase.iter().for_each(|content| match content {
+    PhraseContent::Note(note) => {
+      phrase_indices.insert(note.note_id, index);
+    }
+    PhraseContent::Chord(chord) => chord.iter().for_each(|ChordContent::Note(note)| {
+      phrase_indices.insert(note.note_id, index);
+    }),
+    PhraseContent::Phrase(phrase) => index_phrase_notes(phrase, index, phrase_indices),
+    PhraseContent::MultiVoice(multivoice) => multivoice
+      .iter()
+      .for_each(|MultiVoiceContent::Phrase(phrase)| index_phrase_notes(phrase, index, phrase_indices)),
+  });
+}
+
+/// Distributes the notes of a single staff into as many non-overlapping voices as
+/// the maximum number of simultaneously sounding notes.
+fn separate_voices(events: &[NoteEvent], phrase_indices: &BTreeMap<usize, usize>) -> Vec<Vec<NoteEvent>> {
+  let mut onsets: Vec<Rational> = events.iter().map(|event| event.start_beat).collect();
+  onsets.sort();
+  onsets.dedup();
+  let num_voices = onsets
+    .iter()
+    .map(|onset| {
+      events
+        .iter()
+        .filter(|event| event.start_beat <= *onset && event.end_beat() > *onset)
+        .count()
+    })
+    .max()
+    .unwrap_or_default();
+  let mut voices: Vec<Vec<NoteEvent>> = vec![Vec::new(); num_voices];
+  for onset in onsets {
+    let mut starting: Vec<&NoteEvent> = events.iter().filter(|event| event.start_beat == onset).collect();
+    starting.sort_by_key(|event| core::cmp::Reverse(event.midi_number));
+    let mut free: Vec<usize> = (0..num_voices)
+      .filter(|index| voices[*index].last().is_none_or(|last| last.end_beat() <= onset))
+      .collect();
+    let hints: Vec<usize> = starting
+      .iter()
+      .filter_map(|event| phrase_indices.get(&event.note_id).copied())
+      .collect();
+    let hints_are_usable = hints.len() == starting.len()
+      && hints.iter().all(|hint| free.contains(hint))
+      && (1..hints.len()).all(|index| !hints[..index].contains(&hints[index]));
+    if hints_are_usable {
+      starting
+        .iter()
+        .zip(hints)
+        .for_each(|(event, index)| voices[index].push(**event));
+    } else if starting.len() == free.len() {
+      starting
+        .iter()
+        .zip(free)
+        .for_each(|(event, index)| voices[index].push(**event));
+    } else {
+      for event in starting {
+        let nearest = free.iter().enumerate().min_by_key(|(_, index)| {
+          voices[**index]
+            .last()
+            .map_or(u8::MAX, |last| last.midi_number.abs_diff(event.midi_number))
+        });
+        if let Some((position, index)) = nearest.map(|(position, index)| (position, *index)) {
+          voices[index].push(*event);
+          free.remove(position);
+        }
+      }
+    }
+  }
+  voices
+}
@@ -0,0 +1,565 @@
+use super::events::{collect_note_events, NoteEvent};
+use crate::context::Key;
+use crate::note::{Accidental, PitchName, Rational};
+use crate::temporal::{PartTimeslice, Timeslice, TimesliceContext};
+use crate::Composition;
+use amm_internal::amm_prelude::*;
+
+const ROOT_MISSING_PENALTY: f64 = 0.3;
+const FIFTH_MISSING_PENALTY: f64 = 0.05;
+const TONE_MISSING_PENALTY: f64 = 0.3;
+const ROOT_IN_BASS_BONUS: f64 = 0.1;
+const DIATONIC_ROOT_BONUS: f64 = 0.02;
+const COMPLEXITY_PENALTY: f64 = 0.01;
+
+/// Represents the quality of a chord, as defined by the intervals between
+/// its root and each of its remaining chord tones.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ChordQuality {
+  /// A major triad (e.g., C-E-G).
+  #[default]
+  Major,
+  /// A minor triad (e.g., C-E♭-G).
+  Minor,
+  /// A diminished triad (e.g., C-E♭-G♭).
+  Diminished,
+  /// An augmented triad (e.g., C-E-G♯).
+  Augmented,
+  /// A suspended chord which replaces the third with a major second (e.g., C-D-G).
+  SuspendedSecond,
+  /// A suspended chord which replaces the third with a perfect fourth (e.g., C-F-G).
+  SuspendedFourth,
+  /// A major triad with a minor seventh (e.g., C-E-G-B♭).
+  DominantSeventh,
+  /// A major triad with a major seventh (e.g., C-E-G-B).
+  MajorSeventh,
+  /// A minor triad with a minor seventh (e.g., C-E♭-G-B♭).
+  MinorSeventh,
+  /// A diminished triad with a minor seventh (e.g., C-E♭-G♭-B♭).
+  HalfDiminishedSeventh,
+  /// A diminished triad with a diminished seventh (e.g., C-E♭-G♭-B𝄫).
+  DiminishedSeventh,
+  /// A minor triad with a major seventh (e.g., C-E♭-G-B).
+  MinorMajorSeventh,
+  /// An augmented triad with a major seventh (e.g., C-E-G♯-B).
+  AugmentedMajorSeventh,
+  /// A dominant seventh chord with a major ninth (e.g., C-E-G-B♭-D).
+  DominantNinth,
+  /// A major seventh chord with a major ninth (e.g., C-E-G-B-D).
+  MajorNinth,
+  /// A minor seventh chord with a major ninth (e.g., C-E♭-G-B♭-D).
+  MinorNinth,
+}
+
+impl ChordQuality {
+  /// All chord qualities, ordered from simplest to most complex.
+  const ALL: [Self; 16] = [
+    Self::Major,
+    Self::Minor,
+    Self::Diminished,
+    Self::Augmented,
+    Self::SuspendedSecond,
+    Self::SuspendedFourth,
+    Self::DominantSeventh,
+    Self::MajorSeventh,
+    Self::MinorSeventh,
+    Self::HalfDiminishedSeventh,
+    Self::DiminishedSeventh,
+    Self::MinorMajorSeventh,
+    Self::AugmentedMajorSeventh,
+    Self::DominantNinth,
+    Self::MajorNinth,
+    Self::MinorNinth,
+  ];
+
+  /// Returns the number of semitones between the root of the chord and each of
+  /// its chord tones, ordered by position in a stack of thirds (i.e., root, third,
+  /// fifth, seventh, ninth).
+  #[must_use]
+  pub const fn semitones(&self) -> &'static [u8] {
+    match self {
+      Self::Major => &[0, 4, 7],
+      Self::Minor => &[0, 3, 7],
+      Self::Diminished => &[0, 3, 6],
+      Self::Augmented => &[0, 4, 8],
+      Self::SuspendedSecond => &[0, 2, 7],
+      Self::SuspendedFourth => &[0, 5, 7],
+      Self::DominantSeventh => &[0, 4, 7, 10],
+      Self::MajorSeventh => &[0, 4, 7, 11],
+      Self::MinorSeventh => &[0, 3, 7, 10],
+      Self::HalfDiminishedSeventh => &[0, 3, 6, 10],
+      Self::DiminishedSeventh => &[0, 3, 6, 9],
+      Self::MinorMajorSeventh => &[0, 3, 7, 11],
+      Self::AugmentedMajorSeventh => &[0, 4, 8, 11],
+      Self::DominantNinth => &[0, 4, 7, 10, 2],
+      Self::MajorNinth => &[0, 4, 7, 11, 2],
+      Self::MinorNinth => &[0, 3, 7, 10, 2],
+    }
+  }
+
+  /// Returns whether the chord contains a seventh.
+  #[must_use]
+  pub const fn is_seventh(&self) -> bool {
+    self.semitones().len() > 3
+  }
+}
+
+/// Represents the result of identifying the chord formed by a set of sounding notes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChordAnalysis {
+  /// The pitch name and accidental of the root of the chord.
+  pub root: (PitchName, Accidental),
+  /// The quality of the chord.
+  pub quality: ChordQuality,
+  /// The inversion of the chord, where 0 represents root position, 1 represents
+  /// first inversion, and so on.
+  ///
+  /// If the lowest sounding note is not a chord tone, the chord is considered to
+  /// be in root position over a non-chord bass note (i.e., a slash chord).
+  pub inversion: u8,
+  /// The pitch name and accidental of the lowest sounding note.
+  pub bass: (PitchName, Accidental),
+  /// How well the chord explains the sounding notes, ranging from 0.0 to 1.0.
+  pub confidence: f64,
+}
+
+/// Represents the chord identified within a fixed-length span of a composition.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChordSegment {
+  /// The beat at which the segment starts.
+  pub start_beat: Rational,
+  /// The beat at which the segment ends.
+  pub end_beat: Rational,
+  /// The chord which best explains the notes sounding within the segment.
+  pub analysis: ChordAnalysis,
+}
+
+/// Represents a duration-weighted collection of sounding pitch classes.
+#[derive(Default)]
+pub(super) struct Sonority {
+  weights: [Rational; 12],
+  spellings: [Option<(PitchName, Accidental)>; 12],
+  bass: Option<(u8, PitchName, Accidental)>,
+}
+
+impl Sonority {
+  pub(super) fn add(&mut self, midi_number: u8, name: PitchName, accidental: Accidental, weight: Rational) {
+    if !weight.is_positive() || name == PitchName::Rest {
+      return;
+    }
+    let accidental = if accidental == Accidental::Natural {
+      Accidental::None
+    } else {
+      accidental
+    };
+    let pitch_class = usize::from(midi_number % 12);
+    self.weights[pitch_class] += weight;
+    self.spellings[pitch_class].get_or_insert((name, accidental));
+    if self.bass.is_none_or(|(bass, _, _)| midi_number < bass) {
+      self.bass = Some((midi_number, name, accidental));
+    }
+  }
+
+  fn add_timeslice(&mut self, timeslice: &Timeslice, context: &TimesliceContext) {
+    timeslice
+      .content
+      .iter()
+      .filter(|content| !content.note.is_unpitched())
+      .for_each(|content| {
+        let weight = content.get_beats(&context.current_tempo.base_note);
+        self.add(
+          content.midi_number(Some(context.key)),
+          content.note.pitch.name,
+          content.sounding_accidental(Some(context.key)),
+          weight,
+        );
+      });
+  }
+
+  pub(super) fn pitch_classes(&self) -> [bool; 12] {
+    self.weights.map(|weight| weight.is_positive())
+  }
+
+  pub(super) fn analyze(&self, key: Key) -> Option<ChordAnalysis> {
+    let total: Rational = self.weights.iter().copied().sum();
+    let (bass, bass_name, bass_accidental) = self.bass?;
+    if !total.is_positive() || self.weights.iter().filter(|weight| weight.is_positive()).count() < 2 {
+      return None;
+    }
+    let weights = self.weights.map(|weight| (weight / total).to_f64());
+    let tonic = key.signature.pitch_class();
+    let (mut best, mut best_score) = ((0, ChordQuality::Major), f64::MIN);
+    for root in 0..12_u8 {
+      let is_diatonic = key.mode.semitones().iter().any(|offset| (tonic + offset) % 12 == root);
+      for quality in ChordQuality::ALL {
+        let (mut score, tones) = (-1.0, quality.semitones());
+        for (index, offset) in tones.iter().enumerate() {
+          let weight = weights[usize::from((root + offset) % 12)];
+          score += 2.0 * weight;
+          if weight <= 0.0 {
+            score -= match (index, offset) {
+              (0, _) => ROOT_MISSING_PENALTY,
+              (_, 7) => FIFTH_MISSING_PENALTY,
+              _ => TONE_MISSING_PENALTY,
+            };
+          }
+        }
+        if bass % 12 == root {
+          score += ROOT_IN_BASS_BONUS;
+        }
+        if is_diatonic {
+          score += DIATONIC_ROOT_BONUS;
+        }
+        score -= COMPLEXITY_PENALTY * f64::from(u8::try_from(tones.len()).unwrap_or(u8::MAX));
+        if score > best_score {
+          (best, best_score) = ((root, quality), score);
+        }
+      }
+    }
+    let (root, quality) = best;
+    let inversion = quality
+      .semitones()
+      .iter()
+      .position(|offset| (root + offset) % 12 == bass % 12)
+      .and_then(|index| u8::try_from(index).ok())
+      .unwrap_or_default();
+    Some(ChordAnalysis {
+      root: self.spellings[usize::from(root)].unwrap_or_else(|| spell_pitch_class(root, key)),
+      quality,
+      inversion,
+      bass: (bass_name, bass_accidental),
+      confidence: best_score.clamp(0.0, 1.0),
+    })
+  }
+}
+
+impl ChordAnalysis {
+  /// Identifies the chord formed by all notes which start in the given timeslice.
+  ///
+  /// Notes are weighted by their duration so that short passing tones have less
+  /// influence on the result than sustained chord tones. The key and beat base
+  /// are taken from the specified `context`.
+  ///
+  /// Returns `None` if fewer than two distinct pitch classes are present.
+  #[must_use]
+  pub fn from_timeslice(timeslice: &Timeslice, context: &TimesliceContext) -> Option<Self> {
+    let mut sonority = Sonority::default();
+    sonority.add_timeslice(timeslice, context);
+    sonority.analyze(context.key)
+  }
+
+  /// Identifies the chord formed by all notes which start in any part of the given timeslice.
+  ///
+  /// Returns `None` if fewer than two distinct pitch classes are present.
+  #[must_use]
+  pub fn from_part_timeslice(timeslice: &PartTimeslice, context: &TimesliceContext) -> Option<Self> {
+    let mut sonority = Sonority::default();
+    timeslice
+      .timeslices
+      .values()
+      .for_each(|timeslice| sonority.add_timeslice(timeslice, context));
+    sonority.analyze(context.key)
+  }
+
+  /// Divides the given composition into consecutive segments of `beats_per_segment`
+  /// beats and identifies the chord formed by all notes sounding within each one.
+  ///
+  /// Unlike timeslice-based analysis, notes which started in an earlier segment but
+  /// are still sounding contribute to the result, weighted by the number of beats
+  /// they overlap the segment. Any key changes within the composition are taken into
+  /// account, and segments containing fewer than two distinct pitch classes are omitted.
+  #[must_use]
+  pub fn segment_by_beat(composition: &Composition, beats_per_segment: Rational) -> Vec<ChordSegment> {
+    if !beats_per_segment.is_positive() {
+      return Vec::new();
+    }
+    let beat_base = composition.get_tempo().base_note;
+    let events: Vec<NoteEvent> = composition
+      .iter()
+      .flat_map(|part| {
+        collect_note_events(
+          part,
+          *composition.get_starting_key(),
+          *composition.get_starting_time_signature(),
+          beat_base,
+        )
+      })
+      .collect();
+    let total_beats = events.iter().map(NoteEvent::end_beat).max().unwrap_or_default();
+    let (mut segments, mut start_beat) = (Vec::new(), Rational::ZERO);
+    while start_beat < total_beats {
+      let end_beat = start_beat + beats_per_segment;
+      let (mut sonority, mut key) = (Sonority::default(), None);
+      for event in &events {
+        let overlap = event.end_beat().min(end_beat) - event.start_beat.max(start_beat);
+        if overlap.is_positive() {
+          sonority.add(event.midi_number, event.pitch.name, event.accidental, overlap);
+          key.get_or_insert(event.key);
+        }
+      }
+      if let Some(analysis) = sonority.analyze(key.unwrap_or(*composition.get_starting_key())) {
+        segments.push(ChordSegment {
+          start_beat,
+          end_beat: end_beat.min(total_beats),
+          analysis,
+        });
+      }
+      start_beat = end_beat;
+    }
+    segments
+  }
+
+  /// Returns the pitch class of the root of the chord, where 0 represents C and 11 represents B.
+  #[must_use]
+  pub fn root_pitch_class(&self) -> u8 {
+    pitch_class_of(self.root)
+  }
+
+  /// Returns the pitch class of the lowest sounding note, where 0 represents C and 11 represents B.
+  #[must_use]
+  pub fn bass_pitch_class(&self) -> u8 {
+    pitch_class_of(self.bass)
+  }
+}
+
+/// Returns the pitch class of the given pitch name and accidental.
+fn pitch_class_of((name, accidental): (PitchName, Accidental)) -> u8 {
+  u8::try_from((name.natural_semitones() + i16::from(accidental.value())).rem_euclid(12)).unwrap_or_default()
+}
+
+/// Returns the most natural spelling of a pitch class within the given key,
+/// preferring notes of the key signature followed by flats in flat keys and
+/// sharps otherwise.
+fn spell_pitch_class(pitch_class: u8, key: Key) -> (PitchName, Accidental) {
+  let accidentals = key.accidentals();
+  let names = [
+    PitchName::C,
+    PitchName::D,
+    PitchName::E,
+    PitchName::F,
+    PitchName::G,
+    PitchName::A,
+    PitchName::B,
+  ];
+  names
+    .iter()
+    .map(|name| (*name, accidentals[name.index()]))
+    .find(|spelling| pitch_class_of(*spelling) == pitch_class)
+    .or_else(|| {
+      let accidental = if key.is_flat_key() {
+        Accidental::Flat
+      } else {
+        Accidental::Sharp
+      };
+      names
+        .iter()
+        .map(|name| (*name, accidental))
+        .find(|spelling| pitch_class_of(*spelling) == pitch_class)
+    })
+    .unwrap_or((PitchName::C, Accidental::None))
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for ChordQuality {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if f.alternate() {
+      write!(
+        f,
+        "{}",
+        match self {
+          Self::Major => "Major",
+          Self::Minor => "Minor",
+          Self::Diminished => "Diminished",
+          Self::Augmented => "Augmented",
+          Self::SuspendedSecond => "Suspended Second",
+          Self::SuspendedFourth => "Suspended Fourth",
+          Self::DominantSeventh => "Dominant Seventh",
+          Self::MajorSeventh => "Major Seventh",
+          Self::MinorSeventh => "Minor Seventh",
+          Self::HalfDiminishedSeventh => "Half-Diminished Seventh",
+          Self::DiminishedSeventh => "Diminished Seventh",
+          Self::MinorMajorSeventh => "Minor-Major Seventh",
+          Self::AugmentedMajorSeventh => "Augmented Major Seventh",
+          Self::DominantNinth => "Dominant Ninth",
+          Self::MajorNinth => "Major Ninth",
+          Self::MinorNinth => "Minor Ninth",
+        }
+      )
+    } else {
+      write!(
+        f,
+        "{}",
+        match self {
+          Self::Major => "",
+          Self::Minor => "m",
+          Self::Diminished => "dim",
+          Self::Augmented => "aug",
+          Self::SuspendedSecond => "sus2",
+          Self::SuspendedFourth => "sus4",
+          Self::DominantSeventh => "7",
+          Self::MajorSeventh => "maj7",
+          Self::MinorSeventh => "m7",
+          Self::HalfDiminishedSeventh => "m7♭5",
+          Self::DiminishedSeventh => "dim7",
+          Self::MinorMajorSeventh => "m(maj7)",
+          Self::AugmentedMajorSeventh => "maj7♯5",
+          Self::DominantNinth => "9",
+          Self::MajorNinth => "maj9",
+          Self::MinorNinth => "m9",
+        }
+      )
+    }
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for ChordAnalysis {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let (root_name, root_accidental) = self.root;
+    let (bass_name, bass_accidental) = self.bass;
+    if f.alternate() {
+      write!(f, "{root_name}{root_accidental} {:#}", self.quality)?;
+    } else {
+      write!(f, "{root_name}{root_accidental}{}", self.quality)?;
+    }
+    if self.bass == self.root {
+      Ok(())
+    } else {
+      write!(f, "/{bass_name}{bass_accidental}")
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+  use crate::note::{Duration, DurationType, Note, Pitch};
+
+  fn analyze(notes: &[(PitchName, u8, Option<Accidental>, DurationType)], key: Key) -> Option<ChordAnalysis> {
+    let mut timeslice = Timeslice::new();
+    for (name, octave, accidental, duration) in notes {
+      timeslice.add_note(Note::new(
+        Pitch::new(*name, *octave),
+        Duration::new(*duration, 0),
+        *accidental,
+      ));
+    }
+    let context = TimesliceContext {
+      key,
+      ..Default::default()
+    };
+    ChordAnalysis::from_timeslice(&timeslice, &context)
+  }
+
+  #[test]
+  fn test_triads() {
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let chord = analyze(
+      &[
+        (PitchName::E, 3, None, DurationType::Quarter),
+        (PitchName::G, 3, None, DurationType::Quarter),
+        (PitchName::C, 4, None, DurationType::Quarter),
+      ],
+      key,
+    )
+    .unwrap();
+    assert_eq!(chord.root, (PitchName::C, Accidental::None));
+    assert_eq!(chord.quality, ChordQuality::Major);
+    assert_eq!(chord.inversion, 1);
+    #[cfg(feature = "print")]
+    assert_eq!(chord.to_string(), "C/E");
+    assert!(chord.confidence > 0.9);
+    let chord = analyze(
+      &[
+        (PitchName::B, 3, None, DurationType::Quarter),
+        (PitchName::D, 4, None, DurationType::Quarter),
+        (PitchName::F, 4, None, DurationType::Quarter),
+      ],
+      key,
+    )
+    .unwrap();
+    assert_eq!(chord.root, (PitchName::B, Accidental::None));
+    assert_eq!(chord.quality, ChordQuality::Diminished);
+    #[cfg(feature = "print")]
+    assert_eq!(chord.to_string(), "Bdim");
+    assert!(analyze(&[(PitchName::C, 4, None, DurationType::Quarter)], key).is_none());
+  }
+
+  #[test]
+  fn test_sevenths_and_missing_tones() {
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let chord = analyze(
+      &[
+        (PitchName::G, 2, None, DurationType::Half),
+        (PitchName::B, 3, None, DurationType::Half),
+        (PitchName::F, 4, None, DurationType::Half),
+      ],
+      key,
+    )
+    .unwrap();
+    assert_eq!(chord.root, (PitchName::G, Accidental::None));
+    assert_eq!(chord.quality, ChordQuality::DominantSeventh);
+    #[cfg(feature = "print")]
+    assert_eq!(chord.to_string(), "G7");
+    let chord = analyze(
+      &[
+        (PitchName::F, 3, None, DurationType::Half),
+        (PitchName::C, 4, None, DurationType::Half),
+        (PitchName::E, 4, None, DurationType::Half),
+        (PitchName::G, 4, None, DurationType::Half),
+        (PitchName::A, 4, None, DurationType::Half),
+      ],
+      key,
+    )
+    .unwrap();
+    assert_eq!(chord.root, (PitchName::F, Accidental::None));
+    assert_eq!(chord.quality, ChordQuality::MajorNinth);
+    #[cfg(feature = "print")]
+    assert_eq!(chord.to_string(), "Fmaj9");
+    let key = Key::new(KeySignature::EFlat, KeyMode::Major);
+    let chord = analyze(
+      &[
+        (PitchName::B, 3, None, DurationType::Half),
+        (PitchName::D, 4, None, DurationType::Half),
+        (PitchName::F, 4, None, DurationType::Half),
+        (PitchName::A, 4, None, DurationType::Half),
+        (PitchName::E, 5, Some(Accidental::Natural), DurationType::Sixteenth),
+      ],
+      key,
+    )
+    .unwrap();
+    assert_eq!(chord.root, (PitchName::B, Accidental::Flat));
+    assert_eq!(chord.quality, ChordQuality::DominantSeventh);
+    assert_eq!(chord.inversion, 0);
+  }
+
+  #[test]
+  fn test_segment_by_beat() {
+    let mut composition = Composition::new("Test", None, None, None);
+    let section = composition.add_part("Part").add_section("Section");
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let whole = Duration::new(DurationType::Whole, 0);
+    let melody = section.add_staff("Melody");
+    for name in [PitchName::C, PitchName::D, PitchName::E, PitchName::G] {
+      melody.add_note(Pitch::new(name, 5), quarter, None);
+    }
+    for name in [PitchName::B, PitchName::G, PitchName::D, PitchName::F] {
+      melody.add_note(Pitch::new(name, 4), quarter, None);
+    }
+    let bass = section.add_staff("Bass");
+    let chord = bass.add_chord();
+    chord.add_note(Pitch::new(PitchName::C, 3), whole, None);
+    chord.add_note(Pitch::new(PitchName::G, 3), whole, None);
+    let chord = bass.add_chord();
+    chord.add_note(Pitch::new(PitchName::G, 2), whole, None);
+    chord.add_note(Pitch::new(PitchName::D, 3), whole, None);
+    let segments = ChordAnalysis::segment_by_beat(&composition, Rational::from(4_u8));
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].analysis.root, (PitchName::C, Accidental::None));
+    assert_eq!(segments[0].analysis.quality, ChordQuality::Major);
+    assert_eq!(segments[1].analysis.root, (PitchName::G, Accidental::None));
+    assert_eq!(segments[1].analysis.quality, ChordQuality::DominantSeventh);
+    assert_eq!(segments[1].start_beat, Rational::from(4_u8));
+  }
+}
@@ -0,0 +1,328 @@
+use super::events::{collect_note_events, NoteEvent};
+use crate::context::{Key, KeyMode, TimeSignature};
+use crate::note::{Duration, Rational};
+use crate::structure::Part;
+use crate::temporal::Timeslice;
+use crate::Composition;
+use amm_internal::amm_prelude::*;
+
+const KRUMHANSL_KESSLER_MAJOR: [f64; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const KRUMHANSL_KESSLER_MINOR: [f64; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+const TEMPERLEY_MAJOR: [f64; 12] = [
+  0.748, 0.060, 0.488, 0.082, 0.670, 0.460, 0.096, 0.715, 0.104, 0.366, 0.057, 0.400,
+];
+const TEMPERLEY_MINOR: [f64; 12] = [
+  0.712, 0.084, 0.474, 0.618, 0.049, 0.460, 0.105, 0.747, 0.404, 0.067, 0.133, 0.330,
+];
+const SIMPLE_MAJOR: [f64; 12] = [2.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 2.0, 0.0, 1.0, 0.0, 1.0];
+const SIMPLE_MINOR: [f64; 12] = [2.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 2.0, 1.0, 0.0, 0.5, 0.5];
+
+/// Represents a pair of key profiles, which describe how strongly each of the
+/// twelve pitch classes is expected to be present in music written in a major
+/// or minor key.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum KeyProfile {
+  /// The probe-tone ratings published by Krumhansl and Kessler (1982).
+  #[default]
+  KrumhanslKessler,
+  /// The pitch-class distributions published by Temperley (1999), which were
+  /// derived from the excerpts in the Kostka-Payne harmony textbook.
+  Temperley,
+  /// A simplified profile which weights the tonic and dominant most heavily,
+  /// followed by the remaining tones of the scale.
+  Simple,
+}
+
+impl KeyProfile {
+  /// Returns the profile weights for each pitch class, starting from the tonic.
+  #[must_use]
+  const fn weights(self, mode: KeyMode) -> &'static [f64; 12] {
+    match (self, mode.is_minor()) {
+      (Self::KrumhanslKessler, false) => &KRUMHANSL_KESSLER_MAJOR,
+      (Self::KrumhanslKessler, true) => &KRUMHANSL_KESSLER_MINOR,
+      (Self::Temperley, false) => &TEMPERLEY_MAJOR,
+      (Self::Temperley, true) => &TEMPERLEY_MINOR,
+      (Self::Simple, false) => &SIMPLE_MAJOR,
+      (Self::Simple, true) => &SIMPLE_MINOR,
+    }
+  }
+}
+
+/// Represents a key which may explain the pitches found in a piece of music.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyCandidate {
+  /// The candidate key.
+  pub key: Key,
+  /// The correlation between the music and the profile of the key, ranging from -1.0 to 1.0.
+  pub score: f64,
+}
+
+/// Represents a span of a composition which has been found to be in a single key.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyRegion {
+  /// The beat at which the region starts.
+  pub start_beat: Rational,
+  /// The beat at which the region ends.
+  pub end_beat: Rational,
+  /// The key which best explains the music in the region.
+  pub key: Key,
+  /// The average correlation between the music in the region and the profile of its key.
+  pub score: f64,
+}
+
+/// Represents a Krumhansl-Schmuckler style key-finding analysis, which accumulates
+/// the total duration of each pitch class in a piece of music and correlates the
+/// result against the profile of every major and minor key.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyFinder {
+  profile: KeyProfile,
+  histogram: [f64; 12],
+}
+
+impl KeyFinder {
+  /// Creates a new, empty key finder which uses the given key profile.
+  #[must_use]
+  pub const fn new(profile: KeyProfile) -> Self {
+    Self {
+      profile,
+      histogram: [0.0; 12],
+    }
+  }
+
+  /// Adds a single occurrence of a pitch class, where 0 represents C and 11 represents B.
+  ///
+  /// The `weight` parameter is typically the duration of the occurrence in beats.
+  pub fn add_pitch_class(&mut self, pitch_class: u8, weight: f64) -> &mut Self {
+    if weight > 0.0 {
+      self.histogram[usize::from(pitch_class % 12)] += weight;
+    }
+    self
+  }
+
+  /// Adds all notes which start in the given timeslice, weighted by their duration in beats.
+  ///
+  /// The `key` parameter defines the key signature that the notes are written in, and
+  /// the `beat_base` parameter defines the type of note that represents a single beat.
+  pub fn add_timeslice(&mut self, timeslice: &Timeslice, key: Option<Key>, beat_base: &Duration) -> &mut Self {
+    timeslice
+      .content
+      .iter()
+      .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+      .for_each(|content| {
+        self.add_pitch_class(content.midi_number(key) % 12, content.get_beats(beat_base).to_f64());
+      });
+    self
+  }
+
+  /// Adds all notes in the given part, following any key and time signature changes
+  /// that occur after the specified starting key and time signature.
+  pub fn add_part(
+    &mut self,
+    part: &Part,
+    starting_key: Key,
+    starting_time_signature: TimeSignature,
+    beat_base: &Duration,
+  ) -> &mut Self {
+    for event in collect_note_events(part, starting_key, starting_time_signature, *beat_base) {
+      self.add_pitch_class(event.pitch_class(), event.beats.to_f64());
+    }
+    self
+  }
+
+  /// Adds all notes in every part of the given composition.
+  pub fn add_composition(&mut self, composition: &Composition) -> &mut Self {
+    let beat_base = composition.get_tempo().base_note;
+    for part in composition {
+      self.add_part(
+        part,
+        *composition.get_starting_key(),
+        *composition.get_starting_time_signature(),
+        &beat_base,
+      );
+    }
+    self
+  }
+
+  /// Removes all previously added pitch-class occurrences.
+  pub fn clear(&mut self) -> &mut Self {
+    self.histogram = [0.0; 12];
+    self
+  }
+
+  /// Returns whether no pitched content has been added to the key finder.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.histogram.iter().all(|weight| *weight <= 0.0)
+  }
+
+  /// Returns every major and minor key ranked from most to least likely.
+  ///
+  /// An empty list will be returned if no pitched content has been added.
+  #[must_use]
+  pub fn candidates(&self) -> Vec<KeyCandidate> {
+    if self.is_empty() {
+      return Vec::new();
+    }
+    let mut candidates: Vec<KeyCandidate> = (0..12)
+      .flat_map(|tonic| {
+        [KeyMode::Major, KeyMode::Minor].map(|mode| KeyCandidate {
+          key: Key::from_pitch_class(tonic, mode),
+          score: correlation(&self.histogram, self.profile.weights(mode), tonic),
+        })
+      })
+      .collect();
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(core::cmp::Ordering::Equal));
+    candidates
+  }
+
+  /// Returns the most likely key, or `None` if no pitched content has been added.
+  #[must_use]
+  pub fn best_key(&self) -> Option<Key> {
+    self.candidates().first().map(|candidate| candidate.key)
+  }
+
+  /// Detects modulations by analyzing overlapping windows of the given composition.
+  ///
+  /// Each window spans `window_beats` beats, and consecutive windows start `step_beats`
+  /// beats apart. Adjacent windows which share the same most likely key are merged
+  /// into a single region. Windows which contain no pitched content are absorbed into
+  /// the preceding region. Every window is analyzed by a new key finder using the
+  /// given key profile.
+  #[must_use]
+  pub fn find_regions(
+    composition: &Composition,
+    profile: KeyProfile,
+    window_beats: Rational,
+    step_beats: Rational,
+  ) -> Vec<KeyRegion> {
+    if !window_beats.is_positive() || !step_beats.is_positive() {
+      return Vec::new();
+    }
+    let beat_base = composition.get_tempo().base_note;
+    let events: Vec<NoteEvent> = composition
+      .iter()
+      .flat_map(|part| {
+        collect_note_events(
+          part,
+          *composition.get_starting_key(),
+          *composition.get_starting_time_signature(),
+          beat_base,
+        )
+      })
+      .collect();
+    let total_beats = events.iter().map(NoteEvent::end_beat).max().unwrap_or_default();
+    let (mut regions, mut window_start): (Vec<(KeyRegion, usize)>, Rational) = (Vec::new(), Rational::ZERO);
+    while window_start < total_beats {
+      let window_end = window_start + window_beats;
+      let mut window = Self::new(profile);
+      for event in &events {
+        let overlap = event.end_beat().min(window_end) - event.start_beat.max(window_start);
+        window.add_pitch_class(event.pitch_class(), overlap.to_f64());
+      }
+      let region_end = (window_start + step_beats).min(total_beats);
+      match (window.candidates().first(), regions.last_mut()) {
+        (Some(best), Some((region, count))) if region.key == best.key => {
+          region.end_beat = region_end;
+          region.score += best.score;
+          *count += 1;
+        }
+        (Some(best), _) => regions.push((
+          KeyRegion {
+            start_beat: window_start,
+            end_beat: region_end,
+            key: best.key,
+            score: best.score,
+          },
+          1,
+        )),
+        (None, Some((region, _))) => region.end_beat = region_end,
+        (None, None) => (),
+      }
+      window_start += step_beats;
+    }
+    regions
+      .into_iter()
+      .map(|(mut region, count)| {
+        region.score /= f64::from(u32::try_from(count).unwrap_or(u32::MAX));
+        region
+      })
+      .collect()
+  }
+}
+
+/// Returns the Pearson correlation between a pitch-class histogram and a key profile
+/// that has been rotated so that its first element lines up with the given tonic.
+fn correlation(histogram: &[f64; 12], profile: &[f64; 12], tonic: u8) -> f64 {
+  let histogram_mean = histogram.iter().sum::<f64>() / 12.0;
+  let profile_mean = profile.iter().sum::<f64>() / 12.0;
+  let (mut covariance, mut histogram_variance, mut profile_variance) = (0.0, 0.0, 0.0);
+  for (pitch_class, weight) in histogram.iter().enumerate() {
+    let x = weight - histogram_mean;
+    let y = profile[(pitch_class + 12 - usize::from(tonic)) % 12] - profile_mean;
+    covariance += x * y;
+    histogram_variance += x * x;
+    profile_variance += y * y;
+  }
+  if histogram_variance <= 0.0 || profile_variance <= 0.0 {
+    0.0
+  } else {
+    covariance / (histogram_variance * profile_variance).sqrt()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::KeySignature;
+  use crate::note::{Accidental, DurationType, Pitch, PitchName};
+
+  #[test]
+  fn test_candidates() {
+    let mut finder = KeyFinder::new(KeyProfile::KrumhanslKessler);
+    assert!(finder.candidates().is_empty());
+    [0, 2, 4, 5, 7, 9, 11, 0, 4, 7].into_iter().for_each(|pitch_class| {
+      finder.add_pitch_class(pitch_class, 1.0);
+    });
+    let candidates = finder.candidates();
+    assert_eq!(candidates.len(), 24);
+    assert_eq!(candidates[0].key, Key::new(KeySignature::C, KeyMode::Major));
+    finder.clear();
+    [9, 11, 0, 2, 4, 5, 8, 9, 0, 4, 9].into_iter().for_each(|pitch_class| {
+      finder.add_pitch_class(pitch_class, 1.0);
+    });
+    assert_eq!(finder.best_key(), Some(Key::new(KeySignature::A, KeyMode::Minor)));
+  }
+
+  #[test]
+  fn test_find_regions() {
+    let mut composition = Composition::new("Test", None, None, None);
+    let staff = composition.add_part("Part").add_section("Section").add_staff("1");
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    for _ in 0..4 {
+      for name in [PitchName::C, PitchName::E, PitchName::G, PitchName::C] {
+        staff.add_note(Pitch::new(name, 4), quarter, None);
+      }
+    }
+    for _ in 0..4 {
+      for (name, accidental) in [
+        (PitchName::B, None),
+        (PitchName::D, Some(Accidental::Sharp)),
+        (PitchName::F, Some(Accidental::Sharp)),
+        (PitchName::B, None),
+      ] {
+        staff.add_note(Pitch::new(name, 4), quarter, accidental);
+      }
+    }
+    let regions = KeyFinder::find_regions(
+      &composition,
+      KeyProfile::KrumhanslKessler,
+      Rational::from(4_u8),
+      Rational::from(4_u8),
+    );
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].key, Key::new(KeySignature::C, KeyMode::Major));
+    assert_eq!(regions[1].key, Key::new(KeySignature::B, KeyMode::Major));
+    assert_eq!(regions[1].start_beat, Rational::from(16_u8));
+    assert_eq!(regions[1].end_beat, Rational::from(32_u8));
+  }
+}
@@ -0,0 +1,17 @@
+//! This module contains tools for analyzing the harmonic and tonal
+//! content of the music in a score.
+
+mod counterpoint;
+mod events;
+mod harmony;
+mod key_finding;
+mod pitch_class_set;
+mod roman_numeral;
+mod voice_leading;
+
+pub use counterpoint::{CounterpointDiagnostic, CounterpointIssue, CounterpointRules, CounterpointValidator, Species};
+pub use harmony::{ChordAnalysis, ChordQuality, ChordSegment};
+pub use key_finding::{KeyCandidate, KeyFinder, KeyProfile, KeyRegion};
+pub use pitch_class_set::{ForteNumber, PitchClassSet, PitchClassSetAnalysis};
+pub use roman_numeral::{CadenceType, RomanNumeral, RomanNumeralKind, RomanNumeralLabel};
+pub use voice_leading::{VoiceLeadingDiagnostic, VoiceLeadingIssue, VoiceLeadingLinter};
@@ -0,0 +1,586 @@
+use crate::context::Key;
+use crate::note::{Accidental, Note};
+use crate::structure::{Chord, ChordContent};
+use crate::temporal::Timeslice;
+use amm_internal::amm_prelude::*;
+use amm_macros::{JsonDeserialize, JsonSerialize};
+
+const TRICHORDS: [[u8; 3]; 12] = [
+  [0, 1, 2],
+  [0, 1, 3],
+  [0, 1, 4],
+  [0, 1, 5],
+  [0, 1, 6],
+  [0, 2, 4],
+  [0, 2, 5],
+  [0, 2, 6],
+  [0, 2, 7],
+  [0, 3, 6],
+  [0, 3, 7],
+  [0, 4, 8],
+];
+
+const TETRACHORDS: [[u8; 4]; 29] = [
+  [0, 1, 2, 3],
+  [0, 1, 2, 4],
+  [0, 1, 3, 4],
+  [0, 1, 2, 5],
+  [0, 1, 2, 6],
+  [0, 1, 2, 7],
+  [0, 1, 4, 5],
+  [0, 1, 5, 6],
+  [0, 1, 6, 7],
+  [0, 2, 3, 5],
+  [0, 1, 3, 5],
+  [0, 2, 3, 6],
+  [0, 1, 3, 6],
+  [0, 2, 3, 7],
+  [0, 1, 4, 6],
+  [0, 1, 5, 7],
+  [0, 3, 4, 7],
+  [0, 1, 4, 7],
+  [0, 1, 4, 8],
+  [0, 1, 5, 8],
+  [0, 2, 4, 6],
+  [0, 2, 4, 7],
+  [0, 2, 5, 7],
+  [0, 2, 4, 8],
+  [0, 2, 6, 8],
+  [0, 3, 5, 8],
+  [0, 2, 5, 8],
+  [0, 3, 6, 9],
+  [0, 1, 3, 7],
+];
+
+const PENTACHORDS: [[u8; 5]; 38] = [
+  [0, 1, 2, 3, 4],
+  [0, 1, 2, 3, 5],
+  [0, 1, 2, 4, 5],
+  [0, 1, 2, 3, 6],
+  [0, 1, 2, 3, 7],
+  [0, 1, 2, 5, 6],
+  [0, 1, 2, 6, 7],
+  [0, 2, 3, 4, 6],
+  [0, 1, 2, 4, 6],
+  [0, 1, 3, 4, 6],
+  [0, 2, 3, 4, 7],
+  [0, 1, 3, 5, 6],
+  [0, 1, 2, 4, 8],
+  [0, 1, 2, 5, 7],
+  [0, 1, 2, 6, 8],
+  [0, 1, 3, 4, 7],
+  [0, 1, 3, 4, 8],
+  [0, 1, 4, 5, 7],
+  [0, 1, 3, 6, 7],
+  [0, 1, 3, 7, 8],
+  [0, 1, 4, 5, 8],
+  [0, 1, 4, 7, 8],
+  [0, 2, 3, 5, 7],
+  [0, 1, 3, 5, 7],
+  [0, 2, 3, 5, 8],
+  [0, 2, 4, 5, 8],
+  [0, 1, 3, 5, 8],
+  [0, 2, 3, 6, 8],
+  [0, 1, 3, 6, 8],
+  [0, 1, 4, 6, 8],
+  [0, 1, 3, 6, 9],
+  [0, 1, 4, 6, 9],
+  [0, 2, 4, 6, 8],
+  [0, 2, 4, 6, 9],
+  [0, 2, 4, 7, 9],
+  [0, 1, 2, 4, 7],
+  [0, 3, 4, 5, 8],
+  [0, 1, 2, 5, 8],
+];
+
+const HEXACHORDS: [[u8; 6]; 50] = [
+  [0, 1, 2, 3, 4, 5],
+  [0, 1, 2, 3, 4, 6],
+  [0, 1, 2, 3, 5, 6],
+  [0, 1, 2, 4, 5, 6],
+  [0, 1, 2, 3, 6, 7],
+  [0, 1, 2, 5, 6, 7],
+  [0, 1, 2, 6, 7, 8],
+  [0, 2, 3, 4, 5, 7],
+  [0, 1, 2, 3, 5, 7],
+  [0, 1, 3, 4, 5, 7],
+  [0, 1, 2, 4, 5, 7],
+  [0, 1, 2, 4, 6, 7],
+  [0, 1, 3, 4, 6, 7],
+  [0, 1, 3, 4, 5, 8],
+  [0, 1, 2, 4, 5, 8],
+  [0, 1, 4, 5, 6, 8],
+  [0, 1, 2, 4, 7, 8],
+  [0, 1, 2, 5, 7, 8],
+  [0, 1, 3, 4, 7, 8],
+  [0, 1, 4, 5, 8, 9],
+  [0, 2, 3, 4, 6, 8],
+  [0, 1, 2, 4, 6, 8],
+  [0, 2, 3, 5, 6, 8],
+  [0, 1, 3, 4, 6, 8],
+  [0, 1, 3, 5, 6, 8],
+  [0, 1, 3, 5, 7, 8],
+  [0, 1, 3, 4, 6, 9],
+  [0, 1, 3, 5, 6, 9],
+  [0, 1, 3, 6, 8, 9],
+  [0, 1, 3, 6, 7, 9],
+  [0, 1, 3, 5, 8, 9],
+  [0, 2, 4, 5, 7, 9],
+  [0, 2, 3, 5, 7, 9],
+  [0, 1, 3, 5, 7, 9],
+  [0, 2, 4, 6, 8, 10],
+  [0, 1, 2, 3, 4, 7],
+  [0, 1, 2, 3, 4, 8],
+  [0, 1, 2, 3, 7, 8],
+  [0, 2, 3, 4, 5, 8],
+  [0, 1, 2, 3, 5, 8],
+  [0, 1, 2, 3, 6, 8],
+  [0, 1, 2, 3, 6, 9],
+  [0, 1, 2, 5, 6, 8],
+  [0, 1, 2, 5, 6, 9],
+  [0, 2, 3, 4, 6, 9],
+  [0, 1, 2, 4, 6, 9],
+  [0, 1, 2, 4, 7, 9],
+  [0, 1, 2, 5, 7, 9],
+  [0, 1, 3, 4, 7, 9],
+  [0, 1, 4, 6, 7, 9],
+];
+
+const Z_RELATED_TETRACHORDS: [u8; 2] = [15, 29];
+const Z_RELATED_PENTACHORDS: [u8; 6] = [12, 17, 18, 36, 37, 38];
+const Z_RELATED_HEXACHORDS: [u8; 30] = [
+  3, 4, 6, 10, 11, 12, 13, 17, 19, 23, 24, 25, 26, 28, 29, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50,
+];
+
+/// Represents the Forte number which catalogs a set class by its cardinality
+/// and its position within Allen Forte's list of prime forms.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct ForteNumber {
+  /// The number of distinct pitch classes in the set class.
+  pub cardinality: u8,
+  /// The position of the set class within the list of set classes of the same cardinality.
+  pub ordinal: u8,
+  /// Whether the set class shares its interval-class vector with another set class.
+  pub z_related: bool,
+}
+
+/// Represents an unordered collection of distinct pitch classes, where 0 represents C
+/// and 11 represents B, irrespective of octave or enharmonic spelling.
+#[derive(Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct PitchClassSet {
+  pitch_classes: Vec<u8>,
+}
+
+/// Represents the result of analyzing a pitch-class set.
+#[derive(Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct PitchClassSetAnalysis {
+  /// The distinct pitch classes in the set in ascending order.
+  pub pitch_classes: Vec<u8>,
+  /// The most compact ordering of the pitch classes in the set.
+  pub normal_form: Vec<u8>,
+  /// The most compact form of the set class, transposed to start on 0.
+  pub prime_form: Vec<u8>,
+  /// The Forte number of the set class.
+  pub forte_number: ForteNumber,
+  /// The number of occurrences of each interval class from 1 to 6 within the set.
+  pub interval_class_vector: Vec<u8>,
+}
+
+impl PitchClassSet {
+  /// Creates a new pitch-class set from the given pitch classes.
+  ///
+  /// Values will be reduced modulo 12, and duplicates will be removed.
+  #[must_use]
+  pub fn new(pitch_classes: &[u8]) -> Self {
+    let mut pitch_classes: Vec<u8> = pitch_classes.iter().map(|pitch_class| pitch_class % 12).collect();
+    pitch_classes.sort_unstable();
+    pitch_classes.dedup();
+    Self { pitch_classes }
+  }
+
+  /// Creates a new pitch-class set from the sounding pitches of the given notes,
+  /// where the `key` parameter defines the key signature that the notes are written in.
+  ///
+  /// The notes are read as if they were written in a single measure, so an accidental
+  /// carries over to any later note on the same pitch which is written without one.
+  /// Rests will be ignored.
+  #[must_use]
+  pub fn from_notes<'a>(notes: impl IntoIterator<Item = &'a Note>, key: Option<Key>) -> Self {
+    let key_accidentals = key.unwrap_or_default().accidentals();
+    let mut carried_accidentals = BTreeMap::new();
+    Self::new(
+      &notes
+        .into_iter()
+        .filter(|note| !note.is_rest() && !note.is_unpitched())
+        .map(|note| {
+          let accidental = if note.accidental == Accidental::None {
+            carried_accidentals
+              .get(&note.pitch)
+              .copied()
+              .unwrap_or(key_accidentals[note.pitch.name.index()])
+          } else {
+            carried_accidentals.insert(note.pitch, note.accidental);
+            note.accidental
+          };
+          note.midi_number_with_accidental(accidental)
+        })
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  /// Creates a new pitch-class set from the notes in the given chord, where the `key`
+  /// parameter defines the key signature that the chord is written in.
+  #[must_use]
+  pub fn from_chord(chord: &Chord, key: Option<Key>) -> Self {
+    Self::from_notes(
+      chord.iter().map(|item| match item {
+        ChordContent::Note(note) => note,
+      }),
+      key,
+    )
+  }
+
+  /// Creates a new pitch-class set from the notes which start in the given timeslice,
+  /// where the `key` parameter defines the key signature that the timeslice is written in.
+  #[must_use]
+  pub fn from_timeslice(timeslice: &Timeslice, key: Option<Key>) -> Self {
+    Self::new(
+      &timeslice
+        .content
+        .iter()
+        .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+        .map(|content| content.midi_number(key))
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  /// Returns the distinct pitch classes in the set in ascending order.
+  #[must_use]
+  pub fn pitch_classes(&self) -> &[u8] {
+    &self.pitch_classes
+  }
+
+  /// Returns the number of distinct pitch classes in the set.
+  #[must_use]
+  pub fn cardinality(&self) -> usize {
+    self.pitch_classes.len()
+  }
+
+  /// Returns whether the set contains no pitch classes.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.pitch_classes.is_empty()
+  }
+
+  /// Returns whether the set contains the given pitch class.
+  #[must_use]
+  pub fn contains(&self, pitch_class: u8) -> bool {
+    self.pitch_classes.contains(&(pitch_class % 12))
+  }
+
+  /// Returns the set transposed upward by the given number of semitones (T<sub>n</sub>).
+  #[must_use]
+  pub fn transposed(&self, semitones: u8) -> Self {
+    Self::new(
+      &self
+        .pitch_classes
+        .iter()
+        .map(|pitch_class| pitch_class + semitones % 12)
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  /// Returns the set inverted around 0 and then transposed upward by the
+  /// given number of semitones (T<sub>n</sub>I).
+  #[must_use]
+  pub fn inverted(&self, semitones: u8) -> Self {
+    Self::new(
+      &self
+        .pitch_classes
+        .iter()
+        .map(|pitch_class| 12 + semitones % 12 - pitch_class)
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  /// Returns the set containing every pitch class which is not in this set.
+  #[must_use]
+  pub fn complement(&self) -> Self {
+    Self {
+      pitch_classes: (0..12).filter(|pitch_class| !self.contains(*pitch_class)).collect(),
+    }
+  }
+
+  /// Returns the normal form of the set, which is the rotation of its pitch classes
+  /// spanning the smallest interval, preferring rotations packed toward the left.
+  #[must_use]
+  pub fn normal_form(&self) -> Vec<u8> {
+    most_compact_rotation(&self.pitch_classes)
+  }
+
+  /// Returns the prime form of the set, which is the more compact of the normal forms
+  /// of the set and its inversion, transposed to start on 0.
+  #[must_use]
+  pub fn prime_form(&self) -> Vec<u8> {
+    let original = transposed_to_zero(&self.normal_form());
+    let inversion = transposed_to_zero(&self.inverted(0).normal_form());
+    if compactness(&inversion) < compactness(&original) {
+      inversion
+    } else {
+      original
+    }
+  }
+
+  /// Returns the interval-class vector of the set, which counts the number
+  /// of occurrences of each interval class from 1 to 6.
+  #[must_use]
+  pub fn interval_class_vector(&self) -> [u8; 6] {
+    let mut vector = [0; 6];
+    for (idx, lower) in self.pitch_classes.iter().enumerate() {
+      for upper in &self.pitch_classes[(idx + 1)..] {
+        let interval = upper - lower;
+        vector[usize::from(interval.min(12 - interval)) - 1] += 1;
+      }
+    }
+    vector
+  }
+
+  /// Returns the Forte number of the set class to which the set belongs.
+  #[must_use]
+  pub fn forte_number(&self) -> ForteNumber {
+    let cardinality = u8::try_from(self.cardinality()).unwrap_or_default();
+    let catalogued = if cardinality > 6 {
+      self.complement()
+    } else {
+      self.clone()
+    };
+    let (ordinal, z_related) = match catalogued.cardinality() {
+      2 => (
+        catalogued
+          .interval_class_vector()
+          .iter()
+          .position(|count| *count > 0)
+          .and_then(|idx| u8::try_from(idx + 1).ok())
+          .unwrap_or_default(),
+        false,
+      ),
+      3..=6 => catalog_position(&catalogued.prime_form()),
+      _ => (1, false),
+    };
+    ForteNumber {
+      cardinality,
+      ordinal,
+      z_related,
+    }
+  }
+
+  /// Returns every value of n for which transposing this set by n semitones
+  /// (T<sub>n</sub>) results in the given set.
+  #[must_use]
+  pub fn transpositions_to(&self, other: &Self) -> Vec<u8> {
+    (0..12)
+      .filter(|semitones| self.transposed(*semitones) == *other)
+      .collect()
+  }
+
+  /// Returns every value of n for which inverting this set and then transposing it
+  /// by n semitones (T<sub>n</sub>I) results in the given set.
+  #[must_use]
+  pub fn inversions_to(&self, other: &Self) -> Vec<u8> {
+    (0..12)
+      .filter(|semitones| self.inverted(*semitones) == *other)
+      .collect()
+  }
+
+  /// Returns whether this set belongs to the same set class as the given set,
+  /// meaning that the two sets are related by transposition or inversion.
+  #[must_use]
+  pub fn is_equivalent_to(&self, other: &Self) -> bool {
+    self.cardinality() == other.cardinality() && self.prime_form() == other.prime_form()
+  }
+
+  /// Returns whether this set is Z-related to the given set, meaning that the two sets
+  /// share an interval-class vector without belonging to the same set class.
+  #[must_use]
+  pub fn is_z_related_to(&self, other: &Self) -> bool {
+    self.interval_class_vector() == other.interval_class_vector() && !self.is_equivalent_to(other)
+  }
+
+  /// Returns a complete analysis of the set.
+  #[must_use]
+  pub fn analyze(&self) -> PitchClassSetAnalysis {
+    PitchClassSetAnalysis {
+      pitch_classes: self.pitch_classes.clone(),
+      normal_form: self.normal_form(),
+      prime_form: self.prime_form(),
+      forte_number: self.forte_number(),
+      interval_class_vector: self.interval_class_vector().to_vec(),
+    }
+  }
+}
+
+/// Returns the sort key which orders rotations from most to least compact: the span of
+/// the rotation, followed by the interval from its first pitch class to each subsequent one.
+fn compactness(rotation: &[u8]) -> Vec<u8> {
+  let first = rotation.first().copied().unwrap_or_default();
+  let intervals: Vec<u8> = rotation
+    .iter()
+    .map(|pitch_class| (pitch_class + 12 - first) % 12)
+    .collect();
+  intervals
+    .last()
+    .into_iter()
+    .chain(intervals.iter().skip(1).take(intervals.len().saturating_sub(2)))
+    .copied()
+    .collect()
+}
+
+fn most_compact_rotation(pitch_classes: &[u8]) -> Vec<u8> {
+  (0..pitch_classes.len())
+    .map(|start| {
+      let mut rotation = pitch_classes.to_vec();
+      rotation.rotate_left(start);
+      rotation
+    })
+    .min_by_key(|rotation| compactness(rotation))
+    .unwrap_or_default()
+}
+
+fn transposed_to_zero(pitch_classes: &[u8]) -> Vec<u8> {
+  let first = pitch_classes.first().copied().unwrap_or_default();
+  pitch_classes
+    .iter()
+    .map(|pitch_class| (pitch_class + 12 - first) % 12)
+    .collect()
+}
+
+fn catalog_position(prime_form: &[u8]) -> (u8, bool) {
+  let (position, z_related): (Option<usize>, &[u8]) = match prime_form.len() {
+    3 => (TRICHORDS.iter().position(|set| set == prime_form), &[]),
+    4 => (
+      TETRACHORDS.iter().position(|set| set == prime_form),
+      &Z_RELATED_TETRACHORDS,
+    ),
+    5 => (
+      PENTACHORDS.iter().position(|set| set == prime_form),
+      &Z_RELATED_PENTACHORDS,
+    ),
+    6 => (
+      HEXACHORDS.iter().position(|set| set == prime_form),
+      &Z_RELATED_HEXACHORDS,
+    ),
+    _ => (None, &[]),
+  };
+  let ordinal = position.and_then(|idx| u8::try_from(idx + 1).ok()).unwrap_or_default();
+  (ordinal, z_related.contains(&ordinal))
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for ForteNumber {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}-{}{}",
+      self.cardinality,
+      if self.z_related { "Z" } else { "" },
+      self.ordinal
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for PitchClassSet {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let pitch_classes = self
+      .pitch_classes
+      .iter()
+      .map(|pitch_class| match pitch_class {
+        10 => String::from("T"),
+        11 => String::from("E"),
+        _ => pitch_class.to_string(),
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+    write!(f, "{{{pitch_classes}}}")
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::note::{Accidental, Duration, DurationType, Pitch, PitchName};
+
+  fn forte_number(cardinality: u8, ordinal: u8, z_related: bool) -> ForteNumber {
+    ForteNumber {
+      cardinality,
+      ordinal,
+      z_related,
+    }
+  }
+
+  #[test]
+  fn test_set_forms() {
+    let set = PitchClassSet::new(&[11, 14, 17, 20]);
+    assert_eq!(set.pitch_classes(), [2, 5, 8, 11]);
+    assert_eq!(set.prime_form(), [0, 3, 6, 9]);
+    let set = PitchClassSet::new(&[8, 4, 11, 0]);
+    assert_eq!(set.normal_form(), [8, 11, 0, 4]);
+    assert_eq!(set.prime_form(), [0, 1, 4, 8]);
+    assert_eq!(set.interval_class_vector(), [1, 0, 1, 3, 1, 0]);
+    assert_eq!(set.forte_number(), forte_number(4, 19, false));
+    let major = PitchClassSet::new(&[0, 4, 7]);
+    assert_eq!(major.prime_form(), [0, 3, 7]);
+    assert_eq!(major.forte_number(), forte_number(3, 11, false));
+    assert_eq!(
+      PitchClassSet::new(&[0, 1, 3, 7]).forte_number(),
+      forte_number(4, 29, true)
+    );
+    assert_eq!(
+      PitchClassSet::new(&[0, 2, 4, 5, 7, 9, 11]).forte_number(),
+      forte_number(7, 35, false)
+    );
+    assert_eq!(
+      PitchClassSet::new(&[0, 1, 3, 4, 6, 7, 9, 10]).forte_number(),
+      forte_number(8, 28, false)
+    );
+    assert_eq!(PitchClassSet::new(&[0, 2, 7]).forte_number(), forte_number(3, 9, false));
+    assert_eq!(PitchClassSet::new(&[0, 5]).forte_number(), forte_number(2, 5, false));
+    #[cfg(feature = "print")]
+    assert_eq!(PitchClassSet::new(&[0, 1, 3, 7]).forte_number().to_string(), "4-Z29");
+  }
+
+  #[test]
+  fn test_set_relations() {
+    let major = PitchClassSet::new(&[0, 4, 7]);
+    let minor = PitchClassSet::new(&[0, 3, 7]);
+    assert!(major.transpositions_to(&minor).is_empty());
+    assert_eq!(major.inversions_to(&minor), [7]);
+    assert_eq!(major.transpositions_to(&PitchClassSet::new(&[2, 6, 9])), [2]);
+    assert!(major.is_equivalent_to(&minor));
+    assert_eq!(
+      PitchClassSet::new(&[0, 4, 8]).transpositions_to(&PitchClassSet::new(&[1, 5, 9])),
+      [1, 5, 9]
+    );
+    let all_interval = PitchClassSet::new(&[0, 1, 4, 6]);
+    let partner = PitchClassSet::new(&[0, 1, 3, 7]);
+    assert!(all_interval.is_z_related_to(&partner));
+    assert!(!all_interval.is_z_related_to(&major));
+  }
+
+  #[test]
+  fn test_set_from_chord() {
+    let mut chord = Chord::new();
+    let duration = Duration::new(DurationType::Quarter, 0);
+    chord.add_note(Pitch::new(PitchName::C, 3), duration, None);
+    chord.add_note(Pitch::new(PitchName::E, 4), duration, Some(Accidental::Flat));
+    chord.add_note(Pitch::new(PitchName::G, 4), duration, None);
+    chord.add_note(Pitch::new(PitchName::C, 5), duration, None);
+    let set = PitchClassSet::from_chord(&chord, None);
+    assert_eq!(set.pitch_classes(), [0, 3, 7]);
+    let analysis = set.analyze();
+    assert_eq!(analysis.prime_form, [0, 3, 7]);
+    assert_eq!(analysis.interval_class_vector, [0, 0, 1, 1, 1, 0]);
+    let json = analysis.serialize_json();
+    assert_eq!(PitchClassSetAnalysis::deserialize_json(&json), Ok(analysis));
+  }
+}
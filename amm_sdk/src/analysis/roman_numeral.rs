@@ -0,0 +1,607 @@
+use super::events::{collect_note_events, NoteEvent};
+use super::harmony::{ChordAnalysis, ChordQuality, Sonority};
+use crate::context::Key;
+use crate::note::{Accidental, Rational};
+use crate::Composition;
+use amm_internal::amm_prelude::*;
+
+/// Represents the harmonic role of a chord, beyond its scale degree.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RomanNumeralKind {
+  /// A chord built on a (possibly altered) degree of the current key.
+  #[default]
+  Diatonic,
+  /// A dominant or leading-tone chord which tonicizes the given scale degree (e.g., V/V),
+  /// along with the quality of the triad built on that degree.
+  ///
+  /// The `degree` of a secondary chord still refers to the scale degree of its root
+  /// within the current key (e.g., 2 for V/V).
+  Secondary { target: u8, target_quality: ChordQuality },
+  /// A major triad built on the lowered second scale degree (e.g., N6).
+  Neapolitan,
+  /// An augmented sixth chord containing the lowered sixth, tonic, and raised fourth scale degrees.
+  ItalianSixth,
+  /// An Italian augmented sixth chord with an added second scale degree.
+  FrenchSixth,
+  /// An Italian augmented sixth chord with an added lowered third scale degree.
+  GermanSixth,
+}
+
+/// Represents a chord labeled according to its function within a key (e.g., I, V7/V, viio6).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RomanNumeral {
+  /// The scale degree of the root of the chord, ranging from 1 to 7.
+  pub degree: u8,
+  /// The chromatic alteration of the root relative to the key (e.g., `Flat` for ♭VI).
+  pub alteration: Accidental,
+  /// The quality of the chord.
+  pub quality: ChordQuality,
+  /// The inversion of the chord, where 0 represents root position.
+  pub inversion: u8,
+  /// The harmonic role of the chord.
+  pub kind: RomanNumeralKind,
+}
+
+/// Represents a type of harmonic cadence.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CadenceType {
+  /// A root-position V to root-position I with the tonic in the highest voice.
+  PerfectAuthentic,
+  /// Any other resolution of a dominant or leading-tone chord to the tonic.
+  ImperfectAuthentic,
+  /// A phrase which comes to rest on the dominant.
+  Half,
+  /// A dominant chord which resolves to the submediant instead of the tonic.
+  Deceptive,
+}
+
+/// Represents a Roman numeral label attached to a span of a composition.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RomanNumeralLabel {
+  /// The beat at which the labeled chord starts, aligned to a timeslice start time.
+  pub start_beat: Rational,
+  /// The beat at which the last note of the labeled chord stops sounding.
+  pub end_beat: Rational,
+  /// The key in which the chord was analyzed.
+  pub key: Key,
+  /// The identified chord.
+  pub chord: ChordAnalysis,
+  /// The function of the chord within the key.
+  pub numeral: RomanNumeral,
+  /// The cadence which ends on this chord, if any.
+  pub cadence: Option<CadenceType>,
+}
+
+impl RomanNumeral {
+  /// Labels an identified chord according to its function within the given key.
+  #[must_use]
+  pub fn from_chord(chord: &ChordAnalysis, key: Key) -> Self {
+    let root = chord.root_pitch_class();
+    let mut pitch_classes = [false; 12];
+    chord.quality.semitones().iter().for_each(|offset| {
+      pitch_classes[usize::from((root + offset) % 12)] = true;
+    });
+    pitch_classes[usize::from(chord.bass_pitch_class())] = true;
+    Self::classify(chord, pitch_classes, key)
+  }
+
+  /// Labels every harmony in the given composition, producing one label per change of
+  /// chord with each label aligned to the start time of the timeslice in which it begins.
+  ///
+  /// Any key changes within the composition are taken into account, and cadences are
+  /// identified between consecutive labels in the same key. Timeslices containing fewer
+  /// than two distinct pitch classes do not produce a label.
+  #[must_use]
+  pub fn label_composition(composition: &Composition) -> Vec<RomanNumeralLabel> {
+    let beat_base = composition.get_tempo().base_note;
+    let events: Vec<NoteEvent> = composition
+      .iter()
+      .flat_map(|part| {
+        collect_note_events(
+          part,
+          *composition.get_starting_key(),
+          *composition.get_starting_time_signature(),
+          beat_base,
+        )
+      })
+      .collect();
+    let mut start_beats: Vec<Rational> = events.iter().map(|event| event.start_beat).collect();
+    start_beats.sort();
+    start_beats.dedup();
+    let (mut labels, mut sopranos): (Vec<RomanNumeralLabel>, Vec<u8>) = (Vec::new(), Vec::new());
+    for start_beat in start_beats {
+      let sounding: Vec<&NoteEvent> = events
+        .iter()
+        .filter(|event| event.start_beat <= start_beat && event.end_beat() > start_beat)
+        .collect();
+      let Some(key) = sounding
+        .iter()
+        .find(|event| event.start_beat == start_beat)
+        .or(sounding.first())
+        .map(|event| event.key)
+      else {
+        continue;
+      };
+      let mut sonority = Sonority::default();
+      for event in &sounding {
+        sonority.add(event.midi_number, event.pitch.name, event.accidental, event.beats);
+      }
+      let end_beat = sounding
+        .iter()
+        .map(|event| event.end_beat())
+        .fold(start_beat, Rational::max);
+      let Some(chord) = sonority.analyze(key) else {
+        continue;
+      };
+      let numeral = Self::classify(&chord, sonority.pitch_classes(), key);
+      match labels.last_mut() {
+        Some(label) if label.key == key && label.numeral == numeral && label.end_beat >= start_beat => {
+          label.end_beat = label.end_beat.max(end_beat);
+        }
+        _ => {
+          labels.push(RomanNumeralLabel {
+            start_beat,
+            end_beat,
+            key,
+            chord,
+            numeral,
+            cadence: None,
+          });
+          sopranos.push(sounding.iter().map(|event| event.midi_number).max().unwrap_or_default() % 12);
+        }
+      }
+    }
+    detect_cadences(&mut labels, &sopranos);
+    labels
+  }
+
+  /// Returns whether the chord is a major or dominant-quality chord on the fifth scale degree.
+  #[must_use]
+  pub fn is_dominant(&self) -> bool {
+    self.kind == RomanNumeralKind::Diatonic
+      && self.degree == 5
+      && self.alteration == Accidental::None
+      && matches!(
+        self.quality,
+        ChordQuality::Major | ChordQuality::DominantSeventh | ChordQuality::DominantNinth
+      )
+  }
+
+  /// Returns whether the chord is a diminished-quality chord on the seventh scale degree.
+  #[must_use]
+  pub fn is_leading_tone(&self) -> bool {
+    self.kind == RomanNumeralKind::Diatonic
+      && self.degree == 7
+      && self.alteration == Accidental::None
+      && matches!(
+        self.quality,
+        ChordQuality::Diminished | ChordQuality::DiminishedSeventh | ChordQuality::HalfDiminishedSeventh
+      )
+  }
+
+  /// Returns whether the chord is an unaltered chord on the given scale degree.
+  #[must_use]
+  pub fn is_degree(&self, degree: u8) -> bool {
+    self.kind == RomanNumeralKind::Diatonic && self.degree == degree && self.alteration == Accidental::None
+  }
+
+  fn classify(chord: &ChordAnalysis, pitch_classes: [bool; 12], key: Key) -> Self {
+    let (tonic, scale, is_minor) = (key.signature.pitch_class(), key.mode.semitones(), key.mode.is_minor());
+    let relative = |pitch_class: u8| (pitch_class + 12 - tonic) % 12;
+    let contains = |offset: u8| pitch_classes[usize::from((tonic + offset) % 12)];
+    let root = chord.root_pitch_class();
+    if relative(chord.bass_pitch_class()) == 8 && contains(0) && contains(6) {
+      return Self {
+        degree: 6,
+        alteration: if scale[5] == 8 {
+          Accidental::None
+        } else {
+          Accidental::Flat
+        },
+        quality: chord.quality,
+        inversion: 0,
+        kind: if contains(2) {
+          RomanNumeralKind::FrenchSixth
+        } else if contains(3) {
+          RomanNumeralKind::GermanSixth
+        } else {
+          RomanNumeralKind::ItalianSixth
+        },
+      };
+    } else if relative(root) == 1 && chord.quality == ChordQuality::Major {
+      return Self {
+        degree: 2,
+        alteration: Accidental::Flat,
+        quality: chord.quality,
+        inversion: chord.inversion,
+        kind: RomanNumeralKind::Neapolitan,
+      };
+    }
+    let alteration_of = |index: usize, pitch_class: u8| {
+      let difference = (i16::from(relative(pitch_class)) - i16::from(scale[index]) + 18).rem_euclid(12) - 6;
+      if is_minor && index >= 5 && difference == 1 {
+        0
+      } else {
+        difference
+      }
+    };
+    let degree_index = (chord.root.0.diatonic_index() - key.signature.tonic().0.diatonic_index()).rem_euclid(7);
+    let degree_index = usize::try_from(degree_index).unwrap_or_default();
+    let is_diatonic = chord.quality.semitones().iter().all(|offset| {
+      let relative_tone = relative(root + offset);
+      scale.contains(&relative_tone) || (is_minor && matches!(relative_tone, 9 | 11))
+    });
+    let secondary_target = match chord.quality {
+      _ if is_diatonic => None,
+      ChordQuality::Major | ChordQuality::DominantSeventh | ChordQuality::DominantNinth => {
+        Some(((degree_index + 3) % 7, (root + 5) % 12))
+      }
+      ChordQuality::Diminished | ChordQuality::DiminishedSeventh | ChordQuality::HalfDiminishedSeventh => {
+        Some(((degree_index + 1) % 7, (root + 1) % 12))
+      }
+      _ => None,
+    }
+    .filter(|(index, pitch_class)| {
+      *index != 0 && relative(*pitch_class) == scale[*index] && triad_fifth(scale, *index) == 7
+    });
+    Self {
+      degree: u8::try_from(degree_index + 1).unwrap_or_default(),
+      alteration: Accidental::from_value(alteration_of(degree_index, root)).unwrap_or_default(),
+      quality: chord.quality,
+      inversion: chord.inversion,
+      kind: secondary_target.map_or(RomanNumeralKind::Diatonic, |(index, _)| RomanNumeralKind::Secondary {
+        target: u8::try_from(index + 1).unwrap_or_default(),
+        target_quality: if triad_third(scale, index) == 4 {
+          ChordQuality::Major
+        } else {
+          ChordQuality::Minor
+        },
+      }),
+    }
+  }
+}
+
+/// Returns the number of semitones between the given scale degree and the third above it.
+fn triad_third(scale: [u8; 7], index: usize) -> u8 {
+  (scale[(index + 2) % 7] + 12 - scale[index]) % 12
+}
+
+/// Returns the number of semitones between the given scale degree and the fifth above it.
+fn triad_fifth(scale: [u8; 7], index: usize) -> u8 {
+  (scale[(index + 4) % 7] + 12 - scale[index]) % 12
+}
+
+/// Marks each label which ends a cadence, using the pitch class of the highest sounding
+/// note at the start of each label to distinguish perfect from imperfect authentic cadences.
+fn detect_cadences(labels: &mut [RomanNumeralLabel], sopranos: &[u8]) {
+  for index in 0..labels.len() {
+    let (previous, current) = (index.checked_sub(1).map(|previous| labels[previous]), labels[index]);
+    let follows_dominant =
+      previous.is_some_and(|previous| previous.key == current.key && previous.numeral.is_dominant());
+    let follows_leading_tone =
+      previous.is_some_and(|previous| previous.key == current.key && previous.numeral.is_leading_tone());
+    let ends_phrase = labels
+      .get(index + 1)
+      .is_none_or(|next| next.start_beat > current.end_beat);
+    labels[index].cadence = if (follows_dominant || follows_leading_tone) && current.numeral.is_degree(1) {
+      let is_perfect = follows_dominant
+        && previous.is_some_and(|previous| previous.numeral.inversion == 0)
+        && current.numeral.inversion == 0
+        && sopranos[index] == current.key.signature.pitch_class();
+      Some(if is_perfect {
+        CadenceType::PerfectAuthentic
+      } else {
+        CadenceType::ImperfectAuthentic
+      })
+    } else if follows_dominant && current.numeral.is_degree(6) {
+      Some(CadenceType::Deceptive)
+    } else if current.numeral.is_dominant() && !follows_dominant && previous.is_some() && ends_phrase {
+      Some(CadenceType::Half)
+    } else {
+      None
+    };
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for RomanNumeral {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let inversion_figures = |figures: [&'static str; 4]| figures[usize::from(self.inversion.min(3))];
+    match self.kind {
+      RomanNumeralKind::ItalianSixth => return write!(f, "It+6"),
+      RomanNumeralKind::FrenchSixth => return write!(f, "Fr+6"),
+      RomanNumeralKind::GermanSixth => return write!(f, "Ger+6"),
+      RomanNumeralKind::Neapolitan => return write!(f, "N{}", inversion_figures(["", "6", "64", "64"])),
+      _ => (),
+    }
+    let is_upper = matches!(
+      self.quality,
+      ChordQuality::Major
+        | ChordQuality::Augmented
+        | ChordQuality::SuspendedSecond
+        | ChordQuality::SuspendedFourth
+        | ChordQuality::DominantSeventh
+        | ChordQuality::MajorSeventh
+        | ChordQuality::AugmentedMajorSeventh
+        | ChordQuality::DominantNinth
+        | ChordQuality::MajorNinth
+    );
+    let numeral = match self.kind {
+      RomanNumeralKind::Secondary { .. } if is_upper => numeral_text(5, true),
+      RomanNumeralKind::Secondary { .. } => numeral_text(7, false),
+      _ => numeral_text(self.degree, is_upper),
+    };
+    let symbol = match self.quality {
+      ChordQuality::Diminished | ChordQuality::DiminishedSeventh => "o",
+      ChordQuality::HalfDiminishedSeventh => "ø",
+      ChordQuality::Augmented => "+",
+      ChordQuality::MajorSeventh | ChordQuality::MinorMajorSeventh | ChordQuality::MajorNinth => "M",
+      ChordQuality::AugmentedMajorSeventh => "+M",
+      _ => "",
+    };
+    let figures = match self.quality {
+      ChordQuality::SuspendedSecond => "sus2",
+      ChordQuality::SuspendedFourth => "sus4",
+      ChordQuality::DominantNinth | ChordQuality::MajorNinth | ChordQuality::MinorNinth => "9",
+      quality if quality.is_seventh() => inversion_figures(["7", "65", "43", "42"]),
+      _ => inversion_figures(["", "6", "64", "64"]),
+    };
+    let alteration = match self.alteration {
+      _ if matches!(self.kind, RomanNumeralKind::Secondary { .. }) => "",
+      Accidental::Flat => "♭",
+      Accidental::Sharp => "♯",
+      Accidental::DoubleFlat => "𝄫",
+      Accidental::DoubleSharp => "𝄪",
+      _ => "",
+    };
+    write!(f, "{alteration}{numeral}{symbol}{figures}")?;
+    match self.kind {
+      RomanNumeralKind::Secondary { target, target_quality } => {
+        write!(f, "/{}", numeral_text(target, target_quality == ChordQuality::Major))
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(feature = "print")]
+fn numeral_text(degree: u8, is_upper: bool) -> &'static str {
+  let numerals = if is_upper {
+    ["I", "II", "III", "IV", "V", "VI", "VII"]
+  } else {
+    ["i", "ii", "iii", "iv", "v", "vi", "vii"]
+  };
+  numerals[usize::from(degree.clamp(1, 7) - 1)]
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for CadenceType {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if f.alternate() {
+      write!(
+        f,
+        "{}",
+        match self {
+          Self::PerfectAuthentic => "Perfect Authentic Cadence",
+          Self::ImperfectAuthentic => "Imperfect Authentic Cadence",
+          Self::Half => "Half Cadence",
+          Self::Deceptive => "Deceptive Cadence",
+        }
+      )
+    } else {
+      write!(
+        f,
+        "{}",
+        match self {
+          Self::PerfectAuthentic => "PAC",
+          Self::ImperfectAuthentic => "IAC",
+          Self::Half => "HC",
+          Self::Deceptive => "DC",
+        }
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+  use crate::note::{Duration, DurationType, Pitch, PitchName};
+
+  type ChordNotes<'a> = &'a [(PitchName, u8, Option<Accidental>)];
+
+  fn label(key: Key, chords: &[ChordNotes]) -> Vec<RomanNumeralLabel> {
+    let mut composition = Composition::new("Test", None, Some(key), None);
+    let staff = composition.add_part("Part").add_section("Section").add_staff("1");
+    for notes in chords {
+      let chord = staff.add_chord();
+      for (name, octave, accidental) in *notes {
+        chord.add_note(
+          Pitch::new(*name, *octave),
+          Duration::new(DurationType::Half, 0),
+          *accidental,
+        );
+      }
+    }
+    RomanNumeral::label_composition(&composition)
+  }
+
+  fn numeral_fields(numeral: &RomanNumeral) -> (u8, Accidental, ChordQuality, u8, RomanNumeralKind) {
+    (
+      numeral.degree,
+      numeral.alteration,
+      numeral.quality,
+      numeral.inversion,
+      numeral.kind,
+    )
+  }
+
+  #[test]
+  fn test_major_progression() {
+    let labels = label(
+      Key::new(KeySignature::C, KeyMode::Major),
+      &[
+        &[
+          (PitchName::C, 3, None),
+          (PitchName::E, 4, None),
+          (PitchName::G, 4, None),
+          (PitchName::C, 5, None),
+        ],
+        &[
+          (PitchName::A, 3, None),
+          (PitchName::F, 4, None),
+          (PitchName::A, 4, None),
+          (PitchName::C, 5, None),
+        ],
+        &[
+          (PitchName::D, 3, None),
+          (PitchName::F, 4, Some(Accidental::Sharp)),
+          (PitchName::A, 4, None),
+          (PitchName::C, 5, None),
+        ],
+        &[
+          (PitchName::G, 3, None),
+          (PitchName::D, 4, None),
+          (PitchName::G, 4, None),
+          (PitchName::B, 4, None),
+        ],
+        &[
+          (PitchName::C, 3, None),
+          (PitchName::E, 4, None),
+          (PitchName::G, 4, None),
+          (PitchName::C, 5, None),
+        ],
+      ],
+    );
+    let numerals: Vec<_> = labels.iter().map(|label| numeral_fields(&label.numeral)).collect();
+    assert_eq!(
+      numerals,
+      [
+        (1, Accidental::None, ChordQuality::Major, 0, RomanNumeralKind::Diatonic),
+        (4, Accidental::None, ChordQuality::Major, 1, RomanNumeralKind::Diatonic),
+        (
+          2,
+          Accidental::None,
+          ChordQuality::DominantSeventh,
+          0,
+          RomanNumeralKind::Secondary {
+            target: 5,
+            target_quality: ChordQuality::Major
+          }
+        ),
+        (5, Accidental::None, ChordQuality::Major, 0, RomanNumeralKind::Diatonic),
+        (1, Accidental::None, ChordQuality::Major, 0, RomanNumeralKind::Diatonic),
+      ]
+    );
+    #[cfg(feature = "print")]
+    assert_eq!(
+      labels.iter().map(|label| label.numeral.to_string()).collect::<Vec<_>>(),
+      ["I", "IV6", "V7/V", "V", "I"]
+    );
+    assert_eq!(labels[2].start_beat, Rational::from(4_u8));
+    assert_eq!(labels[4].cadence, Some(CadenceType::PerfectAuthentic));
+    assert!(labels[..4].iter().all(|label| label.cadence.is_none()));
+  }
+
+  #[test]
+  fn test_chromatic_chords_and_cadences() {
+    let labels = label(
+      Key::new(KeySignature::C, KeyMode::Minor),
+      &[
+        &[
+          (PitchName::C, 3, None),
+          (PitchName::E, 4, None),
+          (PitchName::G, 4, None),
+          (PitchName::C, 5, None),
+        ],
+        &[
+          (PitchName::F, 3, None),
+          (PitchName::A, 4, None),
+          (PitchName::D, 5, Some(Accidental::Flat)),
+        ],
+        &[
+          (PitchName::A, 2, None),
+          (PitchName::C, 4, None),
+          (PitchName::E, 4, None),
+          (PitchName::F, 4, Some(Accidental::Sharp)),
+        ],
+        &[
+          (PitchName::G, 2, None),
+          (PitchName::D, 4, None),
+          (PitchName::G, 4, None),
+          (PitchName::B, 4, Some(Accidental::Natural)),
+        ],
+        &[
+          (PitchName::A, 2, None),
+          (PitchName::C, 4, None),
+          (PitchName::E, 4, None),
+          (PitchName::A, 4, None),
+        ],
+        &[
+          (PitchName::D, 3, None),
+          (PitchName::F, 4, None),
+          (PitchName::B, 4, Some(Accidental::Natural)),
+          (PitchName::D, 5, None),
+        ],
+      ],
+    );
+    let numerals: Vec<_> = labels.iter().map(|label| numeral_fields(&label.numeral)).collect();
+    assert_eq!(
+      numerals,
+      [
+        (1, Accidental::None, ChordQuality::Minor, 0, RomanNumeralKind::Diatonic),
+        (
+          2,
+          Accidental::Flat,
+          ChordQuality::Major,
+          1,
+          RomanNumeralKind::Neapolitan
+        ),
+        (
+          6,
+          Accidental::None,
+          ChordQuality::DominantSeventh,
+          0,
+          RomanNumeralKind::GermanSixth
+        ),
+        (5, Accidental::None, ChordQuality::Major, 0, RomanNumeralKind::Diatonic),
+        (6, Accidental::None, ChordQuality::Major, 0, RomanNumeralKind::Diatonic),
+        (
+          7,
+          Accidental::None,
+          ChordQuality::Diminished,
+          1,
+          RomanNumeralKind::Diatonic
+        ),
+      ]
+    );
+    #[cfg(feature = "print")]
+    assert_eq!(
+      labels.iter().map(|label| label.numeral.to_string()).collect::<Vec<_>>(),
+      ["i", "N6", "Ger+6", "V", "VI", "viio6"]
+    );
+    assert_eq!(labels[4].cadence, Some(CadenceType::Deceptive));
+    let labels = label(
+      Key::new(KeySignature::C, KeyMode::Major),
+      &[
+        &[
+          (PitchName::C, 3, None),
+          (PitchName::E, 4, None),
+          (PitchName::G, 4, None),
+        ],
+        &[
+          (PitchName::F, 3, None),
+          (PitchName::A, 4, None),
+          (PitchName::C, 5, None),
+        ],
+        &[
+          (PitchName::G, 3, None),
+          (PitchName::B, 4, None),
+          (PitchName::D, 5, None),
+        ],
+      ],
+    );
+    assert_eq!(labels[2].cadence, Some(CadenceType::Half));
+  }
+}
@@ -0,0 +1,405 @@
+use super::events::{collect_voices, NoteEvent, VoiceLine};
+use super::harmony::Sonority;
+use super::roman_numeral::RomanNumeral;
+use crate::note::Rational;
+use crate::Composition;
+use alloc::collections::BTreeSet;
+use amm_internal::amm_prelude::*;
+
+const DEFAULT_MAX_SPACING: u8 = 12;
+const SATB_RANGES: [(u8, u8); 4] = [(60, 79), (55, 74), (48, 67), (40, 60)];
+
+/// Represents a type of voice-leading error.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum VoiceLeadingIssue {
+  /// Two voices a perfect fifth (or compound fifth) apart move in the same direction to another perfect fifth.
+  ParallelFifths,
+  /// Two voices an octave or unison apart move in the same direction to another octave or unison.
+  ParallelOctaves,
+  /// A lower voice sounds above the voice immediately above it.
+  VoiceCrossing,
+  /// Two adjacent upper voices are spaced further apart than allowed.
+  Spacing,
+  /// The leading tone in an outer voice of a dominant chord does not rise to the tonic.
+  UnresolvedLeadingTone,
+  /// The seventh of a seventh chord does not descend by step when the harmony changes.
+  UnresolvedSeventh,
+  /// A note lies outside the allowable range of its voice.
+  OutOfRange,
+}
+
+/// Represents a single voice-leading error found within a composition.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoiceLeadingDiagnostic {
+  /// The type of error.
+  pub issue: VoiceLeadingIssue,
+  /// The beat at which the error occurs.
+  pub start_beat: Rational,
+  /// The indices of the voices involved, where 0 represents the highest voice.
+  pub voices: Vec<usize>,
+  /// The identifiers of the notes involved in the error.
+  pub note_ids: Vec<usize>,
+}
+
+/// Represents a voice-leading linter which checks the aligned voices of a composition
+/// against the conventions of four-part chorale writing.
+///
+/// Voices are extracted from every staff in the composition, where the notes of a chord
+/// are distributed across voices by pitch, and the phrases of a `MultiVoice` are each
+/// treated as separate voices. All voices are then ordered from highest to lowest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoiceLeadingLinter {
+  ranges: Vec<(u8, u8)>,
+  max_spacing: u8,
+}
+
+impl Default for VoiceLeadingLinter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl VoiceLeadingLinter {
+  /// Creates a new linter which uses standard soprano, alto, tenor, and bass ranges and
+  /// allows at most an octave between adjacent upper voices.
+  #[must_use]
+  pub fn new() -> Self {
+    Self {
+      ranges: SATB_RANGES.to_vec(),
+      max_spacing: DEFAULT_MAX_SPACING,
+    }
+  }
+
+  /// Sets the lowest and highest allowable MIDI numbers for each voice, ordered from
+  /// the highest voice to the lowest.
+  ///
+  /// Range checks are only performed when the number of ranges matches the number of
+  /// voices found in the composition.
+  pub fn set_voice_ranges(&mut self, ranges: &[(u8, u8)]) -> &mut Self {
+    self.ranges = ranges.to_vec();
+    self
+  }
+
+  /// Sets the maximum number of semitones allowed between adjacent upper voices.
+  pub fn set_max_spacing(&mut self, semitones: u8) -> &mut Self {
+    self.max_spacing = semitones;
+    self
+  }
+
+  /// Checks every voice in the given composition and returns all errors found,
+  /// ordered by the beat at which they occur.
+  #[must_use]
+  pub fn lint(&self, composition: &Composition) -> Vec<VoiceLeadingDiagnostic> {
+    let voices = collect_voices(composition);
+    let mut onsets: Vec<Rational> = voices
+      .iter()
+      .flat_map(|voice| voice.events.iter().map(|event| event.start_beat))
+      .collect();
+    onsets.sort();
+    onsets.dedup();
+    let harmonies: Vec<Option<Harmony>> = onsets.iter().map(|onset| harmony_at(&voices, *onset)).collect();
+    let mut diagnostics = Vec::new();
+    if self.ranges.len() == voices.len() {
+      self.check_ranges(&voices, &mut diagnostics);
+    }
+    for (index, onset) in onsets.iter().enumerate() {
+      let sounding: Vec<Option<&NoteEvent>> = voices.iter().map(|voice| voice.sounding_at(*onset)).collect();
+      if let Some(previous_onset) = index.checked_sub(1).map(|previous| onsets[previous]) {
+        let previous: Vec<Option<&NoteEvent>> = voices.iter().map(|voice| voice.sounding_at(previous_onset)).collect();
+        check_parallels(*onset, &previous, &sounding, &mut diagnostics);
+      }
+      self.check_vertical(*onset, &sounding, &mut diagnostics);
+    }
+    check_resolutions(&voices, &onsets, &harmonies, &mut diagnostics);
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.start_beat, diagnostic.issue));
+    diagnostics
+  }
+
+  fn check_ranges(&self, voices: &[VoiceLine], diagnostics: &mut Vec<VoiceLeadingDiagnostic>) {
+    for (index, (voice, (lowest, highest))) in voices.iter().zip(&self.ranges).enumerate() {
+      voice
+        .events
+        .iter()
+        .filter(|event| event.midi_number < *lowest || event.midi_number > *highest)
+        .for_each(|event| {
+          diagnostics.push(VoiceLeadingDiagnostic {
+            issue: VoiceLeadingIssue::OutOfRange,
+            start_beat: event.start_beat,
+            voices: vec![index],
+            note_ids: vec![event.note_id],
+          });
+        });
+    }
+  }
+
+  fn check_vertical(
+    &self,
+    onset: Rational,
+    sounding: &[Option<&NoteEvent>],
+    diagnostics: &mut Vec<VoiceLeadingDiagnostic>,
+  ) {
+    for index in 1..sounding.len() {
+      let (Some(upper), Some(lower)) = (sounding[index - 1], sounding[index]) else {
+        continue;
+      };
+      let is_new = upper.start_beat == onset || lower.start_beat == onset;
+      let issue = if lower.midi_number > upper.midi_number {
+        Some(VoiceLeadingIssue::VoiceCrossing)
+      } else if index + 1 < sounding.len() && upper.midi_number - lower.midi_number > self.max_spacing {
+        Some(VoiceLeadingIssue::Spacing)
+      } else {
+        None
+      };
+      if let Some(issue) = issue.filter(|_| is_new) {
+        diagnostics.push(VoiceLeadingDiagnostic {
+          issue,
+          start_beat: onset,
+          voices: vec![index - 1, index],
+          note_ids: vec![upper.note_id, lower.note_id],
+        });
+      }
+    }
+  }
+}
+
+/// Represents the harmony sounding at a single point in time.
+#[derive(Copy, Clone)]
+struct Harmony {
+  numeral: RomanNumeral,
+  root: u8,
+  tonic: u8,
+}
+
+/// Returns the harmony sounding across all voices at the given beat.
+fn harmony_at(voices: &[VoiceLine], beat: Rational) -> Option<Harmony> {
+  let sounding: Vec<&NoteEvent> = voices.iter().filter_map(|voice| voice.sounding_at(beat)).collect();
+  let key = sounding.first()?.key;
+  let mut sonority = Sonority::default();
+  for event in &sounding {
+    sonority.add(event.midi_number, event.pitch.name, event.accidental, event.beats);
+  }
+  let chord = sonority.analyze(key)?;
+  Some(Harmony {
+    numeral: RomanNumeral::from_chord(&chord, key),
+    root: chord.root_pitch_class(),
+    tonic: key.signature.pitch_class(),
+  })
+}
+
+fn check_parallels(
+  onset: Rational,
+  previous: &[Option<&NoteEvent>],
+  current: &[Option<&NoteEvent>],
+  diagnostics: &mut Vec<VoiceLeadingDiagnostic>,
+) {
+  for upper in 0..current.len() {
+    for lower in (upper + 1)..current.len() {
+      let (Some(upper_from), Some(lower_from), Some(upper_to), Some(lower_to)) =
+        (previous[upper], previous[lower], current[upper], current[lower])
+      else {
+        continue;
+      };
+      let upper_motion = i16::from(upper_to.midi_number) - i16::from(upper_from.midi_number);
+      let lower_motion = i16::from(lower_to.midi_number) - i16::from(lower_from.midi_number);
+      if upper_motion == 0 || upper_motion.signum() != lower_motion.signum() {
+        continue;
+      }
+      let from_interval = upper_from.midi_number.abs_diff(lower_from.midi_number) % 12;
+      let to_interval = upper_to.midi_number.abs_diff(lower_to.midi_number) % 12;
+      let issue = match (from_interval, to_interval) {
+        (7, 7) => VoiceLeadingIssue::ParallelFifths,
+        (0, 0) => VoiceLeadingIssue::ParallelOctaves,
+        _ => continue,
+      };
+      diagnostics.push(VoiceLeadingDiagnostic {
+        issue,
+        start_beat: onset,
+        voices: vec![upper, lower],
+        note_ids: vec![
+          upper_from.note_id,
+          lower_from.note_id,
+          upper_to.note_id,
+          lower_to.note_id,
+        ],
+      });
+    }
+  }
+}
+
+fn check_resolutions(
+  voices: &[VoiceLine],
+  onsets: &[Rational],
+  harmonies: &[Option<Harmony>],
+  diagnostics: &mut Vec<VoiceLeadingDiagnostic>,
+) {
+  let harmony_at_beat = |beat: Rational| {
+    let index = onsets.partition_point(|onset| *onset < beat);
+    harmonies.get(index).copied().flatten()
+  };
+  let mut checked = BTreeSet::new();
+  for (onset, harmony) in onsets.iter().zip(harmonies) {
+    let Some(Harmony { numeral, root, tonic }) = harmony else {
+      continue;
+    };
+    for (index, voice) in voices.iter().enumerate() {
+      let Some(event) = voice.sounding_at(*onset) else {
+        continue;
+      };
+      let Some(next) = voice.next_after(event.note_id) else {
+        continue;
+      };
+      let Some(Harmony {
+        numeral: next_numeral, ..
+      }) = harmony_at_beat(next.start_beat)
+      else {
+        continue;
+      };
+      let motion = i16::from(next.midi_number) - i16::from(event.midi_number);
+      let is_outer_voice = index == 0 || index + 1 == voices.len();
+      let is_leading_tone = is_outer_voice
+        && event.pitch_class() == (tonic + 11) % 12
+        && (numeral.is_dominant() || numeral.is_leading_tone())
+        && (next_numeral.is_degree(1) || next_numeral.is_degree(6))
+        && motion != 1;
+      let is_seventh = numeral.quality.is_seventh()
+        && event.pitch_class() == (root + numeral.quality.semitones()[3]) % 12
+        && next_numeral != *numeral
+        && !(-2..=-1).contains(&motion);
+      let issue = if is_leading_tone {
+        VoiceLeadingIssue::UnresolvedLeadingTone
+      } else if is_seventh {
+        VoiceLeadingIssue::UnresolvedSeventh
+      } else {
+        continue;
+      };
+      if checked.insert((event.note_id, issue)) {
+        diagnostics.push(VoiceLeadingDiagnostic {
+          issue,
+          start_beat: next.start_beat,
+          voices: vec![index],
+          note_ids: vec![event.note_id, next.note_id],
+        });
+      }
+    }
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for VoiceLeadingIssue {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::ParallelFifths => "Parallel Fifths",
+        Self::ParallelOctaves => "Parallel Octaves",
+        Self::VoiceCrossing => "Voice Crossing",
+        Self::Spacing => "Spacing",
+        Self::UnresolvedLeadingTone => "Unresolved Leading Tone",
+        Self::UnresolvedSeventh => "Unresolved Seventh",
+        Self::OutOfRange => "Out of Range",
+      }
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for VoiceLeadingDiagnostic {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{} at beat {} in voices [{}]",
+      self.issue,
+      self.start_beat,
+      self
+        .voices
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(", ")
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::note::{Duration, DurationType, Pitch, PitchName};
+
+  fn issues(diagnostics: &[VoiceLeadingDiagnostic]) -> Vec<(VoiceLeadingIssue, Vec<usize>)> {
+    diagnostics
+      .iter()
+      .map(|diagnostic| (diagnostic.issue, diagnostic.voices.clone()))
+      .collect()
+  }
+
+  #[test]
+  fn test_parallels() {
+    let mut composition = Composition::new("Test", None, None, None);
+    let section = composition.add_part("Choir").add_section("Section");
+    let half = Duration::new(DurationType::Half, 0);
+    for (name, notes) in [
+      ("Soprano", [(PitchName::G, 4), (PitchName::A, 4)]),
+      ("Alto", [(PitchName::E, 4), (PitchName::F, 4)]),
+      ("Tenor", [(PitchName::C, 4), (PitchName::D, 4)]),
+      ("Bass", [(PitchName::C, 3), (PitchName::D, 3)]),
+    ] {
+      let staff = section.add_staff(name);
+      for (pitch_name, octave) in notes {
+        staff.add_note(Pitch::new(pitch_name, octave), half, None);
+      }
+    }
+    let diagnostics = VoiceLeadingLinter::new().lint(&composition);
+    assert_eq!(
+      issues(&diagnostics),
+      [
+        (VoiceLeadingIssue::ParallelFifths, vec![0, 2]),
+        (VoiceLeadingIssue::ParallelFifths, vec![0, 3]),
+        (VoiceLeadingIssue::ParallelOctaves, vec![2, 3]),
+      ]
+    );
+    assert!(diagnostics
+      .iter()
+      .all(|diagnostic| diagnostic.start_beat == Rational::from(2_u8)));
+    assert_eq!(diagnostics[2].note_ids.len(), 4);
+  }
+
+  #[test]
+  fn test_crossing_resolution_and_range() {
+    let mut composition = Composition::new("Test", None, None, None);
+    let section = composition.add_part("Piano").add_section("Section");
+    let half = Duration::new(DurationType::Half, 0);
+    let multivoice = section.add_staff("Upper").add_multivoice();
+    for notes in [
+      [(PitchName::C, 5), (PitchName::B, 4), (PitchName::G, 4)],
+      [(PitchName::G, 4), (PitchName::F, 4), (PitchName::C, 5)],
+    ] {
+      let phrase = multivoice.add_phrase();
+      for (pitch_name, octave) in notes {
+        phrase.add_note(Pitch::new(pitch_name, octave), half, None);
+      }
+    }
+    let staff = section.add_staff("Lower");
+    for notes in [
+      [(PitchName::E, 4), (PitchName::C, 2)],
+      [(PitchName::D, 4), (PitchName::G, 2)],
+      [(PitchName::E, 4), (PitchName::C, 3)],
+    ] {
+      let chord = staff.add_chord();
+      for (pitch_name, octave) in notes {
+        chord.add_note(Pitch::new(pitch_name, octave), half, None);
+      }
+    }
+    let diagnostics = VoiceLeadingLinter::new().lint(&composition);
+    assert_eq!(
+      issues(&diagnostics),
+      [
+        (VoiceLeadingIssue::OutOfRange, vec![3]),
+        (VoiceLeadingIssue::VoiceCrossing, vec![0, 1]),
+        (VoiceLeadingIssue::UnresolvedLeadingTone, vec![0]),
+        (VoiceLeadingIssue::UnresolvedSeventh, vec![1]),
+      ]
+    );
+    assert_eq!(diagnostics[1].start_beat, Rational::from(4_u8));
+  }
+}
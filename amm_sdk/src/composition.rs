@@ -1,7 +1,10 @@
 use crate::context::{Key, Tempo, TimeSignature};
-use crate::note::Note;
-use crate::structure::{Chord, MultiVoice, Part, Phrase, Section, Staff};
-use crate::temporal::{place_and_merge_part_timeslice, PartTimeslice};
+use crate::note::{Interval, Note, Rational, TranspositionMode};
+use crate::structure::{notated_key, transposed_key, Chord, MultiVoice, Part, Phrase, Section, Staff, Transpose};
+use crate::temporal::{
+  place_and_merge_part_timeslice, render_performance, PartTimeslice, PerformanceEvent, PerformanceStyle,
+  RealizationOptions, RealizedNote, TempoCurveOptions, TempoMap, Timeline,
+};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -57,6 +60,32 @@ impl Composition {
     }
   }
 
+  #[must_use]
+  pub fn unroll(&self) -> (Self, BTreeMap<usize, usize>) {
+    // Expands all repeats, endings, and navigation jumps into a linear structure in playback order
+    // and returns it along with a mapping from each note ID in the new Composition to the ID of the
+    // note that it was copied from
+    let mut original_note_ids = BTreeMap::new();
+    let unrolled = Self {
+      title: self.title.clone(),
+      copyright: self.copyright.clone(),
+      publisher: self.publisher.clone(),
+      composers: self.composers.clone(),
+      lyricists: self.lyricists.clone(),
+      arrangers: self.arrangers.clone(),
+      metadata: self.metadata.clone(),
+      parts: self
+        .parts
+        .iter()
+        .map(|part| part.unroll(&mut original_note_ids))
+        .collect(),
+      tempo: self.tempo,
+      starting_key: self.starting_key,
+      starting_time_signature: self.starting_time_signature,
+    };
+    (unrolled, original_note_ids)
+  }
+
   #[must_use]
   pub fn restructure_staves_as_parts(&self) -> Self {
     // Converts each staff in a part into a new part, ensuring that each part
@@ -267,19 +296,67 @@ impl Composition {
   }
 
   #[must_use]
-  pub fn get_beats(&self) -> f64 {
+  pub fn get_beats(&self) -> Rational {
     self
       .parts
       .iter()
       .map(|part| part.get_beats(&self.tempo.base_note))
-      .reduce(f64::max)
+      .max()
       .unwrap_or_default()
   }
 
+  #[must_use]
+  pub fn get_tempo_map(&self, options: &TempoCurveOptions) -> TempoMap {
+    // Note: Beats are measured in units of the base note of the composition tempo
+    TempoMap::from_parallel_timeslices(self.parts.iter().map(Part::iter_timeslices), &self.tempo, options)
+  }
+
+  #[must_use]
+  pub fn get_timeline(&self) -> Timeline {
+    // Note: Use the returned index for repeated time-based queries instead of walking the composition
+    Timeline::new(self, &TempoCurveOptions::default())
+  }
+
+  #[must_use]
+  pub fn realize(&self, options: &RealizationOptions) -> Vec<RealizedNote> {
+    // Note: Notes from all parts are merged in order of their starting beats
+    let mut notes: Vec<RealizedNote> = self
+      .parts
+      .iter()
+      .flat_map(|part| {
+        part.realize(
+          self.starting_key,
+          self.starting_time_signature,
+          &self.tempo.base_note,
+          options,
+        )
+      })
+      .collect();
+    notes.sort_by_key(|note| note.start_beat);
+    notes
+  }
+
+  #[must_use]
+  pub fn perform(&self, style: &PerformanceStyle) -> Vec<PerformanceEvent> {
+    // Note: Produces a flat list of timed events for MIDI export and synthesis, ordered by starting time
+    render_performance(self, style)
+  }
+
+  #[must_use]
+  pub fn time_at_beat(&self, beat: Rational) -> f64 {
+    // Note: Builds a new tempo map on every call, so use `get_tempo_map()` directly for repeated queries
+    self.get_tempo_map(&TempoCurveOptions::default()).time_at_beat(beat)
+  }
+
+  #[must_use]
+  pub fn beat_at_time(&self, seconds: f64) -> f64 {
+    self.get_tempo_map(&TempoCurveOptions::default()).beat_at_time(seconds)
+  }
+
   #[must_use]
   pub fn get_duration(&self) -> f64 {
-    // Note: Does not take into account fermatas or gradual tempo changes like accelerandos as these are style-dependent
-    self.get_beats() * 60.0 / f64::from(self.tempo.beats_per_minute)
+    // Note: Does not take into account fermatas as these are style-dependent
+    self.get_tempo_map(&TempoCurveOptions::default()).get_duration()
   }
 
   pub fn remove_copyright(&mut self) -> &mut Self {
@@ -332,6 +409,50 @@ impl Composition {
     self
   }
 
+  fn transpose_parts(&mut self, interval: Interval, mode: TranspositionMode, target_key: Key) -> &mut Self {
+    let (key, time_signature) = (self.starting_key, self.starting_time_signature);
+    self.parts.iter_mut().for_each(|part| {
+      let (part_key, part_target_key) = if part.is_written_pitch() {
+        (part.written_key(key), part.written_key(target_key))
+      } else {
+        (key, target_key)
+      };
+      part.transpose_in_context(interval, mode, part_key, part_target_key, time_signature);
+    });
+    self.starting_key = target_key;
+    self
+  }
+
+  pub fn transpose(&mut self, interval: Interval, mode: TranspositionMode) -> &mut Self {
+    self.transpose_parts(interval, mode, transposed_key(self.starting_key, interval, mode))
+  }
+
+  pub fn transpose_to_key(&mut self, key: Key) -> &mut Self {
+    // Note: Notes are transposed by the interval between the tonics and then spelled against the target mode
+    self.transpose_parts(
+      self.starting_key.interval_to(&key),
+      TranspositionMode::Chromatic,
+      notated_key(key),
+    )
+  }
+
+  pub fn to_sounding_pitch(&mut self) -> &mut Self {
+    let (key, time_signature) = (self.starting_key, self.starting_time_signature);
+    self.parts.iter_mut().for_each(|part| {
+      let written_key = part.written_key(key);
+      part.transpose_to_sounding_pitch(written_key, time_signature);
+    });
+    self
+  }
+
+  pub fn to_written_pitch(&mut self) -> &mut Self {
+    let (key, time_signature) = (self.starting_key, self.starting_time_signature);
+    self.parts.iter_mut().for_each(|part| {
+      part.transpose_to_written_pitch(key, time_signature);
+    });
+    self
+  }
+
   #[must_use]
   pub fn num_timeslices(&self) -> usize {
     self.parts.iter().map(Part::num_timeslices).max().unwrap_or_default()
@@ -349,10 +470,19 @@ impl Composition {
   pub fn iter_timeslices(&self) -> impl core::iter::FusedIterator<Item = PartTimeslice> {
     // Return PartTimeslices where each slice contains a map of parts and their current timeslice
     // Note: If you want timeslices for a single part, call `iter_timeslices()` on the part directly
-    let mut timeslices: Vec<(f64, PartTimeslice)> = Vec::new();
+    // Note: Parts stored in written pitch are always returned at their sounding pitch
+    let mut timeslices: Vec<(Rational, PartTimeslice)> = Vec::new();
     for part in &self.parts {
+      let mut sounding_part = None;
+      let part = if part.is_written_pitch() {
+        sounding_part
+          .insert(part.clone())
+          .transpose_to_sounding_pitch(part.written_key(self.starting_key), self.starting_time_signature)
+      } else {
+        part
+      };
       let part_name = part.get_name();
-      let (mut index, mut curr_time) = (0, 0.0);
+      let (mut index, mut curr_time) = (0, Rational::ZERO);
       for slice in part.iter_timeslices() {
         (index, curr_time) = place_and_merge_part_timeslice(part_name, &mut timeslices, slice, index, curr_time);
       }
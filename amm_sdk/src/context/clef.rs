@@ -22,6 +22,9 @@ pub enum ClefSymbol {
   ///
   /// The two dots of the F clef surround the staff line used to notate a pitch of F3.
   FClef,
+  /// The two vertical bars of the percussion clef indicate that the staff lines designate
+  /// unpitched percussion instruments rather than pitches.
+  PercussionClef,
 }
 
 /// Designates the meaning of a clef.
@@ -49,6 +52,9 @@ pub enum ClefType {
   MezzoSoprano,
   /// Designates that pitch C4 is located on the top line of the staff.
   Baritone,
+  /// Designates that the staff positions identify unpitched percussion instruments,
+  /// using the same positions as a treble clef.
+  Percussion,
 }
 
 /// Represents a clef which is used to determine the pitches for the notes on a staff.
@@ -75,6 +81,7 @@ impl Clef {
         ClefType::Bass | ClefType::Subbass => ClefSymbol::FClef,
         ClefType::Tenor | ClefType::Alto | ClefType::Soprano | ClefType::MezzoSoprano => ClefSymbol::CClef,
         ClefType::Baritone => symbol.unwrap_or(ClefSymbol::CClef),
+        ClefType::Percussion => ClefSymbol::PercussionClef,
       },
       clef_type,
     }
@@ -90,6 +97,7 @@ impl core::fmt::Display for ClefSymbol {
         Self::GClef => "G-Clef",
         Self::CClef => "C-Clef",
         Self::FClef => "F-Clef",
+        Self::PercussionClef => "Percussion Clef",
       }
     )
   }
@@ -111,6 +119,7 @@ impl core::fmt::Display for ClefType {
         Self::Soprano => "Soprano",
         Self::MezzoSoprano => "Mezzo Soprano",
         Self::Baritone => "Baritone",
+        Self::Percussion => "Percussion",
       }
     )
   }
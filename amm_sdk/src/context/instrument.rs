@@ -0,0 +1,875 @@
+use super::{Clef, ClefType, Transposition};
+use amm_internal::amm_prelude::*;
+use amm_macros::{JsonDeserialize, JsonSerialize};
+
+/// The zero-based MIDI channel reserved for percussion in General MIDI.
+pub(crate) const MIDI_PERCUSSION_CHANNEL: u8 = 9;
+
+/// The playable and comfortable ranges of each General MIDI program, as
+/// `[lowest_playable, highest_playable, lowest_comfortable, highest_comfortable]`
+/// MIDI note numbers.
+const GM_RANGES: [[u8; 4]; 128] = [
+  [21, 108, 28, 103],
+  [21, 108, 28, 103],
+  [21, 108, 28, 103],
+  [21, 108, 28, 103],
+  [21, 108, 28, 103],
+  [21, 108, 28, 103],
+  [29, 89, 36, 84],
+  [29, 89, 36, 84],
+  [60, 108, 60, 103],
+  [79, 108, 79, 105],
+  [60, 96, 60, 91],
+  [53, 89, 53, 89],
+  [45, 96, 48, 96],
+  [65, 108, 65, 103],
+  [60, 77, 60, 77],
+  [52, 88, 55, 84],
+  [36, 96, 36, 91],
+  [36, 96, 36, 91],
+  [36, 96, 36, 91],
+  [36, 96, 36, 91],
+  [36, 96, 36, 91],
+  [53, 89, 53, 84],
+  [60, 96, 60, 91],
+  [53, 89, 53, 84],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [40, 88, 40, 81],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [28, 67, 28, 60],
+  [55, 103, 55, 93],
+  [48, 91, 48, 81],
+  [36, 84, 36, 72],
+  [28, 67, 28, 55],
+  [28, 103, 36, 96],
+  [28, 103, 36, 96],
+  [23, 103, 24, 96],
+  [40, 57, 43, 55],
+  [28, 96, 36, 91],
+  [28, 96, 36, 91],
+  [28, 96, 36, 91],
+  [28, 96, 36, 91],
+  [40, 79, 45, 76],
+  [40, 79, 45, 76],
+  [40, 79, 45, 76],
+  [36, 96, 36, 96],
+  [54, 86, 55, 82],
+  [40, 77, 40, 72],
+  [26, 65, 29, 58],
+  [54, 86, 55, 82],
+  [34, 77, 41, 72],
+  [34, 86, 40, 82],
+  [34, 86, 40, 82],
+  [34, 86, 40, 82],
+  [56, 87, 56, 84],
+  [49, 80, 49, 77],
+  [44, 75, 44, 72],
+  [36, 68, 36, 65],
+  [58, 91, 60, 86],
+  [52, 81, 52, 77],
+  [34, 75, 34, 70],
+  [50, 94, 50, 89],
+  [74, 108, 74, 103],
+  [60, 96, 60, 93],
+  [72, 98, 72, 93],
+  [60, 96, 60, 91],
+  [60, 84, 60, 84],
+  [62, 88, 62, 84],
+  [74, 96, 74, 93],
+  [60, 84, 60, 84],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [21, 108, 36, 96],
+  [48, 84, 48, 79],
+  [48, 84, 48, 79],
+  [50, 84, 50, 79],
+  [55, 91, 55, 86],
+  [60, 88, 60, 84],
+  [67, 81, 67, 81],
+  [55, 96, 55, 88],
+  [60, 84, 60, 79],
+  [72, 108, 72, 103],
+  [60, 84, 60, 84],
+  [52, 88, 55, 84],
+  [60, 84, 60, 84],
+  [36, 60, 36, 60],
+  [36, 72, 36, 72],
+  [36, 72, 36, 72],
+  [36, 96, 36, 96],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+  [0, 127, 0, 127],
+];
+
+/// Represents the family to which an instrument belongs, following the
+/// groupings of the General MIDI program list.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum InstrumentFamily {
+  /// Acoustic and electric pianos, harpsichords, and clavinets.
+  #[default]
+  Piano,
+  /// Pitched percussion instruments such as celestas, mallets, and bells.
+  ChromaticPercussion,
+  /// Organs, accordions, and harmonicas.
+  Organ,
+  /// Acoustic and electric guitars.
+  Guitar,
+  /// Acoustic, electric, and synthesized basses.
+  Bass,
+  /// Orchestral string instruments, harp, and timpani.
+  Strings,
+  /// String ensembles, choirs, and orchestral hits.
+  Ensemble,
+  /// Brass instruments and brass sections.
+  Brass,
+  /// Saxophones and double- and single-reed woodwinds.
+  Reed,
+  /// Flutes, recorders, and other pipe instruments.
+  Pipe,
+  /// Synthesized lead sounds.
+  SynthLead,
+  /// Synthesized pad sounds.
+  SynthPad,
+  /// Synthesized sound effects.
+  SynthEffects,
+  /// Folk and non-Western instruments.
+  Ethnic,
+  /// Percussive sounds available as pitched programs.
+  Percussive,
+  /// Non-musical sound effects.
+  SoundEffects,
+  /// Unpitched percussion played on the General MIDI percussion channel.
+  Percussion,
+}
+
+/// Represents an instrument from the General MIDI program list.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum MidiInstrument {
+  /// General MIDI program 1: Grand Piano.
+  #[default]
+  GrandPiano = 0,
+  /// General MIDI program 2: Bright Piano.
+  BrightPiano = 1,
+  /// General MIDI program 3: Electric Piano.
+  ElectricPiano = 2,
+  /// General MIDI program 4: Honky-Tonk Piano.
+  HonkyTonkPiano = 3,
+  /// General MIDI program 5: Electric Piano 1.
+  ElectricPiano1 = 4,
+  /// General MIDI program 6: Electric Piano 2.
+  ElectricPiano2 = 5,
+  /// General MIDI program 7: Harpsichord.
+  Harpsichord = 6,
+  /// General MIDI program 8: Clavinet.
+  Clavinet = 7,
+  /// General MIDI program 9: Celesta.
+  Celesta = 8,
+  /// General MIDI program 10: Glockenspiel.
+  Glockenspiel = 9,
+  /// General MIDI program 11: Music Box.
+  MusicBox = 10,
+  /// General MIDI program 12: Vibraphone.
+  Vibraphone = 11,
+  /// General MIDI program 13: Marimba.
+  Marimba = 12,
+  /// General MIDI program 14: Xylophone.
+  Xylophone = 13,
+  /// General MIDI program 15: Tubular Bells.
+  TubularBells = 14,
+  /// General MIDI program 16: Dulcimer.
+  Dulcimer = 15,
+  /// General MIDI program 17: Drawbar Organ.
+  DrawbarOrgan = 16,
+  /// General MIDI program 18: Percussive Organ.
+  PercussiveOrgan = 17,
+  /// General MIDI program 19: Rock Organ.
+  RockOrgan = 18,
+  /// General MIDI program 20: Church Organ.
+  ChurchOrgan = 19,
+  /// General MIDI program 21: Reed Organ.
+  ReedOrgan = 20,
+  /// General MIDI program 22: Accordion.
+  Accordion = 21,
+  /// General MIDI program 23: Harmonica.
+  Harmonica = 22,
+  /// General MIDI program 24: Tango Accordion.
+  TangoAccordion = 23,
+  /// General MIDI program 25: Nylon Guitar.
+  NylonGuitar = 24,
+  /// General MIDI program 26: Steel Guitar.
+  SteelGuitar = 25,
+  /// General MIDI program 27: Jazz Guitar.
+  JazzGuitar = 26,
+  /// General MIDI program 28: Clean Guitar.
+  CleanGuitar = 27,
+  /// General MIDI program 29: Muted Guitar.
+  MutedGuitar = 28,
+  /// General MIDI program 30: Overdriven Guitar.
+  OverdrivenGuitar = 29,
+  /// General MIDI program 31: Distortion Guitar.
+  DistortionGuitar = 30,
+  /// General MIDI program 32: Guitar Harmonics.
+  GuitarHarmonics = 31,
+  /// General MIDI program 33: Acoustic Bass.
+  AcousticBass = 32,
+  /// General MIDI program 34: Fingered Bass.
+  FingeredBass = 33,
+  /// General MIDI program 35: Electric Bass.
+  ElectricBass = 34,
+  /// General MIDI program 36: Fretless Bass.
+  FretlessBass = 35,
+  /// General MIDI program 37: Slap Bass 1.
+  SlapBass1 = 36,
+  /// General MIDI program 38: Slap Bass 2.
+  SlapBass2 = 37,
+  /// General MIDI program 39: Synth Bass 1.
+  SynthBass1 = 38,
+  /// General MIDI program 40: Synth Bass 2.
+  SynthBass2 = 39,
+  /// General MIDI program 41: Violin.
+  Violin = 40,
+  /// General MIDI program 42: Viola.
+  Viola = 41,
+  /// General MIDI program 43: Cello.
+  Cello = 42,
+  /// General MIDI program 44: Contrabass.
+  Contrabass = 43,
+  /// General MIDI program 45: Tremolo Strings.
+  TremoloStrings = 44,
+  /// General MIDI program 46: Pizzicato Strings.
+  PizzicatoStrings = 45,
+  /// General MIDI program 47: Harp.
+  Harp = 46,
+  /// General MIDI program 48: Timpani.
+  Timpani = 47,
+  /// General MIDI program 49: String Ensemble 1.
+  StringEnsemble1 = 48,
+  /// General MIDI program 50: String Ensemble 2.
+  StringEnsemble2 = 49,
+  /// General MIDI program 51: Synth Strings 1.
+  SynthStrings1 = 50,
+  /// General MIDI program 52: Synth Strings 2.
+  SynthStrings2 = 51,
+  /// General MIDI program 53: Choir Aahs.
+  ChoirAahs = 52,
+  /// General MIDI program 54: Choir Oohs.
+  ChoirOohs = 53,
+  /// General MIDI program 55: Synth Voice.
+  SynthVoice = 54,
+  /// General MIDI program 56: Orchestra Hit.
+  OrchestraHit = 55,
+  /// General MIDI program 57: Trumpet.
+  Trumpet = 56,
+  /// General MIDI program 58: Trombone.
+  Trombone = 57,
+  /// General MIDI program 59: Tuba.
+  Tuba = 58,
+  /// General MIDI program 60: Muted Trumpet.
+  MutedTrumpet = 59,
+  /// General MIDI program 61: French Horn.
+  FrenchHorn = 60,
+  /// General MIDI program 62: Brass Section.
+  BrassSection = 61,
+  /// General MIDI program 63: Synth Brass 1.
+  SynthBrass1 = 62,
+  /// General MIDI program 64: Synth Brass 2.
+  SynthBrass2 = 63,
+  /// General MIDI program 65: Soprano Sax.
+  SopranoSax = 64,
+  /// General MIDI program 66: Alto Sax.
+  AltoSax = 65,
+  /// General MIDI program 67: Tenor Sax.
+  TenorSax = 66,
+  /// General MIDI program 68: Baritone Sax.
+  BaritoneSax = 67,
+  /// General MIDI program 69: Oboe.
+  Oboe = 68,
+  /// General MIDI program 70: English Horn.
+  EnglishHorn = 69,
+  /// General MIDI program 71: Bassoon.
+  Bassoon = 70,
+  /// General MIDI program 72: Clarinet.
+  Clarinet = 71,
+  /// General MIDI program 73: Piccolo.
+  Piccolo = 72,
+  /// General MIDI program 74: Flute.
+  Flute = 73,
+  /// General MIDI program 75: Recorder.
+  Recorder = 74,
+  /// General MIDI program 76: Pan Flute.
+  PanFlute = 75,
+  /// General MIDI program 77: Blown Bottle.
+  BlownBottle = 76,
+  /// General MIDI program 78: Shakuhachi.
+  Shakuhachi = 77,
+  /// General MIDI program 79: Whistle.
+  Whistle = 78,
+  /// General MIDI program 80: Ocarina.
+  Ocarina = 79,
+  /// General MIDI program 81: Square Lead.
+  SquareLead = 80,
+  /// General MIDI program 82: Sawtooth Lead.
+  SawtoothLead = 81,
+  /// General MIDI program 83: Calliope Lead.
+  CalliopeLead = 82,
+  /// General MIDI program 84: Chiff Lead.
+  ChiffLead = 83,
+  /// General MIDI program 85: Charang Lead.
+  CharangLead = 84,
+  /// General MIDI program 86: Voice Lead.
+  VoiceLead = 85,
+  /// General MIDI program 87: Fifths Lead.
+  FifthsLead = 86,
+  /// General MIDI program 88: Bass Lead.
+  BassLead = 87,
+  /// General MIDI program 89: New Age Pad.
+  NewAgePad = 88,
+  /// General MIDI program 90: Warm Pad.
+  WarmPad = 89,
+  /// General MIDI program 91: Polysynth Pad.
+  PolysynthPad = 90,
+  /// General MIDI program 92: Choir Pad.
+  ChoirPad = 91,
+  /// General MIDI program 93: Bowed Pad.
+  BowedPad = 92,
+  /// General MIDI program 94: Metallic Pad.
+  MetallicPad = 93,
+  /// General MIDI program 95: Halo Pad.
+  HaloPad = 94,
+  /// General MIDI program 96: Sweep Pad.
+  SweepPad = 95,
+  /// General MIDI program 97: Rain.
+  Rain = 96,
+  /// General MIDI program 98: Soundtrack.
+  Soundtrack = 97,
+  /// General MIDI program 99: Crystals.
+  Crystals = 98,
+  /// General MIDI program 100: Atmosphere.
+  Atmosphere = 99,
+  /// General MIDI program 101: Brightness.
+  Brightness = 100,
+  /// General MIDI program 102: Goblins.
+  Goblins = 101,
+  /// General MIDI program 103: Echoes.
+  Echoes = 102,
+  /// General MIDI program 104: Sci-Fi.
+  SciFi = 103,
+  /// General MIDI program 105: Sitar.
+  Sitar = 104,
+  /// General MIDI program 106: Banjo.
+  Banjo = 105,
+  /// General MIDI program 107: Shamisen.
+  Shamisen = 106,
+  /// General MIDI program 108: Koto.
+  Koto = 107,
+  /// General MIDI program 109: Kalimba.
+  Kalimba = 108,
+  /// General MIDI program 110: Bagpipe.
+  Bagpipe = 109,
+  /// General MIDI program 111: Fiddle.
+  Fiddle = 110,
+  /// General MIDI program 112: Shanai.
+  Shanai = 111,
+  /// General MIDI program 113: Tinkle Bell.
+  TinkleBell = 112,
+  /// General MIDI program 114: Agogo.
+  Agogo = 113,
+  /// General MIDI program 115: Steel Drums.
+  SteelDrums = 114,
+  /// General MIDI program 116: Woodblock.
+  Woodblock = 115,
+  /// General MIDI program 117: Taiko Drum.
+  TaikoDrum = 116,
+  /// General MIDI program 118: Melodic Tom.
+  MelodicTom = 117,
+  /// General MIDI program 119: Synth Drum.
+  SynthDrum = 118,
+  /// General MIDI program 120: Reverse Cymbal.
+  ReverseCymbal = 119,
+  /// General MIDI program 121: Guitar Fret Noise.
+  GuitarFretNoise = 120,
+  /// General MIDI program 122: Breath Noise.
+  BreathNoise = 121,
+  /// General MIDI program 123: Seashore.
+  Seashore = 122,
+  /// General MIDI program 124: Bird Tweet.
+  BirdTweet = 123,
+  /// General MIDI program 125: Telephone Ring.
+  TelephoneRing = 124,
+  /// General MIDI program 126: Helicopter.
+  Helicopter = 125,
+  /// General MIDI program 127: Applause.
+  Applause = 126,
+  /// General MIDI program 128: Gunshot.
+  Gunshot = 127,
+}
+
+impl MidiInstrument {
+  /// Returns the instrument corresponding to the given zero-based General MIDI program number.
+  ///
+  /// Program numbers above 127 are treated as [`MidiInstrument::GrandPiano`].
+  #[must_use]
+  pub const fn from_program(program: u8) -> Self {
+    if program > 127 {
+      Self::GrandPiano
+    } else {
+      unsafe { core::mem::transmute::<u8, MidiInstrument>(program) }
+    }
+  }
+
+  /// Returns the zero-based General MIDI program number of the instrument.
+  #[must_use]
+  pub const fn program(&self) -> u8 {
+    *self as u8
+  }
+
+  /// Returns the General MIDI family to which the instrument belongs.
+  #[must_use]
+  pub const fn family(&self) -> InstrumentFamily {
+    match self.program() / 8 {
+      0 => InstrumentFamily::Piano,
+      1 => InstrumentFamily::ChromaticPercussion,
+      2 => InstrumentFamily::Organ,
+      3 => InstrumentFamily::Guitar,
+      4 => InstrumentFamily::Bass,
+      5 => InstrumentFamily::Strings,
+      6 => InstrumentFamily::Ensemble,
+      7 => InstrumentFamily::Brass,
+      8 => InstrumentFamily::Reed,
+      9 => InstrumentFamily::Pipe,
+      10 => InstrumentFamily::SynthLead,
+      11 => InstrumentFamily::SynthPad,
+      12 => InstrumentFamily::SynthEffects,
+      13 => InstrumentFamily::Ethnic,
+      14 => InstrumentFamily::Percussive,
+      _ => InstrumentFamily::SoundEffects,
+    }
+  }
+
+  /// Returns the human-readable name of the instrument.
+  #[must_use]
+  pub const fn name(&self) -> &'static str {
+    match self {
+      Self::GrandPiano => "Grand Piano",
+      Self::BrightPiano => "Bright Piano",
+      Self::ElectricPiano => "Electric Piano",
+      Self::HonkyTonkPiano => "Honky-Tonk Piano",
+      Self::ElectricPiano1 => "Electric Piano 1",
+      Self::ElectricPiano2 => "Electric Piano 2",
+      Self::Harpsichord => "Harpsichord",
+      Self::Clavinet => "Clavinet",
+      Self::Celesta => "Celesta",
+      Self::Glockenspiel => "Glockenspiel",
+      Self::MusicBox => "Music Box",
+      Self::Vibraphone => "Vibraphone",
+      Self::Marimba => "Marimba",
+      Self::Xylophone => "Xylophone",
+      Self::TubularBells => "Tubular Bells",
+      Self::Dulcimer => "Dulcimer",
+      Self::DrawbarOrgan => "Drawbar Organ",
+      Self::PercussiveOrgan => "Percussive Organ",
+      Self::RockOrgan => "Rock Organ",
+      Self::ChurchOrgan => "Church Organ",
+      Self::ReedOrgan => "Reed Organ",
+      Self::Accordion => "Accordion",
+      Self::Harmonica => "Harmonica",
+      Self::TangoAccordion => "Tango Accordion",
+      Self::NylonGuitar => "Nylon Guitar",
+      Self::SteelGuitar => "Steel Guitar",
+      Self::JazzGuitar => "Jazz Guitar",
+      Self::CleanGuitar => "Clean Guitar",
+      Self::MutedGuitar => "Muted Guitar",
+      Self::OverdrivenGuitar => "Overdriven Guitar",
+      Self::DistortionGuitar => "Distortion Guitar",
+      Self::GuitarHarmonics => "Guitar Harmonics",
+      Self::AcousticBass => "Acoustic Bass",
+      Self::FingeredBass => "Fingered Bass",
+      Self::ElectricBass => "Electric Bass",
+      Self::FretlessBass => "Fretless Bass",
+      Self::SlapBass1 => "Slap Bass 1",
+      Self::SlapBass2 => "Slap Bass 2",
+      Self::SynthBass1 => "Synth Bass 1",
+      Self::SynthBass2 => "Synth Bass 2",
+      Self::Violin => "Violin",
+      Self::Viola => "Viola",
+      Self::Cello => "Cello",
+      Self::Contrabass => "Contrabass",
+      Self::TremoloStrings => "Tremolo Strings",
+      Self::PizzicatoStrings => "Pizzicato Strings",
+      Self::Harp => "Harp",
+      Self::Timpani => "Timpani",
+      Self::StringEnsemble1 => "String Ensemble 1",
+      Self::StringEnsemble2 => "String Ensemble 2",
+      Self::SynthStrings1 => "Synth Strings 1",
+      Self::SynthStrings2 => "Synth Strings 2",
+      Self::ChoirAahs => "Choir Aahs",
+      Self::ChoirOohs => "Choir Oohs",
+      Self::SynthVoice => "Synth Voice",
+      Self::OrchestraHit => "Orchestra Hit",
+      Self::Trumpet => "Trumpet",
+      Self::Trombone => "Trombone",
+      Self::Tuba => "Tuba",
+      Self::MutedTrumpet => "Muted Trumpet",
+      Self::FrenchHorn => "French Horn",
+      Self::BrassSection => "Brass Section",
+      Self::SynthBrass1 => "Synth Brass 1",
+      Self::SynthBrass2 => "Synth Brass 2",
+      Self::SopranoSax => "Soprano Sax",
+      Self::AltoSax => "Alto Sax",
+      Self::TenorSax => "Tenor Sax",
+      Self::BaritoneSax => "Baritone Sax",
+      Self::Oboe => "Oboe",
+      Self::EnglishHorn => "English Horn",
+      Self::Bassoon => "Bassoon",
+      Self::Clarinet => "Clarinet",
+      Self::Piccolo => "Piccolo",
+      Self::Flute => "Flute",
+      Self::Recorder => "Recorder",
+      Self::PanFlute => "Pan Flute",
+      Self::BlownBottle => "Blown Bottle",
+      Self::Shakuhachi => "Shakuhachi",
+      Self::Whistle => "Whistle",
+      Self::Ocarina => "Ocarina",
+      Self::SquareLead => "Square Lead",
+      Self::SawtoothLead => "Sawtooth Lead",
+      Self::CalliopeLead => "Calliope Lead",
+      Self::ChiffLead => "Chiff Lead",
+      Self::CharangLead => "Charang Lead",
+      Self::VoiceLead => "Voice Lead",
+      Self::FifthsLead => "Fifths Lead",
+      Self::BassLead => "Bass Lead",
+      Self::NewAgePad => "New Age Pad",
+      Self::WarmPad => "Warm Pad",
+      Self::PolysynthPad => "Polysynth Pad",
+      Self::ChoirPad => "Choir Pad",
+      Self::BowedPad => "Bowed Pad",
+      Self::MetallicPad => "Metallic Pad",
+      Self::HaloPad => "Halo Pad",
+      Self::SweepPad => "Sweep Pad",
+      Self::Rain => "Rain",
+      Self::Soundtrack => "Soundtrack",
+      Self::Crystals => "Crystals",
+      Self::Atmosphere => "Atmosphere",
+      Self::Brightness => "Brightness",
+      Self::Goblins => "Goblins",
+      Self::Echoes => "Echoes",
+      Self::SciFi => "Sci-Fi",
+      Self::Sitar => "Sitar",
+      Self::Banjo => "Banjo",
+      Self::Shamisen => "Shamisen",
+      Self::Koto => "Koto",
+      Self::Kalimba => "Kalimba",
+      Self::Bagpipe => "Bagpipe",
+      Self::Fiddle => "Fiddle",
+      Self::Shanai => "Shanai",
+      Self::TinkleBell => "Tinkle Bell",
+      Self::Agogo => "Agogo",
+      Self::SteelDrums => "Steel Drums",
+      Self::Woodblock => "Woodblock",
+      Self::TaikoDrum => "Taiko Drum",
+      Self::MelodicTom => "Melodic Tom",
+      Self::SynthDrum => "Synth Drum",
+      Self::ReverseCymbal => "Reverse Cymbal",
+      Self::GuitarFretNoise => "Guitar Fret Noise",
+      Self::BreathNoise => "Breath Noise",
+      Self::Seashore => "Seashore",
+      Self::BirdTweet => "Bird Tweet",
+      Self::TelephoneRing => "Telephone Ring",
+      Self::Helicopter => "Helicopter",
+      Self::Applause => "Applause",
+      Self::Gunshot => "Gunshot",
+    }
+  }
+
+  /// Returns the clef most commonly used to notate the instrument.
+  #[must_use]
+  pub fn default_clef(&self) -> Clef {
+    match self {
+      Self::AcousticBass
+      | Self::FingeredBass
+      | Self::ElectricBass
+      | Self::FretlessBass
+      | Self::SlapBass1
+      | Self::SlapBass2
+      | Self::SynthBass1
+      | Self::SynthBass2
+      | Self::Cello
+      | Self::Contrabass
+      | Self::Timpani
+      | Self::Trombone
+      | Self::Tuba
+      | Self::Bassoon
+      | Self::TaikoDrum => Clef::new(ClefType::Bass, None),
+      Self::Viola => Clef::new(ClefType::Alto, None),
+      _ => Clef::new(ClefType::Treble, None),
+    }
+  }
+}
+
+/// Represents the instrument which performs a part, including its General MIDI
+/// program, range, notation defaults, and transposition.
+///
+/// All ranges are specified as MIDI note numbers at sounding pitch.
+#[derive(Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct Instrument {
+  /// The name of the instrument.
+  pub name: String,
+  /// The General MIDI program used to play back the instrument.
+  pub program: MidiInstrument,
+  /// The zero-based MIDI channel assigned to the instrument, if any.
+  pub channel: Option<u8>,
+  /// The family to which the instrument belongs.
+  pub family: InstrumentFamily,
+  /// The lowest note which can be played on the instrument.
+  pub lowest_playable: u8,
+  /// The highest note which can be played on the instrument.
+  pub highest_playable: u8,
+  /// The lowest note which can be played comfortably on the instrument.
+  pub lowest_comfortable: u8,
+  /// The highest note which can be played comfortably on the instrument.
+  pub highest_comfortable: u8,
+  /// The clef most commonly used to notate the instrument.
+  pub clef: Clef,
+  /// Whether the instrument is an unpitched percussion instrument.
+  pub percussion: bool,
+  /// The transposition from the written to the sounding pitch of the instrument.
+  pub transposition: Transposition,
+}
+
+impl Instrument {
+  /// Creates a new instrument with the given name, using the General MIDI program
+  /// whose name best matches (or [`MidiInstrument::GrandPiano`] if none match).
+  #[must_use]
+  pub fn new(name: &str) -> Self {
+    let mut instrument = Self::from_program(Self::program_from_name(name).unwrap_or_default(), None);
+    instrument.name = String::from(name);
+    instrument
+  }
+
+  /// Creates a new instrument with the default name, range, and clef of the given
+  /// General MIDI program, optionally assigned to a specific zero-based MIDI channel.
+  ///
+  /// Instruments assigned to the General MIDI percussion channel (channel 10, or
+  /// zero-based channel 9) are treated as unpitched percussion instruments.
+  #[must_use]
+  pub fn from_program(program: MidiInstrument, channel: Option<u8>) -> Self {
+    let percussion = channel == Some(MIDI_PERCUSSION_CHANNEL);
+    let [lowest_playable, highest_playable, lowest_comfortable, highest_comfortable] = if percussion {
+      [0, 127, 0, 127]
+    } else {
+      GM_RANGES[usize::from(program.program())]
+    };
+    Self {
+      name: String::from(if percussion { "Percussion" } else { program.name() }),
+      program,
+      channel,
+      family: if percussion {
+        InstrumentFamily::Percussion
+      } else {
+        program.family()
+      },
+      lowest_playable,
+      highest_playable,
+      lowest_comfortable,
+      highest_comfortable,
+      clef: if percussion {
+        Clef::new(ClefType::Percussion, None)
+      } else {
+        program.default_clef()
+      },
+      percussion,
+      transposition: Transposition::default(),
+    }
+  }
+
+  /// Returns the General MIDI program whose name best matches the given instrument
+  /// name or `MusicXML` instrument sound identifier (e.g., "Violin I" or
+  /// "wind.reed.clarinet.bflat"), if any.
+  #[must_use]
+  pub fn program_from_name(name: &str) -> Option<MidiInstrument> {
+    let name = name.to_lowercase();
+    let matches = |keyword: &str| name.split(|c: char| !c.is_alphanumeric()).any(|word| word == keyword);
+    PROGRAM_KEYWORDS
+      .iter()
+      .find(|(keywords, _)| keywords.iter().all(|keyword| matches(keyword)))
+      .map(|(_, program)| *program)
+      .or_else(|| {
+        (0..=127)
+          .map(MidiInstrument::from_program)
+          .find(|program| program.name().to_lowercase() == name)
+      })
+  }
+
+  /// Returns whether the given sounding MIDI note number can be played on the instrument.
+  #[must_use]
+  pub fn is_playable(&self, midi_number: u8) -> bool {
+    (self.lowest_playable..=self.highest_playable).contains(&midi_number)
+  }
+
+  /// Returns whether the given sounding MIDI note number can be played comfortably on the instrument.
+  #[must_use]
+  pub fn is_comfortable(&self, midi_number: u8) -> bool {
+    (self.lowest_comfortable..=self.highest_comfortable).contains(&midi_number)
+  }
+}
+
+/// Keywords which identify a General MIDI program within an instrument name, in
+/// order of precedence. Every keyword in a group must appear as a separate word.
+const PROGRAM_KEYWORDS: [(&[&str], MidiInstrument); 35] = [
+  (&["english", "horn"], MidiInstrument::EnglishHorn),
+  (&["cor", "anglais"], MidiInstrument::EnglishHorn),
+  (&["contrabass"], MidiInstrument::Contrabass),
+  (&["double", "bass"], MidiInstrument::Contrabass),
+  (&["bass", "guitar"], MidiInstrument::FingeredBass),
+  (&["horn"], MidiInstrument::FrenchHorn),
+  (&["piano"], MidiInstrument::GrandPiano),
+  (&["pianoforte"], MidiInstrument::GrandPiano),
+  (&["harpsichord"], MidiInstrument::Harpsichord),
+  (&["organ"], MidiInstrument::ChurchOrgan),
+  (&["guitar"], MidiInstrument::NylonGuitar),
+  (&["violin"], MidiInstrument::Violin),
+  (&["violino"], MidiInstrument::Violin),
+  (&["viola"], MidiInstrument::Viola),
+  (&["cello"], MidiInstrument::Cello),
+  (&["violoncello"], MidiInstrument::Cello),
+  (&["harp"], MidiInstrument::Harp),
+  (&["timpani"], MidiInstrument::Timpani),
+  (&["trumpet"], MidiInstrument::Trumpet),
+  (&["trombone"], MidiInstrument::Trombone),
+  (&["tuba"], MidiInstrument::Tuba),
+  (&["soprano", "sax"], MidiInstrument::SopranoSax),
+  (&["soprano", "saxophone"], MidiInstrument::SopranoSax),
+  (&["alto", "sax"], MidiInstrument::AltoSax),
+  (&["alto", "saxophone"], MidiInstrument::AltoSax),
+  (&["tenor", "sax"], MidiInstrument::TenorSax),
+  (&["tenor", "saxophone"], MidiInstrument::TenorSax),
+  (&["baritone", "sax"], MidiInstrument::BaritoneSax),
+  (&["baritone", "saxophone"], MidiInstrument::BaritoneSax),
+  (&["oboe"], MidiInstrument::Oboe),
+  (&["bassoon"], MidiInstrument::Bassoon),
+  (&["clarinet"], MidiInstrument::Clarinet),
+  (&["piccolo"], MidiInstrument::Piccolo),
+  (&["flute"], MidiInstrument::Flute),
+  (&["recorder"], MidiInstrument::Recorder),
+];
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for InstrumentFamily {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Piano => "Piano",
+        Self::ChromaticPercussion => "Chromatic Percussion",
+        Self::Organ => "Organ",
+        Self::Guitar => "Guitar",
+        Self::Bass => "Bass",
+        Self::Strings => "Strings",
+        Self::Ensemble => "Ensemble",
+        Self::Brass => "Brass",
+        Self::Reed => "Reed",
+        Self::Pipe => "Pipe",
+        Self::SynthLead => "Synth Lead",
+        Self::SynthPad => "Synth Pad",
+        Self::SynthEffects => "Synth Effects",
+        Self::Ethnic => "Ethnic",
+        Self::Percussive => "Percussive",
+        Self::SoundEffects => "Sound Effects",
+        Self::Percussion => "Percussion",
+      }
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for MidiInstrument {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Instrument {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{} ({})", self.name, self.program)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_instrument_defaults() {
+    let cello = Instrument::new("Violoncello");
+    assert_eq!(cello.program, MidiInstrument::Cello);
+    assert_eq!(cello.family, InstrumentFamily::Strings);
+    assert_eq!(cello.clef, Clef::new(ClefType::Bass, None));
+    assert!(cello.is_playable(36) && !cello.is_playable(35));
+    assert!(!cello.is_comfortable(84));
+    assert_eq!(Instrument::new("English Horn").program, MidiInstrument::EnglishHorn);
+    assert_eq!(Instrument::new("Horn in F").program, MidiInstrument::FrenchHorn);
+    assert_eq!(
+      Instrument::program_from_name("wind.reed.clarinet.bflat"),
+      Some(MidiInstrument::Clarinet)
+    );
+    let drums = Instrument::from_program(MidiInstrument::GrandPiano, Some(MIDI_PERCUSSION_CHANNEL));
+    assert!(drums.percussion);
+    assert_eq!(drums.family, InstrumentFamily::Percussion);
+    assert_eq!(drums.clef, Clef::new(ClefType::Percussion, None));
+    assert_eq!(MidiInstrument::from_program(73), MidiInstrument::Flute);
+  }
+
+  #[test]
+  fn test_saxophone_names() {
+    for (name, program) in [
+      ("Soprano Saxophone", MidiInstrument::SopranoSax),
+      ("Alto Saxophone", MidiInstrument::AltoSax),
+      ("wind.reed.saxophone.alto", MidiInstrument::AltoSax),
+      ("Tenor Sax.", MidiInstrument::TenorSax),
+      ("Baritone Saxophone", MidiInstrument::BaritoneSax),
+    ] {
+      assert_eq!(Instrument::program_from_name(name), Some(program));
+    }
+  }
+}
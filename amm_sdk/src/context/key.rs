@@ -1,40 +1,57 @@
-use crate::note::{Accidental, PitchName};
+use crate::note::{Accidental, Interval, Pitch, PitchName};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 const FIFTHS_A_MAJOR: i8 = 3;
+const FIFTHS_A_SHARP_MAJOR: i8 = 10;
 const FIFTHS_A_FLAT_MAJOR: i8 = -4;
 const FIFTHS_B_MAJOR: i8 = 5;
+const FIFTHS_B_SHARP_MAJOR: i8 = 12;
 const FIFTHS_B_FLAT_MAJOR: i8 = -2;
 const FIFTHS_C_MAJOR: i8 = 0;
 const FIFTHS_C_SHARP_MAJOR: i8 = 7;
 const FIFTHS_C_FLAT_MAJOR: i8 = -7;
 const FIFTHS_D_MAJOR: i8 = 2;
+const FIFTHS_D_SHARP_MAJOR: i8 = 9;
 const FIFTHS_D_FLAT_MAJOR: i8 = -5;
 const FIFTHS_E_MAJOR: i8 = 4;
+const FIFTHS_E_SHARP_MAJOR: i8 = 11;
 const FIFTHS_E_FLAT_MAJOR: i8 = -3;
 const FIFTHS_F_MAJOR: i8 = -1;
 const FIFTHS_F_SHARP_MAJOR: i8 = 6;
+const FIFTHS_F_FLAT_MAJOR: i8 = -8;
 const FIFTHS_G_MAJOR: i8 = 1;
+const FIFTHS_G_SHARP_MAJOR: i8 = 8;
 const FIFTHS_G_FLAT_MAJOR: i8 = -6;
 
-const FIFTHS_F_SHARP_MINOR: i8 = 3;
-const FIFTHS_F_MINOR: i8 = -4;
-const FIFTHS_G_SHARP_MINOR: i8 = 5;
-const FIFTHS_G_MINOR: i8 = -2;
-const FIFTHS_A_MINOR: i8 = 0;
-const FIFTHS_A_SHARP_MINOR: i8 = 7;
-const FIFTHS_A_FLAT_MINOR: i8 = -7;
-const FIFTHS_B_MINOR: i8 = 2;
-const FIFTHS_B_FLAT_MINOR: i8 = -5;
-const FIFTHS_C_SHARP_MINOR: i8 = 4;
-const FIFTHS_C_MINOR: i8 = -3;
-const FIFTHS_D_MINOR: i8 = -1;
-const FIFTHS_D_SHARP_MINOR: i8 = 6;
-const FIFTHS_E_MINOR: i8 = 1;
-const FIFTHS_E_FLAT_MINOR: i8 = -6;
+const MIN_FIFTHS: i8 = -7;
+const MAX_FIFTHS: i8 = 7;
+
+const ALL_SIGNATURES: [KeySignature; 21] = [
+  KeySignature::A,
+  KeySignature::ASharp,
+  KeySignature::AFlat,
+  KeySignature::B,
+  KeySignature::BSharp,
+  KeySignature::BFlat,
+  KeySignature::C,
+  KeySignature::CSharp,
+  KeySignature::CFlat,
+  KeySignature::D,
+  KeySignature::DSharp,
+  KeySignature::DFlat,
+  KeySignature::E,
+  KeySignature::ESharp,
+  KeySignature::EFlat,
+  KeySignature::F,
+  KeySignature::FSharp,
+  KeySignature::FFlat,
+  KeySignature::G,
+  KeySignature::GSharp,
+  KeySignature::GFlat,
+];
 
 /// Represents the relative intervals between notes in a musical scale.
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -44,13 +61,117 @@ pub enum KeyMode {
   /// starting from the root note of the corresponding key:
   ///
   /// `[2, 2, 1, 2, 2, 2, 1]`
+  ///
+  /// This mode is also known as the Ionian mode.
   #[default]
   Major,
   /// Represents the following note intervals in semitones,
   /// starting from the root note of the corresponding key:
   ///
-  /// `[2, 1, 2, 2, 2, 1, 2]`
+  /// `[2, 1, 2, 2, 1, 2, 2]`
+  ///
+  /// This mode uses the same intervals as [`KeyMode::Aeolian`].
   Minor,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 1, 2, 2, 2, 1, 2]`
+  Dorian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[1, 2, 2, 2, 1, 2, 2]`
+  Phrygian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 2, 2, 1, 2, 2, 1]`
+  Lydian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 2, 1, 2, 2, 1, 2]`
+  Mixolydian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 1, 2, 2, 1, 2, 2]`
+  Aeolian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[1, 2, 2, 1, 2, 2, 2]`
+  Locrian,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 1, 2, 2, 1, 3, 1]`
+  ///
+  /// This mode is notated using the key signature of its natural minor
+  /// counterpart, with the raised seventh written as an accidental.
+  HarmonicMinor,
+  /// Represents the following note intervals in semitones,
+  /// starting from the root note of the corresponding key:
+  ///
+  /// `[2, 1, 2, 2, 2, 2, 1]`
+  ///
+  /// This mode is notated using the key signature of its natural minor
+  /// counterpart, with the raised sixth and seventh written as accidentals.
+  MelodicMinor,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl KeyMode {
+  /// Returns the number of fifths between the key signature of this mode and the
+  /// key signature of a major key with the same tonic (root note).
+  #[must_use]
+  const fn fifths_offset(self) -> i8 {
+    match self {
+      Self::Major => 0,
+      Self::Lydian => 1,
+      Self::Mixolydian => -1,
+      Self::Dorian => -2,
+      Self::Minor | Self::Aeolian | Self::HarmonicMinor | Self::MelodicMinor => -3,
+      Self::Phrygian => -4,
+      Self::Locrian => -5,
+    }
+  }
+
+  /// Returns whether the mode contains a minor third above its tonic (root note).
+  #[must_use]
+  pub const fn is_minor(&self) -> bool {
+    !matches!(self, Self::Major | Self::Lydian | Self::Mixolydian)
+  }
+
+  /// Returns the mode with the same tonic (root note) but the opposite
+  /// major or minor quality (i.e., the opposite of C-Dorian is C-Major).
+  #[must_use]
+  const fn opposite(self) -> Self {
+    if self.is_minor() {
+      Self::Major
+    } else {
+      Self::Minor
+    }
+  }
+}
+
+impl KeyMode {
+  /// Returns the number of semitones between the tonic (root note) of the mode
+  /// and each of the seven degrees of its scale.
+  #[must_use]
+  pub const fn semitones(&self) -> [u8; 7] {
+    match self {
+      Self::Major => [0, 2, 4, 5, 7, 9, 11],
+      Self::Minor | Self::Aeolian => [0, 2, 3, 5, 7, 8, 10],
+      Self::Dorian => [0, 2, 3, 5, 7, 9, 10],
+      Self::Phrygian => [0, 1, 3, 5, 7, 8, 10],
+      Self::Lydian => [0, 2, 4, 6, 7, 9, 11],
+      Self::Mixolydian => [0, 2, 4, 5, 7, 9, 10],
+      Self::Locrian => [0, 1, 3, 5, 6, 8, 10],
+      Self::HarmonicMinor => [0, 2, 3, 5, 7, 8, 11],
+      Self::MelodicMinor => [0, 2, 3, 5, 7, 9, 11],
+    }
+  }
 }
 
 /// Represents the key signature of a musical piece, not taking
@@ -66,6 +187,8 @@ pub enum KeySignature {
   AFlat,
   /// The key of B is defined by a scale with a root note (tonic) of B.
   B,
+  /// The key of B# is defined by a scale with a root note (tonic) of B#.
+  BSharp,
   /// The key of B♭ is defined by a scale with a root note (tonic) of B♭.
   BFlat,
   /// The key of C is defined by a scale with a root note (tonic) of C.
@@ -83,12 +206,16 @@ pub enum KeySignature {
   DFlat,
   /// The key of E is defined by a scale with a root note (tonic) of E.
   E,
+  /// The key of E# is defined by a scale with a root note (tonic) of E#.
+  ESharp,
   /// The key of E♭ is defined by a scale with a root note (tonic) of E♭.
   EFlat,
   /// The key of F is defined by a scale with a root note (tonic) of F.
   F,
   /// The key of F# is defined by a scale with a root note (tonic) of F#.
   FSharp,
+  /// The key of F♭ is defined by a scale with a root note (tonic) of F♭.
+  FFlat,
   /// The key of G is defined by a scale with a root note (tonic) of G.
   G,
   /// The key of G# is defined by a scale with a root note (tonic) of G#.
@@ -97,6 +224,90 @@ pub enum KeySignature {
   GFlat,
 }
 
+impl KeySignature {
+  /// Returns the circle of fifths value for a major key with this signature's tonic,
+  /// which may lie outside of the range of traditional key signatures.
+  #[must_use]
+  const fn major_fifths(self) -> i8 {
+    match self {
+      Self::A => FIFTHS_A_MAJOR,
+      Self::ASharp => FIFTHS_A_SHARP_MAJOR,
+      Self::AFlat => FIFTHS_A_FLAT_MAJOR,
+      Self::B => FIFTHS_B_MAJOR,
+      Self::BSharp => FIFTHS_B_SHARP_MAJOR,
+      Self::BFlat => FIFTHS_B_FLAT_MAJOR,
+      Self::C => FIFTHS_C_MAJOR,
+      Self::CSharp => FIFTHS_C_SHARP_MAJOR,
+      Self::CFlat => FIFTHS_C_FLAT_MAJOR,
+      Self::D => FIFTHS_D_MAJOR,
+      Self::DSharp => FIFTHS_D_SHARP_MAJOR,
+      Self::DFlat => FIFTHS_D_FLAT_MAJOR,
+      Self::E => FIFTHS_E_MAJOR,
+      Self::ESharp => FIFTHS_E_SHARP_MAJOR,
+      Self::EFlat => FIFTHS_E_FLAT_MAJOR,
+      Self::F => FIFTHS_F_MAJOR,
+      Self::FSharp => FIFTHS_F_SHARP_MAJOR,
+      Self::FFlat => FIFTHS_F_FLAT_MAJOR,
+      Self::G => FIFTHS_G_MAJOR,
+      Self::GSharp => FIFTHS_G_SHARP_MAJOR,
+      Self::GFlat => FIFTHS_G_FLAT_MAJOR,
+    }
+  }
+
+  /// Returns the pitch name and accidental of the tonic (root note) of the key signature.
+  #[must_use]
+  pub const fn tonic(&self) -> (PitchName, Accidental) {
+    match self {
+      Self::A => (PitchName::A, Accidental::None),
+      Self::ASharp => (PitchName::A, Accidental::Sharp),
+      Self::AFlat => (PitchName::A, Accidental::Flat),
+      Self::B => (PitchName::B, Accidental::None),
+      Self::BSharp => (PitchName::B, Accidental::Sharp),
+      Self::BFlat => (PitchName::B, Accidental::Flat),
+      Self::C => (PitchName::C, Accidental::None),
+      Self::CSharp => (PitchName::C, Accidental::Sharp),
+      Self::CFlat => (PitchName::C, Accidental::Flat),
+      Self::D => (PitchName::D, Accidental::None),
+      Self::DSharp => (PitchName::D, Accidental::Sharp),
+      Self::DFlat => (PitchName::D, Accidental::Flat),
+      Self::E => (PitchName::E, Accidental::None),
+      Self::ESharp => (PitchName::E, Accidental::Sharp),
+      Self::EFlat => (PitchName::E, Accidental::Flat),
+      Self::F => (PitchName::F, Accidental::None),
+      Self::FSharp => (PitchName::F, Accidental::Sharp),
+      Self::FFlat => (PitchName::F, Accidental::Flat),
+      Self::G => (PitchName::G, Accidental::None),
+      Self::GSharp => (PitchName::G, Accidental::Sharp),
+      Self::GFlat => (PitchName::G, Accidental::Flat),
+    }
+  }
+
+  /// Returns the key signature whose tonic (root note) has the given pitch name
+  /// and accidental, or `None` if no such key signature exists.
+  ///
+  /// An accidental of [`Accidental::Natural`] is treated the same as [`Accidental::None`].
+  #[must_use]
+  pub fn from_tonic(name: PitchName, accidental: Accidental) -> Option<Self> {
+    let accidental = if accidental == Accidental::Natural {
+      Accidental::None
+    } else {
+      accidental
+    };
+    ALL_SIGNATURES
+      .into_iter()
+      .find(|signature| signature.tonic() == (name, accidental))
+  }
+
+  /// Returns the pitch class of the tonic (root note) of the key signature,
+  /// where 0 represents C and 11 represents B.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  pub(crate) const fn pitch_class(self) -> u8 {
+    let (name, accidental) = self.tonic();
+    (name.natural_semitones() + accidental.value() as i16).rem_euclid(12) as u8
+  }
+}
+
 /// Represents the key of a musical piece, including both its
 /// mode (i.e., major, minor, etc.) and its signature (defining root note).
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -129,26 +340,11 @@ impl Key {
   #[must_use]
   pub fn from_fifths(fifths: i8, mode: Option<KeyMode>) -> Self {
     let mode = mode.unwrap_or(KeyMode::Major);
-    let signature = match (fifths, mode) {
-      (FIFTHS_A_MAJOR, KeyMode::Major) | (FIFTHS_A_MINOR, KeyMode::Minor) => KeySignature::A,
-      (FIFTHS_A_SHARP_MINOR, KeyMode::Minor) => KeySignature::ASharp,
-      (FIFTHS_A_FLAT_MAJOR, KeyMode::Major) | (FIFTHS_A_FLAT_MINOR, KeyMode::Minor) => KeySignature::AFlat,
-      (FIFTHS_B_MAJOR, KeyMode::Major) | (FIFTHS_B_MINOR, KeyMode::Minor) => KeySignature::B,
-      (FIFTHS_B_FLAT_MAJOR, KeyMode::Major) | (FIFTHS_B_FLAT_MINOR, KeyMode::Minor) => KeySignature::BFlat,
-      (FIFTHS_C_SHARP_MAJOR, KeyMode::Major) | (FIFTHS_C_SHARP_MINOR, KeyMode::Minor) => KeySignature::CSharp,
-      (FIFTHS_C_FLAT_MAJOR, KeyMode::Major) => KeySignature::CFlat,
-      (FIFTHS_D_MAJOR, KeyMode::Major) | (FIFTHS_D_MINOR, KeyMode::Minor) => KeySignature::D,
-      (FIFTHS_D_SHARP_MINOR, KeyMode::Minor) => KeySignature::DSharp,
-      (FIFTHS_D_FLAT_MAJOR, KeyMode::Major) => KeySignature::DFlat,
-      (FIFTHS_E_MAJOR, KeyMode::Major) | (FIFTHS_E_MINOR, KeyMode::Minor) => KeySignature::E,
-      (FIFTHS_E_FLAT_MAJOR, KeyMode::Major) | (FIFTHS_E_FLAT_MINOR, KeyMode::Minor) => KeySignature::EFlat,
-      (FIFTHS_F_MAJOR, KeyMode::Major) | (FIFTHS_F_MINOR, KeyMode::Minor) => KeySignature::F,
-      (FIFTHS_F_SHARP_MAJOR, KeyMode::Major) | (FIFTHS_F_SHARP_MINOR, KeyMode::Minor) => KeySignature::FSharp,
-      (FIFTHS_G_MAJOR, KeyMode::Major) | (FIFTHS_G_MINOR, KeyMode::Minor) => KeySignature::G,
-      (FIFTHS_G_FLAT_MAJOR, KeyMode::Major) => KeySignature::GFlat,
-      (FIFTHS_G_SHARP_MINOR, KeyMode::Minor) => KeySignature::GSharp,
-      _ => KeySignature::C,
-    };
+    let signature = ALL_SIGNATURES
+      .into_iter()
+      .find(|signature| signature.major_fifths() + mode.fifths_offset() == fifths)
+      .filter(|_| (MIN_FIFTHS..=MAX_FIFTHS).contains(&fifths))
+      .unwrap_or(KeySignature::C);
     Self { mode, signature }
   }
 
@@ -158,38 +354,13 @@ impl Key {
   /// a traditional key signature. Negative numbers are used for flats
   /// and positive numbers for sharps. For example, a key with two flats
   /// would be represented by a `fifths` value of `-2`.
+  ///
+  /// Keys which cannot be represented by a traditional key signature
+  /// (e.g., D♯-Major) will return a value of `0`.
   #[must_use]
   pub const fn fifths(&self) -> i8 {
-    match (self.signature, self.mode) {
-      (KeySignature::A, KeyMode::Major) => FIFTHS_A_MAJOR,
-      (KeySignature::A, KeyMode::Minor) => FIFTHS_A_MINOR,
-      (KeySignature::ASharp, KeyMode::Minor) => FIFTHS_A_SHARP_MINOR,
-      (KeySignature::AFlat, KeyMode::Major) => FIFTHS_A_FLAT_MAJOR,
-      (KeySignature::AFlat, KeyMode::Minor) => FIFTHS_A_FLAT_MINOR,
-      (KeySignature::B, KeyMode::Major) => FIFTHS_B_MAJOR,
-      (KeySignature::B, KeyMode::Minor) => FIFTHS_B_MINOR,
-      (KeySignature::BFlat, KeyMode::Major) => FIFTHS_B_FLAT_MAJOR,
-      (KeySignature::BFlat, KeyMode::Minor) => FIFTHS_B_FLAT_MINOR,
-      (KeySignature::C, KeyMode::Minor) => FIFTHS_C_MINOR,
-      (KeySignature::CSharp, KeyMode::Major) => FIFTHS_C_SHARP_MAJOR,
-      (KeySignature::CSharp, KeyMode::Minor) => FIFTHS_C_SHARP_MINOR,
-      (KeySignature::CFlat, KeyMode::Major) => FIFTHS_C_FLAT_MAJOR,
-      (KeySignature::D, KeyMode::Major) => FIFTHS_D_MAJOR,
-      (KeySignature::D, KeyMode::Minor) => FIFTHS_D_MINOR,
-      (KeySignature::DSharp, KeyMode::Minor) => FIFTHS_D_SHARP_MINOR,
-      (KeySignature::DFlat, KeyMode::Major) => FIFTHS_D_FLAT_MAJOR,
-      (KeySignature::E, KeyMode::Major) => FIFTHS_E_MAJOR,
-      (KeySignature::E, KeyMode::Minor) => FIFTHS_E_MINOR,
-      (KeySignature::EFlat, KeyMode::Major) => FIFTHS_E_FLAT_MAJOR,
-      (KeySignature::EFlat, KeyMode::Minor) => FIFTHS_E_FLAT_MINOR,
-      (KeySignature::F, KeyMode::Major) => FIFTHS_F_MAJOR,
-      (KeySignature::F, KeyMode::Minor) => FIFTHS_F_MINOR,
-      (KeySignature::FSharp, KeyMode::Major) => FIFTHS_F_SHARP_MAJOR,
-      (KeySignature::FSharp, KeyMode::Minor) => FIFTHS_F_SHARP_MINOR,
-      (KeySignature::G, KeyMode::Major) => FIFTHS_G_MAJOR,
-      (KeySignature::G, KeyMode::Minor) => FIFTHS_G_MINOR,
-      (KeySignature::GSharp, KeyMode::Minor) => FIFTHS_G_SHARP_MINOR,
-      (KeySignature::GFlat, KeyMode::Major) => FIFTHS_G_FLAT_MAJOR,
+    match self.signature.major_fifths() + self.mode.fifths_offset() {
+      fifths @ MIN_FIFTHS..=MAX_FIFTHS => fifths,
       _ => FIFTHS_C_MAJOR,
     }
   }
@@ -216,14 +387,13 @@ impl Key {
   /// Returns a new key with the same tonic (root note) as the current key,
   /// but with the opposite mode (i.e., the parallel key of C-Major
   /// would be C-Minor and vice versa).
+  ///
+  /// Modes with a minor third above their tonic (e.g., Dorian) are treated
+  /// as minor, and all others (e.g., Lydian) are treated as major.
   #[must_use]
   pub fn to_parallel(&self) -> Self {
     Self {
-      mode: if self.mode == KeyMode::Major {
-        KeyMode::Minor
-      } else {
-        KeyMode::Major
-      },
+      mode: self.mode.opposite(),
       signature: self.signature,
     }
   }
@@ -231,14 +401,12 @@ impl Key {
   /// Returns a new key with the same accidentals as the current key,
   /// but with the opposite mode (i.e., the relative key of C-Major
   /// would be A-Minor and vice versa).
+  ///
+  /// Modes with a minor third above their tonic (e.g., Dorian) are treated
+  /// as minor, and all others (e.g., Lydian) are treated as major.
   #[must_use]
   pub fn to_relative(&self) -> Self {
-    let new_mode = if self.mode == KeyMode::Major {
-      KeyMode::Minor
-    } else {
-      KeyMode::Major
-    };
-    Key::from_fifths(self.fifths(), Some(new_mode))
+    Key::from_fifths(self.fifths(), Some(self.mode.opposite()))
   }
 
   /// Converts the current key into its parallel key.
@@ -247,11 +415,7 @@ impl Key {
   /// key, but with the opposite mode (i.e., the parallel key of C-Major
   /// would be C-Minor and vice versa).
   pub fn make_parallel(&mut self) {
-    self.mode = if self.mode == KeyMode::Major {
-      KeyMode::Minor
-    } else {
-      KeyMode::Major
-    };
+    self.mode = self.mode.opposite();
   }
 
   /// Converts the current key into its relative key.
@@ -260,12 +424,7 @@ impl Key {
   /// key, but with the opposite mode (i.e., the relative key of C-Major
   /// would be A-Minor and vice versa).
   pub fn make_relative(&mut self) {
-    let new_mode = if self.mode == KeyMode::Major {
-      KeyMode::Minor
-    } else {
-      KeyMode::Major
-    };
-    *self = Key::from_fifths(self.fifths(), Some(new_mode));
+    *self = Key::from_fifths(self.fifths(), Some(self.mode.opposite()));
   }
 
   /// Returns the accidentals for each note in the key.
@@ -316,6 +475,80 @@ impl Key {
   }
 }
 
+impl Key {
+  /// Returns whether the key can be notated using a traditional key
+  /// signature (e.g., D♯-Major cannot, since it would require double sharps).
+  #[must_use]
+  pub fn is_standard(&self) -> bool {
+    Self::from_fifths(self.fifths(), Some(self.mode)) == *self
+  }
+
+  /// Returns a new key whose tonic (root note) has been transposed by the given interval.
+  ///
+  /// If the transposed key cannot be notated using a traditional key signature,
+  /// its enharmonic equivalent with the fewest accidentals will be returned instead.
+  #[must_use]
+  pub fn transposed(&self, interval: Interval) -> Self {
+    let (name, accidental) = self.signature.tonic();
+    interval
+      .apply(Pitch::new(name, 4), accidental)
+      .and_then(|(pitch, accidental)| KeySignature::from_tonic(pitch.name, accidental))
+      .map(|signature| Self::new(signature, self.mode))
+      .filter(Self::is_standard)
+      .unwrap_or_else(|| {
+        let pitch_class = (i16::from(self.signature.pitch_class()) + interval.semitones()).rem_euclid(12);
+        Self::from_pitch_class(u8::try_from(pitch_class).unwrap_or_default(), self.mode)
+      })
+  }
+
+  /// Creates a new key with the given mode whose tonic (root note) has the given
+  /// pitch class, where 0 represents C and 11 represents B.
+  ///
+  /// When multiple spellings of the tonic are possible (e.g., C♯ or D♭), the key
+  /// whose traditional key signature contains the fewest accidentals is returned.
+  #[must_use]
+  pub fn from_pitch_class(pitch_class: u8, mode: KeyMode) -> Self {
+    ALL_SIGNATURES
+      .into_iter()
+      .filter(|signature| signature.pitch_class() == pitch_class % 12)
+      .map(|signature| Self::new(signature, mode))
+      .filter(Self::is_standard)
+      .min_by_key(|key| key.fifths().abs())
+      .unwrap_or(Self::new(KeySignature::C, mode))
+  }
+
+  /// Transposes the tonic (root note) of the current key by the given interval.
+  ///
+  /// If the transposed key cannot be notated using a traditional key signature,
+  /// its enharmonic equivalent with the fewest accidentals will be used instead.
+  pub fn transpose(&mut self, interval: Interval) {
+    *self = self.transposed(interval);
+  }
+
+  /// Returns the smallest interval between the tonic (root note) of the current
+  /// key and the tonic of another key, ascending or descending as needed.
+  #[must_use]
+  pub fn interval_to(&self, other: &Key) -> Interval {
+    let (from_name, from_accidental) = self.signature.tonic();
+    let (to_name, to_accidental) = other.signature.tonic();
+    let from = Pitch::new(from_name, 4);
+    let to_octave = if to_name.diatonic_index() < from_name.diatonic_index() {
+      5
+    } else {
+      4
+    };
+    Interval::between(from, from_accidental, Pitch::new(to_name, to_octave), to_accidental)
+      .map(|interval| {
+        if interval.semitones() > 6 {
+          interval.inverted().reversed()
+        } else {
+          interval
+        }
+      })
+      .unwrap_or_default()
+  }
+}
+
 #[cfg(feature = "print")]
 impl core::fmt::Display for KeyMode {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -325,6 +558,14 @@ impl core::fmt::Display for KeyMode {
       match self {
         Self::Major => "Major",
         Self::Minor => "Minor",
+        Self::Dorian => "Dorian",
+        Self::Phrygian => "Phrygian",
+        Self::Lydian => "Lydian",
+        Self::Mixolydian => "Mixolydian",
+        Self::Aeolian => "Aeolian",
+        Self::Locrian => "Locrian",
+        Self::HarmonicMinor => "Harmonic Minor",
+        Self::MelodicMinor => "Melodic Minor",
       }
     )
   }
@@ -341,6 +582,7 @@ impl core::fmt::Display for KeySignature {
         Self::ASharp => "A♯",
         Self::AFlat => "A♭",
         Self::B => "B",
+        Self::BSharp => "B♯",
         Self::BFlat => "B♭",
         Self::C => "C",
         Self::CSharp => "C♯",
@@ -349,9 +591,11 @@ impl core::fmt::Display for KeySignature {
         Self::DSharp => "D♯",
         Self::DFlat => "D♭",
         Self::E => "E",
+        Self::ESharp => "E♯",
         Self::EFlat => "E♭",
         Self::F => "F",
         Self::FSharp => "F♯",
+        Self::FFlat => "F♭",
         Self::G => "G",
         Self::GSharp => "G♯",
         Self::GFlat => "G♭",
@@ -363,11 +607,74 @@ impl core::fmt::Display for KeySignature {
 #[cfg(feature = "print")]
 impl core::fmt::Display for Key {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-    write!(
-      f,
-      "{}{}",
-      self.signature,
-      if self.mode == KeyMode::Major { "" } else { "m" }
-    )
+    match self.mode {
+      KeyMode::Major => write!(f, "{}", self.signature),
+      KeyMode::Minor => write!(f, "{}m", self.signature),
+      mode => write!(f, "{} {mode}", self.signature),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_modal_fifths() {
+    assert_eq!(Key::from_fifths(0, Some(KeyMode::Dorian)).signature, KeySignature::D);
+    assert_eq!(
+      Key::from_fifths(-1, Some(KeyMode::Lydian)).signature,
+      KeySignature::BFlat
+    );
+    assert_eq!(
+      Key::from_fifths(2, Some(KeyMode::Locrian)).signature,
+      KeySignature::CSharp
+    );
+    assert_eq!(Key::new(KeySignature::E, KeyMode::Phrygian).fifths(), 0);
+    assert_eq!(Key::new(KeySignature::G, KeyMode::HarmonicMinor).fifths(), -2);
+    assert_eq!(Key::new(KeySignature::DSharp, KeyMode::Major).fifths(), 0);
+    assert!(!Key::new(KeySignature::DSharp, KeyMode::Major).is_standard());
+    assert!(Key::new(KeySignature::DSharp, KeyMode::Dorian).is_standard());
+    for mode in [
+      KeyMode::Major,
+      KeyMode::Minor,
+      KeyMode::Dorian,
+      KeyMode::Phrygian,
+      KeyMode::Lydian,
+      KeyMode::Mixolydian,
+      KeyMode::Aeolian,
+      KeyMode::Locrian,
+      KeyMode::HarmonicMinor,
+      KeyMode::MelodicMinor,
+    ] {
+      for fifths in MIN_FIFTHS..=MAX_FIFTHS {
+        let key = Key::from_fifths(fifths, Some(mode));
+        assert_eq!((key.mode, key.fifths()), (mode, fifths));
+      }
+    }
+    assert_eq!(
+      Key::from_fifths(7, Some(KeyMode::Locrian)).signature,
+      KeySignature::BSharp
+    );
+    assert_eq!(
+      Key::from_fifths(7, Some(KeyMode::Phrygian)).signature,
+      KeySignature::ESharp
+    );
+    assert_eq!(
+      Key::from_fifths(-7, Some(KeyMode::Lydian)).signature,
+      KeySignature::FFlat
+    );
+  }
+
+  #[test]
+  fn test_modal_relationships() {
+    let key = Key::new(KeySignature::G, KeyMode::Mixolydian);
+    assert!(!key.contains(PitchName::F));
+    assert_eq!(key.to_relative(), Key::new(KeySignature::A, KeyMode::Minor));
+    assert_eq!(key.to_parallel(), Key::new(KeySignature::G, KeyMode::Minor));
+    let key = Key::new(KeySignature::A, KeyMode::Dorian);
+    assert!(key.contains(PitchName::F));
+    assert_eq!(key.to_relative(), Key::new(KeySignature::G, KeyMode::Major));
+    assert_eq!(key.to_parallel(), Key::new(KeySignature::A, KeyMode::Major));
   }
 }
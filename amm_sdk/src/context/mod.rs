@@ -4,16 +4,21 @@
 mod clef;
 mod dynamic;
 mod id;
+mod instrument;
 mod key;
 mod tempo;
 mod tempo_suggestion;
 mod time_signature;
+mod transposition;
 
 pub(crate) use id::generate_id;
 
 pub use clef::{Clef, ClefSymbol, ClefType};
 pub use dynamic::Dynamic;
+pub(crate) use instrument::MIDI_PERCUSSION_CHANNEL;
+pub use instrument::{Instrument, InstrumentFamily, MidiInstrument};
 pub use key::{Key, KeyMode, KeySignature};
 pub use tempo::Tempo;
 pub use tempo_suggestion::{TempoMarking, TempoSuggestion};
 pub use time_signature::{TimeSignature, TimeSignatureType};
+pub use transposition::Transposition;
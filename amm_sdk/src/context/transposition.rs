@@ -0,0 +1,139 @@
+use crate::note::{Accidental, Interval, Pitch, PitchName};
+use amm_internal::amm_prelude::*;
+use amm_macros::{JsonDeserialize, JsonSerialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Represents the transposition of an instrument, which is the distance that must
+/// be added to its written pitch in order to produce its sounding (concert) pitch.
+///
+/// For example, a B♭ clarinet sounds a major second lower than written, which is
+/// represented by a `diatonic` value of `-1` and a `chromatic` value of `-2`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct Transposition {
+  /// The number of diatonic steps between the written and sounding pitch.
+  pub diatonic: i8,
+  /// The number of semitones between the written and sounding pitch.
+  pub chromatic: i8,
+  /// The number of additional octaves between the written and sounding pitch.
+  pub octave_change: i8,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Transposition {
+  /// Creates a new transposition with the given number of diatonic steps,
+  /// semitones, and additional octaves from written to sounding pitch.
+  #[must_use]
+  pub const fn new(diatonic: i8, chromatic: i8, octave_change: i8) -> Self {
+    Self {
+      diatonic,
+      chromatic,
+      octave_change,
+    }
+  }
+
+  /// Creates a new transposition whose written pitch sounds at the given interval.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation)]
+  pub fn from_interval(interval: Interval) -> Self {
+    let (steps, semitones) = (interval.steps(), interval.semitones());
+    let octave_change = (steps / 7) as i8;
+    Self {
+      diatonic: (steps % 7) as i8,
+      chromatic: (semitones - 12 * i16::from(octave_change)) as i8,
+      octave_change,
+    }
+  }
+
+  /// Returns whether the transposition leaves written pitches unchanged
+  /// (i.e., the instrument sounds at concert pitch).
+  #[must_use]
+  pub const fn is_concert_pitch(&self) -> bool {
+    self.diatonic == 0 && self.chromatic == 0 && self.octave_change == 0
+  }
+
+  /// Returns the total number of semitones between the written and sounding pitch.
+  #[must_use]
+  pub fn semitones(&self) -> i16 {
+    i16::from(self.chromatic) + 12 * i16::from(self.octave_change)
+  }
+
+  /// Returns the interval between the written and sounding pitch,
+  /// where a descending interval indicates that the instrument sounds lower than written.
+  ///
+  /// If the diatonic and chromatic steps do not describe a representable interval,
+  /// the most common interval spanning the same number of semitones is returned.
+  #[must_use]
+  pub fn interval(&self) -> Interval {
+    let steps = i16::from(self.diatonic) + 7 * i16::from(self.octave_change);
+    let from = Pitch::new(PitchName::C, 4);
+    let position = from.diatonic_value() + steps;
+    u8::try_from(position.div_euclid(7))
+      .ok()
+      .map(|octave| Pitch::new(PitchName::from_diatonic_index(position), octave))
+      .and_then(|to| {
+        Accidental::from_value(from.semitones_from_c0() + self.semitones() - to.semitones_from_c0())
+          .and_then(|accidental| Interval::between(from, Accidental::None, to, accidental))
+      })
+      .unwrap_or_else(|| Interval::from_semitones(self.semitones()))
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Transposition {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.is_concert_pitch() {
+      write!(f, "Concert Pitch")
+    } else {
+      write!(f, "Sounds {:#} from Written", self.interval())
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::note::IntervalQuality;
+
+  #[test]
+  fn test_transposition_interval() {
+    let b_flat_clarinet = Transposition::new(-1, -2, 0);
+    assert_eq!(
+      b_flat_clarinet.interval(),
+      Interval::new(IntervalQuality::Major, 2).reversed()
+    );
+    let f_horn = Transposition::new(-4, -7, 0);
+    assert_eq!(f_horn.interval(), Interval::new(IntervalQuality::Perfect, 5).reversed());
+    let bass_clarinet = Transposition::new(-1, -2, -1);
+    assert_eq!(bass_clarinet.semitones(), -14);
+    assert_eq!(Transposition::from_interval(bass_clarinet.interval()), bass_clarinet);
+    assert!(Transposition::default().is_concert_pitch());
+  }
+
+  #[test]
+  fn test_written_and_sounding_pitch() {
+    use crate::context::{Key, KeyMode, KeySignature};
+    use crate::note::Duration;
+    use crate::structure::Part;
+    let mut part = Part::new("Clarinet in B♭");
+    part
+      .add_section("Section")
+      .add_staff("Treble")
+      .add_note(Pitch::new(PitchName::D, 5), Duration::default(), None);
+    let concert_key = Key::new(KeySignature::BFlat, KeyMode::Major);
+    let written_key = Key::new(KeySignature::C, KeyMode::Major);
+    part.set_transposition(Transposition::new(-1, -2, 0), true);
+    assert_eq!(part.written_key(concert_key), written_key);
+    part.to_sounding_pitch(Some(written_key), None);
+    assert!(!part.is_written_pitch());
+    let note = part.iter_timeslices().next().unwrap().content[0].note.clone();
+    assert_eq!(
+      (note.pitch, note.accidental),
+      (Pitch::new(PitchName::C, 5), Accidental::None)
+    );
+    part.to_written_pitch(Some(concert_key), None);
+    let note = part.iter_timeslices().next().unwrap().content[0].note.clone();
+    assert_eq!(note.midi_number(Some(written_key)), 74);
+  }
+}
@@ -2,6 +2,7 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod analysis;
 mod composition;
 pub mod context;
 pub mod modification;
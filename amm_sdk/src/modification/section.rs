@@ -9,6 +9,22 @@ pub enum SectionModificationType {
   /// a few notes or measures.
   #[default]
   Accelerando,
+  /// Represents a section that begins at a coda sign, which is
+  /// jumped to from a [`ToCoda`](Self::ToCoda) section.
+  Coda,
+  /// Represents a section after which the performance returns to the
+  /// beginning of the piece (da capo) the first time it is reached.
+  DaCapo,
+  /// Represents a section after which the performance returns to the
+  /// [`Segno`](Self::Segno) section (dal segno) the first time it is reached.
+  DalSegno,
+  /// Represents a section after which the performance ends once
+  /// a da capo or dal segno jump has been taken.
+  Fine,
+  /// Represents a da capo or dal segno section whose jump takes any
+  /// repeats again (con ripetizione), rather than playing each
+  /// repeated section only once.
+  JumpWithRepeats,
   /// Represents a section that should only be played during
   /// certain iterations.
   OnlyPlay { iterations: Vec<u8> },
@@ -29,6 +45,9 @@ pub enum SectionModificationType {
   Ritardando,
   /// Represents a section with an immediate tempo reduction.
   Ritenuto,
+  /// Represents a section that begins at a segno sign, which is
+  /// jumped to from a [`DalSegno`](Self::DalSegno) section.
+  Segno,
   /// Represents a section with a gradual tempo acceleration
   /// leading to context change.
   Stringendo,
@@ -36,6 +55,21 @@ pub enum SectionModificationType {
   TempoExplicit { tempo: Tempo },
   /// Represents a section with a suggested tempo change.
   TempoImplicit { tempo: TempoSuggestion },
+  /// Represents a section after which the performance jumps to the next
+  /// [`Coda`](Self::Coda) section once a da capo or dal segno jump has been taken.
+  ToCoda,
+}
+
+impl SectionModificationType {
+  /// Returns whether the modification is a navigation marking which
+  /// affects the order in which sections are performed.
+  #[must_use]
+  pub const fn is_navigation(&self) -> bool {
+    matches!(
+      self,
+      Self::Coda | Self::DaCapo | Self::DalSegno | Self::Fine | Self::JumpWithRepeats | Self::Segno | Self::ToCoda
+    )
+  }
 }
 
 /// Represents a modification to a section.
@@ -96,6 +130,11 @@ impl core::fmt::Display for SectionModificationType {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
     match self {
       Self::Accelerando => write!(f, "Accelerando"),
+      Self::Coda => write!(f, "Coda"),
+      Self::DaCapo => write!(f, "Da Capo"),
+      Self::DalSegno => write!(f, "Dal Segno"),
+      Self::Fine => write!(f, "Fine"),
+      Self::JumpWithRepeats => write!(f, "Jump With Repeats"),
       Self::OnlyPlay { iterations } => {
         let iterations = iterations
           .iter()
@@ -108,9 +147,11 @@ impl core::fmt::Display for SectionModificationType {
       Self::Repeat { num_times } => write!(f, "Repeat: {num_times} times"),
       Self::Ritardando => write!(f, "Ritardando"),
       Self::Ritenuto => write!(f, "Ritenuto"),
+      Self::Segno => write!(f, "Segno"),
       Self::Stringendo => write!(f, "Stringendo"),
       Self::TempoExplicit { tempo } => write!(f, "Explicit Tempo: {tempo}"),
       Self::TempoImplicit { tempo } => write!(f, "Implicit Tempo: {tempo}"),
+      Self::ToCoda => write!(f, "To Coda"),
     }
   }
 }
@@ -39,18 +39,104 @@ pub enum Accidental {
   /// Represents a double-flat pitch, which lowers the pitch of a
   /// note by a whole step (2 semitones).
   DoubleFlat,
+  /// <span class="smufl">TODO</span>
+  ///
+  /// Represents a quarter-tone sharp pitch, which raises the pitch of a
+  /// note by a quarter step (50 cents).
+  QuarterSharp,
+  /// <span class="smufl">TODO</span>
+  ///
+  /// Represents a quarter-tone flat pitch, which lowers the pitch of a
+  /// note by a quarter step (50 cents).
+  QuarterFlat,
+  /// <span class="smufl">TODO</span>
+  ///
+  /// Represents a three-quarter-tone sharp pitch, which raises the pitch
+  /// of a note by three quarter steps (150 cents).
+  ThreeQuarterSharp,
+  /// <span class="smufl">TODO</span>
+  ///
+  /// Represents a three-quarter-tone flat pitch, which lowers the pitch
+  /// of a note by three quarter steps (150 cents).
+  ThreeQuarterFlat,
 }
 
 impl Accidental {
-  /// Returns the number of semitones that this accidental raises or lowers a pitch.
+  /// Returns the number of whole semitones that this accidental raises or lowers a pitch.
+  ///
+  /// Microtonal accidentals are truncated toward zero, so a [`Accidental::ThreeQuarterSharp`]
+  /// returns 1; use [`Accidental::cents`] to retrieve the exact alteration.
   #[must_use]
   pub const fn value(&self) -> i8 {
     match self {
-      Self::Sharp => 1,
-      Self::Flat => -1,
+      Self::Sharp | Self::ThreeQuarterSharp => 1,
+      Self::Flat | Self::ThreeQuarterFlat => -1,
       Self::DoubleSharp => 2,
       Self::DoubleFlat => -2,
-      Self::None | Self::Natural => 0,
+      Self::None | Self::Natural | Self::QuarterSharp | Self::QuarterFlat => 0,
+    }
+  }
+
+  /// Returns the number of cents (hundredths of a semitone) that this accidental raises or lowers a pitch.
+  #[must_use]
+  pub const fn cents(&self) -> i16 {
+    match self {
+      Self::QuarterSharp => 50,
+      Self::QuarterFlat => -50,
+      Self::ThreeQuarterSharp => 150,
+      Self::ThreeQuarterFlat => -150,
+      _ => 100 * self.value() as i16,
+    }
+  }
+
+  /// Returns the accidental with [`Accidental::Natural`] replaced by [`Accidental::None`],
+  /// which is useful for comparing sounding alterations.
+  #[must_use]
+  pub(crate) const fn normalized(self) -> Self {
+    match self {
+      Self::Natural => Self::None,
+      _ => self,
+    }
+  }
+
+  /// Returns whether this accidental alters a pitch by a fraction of a semitone.
+  #[must_use]
+  pub const fn is_microtonal(&self) -> bool {
+    matches!(
+      self,
+      Self::QuarterSharp | Self::QuarterFlat | Self::ThreeQuarterSharp | Self::ThreeQuarterFlat
+    )
+  }
+
+  /// Returns the accidental that raises or lowers a pitch by the given number of
+  /// semitones, or `None` if no single accidental can represent the alteration.
+  ///
+  /// An alteration of 0 semitones is represented by [`Accidental::None`].
+  #[must_use]
+  pub(crate) const fn from_value(value: i16) -> Option<Self> {
+    match value {
+      -2 => Some(Self::DoubleFlat),
+      -1 => Some(Self::Flat),
+      0 => Some(Self::None),
+      1 => Some(Self::Sharp),
+      2 => Some(Self::DoubleSharp),
+      _ => None,
+    }
+  }
+
+  /// Returns the accidental that raises or lowers a pitch by the given number of
+  /// cents, or `None` if no single accidental can represent the alteration.
+  ///
+  /// An alteration of 0 cents is represented by [`Accidental::None`].
+  #[must_use]
+  pub(crate) const fn from_cents(cents: i16) -> Option<Self> {
+    match cents {
+      -150 => Some(Self::ThreeQuarterFlat),
+      -50 => Some(Self::QuarterFlat),
+      50 => Some(Self::QuarterSharp),
+      150 => Some(Self::ThreeQuarterSharp),
+      _ if cents % 100 == 0 => Self::from_value(cents / 100),
+      _ => None,
     }
   }
 }
@@ -67,6 +153,10 @@ impl core::fmt::Display for Accidental {
         Self::Flat => "♭",
         Self::DoubleSharp => "𝄪",
         Self::DoubleFlat => "𝄫",
+        Self::QuarterSharp => "𝄲",
+        Self::QuarterFlat => "𝄳",
+        Self::ThreeQuarterSharp => "♯𝄲",
+        Self::ThreeQuarterFlat => "♭𝄳",
         Self::None => "",
       }
     )
@@ -1,24 +1,25 @@
+use super::Rational;
 use crate::context::Tempo;
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-const MAXIMA_VALUE: f64 = 8.0;
-const LONG_VALUE: f64 = 4.0;
-const BREVE_VALUE: f64 = 2.0;
-const WHOLE_VALUE: f64 = 1.0;
-const HALF_VALUE: f64 = 0.5;
-const QUARTER_VALUE: f64 = 0.25;
-const EIGHTH_VALUE: f64 = 0.125;
-const SIXTEENTH_VALUE: f64 = 0.062_5;
-const THIRTY_SECOND_VALUE: f64 = 0.031_25;
-const SIXTY_FOURTH_VALUE: f64 = 0.015_625;
-const ONE_HUNDRED_TWENTY_EIGHTH_VALUE: f64 = 0.007_812_5;
-const TWO_HUNDRED_FIFTY_SIXTH_VALUE: f64 = 0.003_906_25;
-const FIVE_HUNDRED_TWELFTH_VALUE: f64 = 0.001_953_125;
-const ONE_THOUSAND_TWENTY_FOURTH_VALUE: f64 = 0.000_976_562_5;
-const TWO_THOUSAND_FOURTH_EIGHTH_VALUE: f64 = 0.000_488_281_25;
+const MAXIMA_VALUE: Rational = Rational::from_integer(8);
+const LONG_VALUE: Rational = Rational::from_integer(4);
+const BREVE_VALUE: Rational = Rational::from_integer(2);
+const WHOLE_VALUE: Rational = Rational::ONE;
+const HALF_VALUE: Rational = Rational::new(1, 2);
+const QUARTER_VALUE: Rational = Rational::new(1, 4);
+const EIGHTH_VALUE: Rational = Rational::new(1, 8);
+const SIXTEENTH_VALUE: Rational = Rational::new(1, 16);
+const THIRTY_SECOND_VALUE: Rational = Rational::new(1, 32);
+const SIXTY_FOURTH_VALUE: Rational = Rational::new(1, 64);
+const ONE_HUNDRED_TWENTY_EIGHTH_VALUE: Rational = Rational::new(1, 128);
+const TWO_HUNDRED_FIFTY_SIXTH_VALUE: Rational = Rational::new(1, 256);
+const FIVE_HUNDRED_TWELFTH_VALUE: Rational = Rational::new(1, 512);
+const ONE_THOUSAND_TWENTY_FOURTH_VALUE: Rational = Rational::new(1, 1024);
+const TWO_THOUSAND_FOURTH_EIGHTH_VALUE: Rational = Rational::new(1, 2048);
 
 /// Represents the type of duration of a note.
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -60,7 +61,7 @@ pub enum DurationType {
 impl DurationType {
   /// Returns the value of the duration type as its fractional representation.
   #[must_use]
-  pub const fn value(&self) -> f64 {
+  pub const fn value(&self) -> Rational {
     match self {
       DurationType::Maxima => MAXIMA_VALUE,
       DurationType::Long => LONG_VALUE,
@@ -107,11 +108,11 @@ impl Duration {
 
   /// Returns the number of dots required to represent the remainder of a note's value.
   #[must_use]
-  fn dots_from_remainder(base_value: f64, full_value: f64) -> u8 {
+  fn dots_from_remainder(base_value: Rational, full_value: Rational) -> u8 {
     let (mut current_value, mut dots) = (base_value, 0);
     while full_value - current_value >= TWO_THOUSAND_FOURTH_EIGHTH_VALUE {
       dots += 1;
-      current_value += base_value / f64::powi(2.0, i32::from(dots));
+      current_value += base_value * Rational::new(1, 1 << dots);
     }
     dots
   }
@@ -120,7 +121,7 @@ impl Duration {
   ///
   /// The `beat_base_value` defines the type of note that represents a single beat.
   #[must_use]
-  pub fn from_beats(beat_base_value: &Duration, beats: f64) -> Self {
+  pub fn from_beats(beat_base_value: &Duration, beats: Rational) -> Self {
     let value = beats * beat_base_value.value();
     match value {
       v if v >= MAXIMA_VALUE => Duration::new(DurationType::Maxima, Self::dots_from_remainder(MAXIMA_VALUE, v)),
@@ -169,7 +170,7 @@ impl Duration {
   ///
   /// The `beat_base_value` defines the type of note that represents a single beat.
   #[must_use]
-  pub fn from_beats_tied(beat_base_value: &Duration, beats: f64) -> Vec<Self> {
+  pub fn from_beats_tied(beat_base_value: &Duration, beats: Rational) -> Vec<Self> {
     let mut tied_durations: Vec<Duration> = Vec::new();
     let mut beats_remaining = beats * beat_base_value.value();
     while beats_remaining >= TWO_THOUSAND_FOURTH_EIGHTH_VALUE {
@@ -192,7 +193,7 @@ impl Duration {
       };
       beats_remaining -= duration.value();
       if let Some(last_duration) = tied_durations.last_mut() {
-        if (0.5 * last_duration.value() - duration.value()).abs() < f64::EPSILON {
+        if last_duration.value() * HALF_VALUE == duration.value() {
           last_duration.dots += 1;
         } else {
           tied_durations.push(duration);
@@ -207,52 +208,46 @@ impl Duration {
   /// Creates a new [`Duration`] from the given tempo and note duration in seconds.
   #[must_use]
   pub fn from_duration(tempo: &Tempo, duration: f64) -> Self {
-    Duration::from_beats(&tempo.base_note, duration * f64::from(tempo.beats_per_minute) / 60.0)
+    let beats = duration * f64::from(tempo.beats_per_minute) / 60.0;
+    Duration::from_beats(&tempo.base_note, Rational::from_f64(beats, 2048))
   }
 
   /// Returns the minimum number and type of notes that can be used to
   /// represent the specified number of beats.
   #[must_use]
-  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-  pub(crate) fn get_minimum_divisible_notes(beats: f64) -> (DurationType, u32) {
-    match beats {
-      beats if beats.fract() < ONE_THOUSAND_TWENTY_FOURTH_VALUE => (DurationType::Whole, beats as u32),
-      beats if beats.fract() >= HALF_VALUE => (DurationType::Half, (2.0 * beats) as u32),
-      beats if beats.fract() >= QUARTER_VALUE => (DurationType::Quarter, (4.0 * beats) as u32),
-      beats if beats.fract() >= EIGHTH_VALUE => (DurationType::Eighth, (8.0 * beats) as u32),
-      beats if beats.fract() >= SIXTEENTH_VALUE => (DurationType::Sixteenth, (16.0 * beats) as u32),
-      beats if beats.fract() >= THIRTY_SECOND_VALUE => (DurationType::ThirtySecond, (32.0 * beats) as u32),
-      beats if beats.fract() >= SIXTY_FOURTH_VALUE => (DurationType::SixtyFourth, (64.0 * beats) as u32),
-      beats if beats.fract() >= ONE_HUNDRED_TWENTY_EIGHTH_VALUE => {
-        (DurationType::OneHundredTwentyEighth, (128.0 * beats) as u32)
-      }
-      beats if beats.fract() >= TWO_HUNDRED_FIFTY_SIXTH_VALUE => {
-        (DurationType::TwoHundredFiftySixth, (256.0 * beats) as u32)
-      }
-      beats if beats.fract() >= FIVE_HUNDRED_TWELFTH_VALUE => {
-        (DurationType::FiveHundredTwelfth, (512.0 * beats) as u32)
-      }
-      beats if beats.fract() >= ONE_THOUSAND_TWENTY_FOURTH_VALUE => {
-        (DurationType::OneThousandTwentyFourth, (1024.0 * beats) as u32)
-      }
-      _ => (DurationType::TwoThousandFortyEighth, (2048.0 * beats) as u32),
-    }
+  pub(crate) fn get_minimum_divisible_notes(beats: Rational) -> (DurationType, u32) {
+    let fraction = beats.fract();
+    let (note_type, notes_per_beat) = match fraction {
+      fraction if fraction < ONE_THOUSAND_TWENTY_FOURTH_VALUE => (DurationType::Whole, 1),
+      fraction if fraction >= HALF_VALUE => (DurationType::Half, 2),
+      fraction if fraction >= QUARTER_VALUE => (DurationType::Quarter, 4),
+      fraction if fraction >= EIGHTH_VALUE => (DurationType::Eighth, 8),
+      fraction if fraction >= SIXTEENTH_VALUE => (DurationType::Sixteenth, 16),
+      fraction if fraction >= THIRTY_SECOND_VALUE => (DurationType::ThirtySecond, 32),
+      fraction if fraction >= SIXTY_FOURTH_VALUE => (DurationType::SixtyFourth, 64),
+      fraction if fraction >= ONE_HUNDRED_TWENTY_EIGHTH_VALUE => (DurationType::OneHundredTwentyEighth, 128),
+      fraction if fraction >= TWO_HUNDRED_FIFTY_SIXTH_VALUE => (DurationType::TwoHundredFiftySixth, 256),
+      fraction if fraction >= FIVE_HUNDRED_TWELFTH_VALUE => (DurationType::FiveHundredTwelfth, 512),
+      fraction if fraction >= ONE_THOUSAND_TWENTY_FOURTH_VALUE => (DurationType::OneThousandTwentyFourth, 1024),
+      _ => (DurationType::TwoThousandFortyEighth, 2048),
+    };
+    let num_notes = (beats * Rational::from_integer(notes_per_beat)).floor();
+    (note_type, u32::try_from(num_notes).unwrap_or_default())
   }
 
   /// Returns the value of the duration as its fractional representation.
   #[must_use]
-  pub fn value(&self) -> f64 {
-    let base_duration = self.value.value();
-    (0..=self.dots)
-      .map(|i| base_duration / f64::powi(2.0, i32::from(i)))
-      .sum()
+  pub fn value(&self) -> Rational {
+    // A note with `n` dots is worth (2^(n+1) - 1) / 2^n times its undotted value
+    let dots = self.dots.min(61);
+    self.value.value() * Rational::new((1 << (dots + 1)) - 1, 1 << dots)
   }
 
   /// Returns the number of beats that the duration represents.
   ///
   /// The `base_beat_value` parameter defines the type of note that represents a single beat.
   #[must_use]
-  pub fn beats(&self, base_beat_value: f64) -> f64 {
+  pub fn beats(&self, base_beat_value: Rational) -> Rational {
     self.value() / base_beat_value
   }
 
@@ -339,8 +334,8 @@ mod test {
 
   #[test]
   fn test_value() {
-    assert_eq!(Duration::new(DurationType::Whole, 3).value(), 1.875);
-    assert_eq!(Duration::new(DurationType::Quarter, 4).value(), 0.484_375);
+    assert_eq!(Duration::new(DurationType::Whole, 3).value(), Rational::new(15, 8));
+    assert_eq!(Duration::new(DurationType::Quarter, 4).value(), Rational::new(31, 64));
   }
 
   #[test]
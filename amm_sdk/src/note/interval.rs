@@ -0,0 +1,476 @@
+use super::{Accidental, Pitch, PitchName};
+use amm_internal::amm_prelude::*;
+use amm_macros::{JsonDeserialize, JsonSerialize};
+
+const MAJOR_SCALE_SEMITONES: [i16; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Represents the quality of a musical interval.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum IntervalQuality {
+  /// Represents an interval that has been narrowed by the given number of semitones
+  /// from its perfect or minor form (e.g., `Diminished(2)` is doubly diminished).
+  Diminished(u8),
+  /// Represents a second, third, sixth, or seventh that is one semitone narrower than major.
+  Minor,
+  /// Represents a unison, fourth, fifth, or octave in its natural form.
+  #[default]
+  Perfect,
+  /// Represents a second, third, sixth, or seventh in its natural form.
+  Major,
+  /// Represents an interval that has been widened by the given number of semitones
+  /// from its perfect or major form (e.g., `Augmented(2)` is doubly augmented).
+  Augmented(u8),
+}
+
+/// Represents the manner in which pitches are moved when transposing music.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum TranspositionMode {
+  /// Moves every pitch by the exact interval, transposing the key along with it.
+  #[default]
+  Chromatic,
+  /// Moves every pitch by the number of diatonic steps in the interval while
+  /// remaining within the current key, ignoring the quality of the interval.
+  Diatonic,
+}
+
+/// Represents a musical interval, which is the distance between two pitches
+/// in terms of both diatonic steps and semitones.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct Interval {
+  /// The quality of the interval.
+  pub quality: IntervalQuality,
+  /// The diatonic number of the interval, where 1 represents a unison and 8 represents an octave.
+  pub number: u8,
+  /// Whether the interval moves downward in pitch.
+  pub descending: bool,
+}
+
+impl Interval {
+  /// Creates a new ascending interval with the given quality and diatonic number.
+  #[must_use]
+  pub const fn new(quality: IntervalQuality, number: u8) -> Self {
+    Self {
+      quality,
+      number,
+      descending: false,
+    }
+  }
+
+  /// Creates the most common interval spanning the given signed number of semitones.
+  ///
+  /// Negative semitone values will result in a descending interval. Tritones are
+  /// represented as augmented fourths.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  pub fn from_semitones(semitones: i16) -> Self {
+    let (quality, number) = match semitones.unsigned_abs() % 12 {
+      0 => (IntervalQuality::Perfect, 1),
+      1 => (IntervalQuality::Minor, 2),
+      2 => (IntervalQuality::Major, 2),
+      3 => (IntervalQuality::Minor, 3),
+      4 => (IntervalQuality::Major, 3),
+      5 => (IntervalQuality::Perfect, 4),
+      6 => (IntervalQuality::Augmented(1), 4),
+      7 => (IntervalQuality::Perfect, 5),
+      8 => (IntervalQuality::Minor, 6),
+      9 => (IntervalQuality::Major, 6),
+      10 => (IntervalQuality::Minor, 7),
+      _ => (IntervalQuality::Major, 7),
+    };
+    let octaves = (semitones.unsigned_abs() / 12) as u8;
+    let number = if octaves > 0 && number == 1 {
+      7 * octaves + 1
+    } else {
+      number + 7 * octaves
+    };
+    Self {
+      quality,
+      number,
+      descending: semitones < 0,
+    }
+  }
+
+  /// Returns the interval between two pitches, each with their own accidental.
+  ///
+  /// An accidental of [`Accidental::None`] is treated as a natural. Returns `None` if
+  /// either pitch is a rest or if the interval cannot be represented.
+  #[must_use]
+  pub fn between(from: Pitch, from_accidental: Accidental, to: Pitch, to_accidental: Accidental) -> Option<Self> {
+    if from.is_rest() || to.is_rest() {
+      return None;
+    }
+    let steps = to.diatonic_value() - from.diatonic_value();
+    let semitones = (to.semitones_from_c0() + i16::from(to_accidental.value()))
+      - (from.semitones_from_c0() + i16::from(from_accidental.value()));
+    let (steps, semitones, descending) = if steps < 0 || (steps == 0 && semitones < 0) {
+      (-steps, -semitones, true)
+    } else {
+      (steps, semitones, false)
+    };
+    let number = u8::try_from(steps + 1).ok()?;
+    let difference = semitones - Self::base_semitones(number);
+    let quality = if Self::is_perfect_number(number) {
+      match difference {
+        0 => IntervalQuality::Perfect,
+        d if d > 0 => IntervalQuality::Augmented(u8::try_from(d).ok()?),
+        d => IntervalQuality::Diminished(u8::try_from(-d).ok()?),
+      }
+    } else {
+      match difference {
+        0 => IntervalQuality::Major,
+        -1 => IntervalQuality::Minor,
+        d if d > 0 => IntervalQuality::Augmented(u8::try_from(d).ok()?),
+        d => IntervalQuality::Diminished(u8::try_from(-d - 1).ok()?),
+      }
+    };
+    Some(Self {
+      quality,
+      number,
+      descending,
+    })
+  }
+
+  /// Returns whether the given diatonic number belongs to the perfect family
+  /// of intervals (unisons, fourths, fifths, and octaves).
+  #[must_use]
+  const fn is_perfect_number(number: u8) -> bool {
+    matches!(number % 7, 1 | 4 | 5)
+  }
+
+  /// Returns the number of semitones in the perfect or major form of an interval
+  /// with the given diatonic number.
+  #[must_use]
+  fn base_semitones(number: u8) -> i16 {
+    let steps = number.saturating_sub(1);
+    12 * i16::from(steps / 7) + MAJOR_SCALE_SEMITONES[usize::from(steps % 7)]
+  }
+
+  /// Returns whether the quality of the interval is valid for its diatonic number.
+  ///
+  /// For example, a major fifth or a perfect third are not valid intervals.
+  #[must_use]
+  pub fn is_valid(&self) -> bool {
+    self.number > 0
+      && match self.quality {
+        IntervalQuality::Perfect => Self::is_perfect_number(self.number),
+        IntervalQuality::Major | IntervalQuality::Minor => !Self::is_perfect_number(self.number),
+        IntervalQuality::Diminished(amount) | IntervalQuality::Augmented(amount) => amount > 0,
+      }
+  }
+
+  /// Returns the number of semitones spanned by the interval.
+  ///
+  /// Descending intervals will return a negative number of semitones.
+  #[must_use]
+  pub fn semitones(&self) -> i16 {
+    let base = Self::base_semitones(self.number.max(1));
+    let alteration = match self.quality {
+      IntervalQuality::Perfect | IntervalQuality::Major => 0,
+      IntervalQuality::Minor => -1,
+      IntervalQuality::Augmented(amount) => i16::from(amount),
+      IntervalQuality::Diminished(amount) if Self::is_perfect_number(self.number) => -i16::from(amount),
+      IntervalQuality::Diminished(amount) => -1 - i16::from(amount),
+    };
+    if self.descending {
+      -(base + alteration)
+    } else {
+      base + alteration
+    }
+  }
+
+  /// Returns the number of diatonic steps spanned by the interval.
+  ///
+  /// Descending intervals will return a negative number of steps.
+  #[must_use]
+  pub fn steps(&self) -> i16 {
+    let steps = i16::from(self.number.max(1)) - 1;
+    if self.descending {
+      -steps
+    } else {
+      steps
+    }
+  }
+
+  /// Returns whether the interval spans more than an octave.
+  #[must_use]
+  pub fn is_compound(&self) -> bool {
+    self.number > 8
+  }
+
+  /// Returns the simple (within one octave) form of the interval.
+  ///
+  /// Octaves and their compounds are reduced to octaves rather than unisons.
+  #[must_use]
+  pub fn simple(&self) -> Self {
+    Self {
+      quality: self.quality,
+      number: if self.number > 8 {
+        (self.number - 2) % 7 + 2
+      } else {
+        self.number
+      },
+      descending: self.descending,
+    }
+  }
+
+  /// Returns the inversion of the simple form of the interval (e.g., a major
+  /// third becomes a minor sixth, and an augmented fourth becomes a diminished fifth).
+  #[must_use]
+  pub fn inverted(&self) -> Self {
+    let simple = self.simple();
+    Self {
+      quality: match simple.quality {
+        IntervalQuality::Perfect => IntervalQuality::Perfect,
+        IntervalQuality::Major => IntervalQuality::Minor,
+        IntervalQuality::Minor => IntervalQuality::Major,
+        IntervalQuality::Augmented(amount) => IntervalQuality::Diminished(amount),
+        IntervalQuality::Diminished(amount) => IntervalQuality::Augmented(amount),
+      },
+      number: 9 - simple.number.clamp(1, 8),
+      descending: simple.descending,
+    }
+  }
+
+  /// Returns the same interval moving in the opposite direction.
+  #[must_use]
+  pub fn reversed(&self) -> Self {
+    Self {
+      quality: self.quality,
+      number: self.number,
+      descending: !self.descending,
+    }
+  }
+
+  /// Applies the interval to a pitch carrying the specified accidental, returning the
+  /// resulting pitch and its accidental.
+  ///
+  /// An accidental of [`Accidental::None`] is treated as a natural, and a natural result
+  /// is returned with [`Accidental::None`]. Returns `None` if the source pitch is a rest,
+  /// if the result falls outside of the representable octave range, or if the result
+  /// would require more than a double sharp or double flat.
+  #[must_use]
+  pub fn apply(&self, pitch: Pitch, accidental: Accidental) -> Option<(Pitch, Accidental)> {
+    if pitch.is_rest() {
+      return None;
+    }
+    let position = pitch.diatonic_value() + self.steps();
+    let name = PitchName::from_diatonic_index(position);
+    let octave = u8::try_from(position.div_euclid(7)).ok()?;
+    let result = Pitch::new(name, octave);
+    let target = pitch.semitones_from_c0() + i16::from(accidental.value()) + self.semitones();
+    Accidental::from_value(target - result.semitones_from_c0()).map(|accidental| (result, accidental))
+  }
+}
+
+impl Default for Interval {
+  fn default() -> Self {
+    Self::new(IntervalQuality::Perfect, 1)
+  }
+}
+
+impl core::ops::Add<Interval> for Pitch {
+  type Output = Option<(Pitch, Accidental)>;
+
+  fn add(self, rhs: Interval) -> Self::Output {
+    rhs.apply(self, Accidental::None)
+  }
+}
+
+impl core::ops::Sub<Interval> for Pitch {
+  type Output = Option<(Pitch, Accidental)>;
+
+  fn sub(self, rhs: Interval) -> Self::Output {
+    rhs.reversed().apply(self, Accidental::None)
+  }
+}
+
+#[cfg(feature = "print")]
+fn number_to_text(number: u8) -> String {
+  match number {
+    1 => String::from("Unison"),
+    2 => String::from("Second"),
+    3 => String::from("Third"),
+    4 => String::from("Fourth"),
+    5 => String::from("Fifth"),
+    6 => String::from("Sixth"),
+    7 => String::from("Seventh"),
+    8 => String::from("Octave"),
+    9 => String::from("Ninth"),
+    10 => String::from("Tenth"),
+    11 => String::from("Eleventh"),
+    12 => String::from("Twelfth"),
+    13 => String::from("Thirteenth"),
+    _ => format!("{number}th"),
+  }
+}
+
+#[cfg(feature = "print")]
+fn multiplier_to_text(amount: u8) -> String {
+  match amount {
+    0 | 1 => String::new(),
+    2 => String::from("Doubly "),
+    3 => String::from("Triply "),
+    _ => format!("{amount}x "),
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for IntervalQuality {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if f.alternate() {
+      match self {
+        Self::Diminished(amount) => write!(f, "{}Diminished", multiplier_to_text(*amount)),
+        Self::Minor => write!(f, "Minor"),
+        Self::Perfect => write!(f, "Perfect"),
+        Self::Major => write!(f, "Major"),
+        Self::Augmented(amount) => write!(f, "{}Augmented", multiplier_to_text(*amount)),
+      }
+    } else {
+      match self {
+        Self::Diminished(amount) => write!(f, "{}", "d".repeat(usize::from(*amount).max(1))),
+        Self::Minor => write!(f, "m"),
+        Self::Perfect => write!(f, "P"),
+        Self::Major => write!(f, "M"),
+        Self::Augmented(amount) => write!(f, "{}", "A".repeat(usize::from(*amount).max(1))),
+      }
+    }
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Interval {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if f.alternate() {
+      write!(
+        f,
+        "{}{:#} {}",
+        if self.descending { "Descending " } else { "" },
+        self.quality,
+        number_to_text(self.number)
+      )
+    } else {
+      write!(
+        f,
+        "{}{}{}",
+        if self.descending { "-" } else { "" },
+        self.quality,
+        self.number
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_between() {
+    let c4 = Pitch::new(PitchName::C, 4);
+    let e4 = Pitch::new(PitchName::E, 4);
+    let b3 = Pitch::new(PitchName::B, 3);
+    assert_eq!(
+      Interval::between(c4, Accidental::None, e4, Accidental::None),
+      Some(Interval::new(IntervalQuality::Major, 3))
+    );
+    assert_eq!(
+      Interval::between(c4, Accidental::Sharp, e4, Accidental::Flat),
+      Some(Interval::new(IntervalQuality::Diminished(1), 3))
+    );
+    assert_eq!(
+      Interval::between(c4, Accidental::None, b3, Accidental::None),
+      Some(Interval::new(IntervalQuality::Minor, 2).reversed())
+    );
+    assert_eq!(
+      Interval::between(b3, Accidental::Flat, e4, Accidental::Sharp),
+      Some(Interval::new(IntervalQuality::Augmented(2), 4))
+    );
+    assert_eq!(
+      Interval::between(c4, Accidental::None, Pitch::new_rest(), Accidental::None),
+      None
+    );
+  }
+
+  #[test]
+  fn test_semitones() {
+    assert_eq!(Interval::new(IntervalQuality::Perfect, 8).semitones(), 12);
+    assert_eq!(Interval::new(IntervalQuality::Diminished(1), 5).semitones(), 6);
+    assert_eq!(Interval::new(IntervalQuality::Diminished(1), 7).semitones(), 9);
+    assert_eq!(Interval::new(IntervalQuality::Minor, 10).semitones(), 15);
+    assert_eq!(Interval::new(IntervalQuality::Major, 6).reversed().semitones(), -9);
+    assert_eq!(
+      Interval::from_semitones(-19),
+      Interval::new(IntervalQuality::Perfect, 12).reversed()
+    );
+    assert_eq!(
+      Interval::from_semitones(24),
+      Interval::new(IntervalQuality::Perfect, 15)
+    );
+  }
+
+  #[test]
+  fn test_inversion_and_compounds() {
+    assert_eq!(
+      Interval::new(IntervalQuality::Major, 3).inverted(),
+      Interval::new(IntervalQuality::Minor, 6)
+    );
+    assert_eq!(
+      Interval::new(IntervalQuality::Augmented(1), 4).inverted(),
+      Interval::new(IntervalQuality::Diminished(1), 5)
+    );
+    assert_eq!(
+      Interval::new(IntervalQuality::Major, 10).simple(),
+      Interval::new(IntervalQuality::Major, 3)
+    );
+    assert_eq!(
+      Interval::new(IntervalQuality::Perfect, 15).simple(),
+      Interval::new(IntervalQuality::Perfect, 8)
+    );
+    assert!(Interval::new(IntervalQuality::Major, 9).is_compound());
+    assert!(!Interval::new(IntervalQuality::Major, 5).is_valid());
+  }
+
+  #[test]
+  fn test_pitch_arithmetic() {
+    let c4 = Pitch::new(PitchName::C, 4);
+    assert_eq!(
+      c4 + Interval::new(IntervalQuality::Major, 3),
+      Some((Pitch::new(PitchName::E, 4), Accidental::None))
+    );
+    assert_eq!(
+      c4 + Interval::new(IntervalQuality::Augmented(1), 4),
+      Some((Pitch::new(PitchName::F, 4), Accidental::Sharp))
+    );
+    assert_eq!(
+      c4 - Interval::new(IntervalQuality::Minor, 3),
+      Some((Pitch::new(PitchName::A, 3), Accidental::None))
+    );
+    assert_eq!(
+      Interval::new(IntervalQuality::Major, 3).apply(Pitch::new(PitchName::B, 3), Accidental::Flat),
+      Some((Pitch::new(PitchName::D, 4), Accidental::None))
+    );
+    assert_eq!(
+      Interval::new(IntervalQuality::Augmented(1), 2).apply(Pitch::new(PitchName::F, 4), Accidental::Sharp),
+      Some((Pitch::new(PitchName::G, 4), Accidental::DoubleSharp))
+    );
+    assert_eq!(
+      Pitch::new(PitchName::C, 0) - Interval::new(IntervalQuality::Minor, 2),
+      None
+    );
+  }
+
+  #[cfg(feature = "print")]
+  #[test]
+  fn test_display() {
+    assert_eq!(Interval::new(IntervalQuality::Major, 3).to_string(), "M3");
+    assert_eq!(Interval::new(IntervalQuality::Diminished(2), 5).to_string(), "dd5");
+    assert_eq!(
+      format!("{:#}", Interval::new(IntervalQuality::Diminished(2), 5)),
+      "Doubly Diminished Fifth"
+    );
+    assert_eq!(
+      format!("{:#}", Interval::new(IntervalQuality::Perfect, 8).reversed()),
+      "Descending Perfect Octave"
+    );
+  }
+}
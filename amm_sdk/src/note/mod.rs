@@ -2,10 +2,22 @@
 
 mod accidental;
 mod duration;
+mod interval;
 mod note;
+mod percussion;
 mod pitch;
+mod rational;
+mod scale;
+mod spelling;
+mod tuning;
 
 pub use accidental::Accidental;
 pub use duration::{Duration, DurationType};
+pub use interval::{Interval, IntervalQuality, TranspositionMode};
 pub use note::Note;
+pub use percussion::{Notehead, PercussionInstrument, Unpitched};
 pub use pitch::{Pitch, PitchName};
+pub use rational::Rational;
+pub use scale::{Scale, ScaleType};
+pub(crate) use spelling::choose_spellings;
+pub use tuning::{KeyboardMapping, Temperament, Tuning};
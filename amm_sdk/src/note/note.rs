@@ -1,4 +1,8 @@
-use super::{Accidental, Duration, Pitch, PitchName};
+use super::spelling::{spellings_of, written_accidental};
+use super::{
+  Accidental, Duration, Interval, PercussionInstrument, Pitch, PitchName, Rational, TranspositionMode, Tuning,
+  Unpitched,
+};
 use crate::context::{generate_id, Key, Tempo};
 use crate::modification::{NoteModification, NoteModificationType};
 use crate::temporal::Timeslice;
@@ -23,6 +27,11 @@ pub struct Note {
   pub duration: Duration,
   /// An accidental modifier on the note (if any).
   pub accidental: Accidental,
+  /// An additional microtonal offset applied to the pitch of the note, in cents.
+  pub cents_offset: i16,
+  /// The percussion instrument and notehead of an unpitched note, in which case
+  /// the pitch of the note only designates its position on the staff.
+  pub unpitched: Option<Unpitched>,
   /// A list of modifications on the note.
   modifications: BTreeSet<NoteModification>,
 }
@@ -38,10 +47,21 @@ impl Note {
       pitch,
       duration,
       accidental: accidental.unwrap_or_default(),
+      cents_offset: 0,
+      unpitched: None,
       modifications: BTreeSet::new(),
     }
   }
 
+  /// Creates a new unpitched note played by the given percussion instrument with the
+  /// given duration, notated at the conventional staff position and notehead of the instrument.
+  #[must_use]
+  pub fn new_unpitched(instrument: PercussionInstrument, duration: Duration) -> Self {
+    let mut note = Self::new(instrument.display_pitch(), duration, None);
+    note.unpitched = Some(Unpitched::new(instrument));
+    note
+  }
+
   /// Creates a new note from the given MIDI number, duration, and optional key signature.
   #[must_use]
   pub fn from_midi(mut midi_number: u8, duration: Duration, key: Option<Key>) -> Self {
@@ -110,13 +130,87 @@ impl Note {
   /// account the accidentals for a given key signature.
   #[must_use]
   fn semitone_distance(&self, key_accidentals: [Accidental; 8]) -> i8 {
-    let (pitch_index, num_semitones) = self.pitch.value();
-    num_semitones
-      + if self.accidental == Accidental::None {
-        key_accidentals[pitch_index].value()
-      } else {
-        self.accidental.value()
-      }
+    self.pitch.value().1 + self.sounding_accidental(key_accidentals).value()
+  }
+
+  /// Returns the number of cents between the note and A4 when played with the given
+  /// sounding accidental, including any microtonal offset on the note.
+  #[must_use]
+  fn cent_distance(&self, accidental: Accidental) -> i16 {
+    100 * i16::from(self.pitch.value().1) + accidental.cents() + self.cents_offset
+  }
+
+  /// Returns the number of cents by which the sounding accidental of the note deviates
+  /// from the nearest semitone alteration toward zero.
+  #[must_use]
+  fn microtonal_offset(&self, key_accidentals: [Accidental; 8]) -> i16 {
+    let accidental = self.sounding_accidental(key_accidentals);
+    accidental.cents() - 100 * i16::from(accidental.value())
+  }
+
+  /// Returns the pitch of the note in Hertz when played with the given sounding accidental.
+  #[must_use]
+  pub(crate) fn pitch_hz_with_accidental(&self, accidental: Accidental, a4_frequency_hz: Option<f32>) -> f32 {
+    a4_frequency_hz.unwrap_or(A4_FREQUENCY_HZ) * 2f32.powf(f32::from(self.cent_distance(accidental)) / 1200.0)
+  }
+
+  /// Returns the pitch of the note in Hertz in the given tuning when played with the
+  /// given sounding accidental, or `None` if the note is not mapped by the tuning.
+  #[must_use]
+  pub(crate) fn tuned_pitch_hz_with_accidental(&self, accidental: Accidental, tuning: &Tuning) -> Option<f32> {
+    tuning.frequency_hz_with_offset(
+      self.midi_number_with_accidental(accidental),
+      accidental.cents() - 100 * i16::from(accidental.value()) + self.cents_offset,
+    )
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value which corrects the MIDI number of the note
+  /// to its exact pitch when played with the given sounding accidental.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  pub(crate) fn midi_pitch_bend_with_accidental(&self, accidental: Accidental, bend_range_semitones: u8) -> u16 {
+    let deviation = f64::from(accidental.cents() - 100 * i16::from(accidental.value()) + self.cents_offset);
+    let range = f64::from(bend_range_semitones.max(1)) * 100.0;
+    (8192.0 + 8192.0 * deviation / range).round().clamp(0.0, 16383.0) as u16
+  }
+
+  /// Returns the pitch of the note in MIDI number format when played with the given sounding accidental.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  pub(crate) fn midi_number_with_accidental(&self, accidental: Accidental) -> u8 {
+    match self.unpitched {
+      Some(unpitched) => unpitched.instrument.midi_number(),
+      None => (MIDI_NUMBER_A4 + self.pitch.value().1 + accidental.value()) as u8,
+    }
+  }
+
+  /// Returns the accidental that is applied to the note when played, taking into
+  /// account the accidentals for a given key signature.
+  #[must_use]
+  pub(crate) fn sounding_accidental(&self, key_accidentals: [Accidental; 8]) -> Accidental {
+    if self.accidental == Accidental::None {
+      key_accidentals[self.pitch.name.index()]
+    } else {
+      self.accidental
+    }
+  }
+
+  /// Returns the interval from this note to another note, optionally taking
+  /// into account the accidentals for a given key signature.
+  ///
+  /// Returns `None` if either note is a rest or an unpitched note.
+  #[must_use]
+  pub fn interval_to(&self, other: &Note, key: Option<Key>) -> Option<Interval> {
+    if self.is_unpitched() || other.is_unpitched() {
+      return None;
+    }
+    let accidentals = key.unwrap_or_default().accidentals();
+    Interval::between(
+      self.pitch,
+      self.sounding_accidental(accidentals),
+      other.pitch,
+      other.sounding_accidental(accidentals),
+    )
   }
 
   /// Returns whether the note is the same pitch as another note.
@@ -131,6 +225,12 @@ impl Note {
     self.pitch.is_rest()
   }
 
+  /// Returns whether the note is an unpitched percussion note.
+  #[must_use]
+  pub fn is_unpitched(&self) -> bool {
+    self.unpitched.is_some()
+  }
+
   /// Returns whether the note is a grace note.
   #[must_use]
   pub fn is_grace_note(&self) -> bool {
@@ -145,25 +245,186 @@ impl Note {
   #[must_use]
   pub fn pitch_hz(&self, key: Option<Key>, a4_frequency_hz: Option<f32>) -> f32 {
     let accidentals = key.unwrap_or_default().accidentals();
-    a4_frequency_hz.unwrap_or(A4_FREQUENCY_HZ) * 2f32.powf(f32::from(self.semitone_distance(accidentals)) / 12.0)
+    self.pitch_hz_with_accidental(self.sounding_accidental(accidentals), a4_frequency_hz)
+  }
+
+  /// Returns the pitch of the note in Hertz in the given tuning system,
+  /// optionally taking into account a key signature.
+  ///
+  /// The note is mapped onto the tuning by its MIDI number, after which any
+  /// microtonal alteration is applied. Returns `None` for rests and for notes
+  /// which are not mapped to a degree of the tuning.
+  #[must_use]
+  pub fn tuned_pitch_hz(&self, key: Option<Key>, tuning: &Tuning) -> Option<f32> {
+    if self.is_rest() {
+      None
+    } else {
+      let accidentals = key.unwrap_or_default().accidentals();
+      self.tuned_pitch_hz_with_accidental(self.sounding_accidental(accidentals), tuning)
+    }
   }
 
   /// Returns the pitch of the note in MIDI number format,
   /// optionally taking into account a key signature.
+  ///
+  /// Microtonal alterations are not included in the MIDI number;
+  /// use [`Note::midi_pitch_bend`] to retrieve the remaining deviation. Unpitched notes
+  /// return the key of their instrument in the General MIDI percussion key map.
   #[must_use]
   #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
   pub fn midi_number(&self, key: Option<Key>) -> u8 {
     let accidentals = key.unwrap_or_default().accidentals();
-    (MIDI_NUMBER_A4 + self.semitone_distance(accidentals)) as u8
+    match self.unpitched {
+      Some(unpitched) => unpitched.instrument.midi_number(),
+      None => (MIDI_NUMBER_A4 + self.semitone_distance(accidentals)) as u8,
+    }
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value (where 8192 represents no bend) which corrects
+  /// the MIDI number of the note to its exact microtonal pitch, optionally taking into account
+  /// a key signature.
+  ///
+  /// The `bend_range_semitones` parameter defines the number of semitones that the
+  /// receiving device bends the pitch by at either extreme of the pitch bend range.
+  #[must_use]
+  pub fn midi_pitch_bend(&self, key: Option<Key>, bend_range_semitones: u8) -> u16 {
+    let accidentals = key.unwrap_or_default().accidentals();
+    self.midi_pitch_bend_with_accidental(self.sounding_accidental(accidentals), bend_range_semitones)
+  }
+
+  /// Returns a copy of the note for every other way of spelling its sounding pitch,
+  /// ordered from fewest to most accidentals, optionally taking into account the key
+  /// signature that the note is written in.
+  ///
+  /// The accidental of each returned note is written relative to the same key signature,
+  /// and only spellings which require no more than a double sharp or double flat are included.
+  #[must_use]
+  pub fn enharmonic_equivalents(&self, key: Option<Key>) -> Vec<Self> {
+    if self.is_rest() || self.is_unpitched() {
+      return Vec::new();
+    }
+    let key = key.unwrap_or_default();
+    let sounding_accidental = self.sounding_accidental(key.accidentals());
+    let microtonal_offset = self.microtonal_offset(key.accidentals());
+    let mut spellings: Vec<_> = spellings_of(self.midi_number(Some(key)))
+      .into_iter()
+      .filter_map(|(pitch, accidental)| {
+        Accidental::from_cents(100 * i16::from(accidental.value()) + microtonal_offset)
+          .map(|accidental| (pitch, accidental))
+      })
+      .filter(|(pitch, accidental)| *pitch != self.pitch || *accidental != sounding_accidental.normalized())
+      .collect();
+    spellings.sort_by_key(|(_, accidental)| accidental.value().abs());
+    spellings
+      .into_iter()
+      .map(|(pitch, accidental)| {
+        let mut note = self.clone();
+        note.pitch = pitch;
+        note.accidental = written_accidental(pitch.name, accidental, key);
+        note
+      })
+      .collect()
+  }
+
+  /// Transposes the note by the given interval, optionally taking into
+  /// account the key signature that the note is currently written in.
+  ///
+  /// The note is read and re-spelled against the key signature alone, so any
+  /// accidental it inherits from an earlier note in the same measure is ignored.
+  /// Transpose the containing staff, section or part to account for such
+  /// carried accidentals.
+  ///
+  /// In [`TranspositionMode::Chromatic`] mode, the note is re-spelled against the
+  /// key that results from transposing the current key by the same interval. In
+  /// [`TranspositionMode::Diatonic`] mode, the note moves by the number of steps in
+  /// the interval while remaining in the current key, keeping any chromatic
+  /// alteration it had relative to that key.
+  ///
+  /// Unpitched notes and notes that would be transposed outside of the playable MIDI range are left unchanged.
+  pub fn transpose(&mut self, interval: Interval, mode: TranspositionMode, key: Option<Key>) -> &mut Self {
+    if self.is_rest() || self.is_unpitched() {
+      return self;
+    }
+    let key = key.unwrap_or_default();
+    let key_accidentals = key.accidentals();
+    let target_key = match mode {
+      TranspositionMode::Chromatic => key.transposed(interval),
+      TranspositionMode::Diatonic => key,
+    };
+    let target_accidentals = target_key.accidentals();
+    let microtonal_offset = self.microtonal_offset(key_accidentals);
+    if microtonal_offset != 0 {
+      self.accidental = match Accidental::from_value(i16::from(self.accidental.value())) {
+        Some(Accidental::None) | None => Accidental::Natural,
+        Some(accidental) => accidental,
+      };
+    }
+    let sounding_accidental = self.sounding_accidental(key_accidentals);
+    let transposed = match mode {
+      TranspositionMode::Chromatic => interval.apply(self.pitch, sounding_accidental),
+      TranspositionMode::Diatonic => {
+        let position = self.pitch.diatonic_value() + interval.steps();
+        let name = PitchName::from_diatonic_index(position);
+        let alteration = sounding_accidental.value() - key_accidentals[self.pitch.name.index()].value();
+        u8::try_from(position.div_euclid(7)).ok().and_then(|octave| {
+          Accidental::from_value(i16::from(target_accidentals[name.index()].value() + alteration))
+            .map(|accidental| (Pitch::new(name, octave), accidental))
+        })
+      }
+    };
+    let in_key = self.accidental == Accidental::None;
+    match transposed {
+      Some((pitch, accidental)) if !in_key || accidental == target_accidentals[pitch.name.index()] => {
+        self.pitch = pitch;
+        self.accidental = if in_key {
+          Accidental::None
+        } else if accidental == Accidental::None {
+          Accidental::Natural
+        } else {
+          accidental
+        };
+      }
+      _ => {
+        let midi_number = i16::from(self.midi_number(Some(key))) + interval.semitones();
+        if let Ok(midi_number @ 1..=127) = u8::try_from(midi_number) {
+          let respelled = Self::from_midi(midi_number, self.duration, Some(target_key));
+          self.pitch = respelled.pitch;
+          self.accidental = match (in_key, respelled.accidental) {
+            (false, Accidental::None) => match target_accidentals[respelled.pitch.name.index()] {
+              Accidental::None => Accidental::Natural,
+              accidental => accidental,
+            },
+            (_, accidental) => accidental,
+          };
+        }
+      }
+    }
+    if microtonal_offset != 0 {
+      match Accidental::from_cents(100 * i16::from(self.accidental.value()) + microtonal_offset) {
+        Some(accidental) => self.accidental = accidental,
+        None => self.cents_offset += microtonal_offset,
+      }
+    }
+    self
+  }
+
+  /// Transposes the note from the key it is currently written in to the given key,
+  /// using the smallest interval between the tonics of the two keys.
+  pub fn transpose_to_key(&mut self, current_key: Key, target_key: Key) -> &mut Self {
+    self.transpose(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      Some(current_key),
+    )
   }
 
   /// Returns the duration of the note in beats,
   ///
   /// The `base_beat_value` parameter defines the type of note that represents a single beat.
   #[must_use]
-  pub fn beats(&self, base_beat_value: f64) -> f64 {
+  pub fn beats(&self, base_beat_value: Rational) -> Rational {
     if self.is_grace_note() {
-      0.0
+      Rational::ZERO
     } else {
       self.duration.beats(base_beat_value)
     }
@@ -193,8 +454,8 @@ impl Note {
   /// The `tuplet_ratio` parameter defines the ratio of the note's target duration to
   /// its original, unmodified duration.
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<f64>) -> f64 {
-    self.beats(beat_base.value()) * tuplet_ratio.unwrap_or(1.0)
+  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<Rational>) -> Rational {
+    self.beats(beat_base.value()) * tuplet_ratio.unwrap_or(Rational::ONE)
   }
 
   /// Returns the duration of the note in seconds, taking into account
@@ -203,8 +464,8 @@ impl Note {
   /// The `tuplet_ratio` parameter defines the ratio of the note's target duration to
   /// its original, unmodified duration.
   #[must_use]
-  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<f64>) -> f64 {
-    self.get_beats(&tempo.base_note, tuplet_ratio) * 60.0 / f64::from(tempo.beats_per_minute)
+  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<Rational>) -> f64 {
+    self.get_beats(&tempo.base_note, tuplet_ratio).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
   }
 
   /// Removes a modification from the note based on the specified unique identifier.
@@ -231,7 +492,9 @@ impl PartialEq for Note {
   fn eq(&self, other: &Self) -> bool {
     let default_duration = Duration::default().value();
     let default_accidentals = Key::default().accidentals();
-    (self.semitone_distance(default_accidentals) == other.semitone_distance(default_accidentals))
+    (self.cent_distance(self.sounding_accidental(default_accidentals))
+      == other.cent_distance(other.sounding_accidental(default_accidentals)))
+      && (self.unpitched == other.unpitched)
       && (self.beats(default_duration) == other.beats(default_duration))
       && (self.modifications == other.modifications)
   }
@@ -245,6 +508,8 @@ impl Clone for Note {
       pitch: self.pitch,
       duration: self.duration,
       accidental: self.accidental,
+      cents_offset: self.cents_offset,
+      unpitched: self.unpitched,
       modifications: self.modifications.clone(),
     }
   }
@@ -260,9 +525,17 @@ impl core::fmt::Display for Note {
       .join(", ");
     write!(
       f,
-      "{}{}{}{} {}{}",
+      "{}{}{}{}{}{} {}{}",
       self.pitch,
       self.accidental,
+      if self.cents_offset == 0 {
+        String::new()
+      } else {
+        format!(" {:+}¢", self.cents_offset)
+      },
+      self
+        .unpitched
+        .map_or_else(String::new, |unpitched| format!(" {unpitched}")),
       if self.is_rest() { "" } else { " " },
       self.duration,
       if self.is_rest() { "Rest" } else { "Note" },
@@ -311,4 +584,109 @@ mod test {
       }
     }
   }
+
+  #[test]
+  fn test_interval_to() {
+    use crate::note::{Interval, IntervalQuality};
+    let key = Key::new(KeySignature::D, KeyMode::Major);
+    let d4 = Note::new(Pitch::new(PitchName::D, 4), Duration::default(), None);
+    let f4 = Note::new(Pitch::new(PitchName::F, 4), Duration::default(), None);
+    assert_eq!(
+      d4.interval_to(&f4, None),
+      Some(Interval::new(IntervalQuality::Minor, 3))
+    );
+    assert_eq!(
+      d4.interval_to(&f4, Some(key)),
+      Some(Interval::new(IntervalQuality::Major, 3))
+    );
+    assert_eq!(
+      d4.interval_to(&Note::new(Pitch::new_rest(), Duration::default(), None), None),
+      None
+    );
+  }
+
+  #[test]
+  fn test_transpose() {
+    use crate::note::{Interval, IntervalQuality, TranspositionMode};
+    let key = Key::new(KeySignature::F, KeyMode::Major);
+    let major_second = Interval::new(IntervalQuality::Major, 2);
+    let mut b_flat = Note::new(Pitch::new(PitchName::B, 4), Duration::default(), None);
+    b_flat.transpose(major_second, TranspositionMode::Chromatic, Some(key));
+    assert_eq!(
+      (b_flat.pitch, b_flat.accidental),
+      (Pitch::new(PitchName::C, 5), Accidental::None)
+    );
+    let mut e_natural = Note::new(
+      Pitch::new(PitchName::E, 4),
+      Duration::default(),
+      Some(Accidental::Natural),
+    );
+    e_natural.transpose(major_second, TranspositionMode::Chromatic, Some(key));
+    assert_eq!(
+      (e_natural.pitch, e_natural.accidental),
+      (Pitch::new(PitchName::F, 4), Accidental::Sharp)
+    );
+    let mut f_sharp = Note::new(
+      Pitch::new(PitchName::F, 4),
+      Duration::default(),
+      Some(Accidental::Sharp),
+    );
+    f_sharp.transpose(
+      Interval::new(IntervalQuality::Major, 3),
+      TranspositionMode::Diatonic,
+      Some(key),
+    );
+    assert_eq!(
+      (f_sharp.pitch, f_sharp.accidental),
+      (Pitch::new(PitchName::A, 4), Accidental::Sharp)
+    );
+    let mut a = Note::new(Pitch::new(PitchName::A, 4), Duration::default(), None);
+    a.transpose(
+      Interval::new(IntervalQuality::Minor, 3),
+      TranspositionMode::Diatonic,
+      Some(key),
+    );
+    assert_eq!((a.pitch, a.accidental), (Pitch::new(PitchName::C, 5), Accidental::None));
+    let mut b = Note::new(Pitch::new(PitchName::B, 4), Duration::default(), None);
+    b.transpose(
+      Interval::new(IntervalQuality::Augmented(1), 1),
+      TranspositionMode::Chromatic,
+      None,
+    );
+    assert_eq!((b.pitch, b.accidental), (Pitch::new(PitchName::B, 4), Accidental::None));
+    assert_eq!(b.midi_number(Some(Key::new(KeySignature::CSharp, KeyMode::Major))), 72);
+  }
+
+  #[test]
+  fn test_microtonal_pitch() {
+    use crate::note::{Interval, IntervalQuality, TranspositionMode};
+    let a4 = Note::new(Pitch::new(PitchName::A, 4), Duration::default(), None);
+    let a_quarter_sharp = Note::new(
+      Pitch::new(PitchName::A, 4),
+      Duration::default(),
+      Some(Accidental::QuarterSharp),
+    );
+    assert!((a_quarter_sharp.pitch_hz(None, None) - 452.893).abs() < 0.01);
+    assert_eq!(a_quarter_sharp.midi_number(None), 69);
+    assert_eq!(a_quarter_sharp.midi_pitch_bend(None, 2), 10240);
+    assert_eq!(a4.midi_pitch_bend(None, 2), 8192);
+    assert_ne!(a4, a_quarter_sharp);
+    let mut a_offset = a4.clone();
+    a_offset.cents_offset = 50;
+    assert_eq!(a_offset, a_quarter_sharp);
+    let mut c_three_quarter_flat = Note::new(
+      Pitch::new(PitchName::C, 5),
+      Duration::default(),
+      Some(Accidental::ThreeQuarterFlat),
+    );
+    c_three_quarter_flat.transpose(
+      Interval::new(IntervalQuality::Major, 2),
+      TranspositionMode::Chromatic,
+      None,
+    );
+    assert_eq!(
+      (c_three_quarter_flat.pitch, c_three_quarter_flat.accidental),
+      (Pitch::new(PitchName::D, 5), Accidental::ThreeQuarterFlat)
+    );
+  }
 }
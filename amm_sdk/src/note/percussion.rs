@@ -0,0 +1,316 @@
+use super::{Pitch, PitchName};
+use amm_internal::amm_prelude::*;
+use amm_macros::{JsonDeserialize, JsonSerialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// The staff position, notehead, and name of each instrument in the General MIDI
+/// percussion key map, indexed from MIDI note number 35.
+const DRUM_MAP: [(PitchName, u8, Notehead, &str); 47] = [
+  (PitchName::E, 4, Notehead::Normal, "Acoustic Bass Drum"),
+  (PitchName::F, 4, Notehead::Normal, "Bass Drum"),
+  (PitchName::C, 5, Notehead::X, "Side Stick"),
+  (PitchName::C, 5, Notehead::Normal, "Acoustic Snare"),
+  (PitchName::B, 4, Notehead::Slash, "Hand Clap"),
+  (PitchName::C, 5, Notehead::Normal, "Electric Snare"),
+  (PitchName::G, 4, Notehead::Normal, "Low Floor Tom"),
+  (PitchName::G, 5, Notehead::X, "Closed Hi-Hat"),
+  (PitchName::A, 4, Notehead::Normal, "High Floor Tom"),
+  (PitchName::D, 4, Notehead::X, "Pedal Hi-Hat"),
+  (PitchName::B, 4, Notehead::Normal, "Low Tom"),
+  (PitchName::G, 5, Notehead::CircleX, "Open Hi-Hat"),
+  (PitchName::D, 5, Notehead::Normal, "Low-Mid Tom"),
+  (PitchName::E, 5, Notehead::Normal, "High-Mid Tom"),
+  (PitchName::A, 5, Notehead::X, "Crash Cymbal 1"),
+  (PitchName::F, 5, Notehead::Normal, "High Tom"),
+  (PitchName::F, 5, Notehead::X, "Ride Cymbal 1"),
+  (PitchName::C, 6, Notehead::X, "Chinese Cymbal"),
+  (PitchName::F, 5, Notehead::Diamond, "Ride Bell"),
+  (PitchName::E, 5, Notehead::Triangle, "Tambourine"),
+  (PitchName::A, 5, Notehead::CircleX, "Splash Cymbal"),
+  (PitchName::A, 5, Notehead::Triangle, "Cowbell"),
+  (PitchName::B, 5, Notehead::X, "Crash Cymbal 2"),
+  (PitchName::D, 5, Notehead::Square, "Vibraslap"),
+  (PitchName::E, 5, Notehead::X, "Ride Cymbal 2"),
+  (PitchName::E, 5, Notehead::Normal, "High Bongo"),
+  (PitchName::D, 5, Notehead::Normal, "Low Bongo"),
+  (PitchName::C, 5, Notehead::X, "Mute High Conga"),
+  (PitchName::C, 5, Notehead::Normal, "Open High Conga"),
+  (PitchName::A, 4, Notehead::Normal, "Low Conga"),
+  (PitchName::D, 5, Notehead::Normal, "High Timbale"),
+  (PitchName::B, 4, Notehead::Normal, "Low Timbale"),
+  (PitchName::E, 5, Notehead::Triangle, "High Agogo"),
+  (PitchName::D, 5, Notehead::Triangle, "Low Agogo"),
+  (PitchName::C, 5, Notehead::Square, "Cabasa"),
+  (PitchName::B, 4, Notehead::Square, "Maracas"),
+  (PitchName::E, 5, Notehead::Diamond, "Short Whistle"),
+  (PitchName::D, 5, Notehead::Diamond, "Long Whistle"),
+  (PitchName::C, 5, Notehead::Slash, "Short Guiro"),
+  (PitchName::B, 4, Notehead::Slash, "Long Guiro"),
+  (PitchName::A, 4, Notehead::X, "Claves"),
+  (PitchName::G, 5, Notehead::Triangle, "High Wood Block"),
+  (PitchName::F, 5, Notehead::Triangle, "Low Wood Block"),
+  (PitchName::E, 5, Notehead::X, "Mute Cuica"),
+  (PitchName::E, 5, Notehead::CircleX, "Open Cuica"),
+  (PitchName::B, 5, Notehead::Triangle, "Mute Triangle"),
+  (PitchName::B, 5, Notehead::Diamond, "Open Triangle"),
+];
+
+/// Represents the shape of a notehead, which is commonly used to distinguish
+/// between unpitched percussion instruments notated on the same staff position.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum Notehead {
+  /// A standard oval notehead.
+  #[default]
+  Normal,
+  /// A cross-shaped notehead, commonly used for cymbals and hi-hats.
+  X,
+  /// A cross-shaped notehead enclosed in a circle, commonly used for open or splashed cymbals.
+  CircleX,
+  /// A diamond-shaped notehead, commonly used for bells and harmonics.
+  Diamond,
+  /// A triangular notehead, commonly used for auxiliary percussion.
+  Triangle,
+  /// A slash-shaped notehead, commonly used for rhythmic notation and hand claps.
+  Slash,
+  /// A square notehead, commonly used for shakers and other auxiliary percussion.
+  Square,
+}
+
+/// Represents an unpitched percussion instrument from the General MIDI percussion key map.
+#[repr(u8)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub enum PercussionInstrument {
+  /// General MIDI percussion key 35: Acoustic Bass Drum.
+  #[default]
+  AcousticBassDrum = 35,
+  /// General MIDI percussion key 36: Bass Drum.
+  BassDrum = 36,
+  /// General MIDI percussion key 37: Side Stick.
+  SideStick = 37,
+  /// General MIDI percussion key 38: Acoustic Snare.
+  AcousticSnare = 38,
+  /// General MIDI percussion key 39: Hand Clap.
+  HandClap = 39,
+  /// General MIDI percussion key 40: Electric Snare.
+  ElectricSnare = 40,
+  /// General MIDI percussion key 41: Low Floor Tom.
+  LowFloorTom = 41,
+  /// General MIDI percussion key 42: Closed Hi-Hat.
+  ClosedHiHat = 42,
+  /// General MIDI percussion key 43: High Floor Tom.
+  HighFloorTom = 43,
+  /// General MIDI percussion key 44: Pedal Hi-Hat.
+  PedalHiHat = 44,
+  /// General MIDI percussion key 45: Low Tom.
+  LowTom = 45,
+  /// General MIDI percussion key 46: Open Hi-Hat.
+  OpenHiHat = 46,
+  /// General MIDI percussion key 47: Low-Mid Tom.
+  LowMidTom = 47,
+  /// General MIDI percussion key 48: High-Mid Tom.
+  HighMidTom = 48,
+  /// General MIDI percussion key 49: Crash Cymbal 1.
+  CrashCymbal1 = 49,
+  /// General MIDI percussion key 50: High Tom.
+  HighTom = 50,
+  /// General MIDI percussion key 51: Ride Cymbal 1.
+  RideCymbal1 = 51,
+  /// General MIDI percussion key 52: Chinese Cymbal.
+  ChineseCymbal = 52,
+  /// General MIDI percussion key 53: Ride Bell.
+  RideBell = 53,
+  /// General MIDI percussion key 54: Tambourine.
+  Tambourine = 54,
+  /// General MIDI percussion key 55: Splash Cymbal.
+  SplashCymbal = 55,
+  /// General MIDI percussion key 56: Cowbell.
+  Cowbell = 56,
+  /// General MIDI percussion key 57: Crash Cymbal 2.
+  CrashCymbal2 = 57,
+  /// General MIDI percussion key 58: Vibraslap.
+  Vibraslap = 58,
+  /// General MIDI percussion key 59: Ride Cymbal 2.
+  RideCymbal2 = 59,
+  /// General MIDI percussion key 60: High Bongo.
+  HighBongo = 60,
+  /// General MIDI percussion key 61: Low Bongo.
+  LowBongo = 61,
+  /// General MIDI percussion key 62: Mute High Conga.
+  MuteHighConga = 62,
+  /// General MIDI percussion key 63: Open High Conga.
+  OpenHighConga = 63,
+  /// General MIDI percussion key 64: Low Conga.
+  LowConga = 64,
+  /// General MIDI percussion key 65: High Timbale.
+  HighTimbale = 65,
+  /// General MIDI percussion key 66: Low Timbale.
+  LowTimbale = 66,
+  /// General MIDI percussion key 67: High Agogo.
+  HighAgogo = 67,
+  /// General MIDI percussion key 68: Low Agogo.
+  LowAgogo = 68,
+  /// General MIDI percussion key 69: Cabasa.
+  Cabasa = 69,
+  /// General MIDI percussion key 70: Maracas.
+  Maracas = 70,
+  /// General MIDI percussion key 71: Short Whistle.
+  ShortWhistle = 71,
+  /// General MIDI percussion key 72: Long Whistle.
+  LongWhistle = 72,
+  /// General MIDI percussion key 73: Short Guiro.
+  ShortGuiro = 73,
+  /// General MIDI percussion key 74: Long Guiro.
+  LongGuiro = 74,
+  /// General MIDI percussion key 75: Claves.
+  Claves = 75,
+  /// General MIDI percussion key 76: High Wood Block.
+  HighWoodBlock = 76,
+  /// General MIDI percussion key 77: Low Wood Block.
+  LowWoodBlock = 77,
+  /// General MIDI percussion key 78: Mute Cuica.
+  MuteCuica = 78,
+  /// General MIDI percussion key 79: Open Cuica.
+  OpenCuica = 79,
+  /// General MIDI percussion key 80: Mute Triangle.
+  MuteTriangle = 80,
+  /// General MIDI percussion key 81: Open Triangle.
+  OpenTriangle = 81,
+}
+
+impl PercussionInstrument {
+  /// Returns the instrument triggered by the given MIDI note number on the General MIDI
+  /// percussion channel, or `None` if the note number is not part of the percussion key map.
+  #[must_use]
+  pub const fn from_midi_number(midi_number: u8) -> Option<Self> {
+    if midi_number < 35 || midi_number > 81 {
+      None
+    } else {
+      Some(unsafe { core::mem::transmute::<u8, PercussionInstrument>(midi_number) })
+    }
+  }
+
+  /// Returns the instrument most commonly notated at the given staff position using the given
+  /// notehead, or `None` if no instrument in the percussion key map is notated that way.
+  #[must_use]
+  pub fn from_display(display_pitch: Pitch, notehead: Notehead) -> Option<Self> {
+    (35..=81)
+      .filter_map(Self::from_midi_number)
+      .find(|instrument| instrument.display_pitch() == display_pitch && instrument.notehead() == notehead)
+  }
+
+  /// Returns the MIDI note number which triggers the instrument on the General MIDI percussion channel.
+  #[must_use]
+  pub const fn midi_number(&self) -> u8 {
+    *self as u8
+  }
+
+  /// Returns the human-readable name of the instrument.
+  #[must_use]
+  pub const fn name(&self) -> &'static str {
+    DRUM_MAP[(*self as usize) - 35].3
+  }
+
+  /// Returns the staff position at which the instrument is conventionally notated on a
+  /// five-line percussion staff, expressed as the pitch found at that position in a treble clef.
+  #[must_use]
+  pub const fn display_pitch(&self) -> Pitch {
+    let (name, octave, _, _) = DRUM_MAP[(*self as usize) - 35];
+    Pitch::new(name, octave)
+  }
+
+  /// Returns the notehead with which the instrument is conventionally notated.
+  #[must_use]
+  pub const fn notehead(&self) -> Notehead {
+    DRUM_MAP[(*self as usize) - 35].2
+  }
+}
+
+/// Represents the identity and notation of an unpitched percussion note.
+///
+/// The pitch of an unpitched note only designates its position on the staff,
+/// while the sounding instrument is determined by the `instrument` field.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, JsonDeserialize, JsonSerialize)]
+pub struct Unpitched {
+  /// The percussion instrument which plays the note.
+  pub instrument: PercussionInstrument,
+  /// The shape of the notehead used to notate the note.
+  pub notehead: Notehead,
+}
+
+impl Unpitched {
+  /// Creates a new unpitched note identity for the given percussion instrument,
+  /// using its conventional notehead.
+  #[must_use]
+  pub const fn new(instrument: PercussionInstrument) -> Self {
+    Self {
+      instrument,
+      notehead: instrument.notehead(),
+    }
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Notehead {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Normal => "Normal",
+        Self::X => "X",
+        Self::CircleX => "Circle-X",
+        Self::Diamond => "Diamond",
+        Self::Triangle => "Triangle",
+        Self::Slash => "Slash",
+        Self::Square => "Square",
+      }
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for PercussionInstrument {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Unpitched {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.notehead == Notehead::Normal {
+      write!(f, "{}", self.instrument)
+    } else {
+      write!(f, "{} ({} Notehead)", self.instrument, self.notehead)
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_drum_map() {
+    assert_eq!(PercussionInstrument::from_midi_number(34), None);
+    assert_eq!(PercussionInstrument::from_midi_number(82), None);
+    let hi_hat = PercussionInstrument::from_midi_number(42).unwrap();
+    assert_eq!(hi_hat, PercussionInstrument::ClosedHiHat);
+    assert_eq!(hi_hat.name(), "Closed Hi-Hat");
+    assert_eq!(hi_hat.display_pitch(), Pitch::new(PitchName::G, 5));
+    assert_eq!(Unpitched::new(hi_hat).notehead, Notehead::X);
+    assert_eq!(PercussionInstrument::OpenTriangle.midi_number(), 81);
+    assert_eq!(
+      PercussionInstrument::from_display(Pitch::new(PitchName::C, 5), Notehead::Normal),
+      Some(PercussionInstrument::AcousticSnare)
+    );
+    assert_eq!(
+      PercussionInstrument::from_display(Pitch::new(PitchName::C, 5), Notehead::Diamond),
+      None
+    );
+  }
+}
@@ -34,6 +34,48 @@ impl PitchName {
       PitchName::G => 7,
     }
   }
+
+  /// Returns the position of the pitch name within a diatonic octave starting on C.
+  #[must_use]
+  pub(crate) const fn diatonic_index(self) -> i16 {
+    match self {
+      PitchName::Rest | PitchName::C => 0,
+      PitchName::D => 1,
+      PitchName::E => 2,
+      PitchName::F => 3,
+      PitchName::G => 4,
+      PitchName::A => 5,
+      PitchName::B => 6,
+    }
+  }
+
+  /// Returns the pitch name at the given position within a diatonic octave starting on C.
+  #[must_use]
+  pub(crate) const fn from_diatonic_index(index: i16) -> Self {
+    match index.rem_euclid(7) {
+      0 => PitchName::C,
+      1 => PitchName::D,
+      2 => PitchName::E,
+      3 => PitchName::F,
+      4 => PitchName::G,
+      5 => PitchName::A,
+      _ => PitchName::B,
+    }
+  }
+
+  /// Returns the number of semitones between C and the natural version of the pitch name.
+  #[must_use]
+  pub(crate) const fn natural_semitones(self) -> i16 {
+    match self {
+      PitchName::Rest | PitchName::C => 0,
+      PitchName::D => 2,
+      PitchName::E => 4,
+      PitchName::F => 5,
+      PitchName::G => 7,
+      PitchName::A => 9,
+      PitchName::B => 11,
+    }
+  }
 }
 
 /// Represents a musical pitch, which is a combination of a pitch name and octave.
@@ -88,6 +130,18 @@ impl Pitch {
       PitchName::G => (7, (10 + (12 * self.octave)) as i8 - 60),
     }
   }
+
+  /// Returns the number of diatonic steps between C0 and the pitch.
+  #[must_use]
+  pub(crate) const fn diatonic_value(self) -> i16 {
+    7 * self.octave as i16 + self.name.diatonic_index()
+  }
+
+  /// Returns the number of semitones between C0 and the natural version of the pitch.
+  #[must_use]
+  pub(crate) const fn semitones_from_c0(self) -> i16 {
+    12 * self.octave as i16 + self.name.natural_semitones()
+  }
 }
 
 impl Ord for Pitch {
@@ -0,0 +1,346 @@
+use core::cmp::Ordering;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Returns the greatest common divisor of the absolute values of two integers.
+const fn gcd(a: i128, b: i128) -> i128 {
+  let (mut a, mut b) = (a.abs(), b.abs());
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  a
+}
+
+/// Represents an exact rational number, such as a musical duration or a
+/// number of beats, as a fraction of two integers.
+///
+/// Rationals are always stored in lowest terms with a positive denominator, so
+/// that equal values compare equal regardless of how they were computed. This
+/// avoids the rounding drift that accumulates when adding many floating-point
+/// durations, such as the notes of nested tuplets.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rational {
+  numerator: i64,
+  denominator: i64,
+}
+
+impl Rational {
+  /// The rational number zero.
+  pub const ZERO: Self = Self::from_integer(0);
+  /// The rational number one.
+  pub const ONE: Self = Self::from_integer(1);
+
+  /// Creates a new rational number from the given numerator and denominator,
+  /// reduced to lowest terms.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the denominator is zero.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation)]
+  pub const fn new(numerator: i64, denominator: i64) -> Self {
+    assert!(denominator != 0, "Rational denominator must not be zero");
+    let divisor = gcd(numerator as i128, denominator as i128) as i64;
+    let sign = if denominator < 0 { -1 } else { 1 };
+    Self {
+      numerator: sign * numerator / divisor,
+      denominator: sign * denominator / divisor,
+    }
+  }
+
+  /// Creates a new rational number representing the given integer.
+  #[must_use]
+  pub const fn from_integer(value: i64) -> Self {
+    Self {
+      numerator: value,
+      denominator: 1,
+    }
+  }
+
+  /// Returns the closest rational number to the given floating-point value whose
+  /// denominator does not exceed `max_denominator`.
+  ///
+  /// Non-finite values are converted to zero.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+  pub fn from_f64(value: f64, max_denominator: i64) -> Self {
+    if !value.is_finite() {
+      return Self::ZERO;
+    }
+    let max_denominator = max_denominator.max(1);
+    let (mut previous, mut current) = ((0_i64, 1_i64), (1_i64, 0_i64));
+    let mut remainder = value;
+    loop {
+      let term = remainder.floor();
+      let next = (term as i64)
+        .checked_mul(current.0)
+        .and_then(|numerator| numerator.checked_add(previous.0))
+        .zip(
+          (term as i64)
+            .checked_mul(current.1)
+            .and_then(|denominator| denominator.checked_add(previous.1)),
+        );
+      match next {
+        Some(next) if next.1 <= max_denominator => (previous, current) = (current, next),
+        _ => break,
+      }
+      let fraction = remainder - term;
+      if fraction.abs() < f64::EPSILON || (current.0 as f64 / current.1 as f64 - value).abs() < f64::EPSILON {
+        break;
+      }
+      remainder = 1.0 / fraction;
+    }
+    if current.1 == 0 {
+      Self::from_integer(value as i64)
+    } else {
+      Self::new(current.0, current.1)
+    }
+  }
+
+  fn from_i128(numerator: i128, denominator: i128) -> Self {
+    let divisor = gcd(numerator, denominator);
+    let sign = if denominator < 0 { -1 } else { 1 };
+    let (numerator, denominator) = (sign * numerator / divisor, sign * denominator / divisor);
+    if let (Ok(numerator), Ok(denominator)) = (i64::try_from(numerator), i64::try_from(denominator)) {
+      Self { numerator, denominator }
+    } else {
+      #[allow(clippy::cast_precision_loss)]
+      let value = numerator as f64 / denominator as f64;
+      Self::from_f64(value, i64::from(u32::MAX))
+    }
+  }
+
+  /// Returns the numerator of the rational number in lowest terms.
+  #[must_use]
+  pub const fn numerator(&self) -> i64 {
+    self.numerator
+  }
+
+  /// Returns the strictly positive denominator of the rational number in lowest terms.
+  #[must_use]
+  pub const fn denominator(&self) -> i64 {
+    self.denominator
+  }
+
+  /// Returns whether the rational number is zero.
+  #[must_use]
+  pub const fn is_zero(&self) -> bool {
+    self.numerator == 0
+  }
+
+  /// Returns whether the rational number is strictly greater than zero.
+  #[must_use]
+  pub const fn is_positive(&self) -> bool {
+    self.numerator > 0
+  }
+
+  /// Returns whether the rational number is strictly less than zero.
+  #[must_use]
+  pub const fn is_negative(&self) -> bool {
+    self.numerator < 0
+  }
+
+  /// Returns the absolute value of the rational number.
+  #[must_use]
+  pub const fn abs(&self) -> Self {
+    Self {
+      numerator: self.numerator.abs(),
+      denominator: self.denominator,
+    }
+  }
+
+  /// Returns the reciprocal of the rational number.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the rational number is zero.
+  #[must_use]
+  pub const fn recip(&self) -> Self {
+    Self::new(self.denominator, self.numerator)
+  }
+
+  /// Returns the largest integer less than or equal to the rational number.
+  #[must_use]
+  pub const fn floor(&self) -> i64 {
+    self.numerator.div_euclid(self.denominator)
+  }
+
+  /// Returns the non-negative fractional part of the rational number, such that
+  /// `floor() + fract()` is equal to the original number.
+  #[must_use]
+  pub const fn fract(&self) -> Self {
+    Self {
+      numerator: self.numerator.rem_euclid(self.denominator),
+      denominator: self.denominator,
+    }
+  }
+
+  /// Returns the closest floating-point approximation of the rational number.
+  #[must_use]
+  #[allow(clippy::cast_precision_loss)]
+  pub fn to_f64(&self) -> f64 {
+    self.numerator as f64 / self.denominator as f64
+  }
+}
+
+impl Default for Rational {
+  fn default() -> Self {
+    Self::ZERO
+  }
+}
+
+impl From<i64> for Rational {
+  fn from(value: i64) -> Self {
+    Self::from_integer(value)
+  }
+}
+
+impl From<u32> for Rational {
+  fn from(value: u32) -> Self {
+    Self::from_integer(i64::from(value))
+  }
+}
+
+impl From<u16> for Rational {
+  fn from(value: u16) -> Self {
+    Self::from_integer(i64::from(value))
+  }
+}
+
+impl From<u8> for Rational {
+  fn from(value: u8) -> Self {
+    Self::from_integer(i64::from(value))
+  }
+}
+
+impl From<Rational> for f64 {
+  fn from(value: Rational) -> Self {
+    value.to_f64()
+  }
+}
+
+impl Ord for Rational {
+  fn cmp(&self, other: &Self) -> Ordering {
+    (i128::from(self.numerator) * i128::from(other.denominator))
+      .cmp(&(i128::from(other.numerator) * i128::from(self.denominator)))
+  }
+}
+
+impl PartialOrd for Rational {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Add for Rational {
+  type Output = Self;
+  fn add(self, rhs: Self) -> Self::Output {
+    Self::from_i128(
+      i128::from(self.numerator) * i128::from(rhs.denominator)
+        + i128::from(rhs.numerator) * i128::from(self.denominator),
+      i128::from(self.denominator) * i128::from(rhs.denominator),
+    )
+  }
+}
+
+impl Sub for Rational {
+  type Output = Self;
+  fn sub(self, rhs: Self) -> Self::Output {
+    self + -rhs
+  }
+}
+
+impl Mul for Rational {
+  type Output = Self;
+  fn mul(self, rhs: Self) -> Self::Output {
+    Self::from_i128(
+      i128::from(self.numerator) * i128::from(rhs.numerator),
+      i128::from(self.denominator) * i128::from(rhs.denominator),
+    )
+  }
+}
+
+impl Div for Rational {
+  type Output = Self;
+  fn div(self, rhs: Self) -> Self::Output {
+    assert!(!rhs.is_zero(), "Cannot divide a Rational by zero");
+    Self::from_i128(
+      i128::from(self.numerator) * i128::from(rhs.denominator),
+      i128::from(self.denominator) * i128::from(rhs.numerator),
+    )
+  }
+}
+
+impl Neg for Rational {
+  type Output = Self;
+  fn neg(self) -> Self::Output {
+    Self {
+      numerator: -self.numerator,
+      denominator: self.denominator,
+    }
+  }
+}
+
+impl AddAssign for Rational {
+  fn add_assign(&mut self, rhs: Self) {
+    *self = *self + rhs;
+  }
+}
+
+impl SubAssign for Rational {
+  fn sub_assign(&mut self, rhs: Self) {
+    *self = *self - rhs;
+  }
+}
+
+impl MulAssign for Rational {
+  fn mul_assign(&mut self, rhs: Self) {
+    *self = *self * rhs;
+  }
+}
+
+impl DivAssign for Rational {
+  fn div_assign(&mut self, rhs: Self) {
+    *self = *self / rhs;
+  }
+}
+
+impl core::iter::Sum for Rational {
+  fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+    iter.fold(Self::ZERO, Add::add)
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Rational {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.denominator == 1 {
+      write!(f, "{}", self.numerator)
+    } else {
+      write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_rational_arithmetic() {
+    let third = Rational::new(2, 6);
+    assert_eq!((third.numerator(), third.denominator()), (1, 3));
+    assert_eq!(Rational::new(3, -9), -third);
+    assert_eq!(third + third + third, Rational::ONE);
+    assert_eq!(
+      (0..3000).map(|_| Rational::new(1, 3)).sum::<Rational>(),
+      Rational::from(1000_u32)
+    );
+    assert_eq!(Rational::new(3, 2) * Rational::new(2, 3), Rational::ONE);
+    assert_eq!(Rational::new(1, 4) / Rational::new(1, 8), Rational::from(2_u8));
+    assert!(Rational::new(1, 3) < Rational::new(1, 2));
+    assert_eq!(Rational::new(-7, 2).floor(), -4);
+    assert_eq!(Rational::new(-7, 2).fract(), Rational::new(1, 2));
+    assert_eq!(Rational::from_f64(0.375, 2048), Rational::new(3, 8));
+    assert_eq!(Rational::from_f64(1.0 / 3.0, 1000), Rational::new(1, 3));
+  }
+}
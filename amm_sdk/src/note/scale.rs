@@ -0,0 +1,573 @@
+use super::{Accidental, Interval, IntervalQuality, Note, Pitch, PitchName};
+use crate::context::{Key, TimeSignature};
+use crate::structure::{Phrase, Staff};
+use crate::temporal::{AccidentalResolver, Timeslice, TimesliceContent};
+use amm_internal::amm_prelude::*;
+
+const fn perfect(number: u8) -> Interval {
+  Interval::new(IntervalQuality::Perfect, number)
+}
+
+const fn major(number: u8) -> Interval {
+  Interval::new(IntervalQuality::Major, number)
+}
+
+const fn minor(number: u8) -> Interval {
+  Interval::new(IntervalQuality::Minor, number)
+}
+
+const fn augmented(number: u8) -> Interval {
+  Interval::new(IntervalQuality::Augmented(1), number)
+}
+
+const fn diminished(number: u8) -> Interval {
+  Interval::new(IntervalQuality::Diminished(1), number)
+}
+
+const MAJOR: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  major(3),
+  perfect(4),
+  perfect(5),
+  major(6),
+  major(7),
+];
+const DORIAN: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  minor(3),
+  perfect(4),
+  perfect(5),
+  major(6),
+  minor(7),
+];
+const PHRYGIAN: [Interval; 7] = [
+  perfect(1),
+  minor(2),
+  minor(3),
+  perfect(4),
+  perfect(5),
+  minor(6),
+  minor(7),
+];
+const LYDIAN: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  major(3),
+  augmented(4),
+  perfect(5),
+  major(6),
+  major(7),
+];
+const MIXOLYDIAN: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  major(3),
+  perfect(4),
+  perfect(5),
+  major(6),
+  minor(7),
+];
+const AEOLIAN: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  minor(3),
+  perfect(4),
+  perfect(5),
+  minor(6),
+  minor(7),
+];
+const LOCRIAN: [Interval; 7] = [
+  perfect(1),
+  minor(2),
+  minor(3),
+  perfect(4),
+  diminished(5),
+  minor(6),
+  minor(7),
+];
+const HARMONIC_MINOR: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  minor(3),
+  perfect(4),
+  perfect(5),
+  minor(6),
+  major(7),
+];
+const MELODIC_MINOR: [Interval; 7] = [
+  perfect(1),
+  major(2),
+  minor(3),
+  perfect(4),
+  perfect(5),
+  major(6),
+  major(7),
+];
+const MAJOR_PENTATONIC: [Interval; 5] = [perfect(1), major(2), major(3), perfect(5), major(6)];
+const MINOR_PENTATONIC: [Interval; 5] = [perfect(1), minor(3), perfect(4), perfect(5), minor(7)];
+const BLUES: [Interval; 6] = [perfect(1), minor(3), perfect(4), diminished(5), perfect(5), minor(7)];
+const WHOLE_TONE: [Interval; 6] = [perfect(1), major(2), major(3), augmented(4), augmented(5), minor(7)];
+const OCTATONIC_HALF_WHOLE: [Interval; 8] = [
+  perfect(1),
+  minor(2),
+  augmented(2),
+  major(3),
+  augmented(4),
+  perfect(5),
+  major(6),
+  minor(7),
+];
+const OCTATONIC_WHOLE_HALF: [Interval; 8] = [
+  perfect(1),
+  major(2),
+  minor(3),
+  perfect(4),
+  diminished(5),
+  augmented(5),
+  major(6),
+  major(7),
+];
+
+/// Represents a named collection of intervals above a tonic which defines a scale.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ScaleType {
+  /// The major scale (Ionian mode).
+  #[default]
+  Major,
+  /// The Dorian mode.
+  Dorian,
+  /// The Phrygian mode.
+  Phrygian,
+  /// The Lydian mode.
+  Lydian,
+  /// The Mixolydian mode.
+  Mixolydian,
+  /// The natural minor scale (Aeolian mode).
+  Aeolian,
+  /// The Locrian mode.
+  Locrian,
+  /// The harmonic minor scale, which raises the seventh degree of the natural minor scale.
+  HarmonicMinor,
+  /// The ascending melodic minor scale, which raises the sixth and seventh degrees of the natural minor scale.
+  MelodicMinor,
+  /// The five-note major pentatonic scale.
+  MajorPentatonic,
+  /// The five-note minor pentatonic scale.
+  MinorPentatonic,
+  /// The six-note blues scale, which adds a diminished fifth to the minor pentatonic scale.
+  Blues,
+  /// The six-note whole-tone scale.
+  WholeTone,
+  /// The eight-note octatonic scale which starts with a half step.
+  OctatonicHalfWhole,
+  /// The eight-note octatonic scale which starts with a whole step.
+  OctatonicWholeHalf,
+  /// A user-defined collection of intervals.
+  Custom,
+}
+
+impl ScaleType {
+  /// All predefined scale types, ordered from most to least common.
+  const PREDEFINED: [Self; 15] = [
+    Self::Major,
+    Self::Aeolian,
+    Self::HarmonicMinor,
+    Self::MelodicMinor,
+    Self::Dorian,
+    Self::Mixolydian,
+    Self::Phrygian,
+    Self::Lydian,
+    Self::Locrian,
+    Self::MajorPentatonic,
+    Self::MinorPentatonic,
+    Self::Blues,
+    Self::WholeTone,
+    Self::OctatonicHalfWhole,
+    Self::OctatonicWholeHalf,
+  ];
+
+  /// Returns the ascending intervals above the tonic which make up the scale type.
+  ///
+  /// An empty list will be returned for a [`ScaleType::Custom`] scale type.
+  #[must_use]
+  pub const fn intervals(&self) -> &'static [Interval] {
+    match self {
+      Self::Major => &MAJOR,
+      Self::Dorian => &DORIAN,
+      Self::Phrygian => &PHRYGIAN,
+      Self::Lydian => &LYDIAN,
+      Self::Mixolydian => &MIXOLYDIAN,
+      Self::Aeolian => &AEOLIAN,
+      Self::Locrian => &LOCRIAN,
+      Self::HarmonicMinor => &HARMONIC_MINOR,
+      Self::MelodicMinor => &MELODIC_MINOR,
+      Self::MajorPentatonic => &MAJOR_PENTATONIC,
+      Self::MinorPentatonic => &MINOR_PENTATONIC,
+      Self::Blues => &BLUES,
+      Self::WholeTone => &WHOLE_TONE,
+      Self::OctatonicHalfWhole => &OCTATONIC_HALF_WHOLE,
+      Self::OctatonicWholeHalf => &OCTATONIC_WHOLE_HALF,
+      Self::Custom => &[],
+    }
+  }
+}
+
+/// Represents a scale built from a collection of intervals above a tonic (root note).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scale {
+  /// The pitch name of the tonic of the scale.
+  pub tonic: PitchName,
+  /// The accidental applied to the tonic of the scale.
+  pub accidental: Accidental,
+  /// The type of the scale.
+  pub scale_type: ScaleType,
+  /// The ascending intervals above the tonic which make up the scale.
+  intervals: Vec<Interval>,
+}
+
+impl Scale {
+  /// Creates a new predefined scale starting on the given tonic.
+  ///
+  /// Creating a [`ScaleType::Custom`] scale with this function will result in
+  /// a scale with no notes; use [`Scale::custom`] instead.
+  #[must_use]
+  pub fn new(tonic: PitchName, accidental: Accidental, scale_type: ScaleType) -> Self {
+    Self {
+      tonic,
+      accidental,
+      scale_type,
+      intervals: scale_type.intervals().to_vec(),
+    }
+  }
+
+  /// Creates a new user-defined scale from the given intervals above the tonic.
+  ///
+  /// Any descending intervals will be treated as ascending, and the intervals
+  /// will be ordered by their number of semitones.
+  #[must_use]
+  pub fn custom(tonic: PitchName, accidental: Accidental, intervals: &[Interval]) -> Self {
+    let mut intervals: Vec<Interval> = intervals
+      .iter()
+      .map(|interval| Interval {
+        descending: false,
+        ..*interval
+      })
+      .collect();
+    intervals.sort_by_key(|interval| (interval.semitones(), interval.number));
+    intervals.dedup();
+    Self {
+      tonic,
+      accidental,
+      scale_type: ScaleType::Custom,
+      intervals,
+    }
+  }
+
+  /// Returns the ascending intervals above the tonic which make up the scale.
+  #[must_use]
+  pub fn intervals(&self) -> &[Interval] {
+    &self.intervals
+  }
+
+  /// Returns the spelling of each note in the scale, starting from the tonic.
+  ///
+  /// Notes which would require more than a double sharp or double flat are omitted.
+  #[must_use]
+  pub fn spell(&self) -> Vec<(PitchName, Accidental)> {
+    self
+      .pitches(4)
+      .into_iter()
+      .map(|(pitch, accidental)| (pitch.name, accidental))
+      .collect()
+  }
+
+  /// Returns the pitch and accidental of each note in one ascending octave of the
+  /// scale, starting from the tonic in the given octave.
+  ///
+  /// Notes which would require more than a double sharp or double flat are omitted.
+  #[must_use]
+  pub fn pitches(&self, octave: u8) -> Vec<(Pitch, Accidental)> {
+    let tonic = Pitch::new(self.tonic, octave);
+    self
+      .intervals
+      .iter()
+      .filter_map(|interval| interval.apply(tonic, self.accidental))
+      .collect()
+  }
+
+  /// Returns the distinct pitch classes in the scale in ascending order,
+  /// where 0 represents C and 11 represents B.
+  #[must_use]
+  pub fn pitch_classes(&self) -> Vec<u8> {
+    let tonic = pitch_class(self.tonic, self.accidental);
+    let mut pitch_classes: Vec<u8> = self
+      .intervals
+      .iter()
+      .filter_map(|interval| u8::try_from((i16::from(tonic) + interval.semitones()).rem_euclid(12)).ok())
+      .collect();
+    pitch_classes.sort_unstable();
+    pitch_classes.dedup();
+    pitch_classes
+  }
+
+  /// Returns whether the scale contains the given pitch class, where 0 represents C and 11 represents B.
+  #[must_use]
+  pub fn contains_pitch_class(&self, pitch_class: u8) -> bool {
+    self.pitch_classes().contains(&(pitch_class % 12))
+  }
+
+  /// Returns whether the scale contains the given pitch name and accidental,
+  /// regardless of enharmonic spelling.
+  #[must_use]
+  pub fn contains(&self, pitch: PitchName, accidental: Accidental) -> bool {
+    pitch != PitchName::Rest && self.contains_pitch_class(pitch_class(pitch, accidental))
+  }
+
+  /// Returns whether the scale contains the sounding pitch of the given note, where
+  /// the `key` parameter defines the key signature that the note is written in.
+  ///
+  /// The note is read against the key signature alone, since accidentals carried over
+  /// from earlier notes in the same measure are unknown; use [`Scale::contains_content`]
+  /// for notes whose accidentals have been resolved by an [`AccidentalResolver`].
+  #[must_use]
+  pub fn contains_note(&self, note: &Note, key: Option<Key>) -> bool {
+    !note.is_rest() && !note.is_unpitched() && self.contains_pitch_class(note.midi_number(key) % 12)
+  }
+
+  /// Returns whether the scale contains the sounding pitch of the given timeslice content,
+  /// using its resolved accidental if available, where the `key` parameter defines the key
+  /// signature that the content is written in.
+  #[must_use]
+  pub fn contains_content(&self, content: &TimesliceContent, key: Option<Key>) -> bool {
+    !content.note.is_rest() && !content.note.is_unpitched() && self.contains_pitch_class(content.midi_number(key) % 12)
+  }
+
+  /// Returns every predefined scale which contains all notes in the given phrase,
+  /// ordered from the best to the worst fit.
+  ///
+  /// Scales with the fewest unused notes are considered the best fit, followed by
+  /// scales whose tonic occupies the largest share of the phrase's duration.
+  ///
+  /// The `key` and `time_signature` parameters define the key signature and time
+  /// signature that the phrase starts in, which determine how accidentals carry over.
+  #[must_use]
+  pub fn fitting_phrase(phrase: &Phrase, key: Option<Key>, time_signature: Option<TimeSignature>) -> Vec<Self> {
+    Self::fitting_timeslices(phrase.iter_timeslices(), key, time_signature)
+  }
+
+  /// Returns every predefined scale which contains all notes in the given staff,
+  /// ordered from the best to the worst fit.
+  ///
+  /// Scales with the fewest unused notes are considered the best fit, followed by
+  /// scales whose tonic occupies the largest share of the staff's duration.
+  ///
+  /// The `key` and `time_signature` parameters define the key signature and time
+  /// signature that the staff starts in, which determine how accidentals carry over.
+  #[must_use]
+  pub fn fitting_staff(staff: &Staff, key: Option<Key>, time_signature: Option<TimeSignature>) -> Vec<Self> {
+    Self::fitting_timeslices(staff.iter_timeslices(), key, time_signature)
+  }
+
+  fn fitting_timeslices(
+    timeslices: impl Iterator<Item = Timeslice>,
+    key: Option<Key>,
+    time_signature: Option<TimeSignature>,
+  ) -> Vec<Self> {
+    let beat_base = super::Duration::new(super::DurationType::Quarter, 0);
+    let mut histogram = [0.0; 12];
+    let (starting_key, starting_time_signature) = (key.unwrap_or_default(), time_signature.unwrap_or_default());
+    for timeslice in AccidentalResolver::new(timeslices, starting_key, starting_time_signature) {
+      timeslice
+        .content
+        .iter()
+        .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+        .for_each(|content| {
+          histogram[usize::from(content.midi_number(key) % 12)] += content.get_beats(&beat_base).to_f64().max(0.001);
+        });
+    }
+    if histogram.iter().all(|weight| *weight <= 0.0) {
+      return Vec::new();
+    }
+    let mut fits: Vec<(usize, f64, Self)> = ScaleType::PREDEFINED
+      .iter()
+      .flat_map(|scale_type| (0..12).map(|tonic| Self::from_pitch_class(tonic, *scale_type)))
+      .filter_map(|scale| {
+        let pitch_classes = scale.pitch_classes();
+        let is_fit = (0..12_u8)
+          .all(|pitch_class| histogram[usize::from(pitch_class)] <= 0.0 || pitch_classes.contains(&pitch_class));
+        is_fit.then(|| {
+          let unused = pitch_classes
+            .iter()
+            .filter(|pitch_class| histogram[usize::from(**pitch_class)] <= 0.0)
+            .count();
+          let tonic_weight = histogram[usize::from(pitch_class(scale.tonic, scale.accidental))];
+          (unused, tonic_weight, scale)
+        })
+      })
+      .collect();
+    fits.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+    fits.into_iter().map(|(_, _, scale)| scale).collect()
+  }
+
+  /// Creates a predefined scale on the given tonic pitch class, choosing the tonic
+  /// spelling which results in the fewest accidentals.
+  fn from_pitch_class(tonic: u8, scale_type: ScaleType) -> Self {
+    let candidates = [Accidental::None, Accidental::Flat, Accidental::Sharp]
+      .into_iter()
+      .flat_map(|accidental| {
+        [
+          PitchName::C,
+          PitchName::D,
+          PitchName::E,
+          PitchName::F,
+          PitchName::G,
+          PitchName::A,
+          PitchName::B,
+        ]
+        .map(|name| Self::new(name, accidental, scale_type))
+      })
+      .filter(|scale| pitch_class(scale.tonic, scale.accidental) == tonic % 12);
+    candidates
+      .min_by_key(|scale| {
+        let spelling = scale.spell();
+        let missing = scale.intervals.len() - spelling.len();
+        let accidentals: u32 = spelling
+          .iter()
+          .map(|(_, accidental)| u32::from(accidental.value().unsigned_abs()))
+          .sum();
+        (missing, accidentals)
+      })
+      .unwrap_or_else(|| Self::new(PitchName::C, Accidental::None, scale_type))
+  }
+}
+
+/// Returns the pitch class of the given pitch name and accidental, where 0 represents C and 11 represents B.
+fn pitch_class(name: PitchName, accidental: Accidental) -> u8 {
+  u8::try_from((name.natural_semitones() + i16::from(accidental.value())).rem_euclid(12)).unwrap_or_default()
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for ScaleType {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Major => "Major",
+        Self::Dorian => "Dorian",
+        Self::Phrygian => "Phrygian",
+        Self::Lydian => "Lydian",
+        Self::Mixolydian => "Mixolydian",
+        Self::Aeolian => "Aeolian",
+        Self::Locrian => "Locrian",
+        Self::HarmonicMinor => "Harmonic Minor",
+        Self::MelodicMinor => "Melodic Minor",
+        Self::MajorPentatonic => "Major Pentatonic",
+        Self::MinorPentatonic => "Minor Pentatonic",
+        Self::Blues => "Blues",
+        Self::WholeTone => "Whole-Tone",
+        Self::OctatonicHalfWhole => "Octatonic (Half-Whole)",
+        Self::OctatonicWholeHalf => "Octatonic (Whole-Half)",
+        Self::Custom => "Custom",
+      }
+    )
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Scale {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{}{} {}", self.tonic, self.accidental, self.scale_type)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+  use crate::note::{Duration, DurationType};
+
+  #[test]
+  fn test_spelling() {
+    let scale = Scale::new(PitchName::D, Accidental::None, ScaleType::Dorian);
+    assert_eq!(
+      scale.spell(),
+      [
+        (PitchName::D, Accidental::None),
+        (PitchName::E, Accidental::None),
+        (PitchName::F, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::A, Accidental::None),
+        (PitchName::B, Accidental::None),
+        (PitchName::C, Accidental::None),
+      ]
+    );
+    let scale = Scale::new(PitchName::B, Accidental::Flat, ScaleType::Blues);
+    assert_eq!(
+      scale.spell(),
+      [
+        (PitchName::B, Accidental::Flat),
+        (PitchName::D, Accidental::Flat),
+        (PitchName::E, Accidental::Flat),
+        (PitchName::F, Accidental::Flat),
+        (PitchName::F, Accidental::None),
+        (PitchName::A, Accidental::Flat),
+      ]
+    );
+    assert_eq!(
+      Scale::new(PitchName::C, Accidental::None, ScaleType::OctatonicHalfWhole).pitch_classes(),
+      [0, 1, 3, 4, 6, 7, 9, 10]
+    );
+    let pitches = Scale::new(PitchName::A, Accidental::None, ScaleType::HarmonicMinor).pitches(3);
+    assert_eq!(pitches[6], (Pitch::new(PitchName::G, 4), Accidental::Sharp));
+    let custom = Scale::custom(
+      PitchName::C,
+      Accidental::None,
+      &[perfect(5), perfect(1), Interval::from_semitones(-3), perfect(5)],
+    );
+    assert_eq!(custom.pitch_classes(), [0, 3, 7]);
+  }
+
+  #[test]
+  fn test_membership() {
+    let scale = Scale::new(PitchName::E, Accidental::None, ScaleType::Major);
+    assert!(scale.contains(PitchName::G, Accidental::Sharp));
+    assert!(scale.contains(PitchName::A, Accidental::Flat));
+    assert!(!scale.contains(PitchName::G, Accidental::None));
+    let note = Note::new(
+      Pitch::new(PitchName::F, 4),
+      Duration::new(DurationType::Quarter, 0),
+      None,
+    );
+    assert!(scale.contains_note(&note, Some(Key::new(KeySignature::E, KeyMode::Major))));
+    assert!(!scale.contains_note(&note, None));
+  }
+
+  #[test]
+  fn test_fitting_scales() {
+    let mut phrase = Phrase::new();
+    for (name, duration) in [
+      (PitchName::C, DurationType::Half),
+      (PitchName::D, DurationType::Quarter),
+      (PitchName::E, DurationType::Quarter),
+      (PitchName::G, DurationType::Quarter),
+      (PitchName::A, DurationType::Quarter),
+      (PitchName::C, DurationType::Half),
+    ] {
+      phrase.add_note(Pitch::new(name, 4), Duration::new(duration, 0), None);
+    }
+    let scales = Scale::fitting_phrase(&phrase, None, None);
+    assert_eq!(
+      scales[0],
+      Scale::new(PitchName::C, Accidental::None, ScaleType::MajorPentatonic)
+    );
+    assert_eq!(
+      scales[1],
+      Scale::new(PitchName::A, Accidental::None, ScaleType::MinorPentatonic)
+    );
+    assert!(scales.contains(&Scale::new(PitchName::G, Accidental::None, ScaleType::Major)));
+    assert!(!scales.contains(&Scale::new(PitchName::D, Accidental::None, ScaleType::Major)));
+  }
+}
@@ -0,0 +1,498 @@
+use super::{Accidental, Interval, IntervalQuality, Pitch, PitchName};
+use crate::context::{Key, TimeSignature};
+use crate::modification::DirectionType;
+use crate::temporal::{omit_implied_accidentals, AccidentalResolver, Timeslice};
+use alloc::collections::{BTreeMap, BTreeSet};
+use amm_internal::amm_prelude::*;
+
+const MAX_CHORD_STATES: usize = 24;
+const CHROMATIC_ALTERATION_COST: f64 = 1.0;
+const DOUBLE_ALTERATION_COST: f64 = 3.0;
+const DOUBLE_ACCIDENTAL_COST: f64 = 1.0;
+const LETTER_CLASH_COST: f64 = 2.0;
+const HARMONIC_DISSONANT_SPELLING_COST: f64 = 0.5;
+const CHROMATIC_STEP_COST: f64 = 0.25;
+const AUGMENTED_STEP_COST: f64 = 1.5;
+const DIRECTION_COST: f64 = 0.5;
+const REPEATED_NOTE_COST: f64 = 2.0;
+const MIXED_ACCIDENTALS_COST: f64 = 0.25;
+
+const PITCH_NAMES: [PitchName; 7] = [
+  PitchName::C,
+  PitchName::D,
+  PitchName::E,
+  PitchName::F,
+  PitchName::G,
+  PitchName::A,
+  PitchName::B,
+];
+
+/// Represents a single way of spelling a sounding pitch.
+#[derive(Clone, Copy, Debug)]
+struct Spelling {
+  pitch: Pitch,
+  accidental: Accidental,
+  midi_number: u8,
+  alteration: i16,
+  cost: f64,
+}
+
+/// Represents a possible spelling of every note which starts in a single timeslice.
+#[derive(Clone, Debug)]
+struct SliceState {
+  spellings: Vec<Spelling>,
+  cost: f64,
+}
+
+/// Represents all notes which start in a single timeslice, along with the key in which they were written.
+struct Slice {
+  note_ids: Vec<usize>,
+  original: Vec<(Pitch, Accidental)>,
+  key: Key,
+  states: Vec<SliceState>,
+}
+
+/// Returns every spelling of the given MIDI number which requires no more than
+/// a double sharp or double flat, as pairs of written pitches and sounding accidentals.
+pub(crate) fn spellings_of(midi_number: u8) -> Vec<(Pitch, Accidental)> {
+  let semitones = i16::from(midi_number) - 12;
+  PITCH_NAMES
+    .iter()
+    .filter_map(|name| {
+      let octave = (semitones - name.natural_semitones() + 2).div_euclid(12);
+      let pitch = Pitch::new(*name, u8::try_from(octave).ok()?);
+      Accidental::from_value(semitones - pitch.semitones_from_c0()).map(|accidental| (pitch, accidental))
+    })
+    .collect()
+}
+
+/// Returns the accidental which should be written for the given sounding
+/// accidental on a pitch with the given name, so that it sounds correctly in a key.
+///
+/// The result ignores any accidentals carried over from earlier notes in the same
+/// measure; see [`omit_implied_accidentals`] to account for them.
+pub(crate) fn written_accidental(name: PitchName, sounding: Accidental, key: Key) -> Accidental {
+  if sounding.value() == key.accidentals()[name.index()].value() {
+    Accidental::None
+  } else if sounding == Accidental::None {
+    Accidental::Natural
+  } else {
+    sounding
+  }
+}
+
+/// Chooses a spelling for every pitched note which starts in the given timeslices, following
+/// any key and time signature changes that occur after the specified starting key and time
+/// signature.
+///
+/// Spellings are chosen to minimize a cost which accounts for the number of accidentals
+/// needed in the current key, the presence of augmented or diminished melodic steps,
+/// the direction of chromatic motion, and the spelling of simultaneously sounding notes.
+/// Accidentals carry over to later notes on the same pitch within a measure, both when
+/// reading the existing notes and when writing their new spellings. Only notes whose
+/// spelling should change are returned, mapped from their note identifiers to their new
+/// pitches and written accidentals.
+pub(crate) fn choose_spellings(
+  timeslices: impl Iterator<Item = Timeslice>,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+) -> BTreeMap<usize, (Pitch, Accidental)> {
+  // Gather all candidate spellings for each timeslice containing pitched notes, rewriting
+  // every note so that it sounds correctly against its key signature alone
+  let mut key = starting_key;
+  let mut slices = Vec::new();
+  let mut timeslices: Vec<_> = AccidentalResolver::new(timeslices, starting_key, starting_time_signature).collect();
+  let mut original_spellings = BTreeMap::new();
+  let mut implicit_note_ids = BTreeSet::new();
+  for timeslice in &mut timeslices {
+    timeslice.directions.iter().for_each(|direction| {
+      if let DirectionType::KeyChange { key: new_key } = direction.r#type {
+        key = new_key;
+      }
+    });
+    let mut note_ids = Vec::new();
+    let mut original = Vec::new();
+    let mut candidates = Vec::new();
+    for content in timeslice
+      .content
+      .iter_mut()
+      .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+    {
+      let sounding = content.sounding_accidental(Some(key));
+      let note = &mut content.note;
+      original_spellings
+        .entry(note.note_id)
+        .or_insert((note.pitch, note.accidental));
+      if note.accidental == Accidental::None {
+        implicit_note_ids.insert(note.note_id);
+        note.accidental = written_accidental(note.pitch.name, sounding, key);
+      }
+      if note.cents_offset == 0 && !sounding.is_microtonal() {
+        note_ids.push(note.note_id);
+        original.push((note.pitch, sounding));
+        candidates.push(candidate_spellings(note.midi_number_with_accidental(sounding), key));
+      }
+    }
+    if !candidates.is_empty() {
+      slices.push(Slice {
+        note_ids,
+        original,
+        key,
+        states: slice_states(&candidates),
+      });
+    }
+  }
+
+  // Find the lowest-cost sequence of spellings through all timeslices
+  let mut costs: Vec<Vec<f64>> = Vec::with_capacity(slices.len());
+  let mut previous_states: Vec<Vec<usize>> = Vec::with_capacity(slices.len());
+  for (slice_idx, slice) in slices.iter().enumerate() {
+    let (slice_costs, slice_previous) = slice
+      .states
+      .iter()
+      .map(|state| {
+        slice_idx.checked_sub(1).map_or((state.cost, 0), |previous_idx| {
+          slices[previous_idx]
+            .states
+            .iter()
+            .zip(&costs[previous_idx])
+            .enumerate()
+            .map(|(idx, (previous, cost))| (cost + state.cost + transition_cost(previous, state), idx))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap_or((state.cost, 0))
+        })
+      })
+      .unzip();
+    costs.push(slice_costs);
+    previous_states.push(slice_previous);
+  }
+  let mut state_idx = costs
+    .last()
+    .and_then(|last| {
+      last
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(idx, _)| idx)
+    })
+    .unwrap_or_default();
+
+  // Collect the chosen spellings which differ from the original spellings
+  let mut respellings = BTreeMap::new();
+  for (slice_idx, slice) in slices.iter().enumerate().rev() {
+    if let Some(state) = slice.states.get(state_idx) {
+      for ((note_id, original), spelling) in slice.note_ids.iter().zip(&slice.original).zip(&state.spellings) {
+        if original.0 != spelling.pitch || original.1.value() != spelling.accidental.value() {
+          respellings.insert(
+            *note_id,
+            (
+              spelling.pitch,
+              written_accidental(spelling.pitch.name, spelling.accidental, slice.key),
+            ),
+          );
+        }
+      }
+    }
+    state_idx = previous_states[slice_idx].get(state_idx).copied().unwrap_or_default();
+  }
+
+  // Omit any accidentals which are implied by earlier notes in the same measure
+  for content in timeslices.iter_mut().flat_map(|timeslice| timeslice.content.iter_mut()) {
+    if let Some((pitch, accidental)) = respellings.get(&content.note.note_id) {
+      content.note.pitch = *pitch;
+      content.note.accidental = *accidental;
+      implicit_note_ids.insert(content.note.note_id);
+    }
+  }
+  omit_implied_accidentals(
+    timeslices.into_iter(),
+    starting_key,
+    starting_key,
+    starting_time_signature,
+    &implicit_note_ids,
+  )
+  .into_iter()
+  .filter(|(note_id, spelling)| original_spellings.get(note_id) != Some(spelling))
+  .collect()
+}
+
+fn candidate_spellings(midi_number: u8, key: Key) -> Vec<Spelling> {
+  let key_accidentals = key.accidentals();
+  spellings_of(midi_number)
+    .into_iter()
+    .map(|(pitch, accidental)| {
+      let alteration = i16::from(accidental.value() - key_accidentals[pitch.name.index()].value());
+      let cost = match alteration.abs() {
+        0 => 0.0,
+        1 => CHROMATIC_ALTERATION_COST,
+        _ => DOUBLE_ALTERATION_COST,
+      } + if accidental.value().abs() > 1 {
+        DOUBLE_ACCIDENTAL_COST
+      } else {
+        0.0
+      };
+      Spelling {
+        pitch,
+        accidental,
+        midi_number,
+        alteration,
+        cost,
+      }
+    })
+    .collect()
+}
+
+fn slice_states(candidates: &[Vec<Spelling>]) -> Vec<SliceState> {
+  let mut states = vec![SliceState {
+    spellings: Vec::new(),
+    cost: 0.0,
+  }];
+  for note_candidates in candidates {
+    states = states
+      .iter()
+      .flat_map(|state| {
+        note_candidates.iter().map(|candidate| {
+          let harmonic_cost: f64 = state
+            .spellings
+            .iter()
+            .map(|other| harmonic_cost(other, candidate))
+            .sum();
+          let mut spellings = state.spellings.clone();
+          spellings.push(*candidate);
+          SliceState {
+            spellings,
+            cost: state.cost + candidate.cost + harmonic_cost,
+          }
+        })
+      })
+      .collect();
+    states.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+    states.truncate(MAX_CHORD_STATES);
+  }
+  states
+}
+
+fn is_dissonant_spelling(interval: Option<Interval>) -> bool {
+  interval.is_some_and(|interval| {
+    matches!(
+      interval.quality,
+      IntervalQuality::Augmented(_) | IntervalQuality::Diminished(_)
+    )
+  })
+}
+
+fn harmonic_cost(first: &Spelling, second: &Spelling) -> f64 {
+  if first.pitch.name == second.pitch.name && first.accidental != second.accidental {
+    LETTER_CLASH_COST
+  } else {
+    let (lower, upper) = if first.midi_number <= second.midi_number {
+      (first, second)
+    } else {
+      (second, first)
+    };
+    if is_dissonant_spelling(Interval::between(
+      lower.pitch,
+      lower.accidental,
+      upper.pitch,
+      upper.accidental,
+    )) {
+      HARMONIC_DISSONANT_SPELLING_COST
+    } else {
+      0.0
+    }
+  }
+}
+
+fn melodic_cost(previous: &Spelling, current: &Spelling) -> f64 {
+  if previous.midi_number == current.midi_number {
+    return if previous.pitch == current.pitch && previous.accidental.value() == current.accidental.value() {
+      0.0
+    } else {
+      REPEATED_NOTE_COST
+    };
+  }
+  let ascending = current.midi_number > previous.midi_number;
+  let interval = Interval::between(previous.pitch, previous.accidental, current.pitch, current.accidental);
+  let step_cost = if is_dissonant_spelling(interval) {
+    if interval.is_some_and(|interval| interval.number == 1) {
+      CHROMATIC_STEP_COST
+    } else {
+      AUGMENTED_STEP_COST
+    }
+  } else {
+    0.0
+  };
+  let direction_cost = if (ascending && current.alteration < 0) || (!ascending && current.alteration > 0) {
+    DIRECTION_COST
+  } else {
+    0.0
+  };
+  step_cost + direction_cost
+}
+
+fn transition_cost(previous: &SliceState, current: &SliceState) -> f64 {
+  let previous_sign = previous
+    .spellings
+    .iter()
+    .map(|spelling| spelling.alteration.signum())
+    .find(|sign| *sign != 0);
+  current
+    .spellings
+    .iter()
+    .map(|spelling| {
+      let nearest = previous
+        .spellings
+        .iter()
+        .min_by_key(|other| other.midi_number.abs_diff(spelling.midi_number));
+      let mixed_cost = match previous_sign {
+        Some(sign) if spelling.alteration.signum() == -sign => MIXED_ACCIDENTALS_COST,
+        _ => 0.0,
+      };
+      nearest.map_or(0.0, |nearest| melodic_cost(nearest, spelling)) + mixed_cost
+    })
+    .sum()
+}
+
+#[cfg(test)]
+mod test {
+  use crate::context::{Key, KeyMode, KeySignature, TimeSignature};
+  use crate::note::{Accidental, Duration, DurationType, Note, Pitch, PitchName};
+  use crate::structure::{Phrase, PhraseContent, Staff};
+
+  fn spelled_notes(phrase: &Phrase) -> Vec<(PitchName, Accidental)> {
+    phrase
+      .iter()
+      .filter_map(|item| match item {
+        PhraseContent::Note(note) => Some((note.pitch.name, note.accidental)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_enharmonic_equivalents() {
+    let key = Key::new(KeySignature::D, KeyMode::Major);
+    let note = Note::new(
+      Pitch::new(PitchName::F, 4),
+      Duration::new(DurationType::Quarter, 0),
+      None,
+    );
+    let equivalents: Vec<_> = note
+      .enharmonic_equivalents(Some(key))
+      .iter()
+      .map(|note| (note.pitch, note.accidental))
+      .collect();
+    assert_eq!(
+      equivalents,
+      [
+        (Pitch::new(PitchName::G, 4), Accidental::Flat),
+        (Pitch::new(PitchName::E, 4), Accidental::DoubleSharp),
+      ]
+    );
+    let note = Note::new(
+      Pitch::new(PitchName::C, 4),
+      Duration::new(DurationType::Quarter, 0),
+      None,
+    );
+    let equivalents = note.enharmonic_equivalents(None);
+    assert_eq!(equivalents[0].pitch, Pitch::new(PitchName::B, 3));
+    assert_eq!(equivalents[0].accidental, Accidental::Sharp);
+  }
+
+  #[test]
+  fn test_respell_phrase() {
+    let duration = Duration::new(DurationType::Whole, 0);
+    let mut phrase = Phrase::new();
+    for midi_number in [60, 61, 62, 63, 64, 65, 64, 63, 62, 61, 60] {
+      phrase.claim_note(Note::from_midi(midi_number, duration, None));
+    }
+    phrase.add_note(Pitch::new(PitchName::E, 4), duration, Some(Accidental::Sharp));
+    phrase.respell(None, None);
+    assert_eq!(
+      spelled_notes(&phrase),
+      [
+        (PitchName::C, Accidental::None),
+        (PitchName::C, Accidental::Sharp),
+        (PitchName::D, Accidental::None),
+        (PitchName::D, Accidental::Sharp),
+        (PitchName::E, Accidental::None),
+        (PitchName::F, Accidental::None),
+        (PitchName::E, Accidental::None),
+        (PitchName::E, Accidental::Flat),
+        (PitchName::D, Accidental::None),
+        (PitchName::D, Accidental::Flat),
+        (PitchName::C, Accidental::None),
+        (PitchName::F, Accidental::None),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_respell_staff_chords() {
+    let duration = Duration::new(DurationType::Quarter, 0);
+    let key = Key::new(KeySignature::A, KeyMode::Minor);
+    let mut staff = Staff::new("Piano");
+    let chord = staff.add_chord();
+    chord.add_note(Pitch::new(PitchName::E, 4), duration, None);
+    chord.add_note(Pitch::new(PitchName::A, 4), duration, Some(Accidental::Flat));
+    chord.add_note(Pitch::new(PitchName::B, 4), duration, None);
+    staff.add_note(Pitch::new(PitchName::A, 4), duration, None);
+    staff.respell(Some(key), None);
+    let chord = staff.iter().find_map(|item| match item {
+      crate::structure::StaffContent::Chord(chord) => Some(chord),
+      _ => None,
+    });
+    let spellings: Vec<_> = chord
+      .unwrap()
+      .iter()
+      .map(|crate::structure::ChordContent::Note(note)| (note.pitch, note.accidental))
+      .collect();
+    assert_eq!(spellings[1], (Pitch::new(PitchName::G, 4), Accidental::Sharp));
+  }
+
+  #[test]
+  fn test_respell_carried_accidentals() {
+    let duration = Duration::new(DurationType::Quarter, 0);
+    let mut staff = Staff::new("Treble");
+    staff.add_note(Pitch::new(PitchName::E, 4), duration, Some(Accidental::Sharp));
+    staff.add_note(Pitch::new(PitchName::E, 4), duration, None);
+    staff.add_note(Pitch::new(PitchName::G, 4), duration, None);
+    staff.add_note(Pitch::new(PitchName::E, 4), duration, None);
+    staff.respell(None, None);
+    let spellings: Vec<_> = staff
+      .iter_timeslices()
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| (content.note.pitch.name, content.note.accidental))
+      .collect();
+    assert_eq!(
+      spellings,
+      [
+        (PitchName::F, Accidental::None),
+        (PitchName::F, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::F, Accidental::None),
+      ]
+    );
+
+    let mut staff = Staff::new("Treble");
+    staff.add_note(Pitch::new(PitchName::E, 4), duration, Some(Accidental::Sharp));
+    staff.add_note(Pitch::new(PitchName::G, 4), duration, None);
+    staff.add_note(Pitch::new(PitchName::G, 4), duration, None);
+    staff.add_note(Pitch::new(PitchName::E, 4), duration, None);
+    staff.respell(None, Some(TimeSignature::new_explicit(3, 4)));
+    let spellings: Vec<_> = staff
+      .iter_timeslices()
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| (content.note.pitch.name, content.note.accidental))
+      .collect();
+    assert_eq!(
+      spellings,
+      [
+        (PitchName::F, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::E, Accidental::None),
+      ]
+    );
+  }
+}
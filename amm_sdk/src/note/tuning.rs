@@ -0,0 +1,424 @@
+use crate::context::Key;
+use amm_internal::amm_prelude::*;
+
+const A4_FREQUENCY_HZ: f64 = 440.0;
+const MIDI_NUMBER_A4: u8 = 69;
+const MIDI_NUMBER_C4: u8 = 60;
+const OCTAVE_CENTS: f64 = 1200.0;
+
+const EQUAL_TEMPERAMENT: [f64; 12] = [
+  0.0, 100.0, 200.0, 300.0, 400.0, 500.0, 600.0, 700.0, 800.0, 900.0, 1000.0, 1100.0,
+];
+const JUST_INTONATION: [f64; 12] = [
+  0.0, 111.731, 203.910, 315.641, 386.314, 498.045, 590.224, 701.955, 813.686, 884.359, 996.090, 1088.269,
+];
+const PYTHAGOREAN: [f64; 12] = [
+  0.0, 113.685, 203.910, 294.135, 407.820, 498.045, 611.730, 701.955, 815.640, 905.865, 996.090, 1109.775,
+];
+const QUARTER_COMMA_MEANTONE: [f64; 12] = [
+  0.0, 76.049, 193.157, 310.265, 386.314, 503.422, 579.471, 696.578, 772.627, 889.735, 1006.843, 1082.892,
+];
+const WERCKMEISTER_III: [f64; 12] = [
+  0.0, 90.225, 192.180, 294.135, 390.225, 498.045, 588.270, 696.090, 792.180, 888.270, 996.090, 1092.180,
+];
+const KIRNBERGER_III: [f64; 12] = [
+  0.0, 90.225, 193.157, 294.135, 386.314, 498.045, 590.224, 696.578, 792.180, 889.735, 996.090, 1088.269,
+];
+
+/// Represents a predefined twelve-note temperament.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Temperament {
+  /// Twelve-tone equal temperament, with every semitone exactly 100 cents wide.
+  #[default]
+  EqualTemperament,
+  /// Five-limit just intonation built from pure thirds and fifths above the reference tonic.
+  JustIntonation,
+  /// Pythagorean tuning built from a chain of pure fifths from E♭ to G♯.
+  Pythagorean,
+  /// Quarter-comma meantone built from a chain of fifths from E♭ to G♯ which are
+  /// narrowed to produce pure major thirds.
+  QuarterCommaMeantone,
+  /// Werckmeister III well temperament.
+  WerckmeisterIII,
+  /// Kirnberger III well temperament.
+  KirnbergerIII,
+}
+
+impl Temperament {
+  /// Returns the number of cents above the reference tonic for each of the twelve
+  /// chromatic degrees of the temperament.
+  #[must_use]
+  pub const fn cents(&self) -> [f64; 12] {
+    match self {
+      Self::EqualTemperament => EQUAL_TEMPERAMENT,
+      Self::JustIntonation => JUST_INTONATION,
+      Self::Pythagorean => PYTHAGOREAN,
+      Self::QuarterCommaMeantone => QUARTER_COMMA_MEANTONE,
+      Self::WerckmeisterIII => WERCKMEISTER_III,
+      Self::KirnbergerIII => KIRNBERGER_III,
+    }
+  }
+}
+
+/// Represents the mapping of MIDI note numbers to the degrees of a tuning,
+/// following the structure of a Scala keyboard mapping (`.kbm`) file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyboardMapping {
+  /// The lowest MIDI note number which is mapped to a scale degree.
+  pub first_note: u8,
+  /// The highest MIDI note number which is mapped to a scale degree.
+  pub last_note: u8,
+  /// The MIDI note number which is mapped to the first degree of the scale.
+  pub middle_note: u8,
+  /// The MIDI note number whose frequency is specified by `reference_frequency_hz`.
+  pub reference_note: u8,
+  /// The frequency of the reference note in Hertz.
+  pub reference_frequency_hz: f64,
+  /// The scale degree which is considered to be the formal octave of the mapping,
+  /// or 0 to use the period of the scale.
+  pub octave_degree: usize,
+  /// The scale degree assigned to each successive key starting from the middle note,
+  /// where `None` represents an unmapped key. An empty mapping assigns successive
+  /// scale degrees to successive keys.
+  pub mapping: Vec<Option<usize>>,
+}
+
+impl KeyboardMapping {
+  /// Creates a linear keyboard mapping which maps successive MIDI note numbers
+  /// to successive scale degrees, starting from the given middle note, with the
+  /// given reference note sounding at the given frequency.
+  #[must_use]
+  pub const fn linear(middle_note: u8, reference_note: u8, reference_frequency_hz: f64) -> Self {
+    Self {
+      first_note: 0,
+      last_note: 127,
+      middle_note,
+      reference_note,
+      reference_frequency_hz,
+      octave_degree: 0,
+      mapping: Vec::new(),
+    }
+  }
+
+  /// Parses a keyboard mapping from the contents of a Scala keyboard mapping (`.kbm`) file.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the file contents are not a valid Scala keyboard mapping.
+  pub fn from_scala(kbm: &str) -> Result<Self, String> {
+    let mut lines = scala_lines(kbm);
+    let mut next_value = |description: &str| {
+      lines
+        .next()
+        .ok_or_else(|| format!("Missing {description} in Scala keyboard mapping"))
+    };
+    let map_size: usize = parse_value(next_value("map size")?, "map size")?;
+    let first_note = parse_value(next_value("first note")?, "first note")?;
+    let last_note = parse_value(next_value("last note")?, "last note")?;
+    let middle_note = parse_value(next_value("middle note")?, "middle note")?;
+    let reference_note = parse_value(next_value("reference note")?, "reference note")?;
+    let reference_frequency_hz: f64 = parse_value(next_value("reference frequency")?, "reference frequency")?;
+    let octave_degree = parse_value(next_value("octave degree")?, "octave degree")?;
+    let mapping = (0..map_size)
+      .map(|_| {
+        let value = next_value("mapping entry")?;
+        if value.eq_ignore_ascii_case("x") {
+          Ok(None)
+        } else {
+          parse_value(value, "mapping entry").map(Some)
+        }
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    if first_note > last_note {
+      Err(String::from("First note is above last note in Scala keyboard mapping"))
+    } else if reference_frequency_hz <= 0.0 {
+      Err(String::from(
+        "Reference frequency must be positive in Scala keyboard mapping",
+      ))
+    } else {
+      Ok(Self {
+        first_note,
+        last_note,
+        middle_note,
+        reference_note,
+        reference_frequency_hz,
+        octave_degree,
+        mapping,
+      })
+    }
+  }
+}
+
+/// Represents a tuning system which determines the frequency of every MIDI note number.
+///
+/// A tuning consists of a repeating scale, defined by the number of cents above its first
+/// degree of every other degree (ending with the period of the scale, usually an octave),
+/// and a [`KeyboardMapping`] which determines how MIDI note numbers map to scale degrees
+/// and which note sounds at a fixed reference frequency.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tuning {
+  /// A short description of the tuning.
+  pub description: String,
+  degrees: Vec<f64>,
+  mapping: KeyboardMapping,
+}
+
+impl Tuning {
+  /// Creates a new tuning from the given twelve-note temperament.
+  ///
+  /// Key-dependent temperaments are built on the tonic of the major key sharing the
+  /// signature of `reference_key`, and A4 sounds at the specified frequency (or 440 Hz
+  /// if `None`).
+  #[must_use]
+  pub fn new(temperament: Temperament, reference_key: Option<Key>, a4_frequency_hz: Option<f32>) -> Self {
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    let tonic = (7 * i16::from(reference_key.unwrap_or_default().fifths())).rem_euclid(12) as u8;
+    let cents = temperament.cents();
+    Self {
+      description: format!("{temperament:?}"),
+      degrees: cents[1..].iter().copied().chain([OCTAVE_CENTS]).collect(),
+      mapping: KeyboardMapping::linear(
+        MIDI_NUMBER_C4 + tonic,
+        MIDI_NUMBER_A4,
+        a4_frequency_hz.map_or(A4_FREQUENCY_HZ, f64::from),
+      ),
+    }
+  }
+
+  /// Creates a new tuning from the contents of a Scala scale (`.scl`) file and
+  /// an optional Scala keyboard mapping (`.kbm`) file.
+  ///
+  /// Without a keyboard mapping, successive MIDI note numbers are mapped to successive
+  /// scale degrees starting from middle C, with A4 sounding at 440 Hz.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if either file is invalid, or if the reference note of
+  /// the keyboard mapping is not mapped to a scale degree.
+  pub fn from_scala(scl: &str, kbm: Option<&str>) -> Result<Self, String> {
+    let mut lines = scl.lines().filter(|line| !line.starts_with('!'));
+    let description = lines
+      .next()
+      .ok_or_else(|| String::from("Missing description in Scala scale"))?
+      .trim()
+      .to_string();
+    let mut lines = lines.filter_map(|line| line.split_whitespace().next());
+    let num_degrees: usize = parse_value(
+      lines
+        .next()
+        .ok_or_else(|| String::from("Missing number of notes in Scala scale"))?,
+      "number of notes",
+    )?;
+    let degrees = (0..num_degrees)
+      .map(|_| {
+        lines
+          .next()
+          .ok_or_else(|| String::from("Missing pitch in Scala scale"))
+          .and_then(parse_scala_pitch)
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+    let mapping = match kbm {
+      Some(kbm) => KeyboardMapping::from_scala(kbm)?,
+      None => KeyboardMapping::linear(MIDI_NUMBER_C4, MIDI_NUMBER_A4, A4_FREQUENCY_HZ),
+    };
+    if degrees.last().is_none_or(|period| *period <= 0.0) {
+      Err(String::from("Scala scale must end with a positive period"))
+    } else if mapping.mapping.iter().flatten().any(|degree| *degree > degrees.len()) {
+      Err(String::from(
+        "Scala keyboard mapping refers to a degree outside of the scale",
+      ))
+    } else {
+      let tuning = Self {
+        description,
+        degrees,
+        mapping,
+      };
+      if tuning.cents_from_middle_note(tuning.mapping.reference_note).is_none() {
+        Err(String::from("Reference note of Scala keyboard mapping is not mapped"))
+      } else {
+        Ok(tuning)
+      }
+    }
+  }
+
+  /// Returns the number of cents above the first scale degree of every other
+  /// degree in the tuning, ending with the period of the scale.
+  #[must_use]
+  pub fn degrees(&self) -> &[f64] {
+    &self.degrees
+  }
+
+  /// Returns the keyboard mapping which assigns MIDI note numbers to scale degrees.
+  #[must_use]
+  pub const fn mapping(&self) -> &KeyboardMapping {
+    &self.mapping
+  }
+
+  /// Returns the period of the tuning in cents (usually 1200 for an octave).
+  #[must_use]
+  pub fn period_cents(&self) -> f64 {
+    self.degrees.last().copied().unwrap_or(OCTAVE_CENTS)
+  }
+
+  /// Returns the frequency in Hertz of the given MIDI note number, or `None`
+  /// if the note number is not mapped to a scale degree.
+  #[must_use]
+  pub fn frequency_hz(&self, midi_number: u8) -> Option<f64> {
+    let reference_cents = self.cents_from_middle_note(self.mapping.reference_note)?;
+    self
+      .cents_from_middle_note(midi_number)
+      .map(|cents| self.mapping.reference_frequency_hz * 2f64.powf((cents - reference_cents) / OCTAVE_CENTS))
+  }
+
+  /// Returns the frequency in Hertz of the given MIDI note number after applying
+  /// an additional offset in cents, or `None` if the note number is not mapped.
+  #[must_use]
+  #[allow(clippy::cast_possible_truncation)]
+  pub(crate) fn frequency_hz_with_offset(&self, midi_number: u8, cents_offset: i16) -> Option<f32> {
+    self
+      .frequency_hz(midi_number)
+      .map(|frequency| (frequency * 2f64.powf(f64::from(cents_offset) / OCTAVE_CENTS)) as f32)
+  }
+
+  #[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+  )]
+  fn degree_cents(&self, degree: i64) -> f64 {
+    let num_degrees = i64::try_from(self.degrees.len()).unwrap_or(i64::MAX);
+    let (periods, index) = (degree.div_euclid(num_degrees), degree.rem_euclid(num_degrees) as usize);
+    let cents = if index == 0 { 0.0 } else { self.degrees[index - 1] };
+    periods as f64 * self.period_cents() + cents
+  }
+
+  fn cents_from_middle_note(&self, midi_number: u8) -> Option<f64> {
+    if midi_number < self.mapping.first_note || midi_number > self.mapping.last_note {
+      return None;
+    }
+    let offset = i64::from(midi_number) - i64::from(self.mapping.middle_note);
+    if self.mapping.mapping.is_empty() {
+      Some(self.degree_cents(offset))
+    } else {
+      let map_size = i64::try_from(self.mapping.mapping.len()).unwrap_or(i64::MAX);
+      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+      let entry = self.mapping.mapping[offset.rem_euclid(map_size) as usize]?;
+      let octave_degree = if self.mapping.octave_degree == 0 {
+        self.degrees.len()
+      } else {
+        self.mapping.octave_degree
+      };
+      #[allow(clippy::cast_precision_loss)]
+      let octaves = offset.div_euclid(map_size) as f64;
+      Some(
+        octaves * self.degree_cents(i64::try_from(octave_degree).unwrap_or(i64::MAX))
+          + self.degree_cents(i64::try_from(entry).unwrap_or(i64::MAX)),
+      )
+    }
+  }
+}
+
+impl Default for Tuning {
+  fn default() -> Self {
+    Self::new(Temperament::EqualTemperament, None, None)
+  }
+}
+
+#[cfg(feature = "print")]
+impl core::fmt::Display for Tuning {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(
+      f,
+      "{} ({} notes, A4 = {:.2} Hz)",
+      self.description,
+      self.degrees.len(),
+      self.frequency_hz(MIDI_NUMBER_A4).unwrap_or_default()
+    )
+  }
+}
+
+/// Returns the non-empty, non-comment lines of a Scala file, trimmed to their first value.
+fn scala_lines(contents: &str) -> impl Iterator<Item = &str> {
+  contents
+    .lines()
+    .filter(|line| !line.starts_with('!'))
+    .filter_map(|line| line.split_whitespace().next())
+}
+
+fn parse_value<T: core::str::FromStr>(value: &str, description: &str) -> Result<T, String> {
+  value
+    .parse()
+    .map_err(|_| format!("Invalid {description} \"{value}\" in Scala file"))
+}
+
+/// Parses a pitch from a Scala scale file, which is a value in cents if it contains
+/// a period, or otherwise a ratio or whole number.
+fn parse_scala_pitch(value: &str) -> Result<f64, String> {
+  if value.contains('.') {
+    parse_value(value, "pitch")
+  } else {
+    let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+    let numerator: u64 = parse_value(numerator, "pitch")?;
+    let denominator: u64 = parse_value(denominator, "pitch")?;
+    if numerator == 0 || denominator == 0 {
+      Err(format!("Invalid pitch \"{value}\" in Scala file"))
+    } else {
+      #[allow(clippy::cast_precision_loss)]
+      let ratio = numerator as f64 / denominator as f64;
+      Ok(OCTAVE_CENTS * ratio.log2())
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+
+  #[test]
+  fn test_temperaments() {
+    let equal = Tuning::default();
+    assert!((equal.frequency_hz(69).unwrap() - 440.0).abs() < 1e-9);
+    assert!((equal.frequency_hz(60).unwrap() - 261.626).abs() < 0.001);
+    let baroque = Tuning::new(Temperament::EqualTemperament, None, Some(415.0));
+    assert!((baroque.frequency_hz(81).unwrap() - 830.0).abs() < 1e-9);
+
+    let just_c = Tuning::new(Temperament::JustIntonation, None, None);
+    let (c4, e4, g4) = (
+      just_c.frequency_hz(60).unwrap(),
+      just_c.frequency_hz(64).unwrap(),
+      just_c.frequency_hz(67).unwrap(),
+    );
+    assert!((e4 / c4 - 5.0 / 4.0).abs() < 1e-5);
+    assert!((g4 / c4 - 3.0 / 2.0).abs() < 1e-5);
+    assert!((just_c.frequency_hz(69).unwrap() - 440.0).abs() < 1e-9);
+
+    let just_d = Tuning::new(
+      Temperament::JustIntonation,
+      Some(Key::new(KeySignature::B, KeyMode::Minor)),
+      None,
+    );
+    let (d4, fs4) = (just_d.frequency_hz(62).unwrap(), just_d.frequency_hz(66).unwrap());
+    assert!((fs4 / d4 - 5.0 / 4.0).abs() < 1e-5);
+  }
+
+  #[test]
+  fn test_scala_parsing() {
+    let scl = "! meantone.scl\n!\nQuarter-comma meantone\n 12\n!\n 76.049\n 193.157\n 310.265\n 5/4\n \
+               503.422\n 579.471\n 696.578\n 772.627\n 889.735\n 1006.843\n 1082.892\n 2/1\n";
+    let tuning = Tuning::from_scala(scl, None).unwrap();
+    assert_eq!(tuning.description, "Quarter-comma meantone");
+    assert_eq!(tuning.degrees().len(), 12);
+    assert!((tuning.period_cents() - 1200.0).abs() < 1e-9);
+    assert!((tuning.frequency_hz(64).unwrap() / tuning.frequency_hz(60).unwrap() - 1.25).abs() < 1e-9);
+
+    let kbm = "! white keys only\n12\n0\n127\n60\n69\n432.0\n12\n0\nx\n2\nx\n4\n5\nx\n7\nx\n9\nx\n11\n";
+    let tuning = Tuning::from_scala(scl, Some(kbm)).unwrap();
+    assert!((tuning.frequency_hz(69).unwrap() - 432.0).abs() < 1e-9);
+    assert_eq!(tuning.frequency_hz(61), None);
+    assert!((tuning.frequency_hz(74).unwrap() / tuning.frequency_hz(62).unwrap() - 2.0).abs() < 1e-9);
+
+    assert!(Tuning::from_scala("Empty\n0\n", None).is_err());
+    assert!(Tuning::from_scala("Bad\n1\n3/0\n", None).is_err());
+  }
+}
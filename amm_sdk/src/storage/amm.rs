@@ -85,6 +85,9 @@ mod test {
       staff.add_direction(DirectionType::ClefChange {
         clef: Clef::new(ClefType::MezzoSoprano, None),
       });
+      staff.add_direction(DirectionType::ClefChange {
+        clef: Clef::new(ClefType::Percussion, None),
+      });
       staff.add_direction(DirectionType::ClefChange {
         clef: Clef::new(ClefType::Soprano, None),
       });
@@ -494,6 +497,11 @@ mod test {
         into_beats: 2,
       });
       section.add_modification(SectionModificationType::Accelerando);
+      section.add_modification(SectionModificationType::Coda);
+      section.add_modification(SectionModificationType::DaCapo);
+      section.add_modification(SectionModificationType::DalSegno);
+      section.add_modification(SectionModificationType::Fine);
+      section.add_modification(SectionModificationType::JumpWithRepeats);
       section.add_modification(SectionModificationType::OnlyPlay {
         iterations: vec![0, 1, 3],
       });
@@ -501,6 +509,7 @@ mod test {
       section.add_modification(SectionModificationType::Repeat { num_times: 2 });
       section.add_modification(SectionModificationType::Ritardando);
       section.add_modification(SectionModificationType::Ritenuto);
+      section.add_modification(SectionModificationType::Segno);
       section.add_modification(SectionModificationType::Stringendo);
       section.add_modification(SectionModificationType::TempoExplicit {
         tempo: Tempo::new(Duration::new(DurationType::Whole, 0), 10),
@@ -508,6 +517,7 @@ mod test {
       section.add_modification(SectionModificationType::TempoImplicit {
         tempo: TempoSuggestion::new(TempoMarking::Allegretto),
       });
+      section.add_modification(SectionModificationType::ToCoda);
     }
     let serialized = composition.serialize_json();
     match AmmStorage::load_data(serialized.as_bytes().to_vec()).as_ref() {
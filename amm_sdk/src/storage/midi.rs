@@ -1,7 +1,8 @@
 use super::Load;
-use crate::context::{Key, KeyMode, Tempo, TimeSignature};
+use crate::analysis::{KeyFinder, KeyProfile};
+use crate::context::{Instrument, Key, KeyMode, MidiInstrument, Tempo, TimeSignature, MIDI_PERCUSSION_CHANNEL};
 use crate::modification::{Direction, DirectionType, NoteModificationType};
-use crate::note::{Duration, DurationType, Note};
+use crate::note::{Duration, DurationType, Note, PercussionInstrument, Rational};
 use crate::structure::{PartContent, Staff, StaffContent};
 use crate::Composition;
 use alloc::{collections::VecDeque, string::String};
@@ -10,295 +11,19 @@ use std::fs;
 
 type TimeStamp = u32;
 
-const MIDI_DRUM_CHANNEL: u8 = 10;
-
-#[allow(dead_code)]
-#[repr(u8)]
-#[derive(Clone, Copy, Debug)]
-enum MidiInstrument {
-  GrandPiano = 0,
-  BrightPiano = 1,
-  ElectricPiano = 2,
-  HonkyTonkPiano = 3,
-  ElectricPiano1 = 4,
-  ElectricPiano2 = 5,
-  Harpsichord = 6,
-  Clavinet = 7,
-  Celesta = 8,
-  Glockenspiel = 9,
-  MusicBox = 10,
-  Vibraphone = 11,
-  Marimba = 12,
-  Xylophone = 13,
-  TubularBells = 14,
-  Dulcimer = 15,
-  DrawbarOrgan = 16,
-  PercussiveOrgan = 17,
-  RockOrgan = 18,
-  ChurchOrgan = 19,
-  ReedOrgan = 20,
-  Accordion = 21,
-  Harmonica = 22,
-  TangoAccordion = 23,
-  NylonGuitar = 24,
-  SteelGuitar = 25,
-  JazzGuitar = 26,
-  CleanGuitar = 27,
-  MutedGuitar = 28,
-  OverdrivenGuitar = 29,
-  DistortionGuitar = 30,
-  GuitarHarmonics = 31,
-  AcousticBass = 32,
-  FingeredBass = 33,
-  ElectricBass = 34,
-  FretlessBass = 35,
-  SlapBass1 = 36,
-  SlapBass2 = 37,
-  SynthBass1 = 38,
-  SynthBass2 = 39,
-  Violin = 40,
-  Viola = 41,
-  Cello = 42,
-  Contrabass = 43,
-  TremoloStrings = 44,
-  PizzicatoStrings = 45,
-  Harp = 46,
-  Timpani = 47,
-  StringEnsemble1 = 48,
-  StringEnsemble2 = 49,
-  SynthStrings1 = 50,
-  SynthStrings2 = 51,
-  ChoirAahs = 52,
-  ChoirOohs = 53,
-  SynthVoice = 54,
-  OrchestraHit = 55,
-  Trumpet = 56,
-  Trombone = 57,
-  Tuba = 58,
-  MutedTrumpet = 59,
-  FrenchHorn = 60,
-  BrassSection = 61,
-  SynthBrass1 = 62,
-  SynthBrass2 = 63,
-  SopranoSax = 64,
-  AltoSax = 65,
-  TenorSax = 66,
-  BaritoneSax = 67,
-  Oboe = 68,
-  EnglishHorn = 69,
-  Bassoon = 70,
-  Clarinet = 71,
-  Piccolo = 72,
-  Flute = 73,
-  Recorder = 74,
-  PanFlute = 75,
-  BlownBottle = 76,
-  Shakuhachi = 77,
-  Whistle = 78,
-  Ocarina = 79,
-  SquareLead = 80,
-  SawtoothLead = 81,
-  CalliopeLead = 82,
-  ChiffLead = 83,
-  CharangLead = 84,
-  VoiceLead = 85,
-  FifthsLead = 86,
-  BassLead = 87,
-  NewAgePad = 88,
-  WarmPad = 89,
-  PolysynthPad = 90,
-  ChoirPad = 91,
-  BowedPad = 92,
-  MetallicPad = 93,
-  HaloPad = 94,
-  SweepPad = 95,
-  Rain = 96,
-  Soundtrack = 97,
-  Crystals = 98,
-  Atmosphere = 99,
-  Brightness = 100,
-  Goblins = 101,
-  Echoes = 102,
-  SciFi = 103,
-  Sitar = 104,
-  Banjo = 105,
-  Shamisen = 106,
-  Koto = 107,
-  Kalimba = 108,
-  Bagpipe = 109,
-  Fiddle = 110,
-  Shanai = 111,
-  TinkleBell = 112,
-  Agogo = 113,
-  SteelDrums = 114,
-  Woodblock = 115,
-  TaikoDrum = 116,
-  MelodicTom = 117,
-  SynthDrum = 118,
-  ReverseCymbal = 119,
-  GuitarFretNoise = 120,
-  BreathNoise = 121,
-  Seashore = 122,
-  BirdTweet = 123,
-  TelephoneRing = 124,
-  Helicopter = 125,
-  Applause = 126,
-  Gunshot = 127,
-}
-
-impl MidiInstrument {
-  const fn from_midi_number(midi_number: u8) -> Self {
-    if midi_number > 127 {
-      Self::GrandPiano
-    } else {
-      unsafe { core::mem::transmute::<u8, MidiInstrument>(midi_number) }
-    }
-  }
-}
-
-impl core::fmt::Display for MidiInstrument {
-  fn fmt(&self, f: &mut alloc::fmt::Formatter) -> alloc::fmt::Result {
-    write!(
-      f,
-      "{}",
-      match self {
-        Self::GrandPiano => "Grand Piano",
-        Self::BrightPiano => "Bright Piano",
-        Self::ElectricPiano => "Electric Piano",
-        Self::HonkyTonkPiano => "Honky-Tonk Piano",
-        Self::ElectricPiano1 => "Electric Piano 1",
-        Self::ElectricPiano2 => "Electric Piano 2",
-        Self::Harpsichord => "Harpsichord",
-        Self::Clavinet => "Clavinet",
-        Self::Celesta => "Celesta",
-        Self::Glockenspiel => "Glockenspiel",
-        Self::MusicBox => "Music Box",
-        Self::Vibraphone => "Vibraphone",
-        Self::Marimba => "Marimba",
-        Self::Xylophone => "Xylophone",
-        Self::TubularBells => "Tubular Bells",
-        Self::Dulcimer => "Dulcimer",
-        Self::DrawbarOrgan => "Drawbar Organ",
-        Self::PercussiveOrgan => "Percussive Organ",
-        Self::RockOrgan => "Rock Organ",
-        Self::ChurchOrgan => "Church Organ",
-        Self::ReedOrgan => "Reed Organ",
-        Self::Accordion => "Accordion",
-        Self::Harmonica => "Harmonica",
-        Self::TangoAccordion => "Tango Accordion",
-        Self::NylonGuitar => "Nylon Guitar",
-        Self::SteelGuitar => "Steel Guitar",
-        Self::JazzGuitar => "Jazz Guitar",
-        Self::CleanGuitar => "Clean Guitar",
-        Self::MutedGuitar => "Muted Guitar",
-        Self::OverdrivenGuitar => "Overdriven Guitar",
-        Self::DistortionGuitar => "Distortion Guitar",
-        Self::GuitarHarmonics => "Guitar Harmonics",
-        Self::AcousticBass => "Acoustic Bass",
-        Self::FingeredBass => "Fingered Bass",
-        Self::ElectricBass => "Electric Bass",
-        Self::FretlessBass => "Fretless Bass",
-        Self::SlapBass1 => "Slap Bass 1",
-        Self::SlapBass2 => "Slap Bass 2",
-        Self::SynthBass1 => "Synth Bass 1",
-        Self::SynthBass2 => "Synth Bass 2",
-        Self::Violin => "Violin",
-        Self::Viola => "Viola",
-        Self::Cello => "Cello",
-        Self::Contrabass => "Contrabass",
-        Self::TremoloStrings => "Tremolo Strings",
-        Self::PizzicatoStrings => "Pizzicato Strings",
-        Self::Harp => "Harp",
-        Self::Timpani => "Timpani",
-        Self::StringEnsemble1 => "String Ensemble 1",
-        Self::StringEnsemble2 => "String Ensemble 2",
-        Self::SynthStrings1 => "Synth Strings 1",
-        Self::SynthStrings2 => "Synth Strings 2",
-        Self::ChoirAahs => "Choir Aahs",
-        Self::ChoirOohs => "Choir Oohs",
-        Self::SynthVoice => "Synth Voice",
-        Self::OrchestraHit => "Orchestra Hit",
-        Self::Trumpet => "Trumpet",
-        Self::Trombone => "Trombone",
-        Self::Tuba => "Tuba",
-        Self::MutedTrumpet => "Muted Trumpet",
-        Self::FrenchHorn => "French Horn",
-        Self::BrassSection => "Brass Section",
-        Self::SynthBrass1 => "Synth Brass 1",
-        Self::SynthBrass2 => "Synth Brass 2",
-        Self::SopranoSax => "Soprano Sax",
-        Self::AltoSax => "Alto Sax",
-        Self::TenorSax => "Tenor Sax",
-        Self::BaritoneSax => "Baritone Sax",
-        Self::Oboe => "Oboe",
-        Self::EnglishHorn => "English Horn",
-        Self::Bassoon => "Bassoon",
-        Self::Clarinet => "Clarinet",
-        Self::Piccolo => "Piccolo",
-        Self::Flute => "Flute",
-        Self::Recorder => "Recorder",
-        Self::PanFlute => "Pan Flute",
-        Self::BlownBottle => "Blown Bottle",
-        Self::Shakuhachi => "Shakuhachi",
-        Self::Whistle => "Whistle",
-        Self::Ocarina => "Ocarina",
-        Self::SquareLead => "Square Lead",
-        Self::SawtoothLead => "Sawtooth Lead",
-        Self::CalliopeLead => "Calliope Lead",
-        Self::ChiffLead => "Chiff Lead",
-        Self::CharangLead => "Charang Lead",
-        Self::VoiceLead => "Voice Lead",
-        Self::FifthsLead => "Fifths Lead",
-        Self::BassLead => "Bass Lead",
-        Self::NewAgePad => "New Age Pad",
-        Self::WarmPad => "Warm Pad",
-        Self::PolysynthPad => "Polysynth Pad",
-        Self::ChoirPad => "Choir Pad",
-        Self::BowedPad => "Bowed Pad",
-        Self::MetallicPad => "Metallic Pad",
-        Self::HaloPad => "Halo Pad",
-        Self::SweepPad => "Sweep Pad",
-        Self::Rain => "Rain",
-        Self::Soundtrack => "Soundtrack",
-        Self::Crystals => "Crystals",
-        Self::Atmosphere => "Atmosphere",
-        Self::Brightness => "Brightness",
-        Self::Goblins => "Goblins",
-        Self::Echoes => "Echoes",
-        Self::SciFi => "Sci-Fi",
-        Self::Sitar => "Sitar",
-        Self::Banjo => "Banjo",
-        Self::Shamisen => "Shamisen",
-        Self::Koto => "Koto",
-        Self::Kalimba => "Kalimba",
-        Self::Bagpipe => "Bagpipe",
-        Self::Fiddle => "Fiddle",
-        Self::Shanai => "Shanai",
-        Self::TinkleBell => "Tinkle Bell",
-        Self::Agogo => "Agogo",
-        Self::SteelDrums => "Steel Drums",
-        Self::Woodblock => "Woodblock",
-        Self::TaikoDrum => "Taiko Drum",
-        Self::MelodicTom => "Melodic Tom",
-        Self::SynthDrum => "Synth Drum",
-        Self::ReverseCymbal => "Reverse Cymbal",
-        Self::GuitarFretNoise => "Guitar Fret Noise",
-        Self::BreathNoise => "Breath Noise",
-        Self::Seashore => "Seashore",
-        Self::BirdTweet => "Bird Tweet",
-        Self::TelephoneRing => "Telephone Ring",
-        Self::Helicopter => "Helicopter",
-        Self::Applause => "Applause",
-        Self::Gunshot => "Gunshot",
-      }
-    )
-  }
-}
-
 impl Note {
-  fn from_raw_note_data(midi_number: u8, beat_length: f64, beat_base_value: Duration, key: Key) -> Vec<Note> {
+  fn from_raw_note_data(
+    midi_number: u8,
+    beat_length: Rational,
+    beat_base_value: Duration,
+    key: Key,
+    percussion: bool,
+  ) -> Vec<Note> {
     let mut staff_content = Vec::new();
-    let mut note = Note::from_midi(midi_number, beat_base_value, Some(key));
+    let mut note = match PercussionInstrument::from_midi_number(midi_number) {
+      Some(instrument) if percussion => Note::new_unpitched(instrument, beat_base_value),
+      _ => Note::from_midi(midi_number, beat_base_value, Some(key)),
+    };
     for duration in Duration::from_beats_tied(&beat_base_value, beat_length) {
       note.duration = duration;
       staff_content.push(note.clone());
@@ -381,19 +106,21 @@ struct NoteHandler {
   last_note_on_offset: u32,
   last_note_off_offset: u32,
   last_note_velocity: u8,
-  ticks_per_beat: f64,
-  rest_epsilon: f64,
+  ticks_per_beat: i64,
+  rest_threshold: u32,
+  percussion: bool,
 }
 
 impl NoteHandler {
-  fn new(base_beat_type: Duration, ticks_per_beat: u16) -> Self {
+  fn new(base_beat_type: Duration, ticks_per_beat: u16, percussion: bool) -> Self {
     Self {
       base_beat_type,
       last_note_on_offset: 0,
       last_note_off_offset: 0,
       last_note_velocity: 0,
-      ticks_per_beat: f64::from(ticks_per_beat),
-      rest_epsilon: (f64::from(ticks_per_beat) * 0.125).ceil(),
+      ticks_per_beat: i64::from(ticks_per_beat),
+      rest_threshold: u32::from(ticks_per_beat.div_ceil(8)),
+      percussion,
     }
   }
 
@@ -403,14 +130,18 @@ impl NoteHandler {
         self.last_note_on_offset = cur_time;
         self.last_note_velocity = vel.as_int();
         if self.last_note_on_offset > self.last_note_off_offset
-          && f64::from(self.last_note_on_offset - self.last_note_off_offset) >= self.rest_epsilon
+          && self.last_note_on_offset - self.last_note_off_offset >= self.rest_threshold
         {
-          let beat_length = f64::from(self.last_note_on_offset - self.last_note_off_offset) / self.ticks_per_beat;
+          let beat_length = Rational::new(
+            i64::from(self.last_note_on_offset - self.last_note_off_offset),
+            self.ticks_per_beat,
+          );
           Some(Note::from_raw_note_data(
             255,
             beat_length,
             self.base_beat_type,
             current_key,
+            self.percussion,
           ))
         } else {
           None
@@ -418,12 +149,16 @@ impl NoteHandler {
       }
       midly::MidiMessage::NoteOff { key, vel: _ } => {
         self.last_note_off_offset = cur_time;
-        let beat_length = f64::from(self.last_note_off_offset - self.last_note_on_offset) / self.ticks_per_beat;
+        let beat_length = Rational::new(
+          i64::from(self.last_note_off_offset - self.last_note_on_offset),
+          self.ticks_per_beat,
+        );
         Some(Note::from_raw_note_data(
           key.as_int(),
           beat_length,
           self.base_beat_type,
           current_key,
+          self.percussion,
         ))
       }
       _ => None,
@@ -441,29 +176,56 @@ impl MidiConverter {
     }
   }
 
-  fn get_starting_key(tracks: &[Track]) -> Key {
+  fn get_starting_key(tracks: &[Track]) -> Option<Key> {
     for track in tracks {
       for event in track {
         if let midly::TrackEventKind::Meta(MetaMessage::KeySignature(fifths, minor)) = event.kind {
           let mode = if minor { KeyMode::Minor } else { KeyMode::Major };
-          return Key::from_fifths(fifths, Some(mode));
+          return Some(Key::from_fifths(fifths, Some(mode)));
         }
       }
     }
-    Key::default()
+    None
   }
 
-  fn get_track_name(track: &Track) -> String {
+  fn detect_starting_key(tracks: &[Track]) -> Key {
+    // Weight each pitch class by the number of ticks that it sounds for
+    let mut key_finder = KeyFinder::new(KeyProfile::default());
+    for track in tracks {
+      let (mut cur_time, mut note_on_times) = (0, [None; 128]);
+      for event in track {
+        cur_time += event.delta.as_int();
+        if let midly::TrackEventKind::Midi { channel, message } = event.kind {
+          if channel != MIDI_PERCUSSION_CHANNEL {
+            match message {
+              midly::MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                note_on_times[usize::from(key.as_int())] = Some(cur_time);
+              }
+              midly::MidiMessage::NoteOn { key, vel: _ } | midly::MidiMessage::NoteOff { key, vel: _ } => {
+                if let Some(start_time) = note_on_times[usize::from(key.as_int())].take() {
+                  key_finder.add_pitch_class(key.as_int() % 12, f64::from(cur_time - start_time));
+                }
+              }
+              _ => (),
+            }
+          }
+        }
+      }
+    }
+    key_finder.best_key().unwrap_or_default()
+  }
+
+  fn get_track_instrument(track: &Track) -> Instrument {
     for event in track {
       if let midly::TrackEventKind::Midi { channel, message } = event.kind {
-        if channel != MIDI_DRUM_CHANNEL {
-          if let midly::MidiMessage::ProgramChange { program } = message {
-            return MidiInstrument::from_midi_number(program.as_int()).to_string();
-          }
+        if channel == MIDI_PERCUSSION_CHANNEL {
+          return Instrument::from_program(MidiInstrument::default(), Some(channel.as_int()));
+        } else if let midly::MidiMessage::ProgramChange { program } = message {
+          return Instrument::from_program(MidiInstrument::from_program(program.as_int()), Some(channel.as_int()));
         }
       }
     }
-    MidiInstrument::GrandPiano.to_string()
+    Instrument::from_program(MidiInstrument::GrandPiano, None)
   }
 
   fn parse_control_track(composition: &mut Composition, control_track: &Track) -> VecDeque<(MetaContent, TimeStamp)> {
@@ -520,11 +282,12 @@ impl MidiConverter {
     ticks_per_beat: u16,
     base_beat_type: Duration,
     mut current_key: Key,
+    percussion: bool,
   ) {
     // Iterate through all track events
     let mut cur_time = 0;
     let mut meta_handler = MetaHandler::new();
-    let mut note_handler = NoteHandler::new(base_beat_type, ticks_per_beat);
+    let mut note_handler = NoteHandler::new(base_beat_type, ticks_per_beat, percussion);
     for event in track {
       // Check if any musical context changes are needed at the current timestamp
       cur_time += event.delta.as_int();
@@ -565,7 +328,7 @@ impl MidiConverter {
   fn load_from_midi(data: &[u8]) -> Result<Composition, String> {
     // Parse the MIDI representation
     let midi = Smf::parse(data).map_err(|err| err.to_string())?;
-    let starting_key = Self::get_starting_key(&midi.tracks);
+    let starting_key = Self::get_starting_key(&midi.tracks).unwrap_or_else(|| Self::detect_starting_key(&midi.tracks));
     let ticks_per_beat = Self::get_ticks_per_beat(midi.header);
     let base_beat_type = Duration::new(DurationType::Quarter, 0);
 
@@ -575,13 +338,14 @@ impl MidiConverter {
 
     // Parse the MIDI tracks and fill in all musical data
     for idx in 1..midi.tracks.len() {
-      let part_name = Self::get_track_name(&midi.tracks[idx]);
-      let top_section = if let Some(part) = composition.get_part_mut_by_name(&part_name) {
+      let instrument = Self::get_track_instrument(&midi.tracks[idx]);
+      let percussion = instrument.percussion;
+      let top_section = if let Some(part) = composition.get_part_mut_by_name(&instrument.name) {
         let PartContent::Section(top_level_section) = unsafe { part.iter_mut().next().unwrap_unchecked() };
         top_level_section
       } else {
-        let part = composition.add_part(&Self::get_track_name(&midi.tracks[idx]));
-        part.add_section("Top-Level Section")
+        let part = composition.add_part(&instrument.name);
+        part.set_instrument(instrument).add_section("Top-Level Section")
       };
       let staff = top_section.add_staff((top_section.num_items() + 1).to_string().as_str());
       Self::load_staff_content(
@@ -591,6 +355,7 @@ impl MidiConverter {
         ticks_per_beat,
         base_beat_type,
         starting_key,
+        percussion,
       );
     }
 
@@ -631,32 +396,32 @@ mod test {
   fn test_midi_tie_note() {
     let beat_base_value = Duration::new(DurationType::Quarter, 0);
 
-    let beat_length = 2.5;
+    let beat_length = Rational::new(5, 2);
     let tied = Duration::from_beats_tied(&beat_base_value, beat_length);
     assert_eq!(tied.len(), 2);
     assert_eq!(tied[0].value, DurationType::Half);
     assert_eq!(tied[1].value, DurationType::Eighth);
 
-    let beat_length = 5.0;
+    let beat_length = Rational::from(5_u8);
     let tied = Duration::from_beats_tied(&beat_base_value, beat_length);
     assert_eq!(tied.len(), 2);
     assert_eq!(tied[0].value, DurationType::Whole);
     assert_eq!(tied[1].value, DurationType::Quarter);
 
-    let beat_length = 1.25;
+    let beat_length = Rational::new(5, 4);
     let tied = Duration::from_beats_tied(&beat_base_value, beat_length);
     assert_eq!(tied.len(), 2);
     assert_eq!(tied[0].value, DurationType::Quarter);
     assert_eq!(tied[1].value, DurationType::Sixteenth);
 
-    let beat_length = 5.25;
+    let beat_length = Rational::new(21, 4);
     let tied = Duration::from_beats_tied(&beat_base_value, beat_length);
     assert_eq!(tied.len(), 3);
     assert_eq!(tied[0].value, DurationType::Whole);
     assert_eq!(tied[1].value, DurationType::Quarter);
     assert_eq!(tied[2].value, DurationType::Sixteenth);
 
-    let beat_length = 3.0;
+    let beat_length = Rational::from(3_u8);
     let tied = Duration::from_beats_tied(&beat_base_value, beat_length);
     assert_eq!(tied.len(), 1);
     assert_eq!(tied[0].value, DurationType::Half);
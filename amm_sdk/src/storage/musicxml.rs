@@ -12,6 +12,7 @@ use core::{
   sync::atomic::{AtomicUsize, Ordering},
 };
 use musicxml::{self, elements::ScorePartwise};
+use std::fs;
 
 pub struct MusicXmlConverter;
 
@@ -46,6 +47,8 @@ struct NoteDetails {
   pub pitch: Pitch,
   pub duration: Duration,
   pub accidental: Accidental,
+  pub cents_offset: i16,
+  pub unpitched: Option<Unpitched>,
   pub divisions: usize,
   pub voice: Option<String>,
   pub arpeggiated: bool,
@@ -115,6 +118,7 @@ fn section_generate_id() -> usize {
 struct SectionDetails {
   pub starting_sections: BTreeMap<usize, Section>,
   pub ending_sections: Vec<usize>,
+  pub navigation: Vec<SectionModificationType>,
 }
 
 impl SectionDetails {
@@ -145,9 +149,15 @@ impl core::fmt::Display for SectionDetails {
       .map(|(key, val)| key.to_string() + ": " + val.to_string().as_str())
       .collect::<Vec<String>>()
       .join(", ");
+    let navigation = self
+      .navigation
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<String>>()
+      .join(", ");
     write!(
       f,
-      "   Ending Sections: [{ending_sections}],\n   Starting Sections: [{starting_sections}]",
+      "   Ending Sections: [{ending_sections}],\n   Starting Sections: [{starting_sections}],\n   Navigation: [{navigation}]",
     )
   }
 }
@@ -158,7 +168,7 @@ struct TimeSliceContainer {
   pub chord_modification: Vec<ChordModificationType>,
   pub phrase_modification_start: Vec<PhraseModDetails>,
   pub phrase_modification_end: Vec<PhraseModDetails>,
-  pub jump_to: Option<String>,
+  pub navigation: Vec<SectionModificationType>,
   pub section_start: Option<String>,
   pub ending: Vec<(bool, Vec<u8>)>,
   pub repeat: Vec<(bool, u8)>,
@@ -173,7 +183,7 @@ impl TimeSliceContainer {
       && self.chord_modification.is_empty()
       && self.phrase_modification_start.is_empty()
       && self.phrase_modification_end.is_empty()
-      && self.jump_to.is_none()
+      && self.navigation.is_empty()
       && self.section_start.is_none()
       && self.ending.is_empty()
       && self.repeat.is_empty()
@@ -209,7 +219,7 @@ impl core::fmt::Display for TimeSliceContainer {
         .iter()
         .map(|item| format!("\"Phrase Modification: {item}\"")),
     );
-    description.extend(self.jump_to.iter().map(|item| format!("\"Jump To: {item}\"")));
+    description.extend(self.navigation.iter().map(|item| format!("\"Navigation: {item}\"")));
     description.extend(
       self
         .section_start
@@ -304,6 +314,19 @@ impl MusicXmlConverter {
       .sum()
   }
 
+  fn convert_key_mode(mode: Option<&musicxml::elements::Mode>) -> KeyMode {
+    match mode.map(|mode| &mode.content) {
+      Some(musicxml::datatypes::Mode::Minor) => KeyMode::Minor,
+      Some(musicxml::datatypes::Mode::Dorian) => KeyMode::Dorian,
+      Some(musicxml::datatypes::Mode::Phrygian) => KeyMode::Phrygian,
+      Some(musicxml::datatypes::Mode::Lydian) => KeyMode::Lydian,
+      Some(musicxml::datatypes::Mode::Mixolydian) => KeyMode::Mixolydian,
+      Some(musicxml::datatypes::Mode::Aeolian) => KeyMode::Aeolian,
+      Some(musicxml::datatypes::Mode::Locrian) => KeyMode::Locrian,
+      _ => KeyMode::Major,
+    }
+  }
+
   fn find_starting_key(parts: &Vec<musicxml::elements::Part>) -> Key {
     for part in parts {
       if let musicxml::elements::PartElement::Measure(measure) = &part.content[0] {
@@ -311,14 +334,9 @@ impl MusicXmlConverter {
           if let musicxml::elements::MeasureElement::Attributes(attributes) = measure_element {
             for key_element in &attributes.content.key {
               if let musicxml::elements::KeyContents::Explicit(key) = &key_element.content {
-                let mode = match &key.mode {
-                  Some(mode) => match mode.content {
-                    musicxml::datatypes::Mode::Minor => KeyMode::Minor,
-                    _ => KeyMode::Major,
-                  },
-                  None => KeyMode::Major,
-                };
-                return Key::from_fifths(*key.fifths.content, Some(mode));
+                // Keys in transposed parts are written keys, so convert them to sounding keys
+                return Key::from_fifths(*key.fifths.content, Some(Self::convert_key_mode(key.mode.as_ref())))
+                  .transposed(Self::find_transposition(&part.content).interval());
               }
             }
           }
@@ -479,6 +497,72 @@ impl MusicXmlConverter {
     parts_map
   }
 
+  fn find_instruments(parts_list: &Vec<musicxml::elements::PartListElement>) -> BTreeMap<String, Instrument> {
+    let mut instruments = BTreeMap::new();
+    for parts_list_element in parts_list {
+      if let musicxml::elements::PartListElement::ScorePart(score_part) = parts_list_element {
+        let part_name = &score_part.content.part_name.content;
+        let score_instrument = score_part.content.score_instrument.first();
+        let midi_instrument = score_part.content.midi_instrument.first();
+        let program = midi_instrument
+          .and_then(|midi_instrument| midi_instrument.content.midi_program.as_ref())
+          .map(|program| MidiInstrument::from_program(program.content.0.saturating_sub(1)))
+          .or_else(|| {
+            score_instrument.and_then(|score_instrument| {
+              score_instrument
+                .content
+                .instrument_sound
+                .as_ref()
+                .and_then(|sound| Instrument::program_from_name(&sound.content))
+                .or_else(|| Instrument::program_from_name(&score_instrument.content.instrument_name.content))
+            })
+          })
+          .or_else(|| Instrument::program_from_name(part_name))
+          .unwrap_or_default();
+        let channel = midi_instrument
+          .and_then(|midi_instrument| midi_instrument.content.midi_channel.as_ref())
+          .map(|channel| channel.content.0.saturating_sub(1));
+        let mut instrument = Instrument::from_program(program, channel);
+        instrument.name = score_instrument.map_or_else(
+          || part_name.clone(),
+          |score_instrument| score_instrument.content.instrument_name.content.clone(),
+        );
+        if midi_instrument.is_some_and(|midi_instrument| midi_instrument.content.midi_unpitched.is_some()) {
+          instrument.percussion = true;
+          instrument.family = InstrumentFamily::Percussion;
+          instrument.clef = Clef::new(ClefType::Percussion, None);
+        }
+        instruments.insert(part_name.clone(), instrument);
+      }
+    }
+    instruments
+  }
+
+  fn find_percussion_instruments(
+    parts_list: &Vec<musicxml::elements::PartListElement>,
+  ) -> BTreeMap<String, BTreeMap<String, PercussionInstrument>> {
+    let mut percussion_instruments = BTreeMap::new();
+    for parts_list_element in parts_list {
+      if let musicxml::elements::PartListElement::ScorePart(score_part) = parts_list_element {
+        let instruments: BTreeMap<_, _> = score_part
+          .content
+          .midi_instrument
+          .iter()
+          .filter_map(|midi_instrument| {
+            midi_instrument
+              .content
+              .midi_unpitched
+              .as_ref()
+              .and_then(|unpitched| PercussionInstrument::from_midi_number(unpitched.content.0.saturating_sub(1)))
+              .map(|instrument| ((*midi_instrument.attributes.id).clone(), instrument))
+          })
+          .collect();
+        percussion_instruments.insert((*score_part.attributes.id).clone(), instruments);
+      }
+    }
+    percussion_instruments
+  }
+
   fn find_staves(part_elements: &Vec<musicxml::elements::PartElement>) -> Vec<String> {
     for element in part_elements {
       if let musicxml::elements::PartElement::Measure(measure) = element {
@@ -494,6 +578,35 @@ impl MusicXmlConverter {
     vec![String::from("1")]
   }
 
+  #[allow(clippy::cast_possible_truncation)]
+  fn find_transposition(part_elements: &Vec<musicxml::elements::PartElement>) -> Transposition {
+    for element in part_elements {
+      if let musicxml::elements::PartElement::Measure(measure) = element {
+        for measure_element in &measure.content {
+          if let musicxml::elements::MeasureElement::Attributes(attributes) = measure_element {
+            if let Some(transpose) = attributes.content.transpose.first() {
+              let chromatic = transpose.content.chromatic.content.0;
+              let diatonic = transpose.content.diatonic.as_ref().map_or_else(
+                || Interval::from_semitones(chromatic).steps(),
+                |diatonic| diatonic.content,
+              );
+              return Transposition::new(
+                diatonic as i8,
+                chromatic as i8,
+                transpose
+                  .content
+                  .octave_change
+                  .as_ref()
+                  .map_or(0, |octave_change| octave_change.content),
+              );
+            }
+          }
+        }
+      }
+    }
+    Transposition::default()
+  }
+
   fn find_divisions_per_quarter_note(part_elements: &Vec<musicxml::elements::PartElement>) -> usize {
     for element in part_elements {
       if let musicxml::elements::PartElement::Measure(measure) = element {
@@ -677,6 +790,7 @@ impl MusicXmlConverter {
             },
             None => Clef::new(ClefType::Alto, None),
           },
+          musicxml::datatypes::ClefSign::Percussion => Clef::new(ClefType::Percussion, None),
           _ => Clef::new(ClefType::Treble, None),
         },
       };
@@ -689,15 +803,8 @@ impl MusicXmlConverter {
         } else {
           String::from("1")
         };
-        let mode = match &key.mode {
-          Some(mode) => match mode.content {
-            musicxml::datatypes::Mode::Minor => KeyMode::Minor,
-            _ => KeyMode::Major,
-          },
-          None => KeyMode::Major,
-        };
         let item = DirectionType::KeyChange {
-          key: Key::from_fifths(*key.fifths.content, Some(mode)),
+          key: Key::from_fifths(*key.fifths.content, Some(Self::convert_key_mode(key.mode.as_ref()))),
         };
         time_slices.get_mut(&staff_name).unwrap()[cursor].direction.push(item);
       }
@@ -736,12 +843,47 @@ impl MusicXmlConverter {
     *element.duration.content as isize
   }
 
+  fn parse_sound_element(
+    element: &musicxml::elements::Sound,
+    time_slice: &mut BTreeMap<String, Vec<TimeSliceContainer>>,
+    navigation: &mut Vec<SectionModificationType>,
+    cursor: usize,
+  ) -> isize {
+    if element.attributes.segno.is_some() {
+      for slice in time_slice.values_mut() {
+        slice[cursor].section_start = Some(String::from("Segno"));
+      }
+    }
+    if element.attributes.coda.is_some() {
+      for slice in time_slice.values_mut() {
+        slice[cursor].section_start = Some(String::from("Coda"));
+      }
+    }
+    if element.attributes.dacapo == Some(musicxml::datatypes::YesNo::Yes) {
+      navigation.push(SectionModificationType::DaCapo);
+    }
+    if element.attributes.dalsegno.is_some() {
+      navigation.push(SectionModificationType::DalSegno);
+    }
+    if element.attributes.tocoda.is_some() {
+      navigation.push(SectionModificationType::ToCoda);
+    }
+    if element.attributes.fine.is_some() {
+      navigation.push(SectionModificationType::Fine);
+    }
+    0
+  }
+
   fn parse_direction_element(
     element: &musicxml::elements::Direction,
     time_slice: &mut BTreeMap<String, Vec<TimeSliceContainer>>,
     open_wedges: &mut BTreeMap<Option<u8>, Vec<musicxml::datatypes::WedgeType>>,
+    navigation: &mut Vec<SectionModificationType>,
     cursor: usize,
   ) -> isize {
+    if let Some(sound) = &element.content.sound {
+      Self::parse_sound_element(sound, time_slice, navigation, cursor);
+    }
     let staff_name = if let Some(staff) = &element.content.staff {
       staff.content.to_string()
     } else {
@@ -1018,20 +1160,139 @@ impl MusicXmlConverter {
     }
     if element.attributes.coda.is_some() {
       for slice in time_slice.values_mut() {
-        slice[cursor].jump_to = Some(String::from("Coda"));
+        slice[cursor].section_start = Some(String::from("Coda"));
       }
     }
     if element.attributes.segno.is_some() {
       for slice in time_slice.values_mut() {
-        slice[cursor].jump_to = Some(String::from("Segno"));
+        slice[cursor].section_start = Some(String::from("Segno"));
       }
     }
     0
   }
 
+  fn convert_step(step: &musicxml::datatypes::Step, octave: u8) -> Pitch {
+    match step {
+      musicxml::datatypes::Step::A => Pitch::new(PitchName::A, octave),
+      musicxml::datatypes::Step::B => Pitch::new(PitchName::B, octave),
+      musicxml::datatypes::Step::C => Pitch::new(PitchName::C, octave),
+      musicxml::datatypes::Step::D => Pitch::new(PitchName::D, octave),
+      musicxml::datatypes::Step::E => Pitch::new(PitchName::E, octave),
+      musicxml::datatypes::Step::F => Pitch::new(PitchName::F, octave),
+      musicxml::datatypes::Step::G => Pitch::new(PitchName::G, octave),
+    }
+  }
+
+  fn get_alter_cents(note: &musicxml::elements::Note) -> Option<i16> {
+    // Note: All alterations are rewritten from semitones into cents before parsing
+    let audible = match &note.content.info {
+      musicxml::elements::NoteType::Normal(normal) => &normal.audible,
+      musicxml::elements::NoteType::Grace(grace) => match &grace.info {
+        musicxml::elements::GraceType::Normal(normal) => &normal.audible,
+        musicxml::elements::GraceType::Cue(_) => return None,
+      },
+      musicxml::elements::NoteType::Cue(_) => return None,
+    };
+    match audible {
+      musicxml::elements::AudibleType::Pitch(pitch) => pitch.content.alter.as_ref().map(|alter| *alter.content),
+      _ => None,
+    }
+  }
+
+  fn convert_alters_to_cents(contents: &str) -> String {
+    let mut converted = String::with_capacity(contents.len());
+    let mut remaining = contents;
+    while let Some(start) = remaining.find("<alter>") {
+      let (before, after) = remaining.split_at(start + "<alter>".len());
+      converted.push_str(before);
+      remaining = after;
+      if let Some(end) = remaining.find("</alter>") {
+        match remaining[..end].trim().parse::<f64>() {
+          #[allow(clippy::cast_possible_truncation)]
+          Ok(semitones) => converted.push_str(&((semitones * 100.0).round() as i16).to_string()),
+          Err(_) => converted.push_str(&remaining[..end]),
+        }
+        remaining = &remaining[end..];
+      }
+    }
+    converted.push_str(remaining);
+    converted
+  }
+
+  fn read_score_data(data: Vec<u8>) -> Result<ScorePartwise, String> {
+    // Note: Alterations are parsed as whole semitones by the musicxml crate, so they are converted
+    // into cents beforehand to avoid rejecting or truncating microtonal alterations
+    let contents = match String::from_utf8(data) {
+      Ok(contents) if !contents.starts_with("PK\u{3}\u{4}") => contents,
+      Ok(contents) => Self::serialize_score_data(contents.into_bytes())?,
+      Err(err) => Self::serialize_score_data(err.into_bytes())?,
+    };
+    musicxml::read_score_data_partwise(Self::convert_alters_to_cents(&contents).into_bytes())
+  }
+
+  fn serialize_score_data(data: Vec<u8>) -> Result<String, String> {
+    // Note: Compressed or non-UTF-8 data is decoded by the musicxml crate, so it cannot contain fractional alterations
+    let score = musicxml::read_score_data_partwise(data)?;
+    Ok(musicxml::parser::parse_to_xml_str(&score, false))
+  }
+
+  fn convert_notehead(notehead: &musicxml::datatypes::NoteheadValue) -> Notehead {
+    match notehead {
+      musicxml::datatypes::NoteheadValue::X | musicxml::datatypes::NoteheadValue::Cross => Notehead::X,
+      musicxml::datatypes::NoteheadValue::CircleX => Notehead::CircleX,
+      musicxml::datatypes::NoteheadValue::Diamond => Notehead::Diamond,
+      musicxml::datatypes::NoteheadValue::Triangle | musicxml::datatypes::NoteheadValue::InvertedTriangle => {
+        Notehead::Triangle
+      }
+      musicxml::datatypes::NoteheadValue::Slash => Notehead::Slash,
+      musicxml::datatypes::NoteheadValue::Square | musicxml::datatypes::NoteheadValue::Rectangle => Notehead::Square,
+      _ => Notehead::Normal,
+    }
+  }
+
+  fn parse_unpitched(
+    note: &musicxml::elements::Note,
+    display_pitch: Pitch,
+    percussion_instruments: &BTreeMap<String, PercussionInstrument>,
+  ) -> Option<Unpitched> {
+    let audible = match &note.content.info {
+      musicxml::elements::NoteType::Normal(normal) => &normal.audible,
+      musicxml::elements::NoteType::Grace(grace) => match &grace.info {
+        musicxml::elements::GraceType::Normal(normal) => &normal.audible,
+        musicxml::elements::GraceType::Cue(_) => return None,
+      },
+      musicxml::elements::NoteType::Cue(_) => return None,
+    };
+    if !matches!(audible, musicxml::elements::AudibleType::Unpitched(_)) {
+      return None;
+    }
+    let notehead = note
+      .content
+      .notehead
+      .as_ref()
+      .map_or(Notehead::Normal, |notehead| Self::convert_notehead(&notehead.content));
+    let instrument = note
+      .content
+      .instrument
+      .first()
+      .and_then(|instrument| percussion_instruments.get(&*instrument.attributes.id).copied())
+      .or_else(|| {
+        if percussion_instruments.len() == 1 {
+          percussion_instruments.values().next().copied()
+        } else {
+          None
+        }
+      })
+      .or_else(|| PercussionInstrument::from_display(display_pitch, notehead))
+      .or_else(|| PercussionInstrument::from_display(display_pitch, Notehead::Normal))
+      .unwrap_or_default();
+    Some(Unpitched { instrument, notehead })
+  }
+
   #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
   fn parse_note_element(
     note: &musicxml::elements::Note,
+    percussion_instruments: &BTreeMap<String, PercussionInstrument>,
     accidental_context: &mut BTreeMap<Pitch, Vec<(usize, Accidental)>>,
     time_slices: &mut BTreeMap<String, Vec<TimeSliceContainer>>,
     divisions_per_quarter_note: usize,
@@ -1068,18 +1329,13 @@ impl MusicXmlConverter {
           normal.chord.is_some(),
           match &normal.audible {
             musicxml::elements::AudibleType::Pitch(pitch) => {
-              let octave = *pitch.content.octave.content;
-              match &pitch.content.step.content {
-                musicxml::datatypes::Step::A => Pitch::new(PitchName::A, octave),
-                musicxml::datatypes::Step::B => Pitch::new(PitchName::B, octave),
-                musicxml::datatypes::Step::C => Pitch::new(PitchName::C, octave),
-                musicxml::datatypes::Step::D => Pitch::new(PitchName::D, octave),
-                musicxml::datatypes::Step::E => Pitch::new(PitchName::E, octave),
-                musicxml::datatypes::Step::F => Pitch::new(PitchName::F, octave),
-                musicxml::datatypes::Step::G => Pitch::new(PitchName::G, octave),
-              }
+              Self::convert_step(&pitch.content.step.content, *pitch.content.octave.content)
             }
-            _ => Pitch::new_rest(),
+            musicxml::elements::AudibleType::Unpitched(unpitched) => Self::convert_step(
+              &unpitched.content.display_step.content,
+              *unpitched.content.display_octave.content,
+            ),
+            musicxml::elements::AudibleType::Rest(_) => Pitch::new_rest(),
           },
         ),
       },
@@ -1094,18 +1350,13 @@ impl MusicXmlConverter {
         normal.chord.is_some(),
         match &normal.audible {
           musicxml::elements::AudibleType::Pitch(pitch) => {
-            let octave = *pitch.content.octave.content;
-            match &pitch.content.step.content {
-              musicxml::datatypes::Step::A => Pitch::new(PitchName::A, octave),
-              musicxml::datatypes::Step::B => Pitch::new(PitchName::B, octave),
-              musicxml::datatypes::Step::C => Pitch::new(PitchName::C, octave),
-              musicxml::datatypes::Step::D => Pitch::new(PitchName::D, octave),
-              musicxml::datatypes::Step::E => Pitch::new(PitchName::E, octave),
-              musicxml::datatypes::Step::F => Pitch::new(PitchName::F, octave),
-              musicxml::datatypes::Step::G => Pitch::new(PitchName::G, octave),
-            }
+            Self::convert_step(&pitch.content.step.content, *pitch.content.octave.content)
           }
-          _ => Pitch::new_rest(),
+          musicxml::elements::AudibleType::Unpitched(unpitched) => Self::convert_step(
+            &unpitched.content.display_step.content,
+            *unpitched.content.display_octave.content,
+          ),
+          musicxml::elements::AudibleType::Rest(_) => Pitch::new_rest(),
         },
       ),
     };
@@ -1165,7 +1416,24 @@ impl MusicXmlConverter {
       (duration, extra_durations, altered_divisions)
     };
     let voice = note.content.voice.as_ref().map(|voice| voice.content.clone());
-    let accidental = if let Some(accidental) = &note.content.accidental {
+    let microtonal_alter = Self::get_alter_cents(note)
+      .filter(|cents| cents % 100 != 0)
+      .map(|cents| {
+        // Note: Alterations are mapped to the nearest quarter tone, with any remainder kept as a cents offset
+        let nearest = ((cents.div_euclid(50) + i16::from(cents.rem_euclid(50) >= 25)) * 50).clamp(-200, 200);
+        (nearest, cents - nearest)
+      });
+    let accidental = if let Some((nearest, _)) = microtonal_alter {
+      let accidental = match Accidental::from_cents(nearest) {
+        Some(Accidental::None) | None => Accidental::Natural,
+        Some(accidental) => accidental,
+      };
+      accidental_context
+        .entry(pitch)
+        .or_default()
+        .push((if chord { previous_cursor } else { cursor }, accidental));
+      Some(accidental)
+    } else if let Some(accidental) = &note.content.accidental {
       let accidental = match accidental.content {
         musicxml::datatypes::AccidentalValue::Sharp | musicxml::datatypes::AccidentalValue::NaturalSharp => {
           Accidental::Sharp
@@ -1177,6 +1445,10 @@ impl MusicXmlConverter {
           Accidental::DoubleSharp
         }
         musicxml::datatypes::AccidentalValue::FlatFlat => Accidental::DoubleFlat,
+        musicxml::datatypes::AccidentalValue::QuarterSharp => Accidental::QuarterSharp,
+        musicxml::datatypes::AccidentalValue::QuarterFlat => Accidental::QuarterFlat,
+        musicxml::datatypes::AccidentalValue::ThreeQuartersSharp => Accidental::ThreeQuarterSharp,
+        musicxml::datatypes::AccidentalValue::ThreeQuartersFlat => Accidental::ThreeQuarterFlat,
         _ => Accidental::Natural,
       };
       accidental_context
@@ -1583,6 +1855,8 @@ impl MusicXmlConverter {
       pitch,
       duration,
       accidental: accidental.unwrap_or_default(),
+      cents_offset: microtonal_alter.map_or(0, |(_, remainder)| remainder),
+      unpitched: Self::parse_unpitched(note, pitch, percussion_instruments),
       divisions: altered_divisions,
       voice,
       arpeggiated: arpeggiate,
@@ -1630,7 +1904,7 @@ impl MusicXmlConverter {
     // Gather section details
     for (time_slice_idx, time_slice) in time_slices.iter().enumerate().filter(|(_, slice)| {
       add_implicit_repeat.get()
-        || slice.jump_to.is_some()
+        || !slice.navigation.is_empty()
         || slice.section_start.is_some()
         || !slice.ending.is_empty()
         || !slice.repeat.is_empty()
@@ -1658,6 +1932,26 @@ impl MusicXmlConverter {
           details.ending_sections.push(open_repeats.pop().unwrap_or_default());
         }
       }
+      if !time_slice.navigation.is_empty() {
+        // Navigation applies to all content preceding this point, so every open section must end here
+        for section in open_endings.drain(..) {
+          details.ending_sections.push(section);
+        }
+        for section in open_repeats.drain(..) {
+          details.ending_sections.push(section);
+        }
+        for section in open_tempos.drain(..) {
+          details.ending_sections.push(section);
+        }
+        for section in open_sections.drain(..) {
+          details.ending_sections.push(section);
+        }
+        details.navigation.clone_from(&time_slice.navigation);
+        if time_slice.section_start.is_none() {
+          let new_section_id = details.new_section("Implicit Section").0;
+          open_sections.push(new_section_id);
+        }
+      }
       if let Some(section_name) = &time_slice.section_start {
         for section in open_endings.drain(..) {
           details.ending_sections.push(section);
@@ -1671,7 +1965,12 @@ impl MusicXmlConverter {
         for section in open_sections.drain(..) {
           details.ending_sections.push(section);
         }
-        let new_section_id = details.new_section(section_name).0;
+        let (new_section_id, new_section) = details.new_section(section_name);
+        match section_name.as_str() {
+          "Segno" => new_section.add_modification(SectionModificationType::Segno),
+          "Coda" => new_section.add_modification(SectionModificationType::Coda),
+          _ => 0,
+        };
         open_sections.push(new_section_id);
       }
       if let Some(tempo) = &time_slice.tempo_change_explicit {
@@ -1718,7 +2017,6 @@ impl MusicXmlConverter {
           open_endings.push(new_section_id);
         }
       }
-      // TODO: How handle "Jump To"?? time_slice.jump_to.iter().for_each(|item| println!("[{time_slice_idx}]: JumpTo: {item}"));
     }
     section_details
   }
@@ -1734,10 +2032,12 @@ impl MusicXmlConverter {
     let mut section_structure = BTreeMap::from([(0, top_level_section.get_id())]);
     unsafe {
       for (&index, details) in section_details {
+        let mut preceding_section_id = None;
         for &ending_section_number in &details.ending_sections {
           if ending_section_number == current_section_number {
             if let Some((parent_section_number, parent_section_id, parent_section_repeats)) = parent_sections.pop() {
               last_closed_index = index;
+              preceding_section_id = Some(current_section_id);
               current_section_id = parent_section_id;
               current_section_number = parent_section_number;
               current_section_repeats = parent_section_repeats;
@@ -1750,14 +2050,13 @@ impl MusicXmlConverter {
             .iter_modifications()
             .any(|modification| matches!(modification.r#type, SectionModificationType::OnlyPlay { .. }));
           if last_closed_index != index {
-            section_structure.insert(
-              last_closed_index,
-              top_level_section
-                .get_section_mut(current_section_id)
-                .unwrap_unchecked()
-                .add_section("Implicit Section")
-                .get_id(),
-            );
+            let implicit_section_id = top_level_section
+              .get_section_mut(current_section_id)
+              .unwrap_unchecked()
+              .add_section("Implicit Section")
+              .get_id();
+            section_structure.insert(last_closed_index, implicit_section_id);
+            preceding_section_id = Some(implicit_section_id);
             last_closed_index = index;
           }
           if !is_ending_section || current_section_repeats {
@@ -1774,6 +2073,12 @@ impl MusicXmlConverter {
             section_structure.insert(index, current_section_id);
           }
         }
+        if let Some(section_id) = preceding_section_id {
+          let preceding_section = top_level_section.get_section_mut(section_id).unwrap_unchecked();
+          for navigation in &details.navigation {
+            preceding_section.add_modification(navigation.clone());
+          }
+        }
       }
     }
     section_structure
@@ -2070,6 +2375,8 @@ impl MusicXmlConverter {
         note_accidentals.insert(item.pitch, item.accidental);
       };
       let mut note = Note::new(item.pitch, item.duration, Some(item.accidental));
+      note.cents_offset = item.cents_offset;
+      note.unpitched = item.unpitched;
       for modification in &item.note_modifications {
         if let Some(chord_mod) = ChordModification::from_note_modification(modification) {
           voice_mods.push(chord_mod.r#type);
@@ -2188,8 +2495,14 @@ impl MusicXmlConverter {
     } else if score.content.part.iter().all(|part| part.content.is_empty()) {
       return Err(String::from("All parts in the MusicXML score are empty"));
     }
+    let mut instruments = MusicXmlConverter::find_instruments(&score.content.part_list.content.content);
+    let percussion_instruments =
+      MusicXmlConverter::find_percussion_instruments(&score.content.part_list.content.content);
     for name in parts_map.values() {
-      composition.add_part(name);
+      let part = composition.add_part(name);
+      if let Some(instrument) = instruments.remove(name) {
+        part.set_instrument(instrument);
+      }
     }
 
     // Parse the initial musical attributes of the score
@@ -2215,6 +2528,7 @@ impl MusicXmlConverter {
 
     // Parse the actual musical contents of the score into discrete time slices
     let mut part_divisions_per_quarter_note = BTreeMap::new();
+    let mut part_transpositions = BTreeMap::new();
     for part in &score.content.part {
       if part.content.is_empty() {
         composition.remove_part_by_name(unsafe { parts_map.get(&*part.attributes.id).unwrap_unchecked() });
@@ -2226,6 +2540,10 @@ impl MusicXmlConverter {
           unsafe { parts_map.get(&*part.attributes.id).unwrap_unchecked() },
           divisions_per_quarter_note,
         );
+        part_transpositions.insert(
+          unsafe { parts_map.get(&*part.attributes.id).unwrap_unchecked() },
+          Self::find_transposition(&part.content),
+        );
         let time_slices = unsafe {
           part_data
             .data
@@ -2235,7 +2553,7 @@ impl MusicXmlConverter {
         for element in &part.content {
           if let musicxml::elements::PartElement::Measure(measure) = element {
             let mut latest_cursor_reached = cursor;
-            let mut accidental_context = BTreeMap::new();
+            let (mut accidental_context, mut navigation) = (BTreeMap::new(), Vec::new());
             for measure_element in &measure.content {
               let cursor_change = match measure_element {
                 musicxml::elements::MeasureElement::Attributes(attributes) => {
@@ -2243,6 +2561,7 @@ impl MusicXmlConverter {
                 }
                 musicxml::elements::MeasureElement::Note(note) => MusicXmlConverter::parse_note_element(
                   note,
+                  &percussion_instruments[&*part.attributes.id],
                   &mut accidental_context,
                   time_slices,
                   divisions_per_quarter_note,
@@ -2255,8 +2574,15 @@ impl MusicXmlConverter {
                 musicxml::elements::MeasureElement::Forward(forward) => {
                   MusicXmlConverter::parse_forward_element(&forward.content)
                 }
-                musicxml::elements::MeasureElement::Direction(direction) => {
-                  MusicXmlConverter::parse_direction_element(direction, time_slices, &mut open_wedges, cursor)
+                musicxml::elements::MeasureElement::Direction(direction) => MusicXmlConverter::parse_direction_element(
+                  direction,
+                  time_slices,
+                  &mut open_wedges,
+                  &mut navigation,
+                  cursor,
+                ),
+                musicxml::elements::MeasureElement::Sound(sound) => {
+                  MusicXmlConverter::parse_sound_element(sound, time_slices, &mut navigation, cursor)
                 }
                 musicxml::elements::MeasureElement::Barline(barline) => {
                   MusicXmlConverter::parse_barline_element(barline, time_slices, cursor)
@@ -2270,6 +2596,11 @@ impl MusicXmlConverter {
               }
             }
             cursor = latest_cursor_reached;
+
+            // Navigation markings take effect at the end of the measure in which they appear
+            for slice in time_slices.values_mut() {
+              slice[cursor].navigation.extend(navigation.iter().cloned());
+            }
           }
         }
       }
@@ -2292,6 +2623,10 @@ impl MusicXmlConverter {
       .collect();
 
     // Use the temporally ordered time slices for each part to construct a final composition structure
+    let (starting_key, starting_time_signature) = (
+      *composition.get_starting_key(),
+      *composition.get_starting_time_signature(),
+    );
     for (part_name, staves) in part_data.data {
       let part = composition
         .get_part_mut_by_name(&part_name)
@@ -2606,6 +2941,13 @@ impl MusicXmlConverter {
 
       // Simplify the part to remove any unnecessary nesting structures
       part.simplify();
+
+      // Convert transposing parts from their written pitch to sounding pitch
+      if let Some(transposition) = part_transpositions.get(&part_name) {
+        part.set_transposition(*transposition, true);
+        let written_key = part.written_key(starting_key);
+        part.transpose_to_sounding_pitch(written_key, starting_time_signature);
+      }
     }
 
     Ok(composition)
@@ -2614,12 +2956,53 @@ impl MusicXmlConverter {
 
 impl Load for MusicXmlConverter {
   fn load(path: &str) -> Result<Composition, String> {
-    let score = musicxml::read_score_partwise(path)?;
-    MusicXmlConverter::load_from_musicxml(&score)
+    let data = fs::read(path).map_err(|err| err.to_string())?;
+    MusicXmlConverter::load_data(data)
   }
 
   fn load_data(data: Vec<u8>) -> Result<Composition, String> {
-    let score = musicxml::read_score_data_partwise(data)?;
+    let score = MusicXmlConverter::read_score_data(data)?;
     MusicXmlConverter::load_from_musicxml(&score)
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_fractional_alters() {
+    let note = |step: &str, alter: &str| {
+      format!(
+        "<note><pitch><step>{step}</step><alter>{alter}</alter><octave>4</octave></pitch>\
+         <duration>1</duration><type>quarter</type></note>"
+      )
+    };
+    let data = format!(
+      "<score-partwise version=\"4.0\"><part-list><score-part id=\"P1\"><part-name>Oud</part-name></score-part>\
+       </part-list><part id=\"P1\"><measure number=\"1\"><attributes><divisions>1</divisions></attributes>\
+       {}{}{}{}</measure></part></score-partwise>",
+      note("F", "1"),
+      note("E", "-0.5"),
+      note("B", "-0.3"),
+      note("C", "0.85"),
+    );
+    let composition = MusicXmlConverter::load_data(data.into_bytes()).unwrap();
+    let notes: Vec<_> = composition
+      .get_part_by_name("Oud")
+      .unwrap()
+      .iter_timeslices()
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| (content.note.accidental, content.note.cents_offset))
+      .collect();
+    assert_eq!(
+      notes,
+      [
+        (Accidental::None, 0),
+        (Accidental::QuarterFlat, 0),
+        (Accidental::QuarterFlat, 20),
+        (Accidental::Sharp, -15),
+      ]
+    );
+  }
+}
@@ -1,7 +1,8 @@
-use crate::context::{generate_id, Tempo};
+use crate::context::{generate_id, Key, Tempo};
 use crate::modification::{ChordModification, ChordModificationType, NoteModification};
-use crate::note::{Accidental, Duration, Note, Pitch};
+use crate::note::{Accidental, Duration, Interval, Note, Pitch, Rational, TranspositionMode};
 use crate::temporal::Timeslice;
+use alloc::collections::BTreeMap;
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -94,7 +95,7 @@ impl Chord {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<f64>) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<Rational>) -> Rational {
     self
       .iter()
       .filter_map(|ChordContent::Note(note)| {
@@ -104,13 +105,13 @@ impl Chord {
           Some(note.get_beats(beat_base, tuplet_ratio))
         }
       })
-      .reduce(f64::min)
+      .min()
       .unwrap_or_default()
   }
 
   #[must_use]
-  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<f64>) -> f64 {
-    self.get_beats(&tempo.base_note, tuplet_ratio) * 60.0 / f64::from(tempo.beats_per_minute)
+  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<Rational>) -> f64 {
+    self.get_beats(&tempo.base_note, tuplet_ratio).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
   }
 
   pub fn remove_item(&mut self, id: usize) -> &mut Self {
@@ -126,6 +127,37 @@ impl Chord {
     self
   }
 
+  pub fn transpose(&mut self, interval: Interval, mode: TranspositionMode, key: Option<Key>) -> &mut Self {
+    self.iter_mut().for_each(|ChordContent::Note(note)| {
+      note.transpose(interval, mode, key);
+    });
+    self
+  }
+
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|ChordContent::Note(note)| {
+      if let Some((pitch, accidental)) = spellings.get(&note.note_id) {
+        note.pitch = *pitch;
+        note.accidental = *accidental;
+      }
+    });
+  }
+
+  pub(crate) fn renumber_notes(&mut self, original_note_ids: &mut BTreeMap<usize, usize>) {
+    self.iter_mut().for_each(|ChordContent::Note(note)| {
+      original_note_ids.insert(note.get_id(), note.note_id);
+      note.note_id = note.get_id();
+    });
+  }
+
+  pub fn transpose_to_key(&mut self, current_key: Key, target_key: Key) -> &mut Self {
+    self.transpose(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      Some(current_key),
+    )
+  }
+
   #[must_use]
   pub fn num_items(&self) -> usize {
     self.content.len()
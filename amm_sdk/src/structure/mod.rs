@@ -11,3 +11,70 @@ pub use part::{Part, PartContent};
 pub use phrase::{Phrase, PhraseContent};
 pub use section::{Section, SectionContent};
 pub use staff::{Staff, StaffContent};
+
+use crate::context::{Key, TimeSignature};
+use crate::note::{Interval, TranspositionMode};
+use alloc::collections::BTreeSet;
+
+pub(crate) trait Transpose {
+  fn make_accidentals_explicit(&mut self, key: Key, time_signature: TimeSignature) -> BTreeSet<usize>;
+  fn transpose_from_key(&mut self, interval: Interval, mode: TranspositionMode, key: Key) -> Key;
+  fn omit_implied_accidentals(
+    &mut self,
+    written_key: Key,
+    key: Key,
+    time_signature: TimeSignature,
+    implicit_note_ids: &BTreeSet<usize>,
+  );
+  fn set_starting_key(&mut self, key: Key);
+
+  fn transpose_in_context(
+    &mut self,
+    interval: Interval,
+    mode: TranspositionMode,
+    key: Key,
+    target_key: Key,
+    time_signature: TimeSignature,
+  ) -> Key {
+    let implicit_note_ids = self.make_accidentals_explicit(key, time_signature);
+    let end_key = self.transpose_from_key(interval, mode, key);
+    self.omit_implied_accidentals(
+      transposed_key(key, interval, mode),
+      target_key,
+      time_signature,
+      &implicit_note_ids,
+    );
+    end_key
+  }
+
+  fn transpose_and_record_key(
+    &mut self,
+    interval: Interval,
+    mode: TranspositionMode,
+    key: Key,
+    target_key: Key,
+    time_signature: TimeSignature,
+  ) {
+    self.transpose_in_context(interval, mode, key, target_key, time_signature);
+    if target_key != key {
+      // Note: Transposed notes are spelled against the new key, so it must be recorded in the content
+      self.set_starting_key(target_key);
+    }
+  }
+}
+
+pub(crate) fn transposed_key(key: Key, interval: Interval, mode: TranspositionMode) -> Key {
+  match mode {
+    TranspositionMode::Chromatic => key.transposed(interval),
+    TranspositionMode::Diatonic => key,
+  }
+}
+
+pub(crate) fn notated_key(key: Key) -> Key {
+  // Note: Keys without a traditional key signature are replaced by their enharmonic equivalent
+  if key.is_standard() {
+    key
+  } else {
+    Key::from_pitch_class(key.signature.pitch_class(), key.mode)
+  }
+}
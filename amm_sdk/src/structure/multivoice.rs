@@ -2,11 +2,11 @@ use super::{
   chord::{Chord, ChordContent},
   phrase::{Phrase, PhraseContent, PhraseTimesliceIter},
 };
-use crate::context::{generate_id, Tempo};
+use crate::context::{generate_id, Key, Tempo};
 use crate::modification::PhraseModificationType;
-use crate::note::{Duration, DurationType, Note};
+use crate::note::{Accidental, Duration, DurationType, Interval, Note, Pitch, Rational, TranspositionMode};
 use crate::temporal::Timeslice;
-use alloc::collections::VecDeque;
+use alloc::collections::{BTreeMap, VecDeque};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -43,9 +43,9 @@ impl MultiVoice {
     }
   }
 
-  fn flatten_tuplets(timeslices: VecDeque<(f64, Vec<PhraseContent>)>, beat_base_note: Duration) -> Phrase {
+  fn flatten_tuplets(timeslices: VecDeque<(Rational, Vec<PhraseContent>)>, beat_base_note: Duration) -> Phrase {
     let mut phrase = Phrase::new();
-    let (mut tuplet_end_time, mut tuplet_ratio) = (0.0, Some(3.0 / 2.0));
+    let (mut tuplet_end_time, mut tuplet_ratio) = (Rational::ZERO, Some(Rational::new(3, 2)));
     if let Some((_, slice_content)) = timeslices.front() {
       // Pre-calculate required tuplet statistics
       let tuplet_phrases = slice_content
@@ -55,12 +55,8 @@ impl MultiVoice {
           _ => None,
         })
         .collect::<Vec<_>>();
-      let (longest_duration, longest_tuplet) = unsafe {
-        *tuplet_phrases
-          .iter()
-          .max_by(|(beats1, _), (beats2, _)| beats1.partial_cmp(beats2).unwrap_unchecked())
-          .unwrap_unchecked()
-      };
+      let (longest_duration, longest_tuplet) =
+        unsafe { *tuplet_phrases.iter().max_by_key(|(beats, _)| *beats).unwrap_unchecked() };
       let target_type = unsafe {
         longest_tuplet
           .iter_modifications()
@@ -68,13 +64,12 @@ impl MultiVoice {
           .unwrap_unchecked()
           .r#type
       };
-      tuplet_ratio = Some(
-        if let PhraseModificationType::Tuplet { num_beats, into_beats } = target_type {
-          f64::from(into_beats) / f64::from(num_beats)
-        } else {
-          1.0
-        },
-      );
+      tuplet_ratio = Some(match target_type {
+        PhraseModificationType::Tuplet { num_beats, into_beats } if num_beats > 0 => {
+          Rational::new(i64::from(into_beats), i64::from(num_beats))
+        }
+        _ => Rational::ONE,
+      });
       tuplet_end_time = longest_duration;
 
       // Check for various tuplet situations
@@ -90,7 +85,7 @@ impl MultiVoice {
         phrase.add_modification(target_type);
         let mut phrase_chords = Vec::new();
         for (_, tuplet) in tuplet_phrases.into_iter().filter(|(tuplet_duration, tuplet)| {
-          (longest_duration - *tuplet_duration).abs() < 0.000_001
+          longest_duration == *tuplet_duration
             && target_num_notes == tuplet.num_items()
             && tuplet
               .iter_modifications()
@@ -125,7 +120,7 @@ impl MultiVoice {
     }
 
     // Fill in the list of valid times for each item in the tuplet
-    let mut curr_time = 0.0;
+    let mut curr_time = Rational::ZERO;
     let mut valid_times_reversed = phrase
       .iter()
       .enumerate()
@@ -140,9 +135,9 @@ impl MultiVoice {
           curr_time += note.get_beats(&beat_base_note, tuplet_ratio);
           (idx, start_time, false)
         }
-        _ => (0, 0.0, false),
+        _ => (0, Rational::ZERO, false),
       })
-      .collect::<Vec<(usize, f64, bool)>>();
+      .collect::<Vec<(usize, Rational, bool)>>();
     valid_times_reversed.reverse();
 
     // Deal with reminder of the timeslices
@@ -219,9 +214,9 @@ impl MultiVoice {
       .collect();
 
     // Place all resulting phrase content into temporally ordered timeslices
-    let mut timeslices: Vec<(f64, Vec<PhraseContent>)> = Vec::new();
+    let mut timeslices: Vec<(Rational, Vec<PhraseContent>)> = Vec::new();
     for phrase in phrases {
-      let (mut index, mut curr_time) = (0, 0.0);
+      let (mut index, mut curr_time) = (0, Rational::ZERO);
       for item in phrase {
         let slice_duration = match &item {
           PhraseContent::Note(note) => note.get_beats(&beat_base_note, None),
@@ -231,7 +226,7 @@ impl MultiVoice {
         };
         if let Some(slice_details) = timeslices.get_mut(index) {
           let (mut slice_time, mut existing_slice) = (slice_details.0, &mut slice_details.1);
-          while curr_time > slice_time && curr_time - slice_time > 0.000_001 {
+          while curr_time > slice_time {
             index += 1;
             (slice_time, existing_slice) = if let Some((start_time, slice)) = timeslices.get_mut(index) {
               (*start_time, slice)
@@ -243,7 +238,7 @@ impl MultiVoice {
               }
             };
           }
-          if (slice_time - curr_time).abs() < 0.000_001 {
+          if slice_time == curr_time {
             existing_slice.push(item);
           } else {
             timeslices.insert(index, (curr_time, vec![item]));
@@ -258,7 +253,7 @@ impl MultiVoice {
 
     // Combine the content of each timeslice into a new phrase, taking special care of tuplets
     let mut phrase = Phrase::new();
-    let (mut tuplet_slices, mut tuplet_end) = (VecDeque::new(), 0.0);
+    let (mut tuplet_slices, mut tuplet_end) = (VecDeque::new(), Rational::ZERO);
     for (slice_time, mut slice_content) in timeslices {
       if !tuplet_slices.is_empty() {
         if slice_time < tuplet_end {
@@ -401,17 +396,17 @@ impl MultiVoice {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<f64>) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<Rational>) -> Rational {
     self
       .iter()
       .map(|MultiVoiceContent::Phrase(phrase)| phrase.get_beats(beat_base, tuplet_ratio))
-      .reduce(f64::max)
+      .max()
       .unwrap_or_default()
   }
 
   #[must_use]
-  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<f64>) -> f64 {
-    self.get_beats(&tempo.base_note, tuplet_ratio) * 60.0 / f64::from(tempo.beats_per_minute)
+  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<Rational>) -> f64 {
+    self.get_beats(&tempo.base_note, tuplet_ratio).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
   }
 
   pub fn remove_item(&mut self, id: usize) -> &mut Self {
@@ -431,6 +426,33 @@ impl MultiVoice {
     self
   }
 
+  pub fn transpose(&mut self, interval: Interval, mode: TranspositionMode, key: Option<Key>) -> &mut Self {
+    self.iter_mut().for_each(|MultiVoiceContent::Phrase(phrase)| {
+      phrase.transpose(interval, mode, key);
+    });
+    self
+  }
+
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|MultiVoiceContent::Phrase(phrase)| {
+      phrase.apply_spellings(spellings);
+    });
+  }
+
+  pub(crate) fn renumber_notes(&mut self, original_note_ids: &mut BTreeMap<usize, usize>) {
+    self.iter_mut().for_each(|MultiVoiceContent::Phrase(phrase)| {
+      phrase.renumber_notes(original_note_ids);
+    });
+  }
+
+  pub fn transpose_to_key(&mut self, current_key: Key, target_key: Key) -> &mut Self {
+    self.transpose(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      Some(current_key),
+    )
+  }
+
   #[must_use]
   pub fn is_empty(&self) -> bool {
     self.content.is_empty()
@@ -463,7 +485,7 @@ impl MultiVoice {
         .map(|MultiVoiceContent::Phrase(phrase)| {
           let mut iter = phrase.iter_timeslices();
           let next = iter.next();
-          (0.0, iter, next)
+          (Rational::ZERO, iter, next)
         })
         .collect(),
     }
@@ -516,24 +538,24 @@ impl PartialEq for MultiVoice {
 
 pub struct MultiVoiceTimesliceIter<'a> {
   base_duration: Duration,
-  phrase_iterators: Vec<(f64, PhraseTimesliceIter<'a>, Option<Timeslice>)>,
+  phrase_iterators: Vec<(Rational, PhraseTimesliceIter<'a>, Option<Timeslice>)>,
 }
 
 impl Iterator for MultiVoiceTimesliceIter<'_> {
   type Item = Timeslice;
   fn next(&mut self) -> Option<Self::Item> {
-    let mut next_start_time = f64::MAX;
+    let mut next_start_time: Option<Rational> = None;
     let mut timeslice: Option<Timeslice> = None;
     self
       .phrase_iterators
       .iter_mut()
       .for_each(|(next_time, iterator, next_item)| {
-        if next_time.abs() <= 0.000_001 {
+        if next_time.is_zero() {
           if let Some(mut slice) = next_item.take() {
             *next_item = iterator.next();
             *next_time = slice.get_beats(&self.base_duration);
-            if *next_time < next_start_time && next_item.is_some() {
-              next_start_time = *next_time;
+            if next_item.is_some() && next_start_time.is_none_or(|start_time| *next_time < start_time) {
+              next_start_time = Some(*next_time);
             }
             if let Some(timeslice) = timeslice.as_mut() {
               timeslice.combine_with(&mut slice);
@@ -541,13 +563,14 @@ impl Iterator for MultiVoiceTimesliceIter<'_> {
               timeslice = Some(slice);
             }
           }
-        } else if *next_time >= 0.0 && *next_time < next_start_time {
-          next_start_time = *next_time;
+        } else if !next_time.is_negative() && next_start_time.is_none_or(|start_time| *next_time < start_time) {
+          next_start_time = Some(*next_time);
         }
       });
     if timeslice.is_some() {
+      // Iterators with a negative time remaining have no more timeslices to contribute
       self.phrase_iterators.iter_mut().for_each(|(next_time, _, _)| {
-        *next_time -= next_start_time;
+        *next_time = next_start_time.map_or(-Rational::ONE, |start_time| *next_time - start_time);
       });
     }
     timeslice
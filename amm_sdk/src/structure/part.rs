@@ -1,7 +1,13 @@
-use super::{chord::Chord, multivoice::MultiVoice, phrase::Phrase, section::Section, staff::Staff};
-use crate::context::{generate_id, Tempo};
-use crate::note::{Duration, Note};
-use crate::temporal::Timeslice;
+use super::{
+  chord::Chord, multivoice::MultiVoice, notated_key, phrase::Phrase, section::Section, staff::Staff, transposed_key,
+  Transpose,
+};
+use crate::context::{generate_id, Instrument, Key, Tempo, TimeSignature, Transposition};
+use crate::note::{Accidental, Duration, Interval, Note, Pitch, Rational, TranspositionMode};
+use crate::temporal::{
+  make_accidentals_explicit, omit_implied_accidentals, realize_part, RealizationOptions, RealizedNote,
+  TempoCurveOptions, TempoMap, Timeslice,
+};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -14,6 +20,8 @@ pub enum PartContent {
 pub struct Part {
   id: usize,
   name: String,
+  instrument: Instrument,
+  written_pitch: bool,
   content: Vec<PartContent>,
 }
 
@@ -23,6 +31,8 @@ impl Part {
     Self {
       id: generate_id(),
       name: String::from(name),
+      instrument: Instrument::new(name),
+      written_pitch: false,
       content: Vec::new(),
     }
   }
@@ -39,6 +49,8 @@ impl Part {
     Self {
       id: generate_id(),
       name: self.name.clone(),
+      instrument: self.instrument.clone(),
+      written_pitch: self.written_pitch,
       content: self
         .iter()
         .map(|PartContent::Section(section)| PartContent::Section(section.flatten()))
@@ -46,6 +58,24 @@ impl Part {
     }
   }
 
+  #[must_use]
+  pub fn unroll(&self, original_note_ids: &mut BTreeMap<usize, usize>) -> Self {
+    Self {
+      id: generate_id(),
+      name: self.name.clone(),
+      instrument: self.instrument.clone(),
+      written_pitch: self.written_pitch,
+      content: self
+        .navigation_order()
+        .into_iter()
+        .map(|(idx, repeats)| {
+          let PartContent::Section(section) = &self.content[idx];
+          PartContent::Section(section.unroll_with_repeats(original_note_ids, repeats))
+        })
+        .collect(),
+    }
+  }
+
   #[must_use]
   pub fn extract_staves_as_parts(&self) -> Vec<Self> {
     let mut staff_parts: BTreeMap<String, Self> = self
@@ -54,7 +84,7 @@ impl Part {
       .map(|staff_name| {
         (
           String::from(staff_name),
-          Self::new((self.name.clone() + "_" + staff_name).as_str()),
+          Self::new((self.name.clone() + "_" + staff_name).as_str()).with_instrument_of(self),
         )
       })
       .collect();
@@ -78,11 +108,44 @@ impl Part {
     &self.name
   }
 
+  #[must_use]
+  pub const fn get_instrument(&self) -> &Instrument {
+    &self.instrument
+  }
+
+  #[must_use]
+  pub const fn get_transposition(&self) -> Transposition {
+    self.instrument.transposition
+  }
+
+  #[must_use]
+  pub const fn is_written_pitch(&self) -> bool {
+    self.written_pitch
+  }
+
   pub fn rename(&mut self, name: &str) -> &mut Self {
     self.name = String::from(name);
     self
   }
 
+  pub fn set_instrument(&mut self, instrument: Instrument) -> &mut Self {
+    self.written_pitch &= !instrument.transposition.is_concert_pitch();
+    self.instrument = instrument;
+    self
+  }
+
+  pub fn set_transposition(&mut self, transposition: Transposition, written_pitch: bool) -> &mut Self {
+    self.instrument.transposition = transposition;
+    self.written_pitch = written_pitch && !transposition.is_concert_pitch();
+    self
+  }
+
+  fn with_instrument_of(mut self, other: &Self) -> Self {
+    self.instrument = other.instrument.clone();
+    self.written_pitch = other.written_pitch;
+    self
+  }
+
   pub fn add_section(&mut self, name: &str) -> &mut Section {
     self.content.push(PartContent::Section(Section::new(name)));
     match self.content.last_mut() {
@@ -212,17 +275,51 @@ impl Part {
       .find_map(|PartContent::Section(section)| section.get_staff_mut(id))
   }
 
+  fn navigation_order(&self) -> Vec<(usize, bool)> {
+    Section::follow_navigation(
+      &self
+        .iter()
+        .map(|PartContent::Section(section)| Some(section))
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  #[must_use]
+  pub fn performance_order(&self) -> Vec<usize> {
+    self.navigation_order().into_iter().map(|(idx, _)| idx).collect()
+  }
+
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration) -> Rational {
     self
-      .iter()
-      .map(|PartContent::Section(section)| section.get_beats(beat_base))
+      .navigation_order()
+      .into_iter()
+      .map(|(idx, repeats)| {
+        let PartContent::Section(section) = &self.content[idx];
+        section.get_beats_with_repeats(*beat_base, repeats)
+      })
       .sum()
   }
 
+  #[must_use]
+  pub fn get_tempo_map(&self, tempo: &Tempo, options: &TempoCurveOptions) -> TempoMap {
+    TempoMap::from_timeslices(self.iter_timeslices(), tempo, options)
+  }
+
+  #[must_use]
+  pub fn realize(
+    &self,
+    starting_key: Key,
+    starting_time_signature: TimeSignature,
+    beat_base: &Duration,
+    options: &RealizationOptions,
+  ) -> Vec<RealizedNote> {
+    realize_part(self, starting_key, starting_time_signature, *beat_base, options)
+  }
+
   #[must_use]
   pub fn get_duration(&self, tempo: &Tempo) -> f64 {
-    self.get_beats(&tempo.base_note) * 60.0 / f64::from(tempo.beats_per_minute)
+    self.get_tempo_map(tempo, &TempoCurveOptions::default()).get_duration()
   }
 
   pub fn remove_section(&mut self, id: usize) -> &mut Self {
@@ -249,6 +346,87 @@ impl Part {
     self
   }
 
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|PartContent::Section(section)| {
+      section.apply_spellings(spellings);
+    });
+  }
+
+  pub fn transpose(
+    &mut self,
+    interval: Interval,
+    mode: TranspositionMode,
+    key: Option<Key>,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    let key = key.unwrap_or_default();
+    self.transpose_and_record_key(
+      interval,
+      mode,
+      key,
+      transposed_key(key, interval, mode),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
+  pub fn transpose_to_key(
+    &mut self,
+    current_key: Key,
+    target_key: Key,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    self.transpose_and_record_key(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      current_key,
+      notated_key(target_key),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
+  pub fn to_sounding_pitch(&mut self, written_key: Option<Key>, time_signature: Option<TimeSignature>) -> &mut Self {
+    self.transpose_to_sounding_pitch(written_key.unwrap_or_default(), time_signature.unwrap_or_default())
+  }
+
+  pub(crate) fn transpose_to_sounding_pitch(&mut self, written_key: Key, time_signature: TimeSignature) -> &mut Self {
+    if self.written_pitch {
+      self.transpose_in_context(
+        self.instrument.transposition.interval(),
+        TranspositionMode::Chromatic,
+        written_key,
+        written_key.transposed(self.instrument.transposition.interval()),
+        time_signature,
+      );
+      self.written_pitch = false;
+    }
+    self
+  }
+
+  pub fn to_written_pitch(&mut self, sounding_key: Option<Key>, time_signature: Option<TimeSignature>) -> &mut Self {
+    self.transpose_to_written_pitch(sounding_key.unwrap_or_default(), time_signature.unwrap_or_default())
+  }
+
+  pub(crate) fn transpose_to_written_pitch(&mut self, sounding_key: Key, time_signature: TimeSignature) -> &mut Self {
+    if !self.written_pitch && !self.instrument.transposition.is_concert_pitch() {
+      self.transpose_in_context(
+        self.instrument.transposition.interval().reversed(),
+        TranspositionMode::Chromatic,
+        sounding_key,
+        self.written_key(sounding_key),
+        time_signature,
+      );
+      self.written_pitch = true;
+    }
+    self
+  }
+
+  #[must_use]
+  pub fn written_key(&self, sounding_key: Key) -> Key {
+    sounding_key.transposed(self.instrument.transposition.interval().reversed())
+  }
+
   #[must_use]
   pub fn num_timeslices(&self) -> usize {
     self
@@ -273,9 +451,10 @@ impl Part {
   #[must_use]
   pub fn iter_timeslices(&self) -> impl core::iter::FusedIterator<Item = Timeslice> + '_ {
     // Note: use this to return timeslices for a single part
-    self
-      .iter()
-      .flat_map(|PartContent::Section(section)| section.iter_timeslices())
+    self.navigation_order().into_iter().flat_map(|(idx, repeats)| {
+      let PartContent::Section(section) = &self.content[idx];
+      section.iter_timeslices_with_repeats(repeats)
+    })
   }
 
   #[must_use]
@@ -284,6 +463,50 @@ impl Part {
   }
 }
 
+impl Transpose for Part {
+  fn make_accidentals_explicit(&mut self, key: Key, time_signature: TimeSignature) -> BTreeSet<usize> {
+    let mut implicit_note_ids = BTreeSet::new();
+    for staff_part in self.extract_staves_as_parts() {
+      let (accidentals, note_ids) = make_accidentals_explicit(staff_part.iter_timeslices(), key, time_signature);
+      self.apply_spellings(&accidentals);
+      implicit_note_ids.extend(note_ids);
+    }
+    implicit_note_ids
+  }
+
+  fn transpose_from_key(&mut self, interval: Interval, mode: TranspositionMode, mut key: Key) -> Key {
+    self.iter_mut().for_each(|PartContent::Section(section)| {
+      key = section.transpose_from_key(interval, mode, key);
+    });
+    key
+  }
+
+  fn omit_implied_accidentals(
+    &mut self,
+    written_key: Key,
+    key: Key,
+    time_signature: TimeSignature,
+    implicit_note_ids: &BTreeSet<usize>,
+  ) {
+    for staff_part in self.extract_staves_as_parts() {
+      let spellings = omit_implied_accidentals(
+        staff_part.iter_timeslices(),
+        written_key,
+        key,
+        time_signature,
+        implicit_note_ids,
+      );
+      self.apply_spellings(&spellings);
+    }
+  }
+
+  fn set_starting_key(&mut self, key: Key) {
+    if let Some(PartContent::Section(section)) = self.content.first_mut() {
+      section.set_starting_key(key);
+    }
+  }
+}
+
 impl IntoIterator for Part {
   type Item = PartContent;
   type IntoIter = alloc::vec::IntoIter<Self::Item>;
@@ -313,6 +536,8 @@ impl Clone for Part {
     Self {
       id: generate_id(),
       name: self.name.clone(),
+      instrument: self.instrument.clone(),
+      written_pitch: self.written_pitch,
       content: self.content.clone(),
     }
   }
@@ -320,7 +545,10 @@ impl Clone for Part {
 
 impl PartialEq for Part {
   fn eq(&self, other: &Self) -> bool {
-    self.content == other.content && self.name == other.name
+    self.content == other.content
+      && self.name == other.name
+      && self.instrument == other.instrument
+      && self.written_pitch == other.written_pitch
   }
 }
 
@@ -335,3 +563,67 @@ impl core::fmt::Display for Part {
     write!(f, "Part {}: [{sections}]", self.name)
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature};
+  use crate::modification::SectionModificationType;
+  use crate::note::{DurationType, IntervalQuality, PitchName};
+  use crate::temporal::{RealizationOptions, RealizedNote};
+
+  #[test]
+  fn test_unroll_follows_navigation() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut part = Part::new("Part");
+    for (pitch, navigation) in [
+      (PitchName::C, SectionModificationType::Fine),
+      (PitchName::D, SectionModificationType::DaCapo),
+    ] {
+      let section = part.add_section("Section");
+      section.add_modification(navigation);
+      section.add_staff("Staff").add_note(Pitch::new(pitch, 4), quarter, None);
+    }
+    let pitches = |part: &Part| -> Vec<_> {
+      part
+        .iter_timeslices()
+        .map(|timeslice| timeslice.content[0].note.pitch.name)
+        .collect()
+    };
+    let unrolled = part.unroll(&mut BTreeMap::new());
+    assert_eq!(pitches(&part), vec![PitchName::C, PitchName::D, PitchName::C]);
+    assert_eq!(pitches(&unrolled), pitches(&part));
+    assert_eq!(unrolled.get_beats(&quarter), Rational::from(3_u8));
+  }
+
+  #[test]
+  fn test_transpose_records_key() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let d_major = Key::new(KeySignature::D, KeyMode::Major);
+    let mut part = Part::new("Part");
+    let section = part.add_section("Section");
+    for pitch in [PitchName::F, PitchName::C] {
+      section
+        .add_section("Section")
+        .add_staff("Staff")
+        .add_note(Pitch::new(pitch, 4), quarter, None);
+    }
+    part.transpose(
+      Interval::new(IntervalQuality::Major, 2),
+      TranspositionMode::Chromatic,
+      Some(d_major),
+      None,
+    );
+    let midi_numbers: Vec<_> = part
+      .realize(
+        d_major,
+        TimeSignature::default(),
+        &quarter,
+        &RealizationOptions::default(),
+      )
+      .iter()
+      .map(RealizedNote::midi_number)
+      .collect();
+    assert_eq!(midi_numbers, vec![68, 63]);
+  }
+}
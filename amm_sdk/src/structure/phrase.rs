@@ -2,10 +2,11 @@ use super::{
   chord::Chord,
   multivoice::{MultiVoice, MultiVoiceTimesliceIter},
 };
-use crate::context::{generate_id, Tempo};
+use crate::context::{generate_id, Key, Tempo, TimeSignature};
 use crate::modification::{PhraseModification, PhraseModificationType};
-use crate::note::{Accidental, Duration, Note, Pitch};
+use crate::note::{choose_spellings, Accidental, Duration, Interval, Note, Pitch, Rational, TranspositionMode};
 use crate::temporal::Timeslice;
+use alloc::collections::BTreeMap;
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -328,10 +329,13 @@ impl Phrase {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<f64>) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration, tuplet_ratio: Option<Rational>) -> Rational {
     // Determine if this phrase creates a tuplet
     let new_tuplet_ratio = self.iter_modifications().find_map(|item| match item.r#type {
-      PhraseModificationType::Tuplet { num_beats, into_beats } => Some(f64::from(into_beats) / f64::from(num_beats)),
+      // Note: A tuplet spanning zero beats is malformed, so it is ignored
+      PhraseModificationType::Tuplet { num_beats, into_beats } if num_beats > 0 => {
+        Some(Rational::new(i64::from(into_beats), i64::from(num_beats)))
+      }
       _ => None,
     });
     let tuplet_ratio = match tuplet_ratio {
@@ -355,8 +359,8 @@ impl Phrase {
   }
 
   #[must_use]
-  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<f64>) -> f64 {
-    self.get_beats(&tempo.base_note, tuplet_ratio) * 60.0 / f64::from(tempo.beats_per_minute)
+  pub fn get_duration(&self, tempo: &Tempo, tuplet_ratio: Option<Rational>) -> f64 {
+    self.get_beats(&tempo.base_note, tuplet_ratio).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
   }
 
   pub fn remove_item(&mut self, id: usize) -> &mut Self {
@@ -400,6 +404,68 @@ impl Phrase {
     self
   }
 
+  pub fn transpose(&mut self, interval: Interval, mode: TranspositionMode, key: Option<Key>) -> &mut Self {
+    self.iter_mut().for_each(|item| match item {
+      PhraseContent::Note(note) => {
+        note.transpose(interval, mode, key);
+      }
+      PhraseContent::Chord(chord) => {
+        chord.transpose(interval, mode, key);
+      }
+      PhraseContent::Phrase(phrase) => {
+        phrase.transpose(interval, mode, key);
+      }
+      PhraseContent::MultiVoice(multivoice) => {
+        multivoice.transpose(interval, mode, key);
+      }
+    });
+    self
+  }
+
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|item| match item {
+      PhraseContent::Note(note) => {
+        if let Some((pitch, accidental)) = spellings.get(&note.note_id) {
+          note.pitch = *pitch;
+          note.accidental = *accidental;
+        }
+      }
+      PhraseContent::Chord(chord) => chord.apply_spellings(spellings),
+      PhraseContent::Phrase(phrase) => phrase.apply_spellings(spellings),
+      PhraseContent::MultiVoice(multivoice) => multivoice.apply_spellings(spellings),
+    });
+  }
+
+  pub(crate) fn renumber_notes(&mut self, original_note_ids: &mut BTreeMap<usize, usize>) {
+    self.iter_mut().for_each(|item| match item {
+      PhraseContent::Note(note) => {
+        original_note_ids.insert(note.get_id(), note.note_id);
+        note.note_id = note.get_id();
+      }
+      PhraseContent::Chord(chord) => chord.renumber_notes(original_note_ids),
+      PhraseContent::Phrase(phrase) => phrase.renumber_notes(original_note_ids),
+      PhraseContent::MultiVoice(multivoice) => multivoice.renumber_notes(original_note_ids),
+    });
+  }
+
+  pub fn respell(&mut self, key: Option<Key>, time_signature: Option<TimeSignature>) -> &mut Self {
+    let spellings = choose_spellings(
+      self.iter_timeslices(),
+      key.unwrap_or_default(),
+      time_signature.unwrap_or_default(),
+    );
+    self.apply_spellings(&spellings);
+    self
+  }
+
+  pub fn transpose_to_key(&mut self, current_key: Key, target_key: Key) -> &mut Self {
+    self.transpose(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      Some(current_key),
+    )
+  }
+
   #[must_use]
   pub fn is_tuplet(&self) -> bool {
     self
@@ -733,6 +799,23 @@ mod test {
     assert_eq!(phrase.get_duration(&tempo, None), 1.0);
   }
 
+  #[test]
+  fn test_zero_beat_tuplet() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut phrase = Phrase::new();
+    for _ in 0..3 {
+      phrase.add_note(Pitch::new(PitchName::C, 4), quarter, None);
+    }
+    phrase.add_modification(PhraseModificationType::Tuplet {
+      num_beats: 0,
+      into_beats: 2,
+    });
+    assert_eq!(phrase.get_beats(&quarter, None), Rational::from(3_u8));
+    assert!(phrase
+      .iter_timeslices()
+      .all(|timeslice| timeslice.get_beats(&quarter) == Rational::ONE));
+  }
+
   #[test]
   fn test_flatten_light() {
     let tempo = Tempo::new(Duration::new(DurationType::Quarter, 0), 120);
@@ -1,13 +1,15 @@
 use super::{
   chord::Chord,
   multivoice::MultiVoice,
+  notated_key,
   phrase::Phrase,
   staff::{Staff, StaffTimesliceIter},
+  transposed_key, Transpose,
 };
-use crate::context::{generate_id, Tempo};
+use crate::context::{generate_id, Key, Tempo, TimeSignature};
 use crate::modification::{SectionModification, SectionModificationType};
-use crate::note::{Duration, DurationType, Note, Pitch};
-use crate::temporal::Timeslice;
+use crate::note::{Accidental, Duration, DurationType, Interval, Note, Pitch, Rational, TranspositionMode};
+use crate::temporal::{make_accidentals_explicit, omit_implied_accidentals, TempoCurveOptions, TempoMap, Timeslice};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -62,10 +64,12 @@ impl Section {
     }
     if self.modifications.is_empty()
       && self.content.len() == 1
-      && self
-        .content
-        .iter()
-        .all(|item| matches!(item, SectionContent::Section(_)))
+      && self.content.iter().all(|item| match item {
+        SectionContent::Section(section) => !section
+          .iter_modifications()
+          .any(|modification| modification.r#type.is_navigation()),
+        SectionContent::Staff(_) => false,
+      })
     {
       if let Some(SectionContent::Section(section)) = self.content.pop() {
         self.id = section.id;
@@ -80,7 +84,7 @@ impl Section {
   pub(crate) fn clone_with_single_staff(&self, retained_staff: &str) -> Self {
     // Create an implicit section for all naked staff groupings
     let mut sections = Vec::new();
-    let mut implicit_section: Option<&mut (Section, f64, bool)> = None;
+    let mut implicit_section: Option<&mut (Section, Rational, bool)> = None;
     let beat_base_note = Duration::new(DurationType::Whole, 0);
     for item in &self.content {
       match item {
@@ -99,7 +103,7 @@ impl Section {
           }
         }
         SectionContent::Section(section) => {
-          sections.push((section.clone_with_single_staff(retained_staff), 0.0, false));
+          sections.push((section.clone_with_single_staff(retained_staff), Rational::ZERO, false));
           implicit_section = None;
         }
       }
@@ -147,6 +151,74 @@ impl Section {
     }
   }
 
+  #[must_use]
+  pub fn unroll(&self, original_note_ids: &mut BTreeMap<usize, usize>) -> Self {
+    self.unroll_with_repeats(original_note_ids, true)
+  }
+
+  pub(crate) fn unroll_with_repeats(&self, original_note_ids: &mut BTreeMap<usize, usize>, repeats: bool) -> Self {
+    // Materializes all repeats, endings, and navigation jumps in playback order, with each
+    // repeated iteration placed in its own section so that its staves are not played simultaneously
+    // Note: Without repeats, only the final iteration is played
+    let navigation_order = self.navigation_order();
+    let num_iterations = self.get_total_iterations();
+    let first_iteration = if repeats { 0 } else { num_iterations.saturating_sub(1) };
+    let mut iterations: Vec<Vec<SectionContent>> = (first_iteration..num_iterations)
+      .map(|iteration| {
+        navigation_order
+          .iter()
+          .filter_map(|&(idx, content_repeats)| match &self.content[idx] {
+            SectionContent::Staff(staff) => {
+              let mut staff = staff.clone();
+              staff.renumber_notes(original_note_ids);
+              Some(SectionContent::Staff(staff))
+            }
+            SectionContent::Section(section) => {
+              let playable_iterations = section.get_playable_iterations();
+              if playable_iterations.is_empty() || playable_iterations.contains(&iteration) {
+                Some(SectionContent::Section(
+                  section.unroll_with_repeats(original_note_ids, repeats && content_repeats),
+                ))
+              } else {
+                None
+              }
+            }
+          })
+          .collect()
+      })
+      .collect();
+    Self {
+      id: generate_id(),
+      name: self.name.clone(),
+      content: if iterations.len() == 1 {
+        iterations.pop().unwrap_or_default()
+      } else {
+        iterations
+          .into_iter()
+          .map(|content| {
+            SectionContent::Section(Self {
+              id: generate_id(),
+              name: self.name.clone(),
+              content,
+              modifications: BTreeSet::new(),
+            })
+          })
+          .collect()
+      },
+      modifications: self
+        .iter_modifications()
+        .filter(|modification| {
+          !modification.r#type.is_navigation()
+            && !matches!(
+              modification.r#type,
+              SectionModificationType::OnlyPlay { .. } | SectionModificationType::Repeat { .. }
+            )
+        })
+        .cloned()
+        .collect(),
+    }
+  }
+
   #[must_use]
   pub const fn get_id(&self) -> usize {
     self.id
@@ -417,6 +489,62 @@ impl Section {
       .unwrap_or_default()
   }
 
+  #[must_use]
+  pub fn has_modification_type(&self, mod_type: &SectionModificationType) -> bool {
+    self.iter_modifications().any(|item| item.r#type == *mod_type)
+  }
+
+  pub(crate) fn follow_navigation(sections: &[Option<&Self>]) -> Vec<(usize, bool)> {
+    // Follow any navigation markings, taking at most one da capo or dal segno jump
+    // Note: Repeats are only taken after the jump if the jumping section asks for them
+    let has_navigation = |idx: usize, navigation: &SectionModificationType| {
+      sections[idx].is_some_and(|section| section.has_modification_type(navigation))
+    };
+    let find_navigation = |mut indices: core::ops::Range<usize>, navigation: &SectionModificationType| {
+      indices.find(|&idx| has_navigation(idx, navigation))
+    };
+    let (mut order, mut idx, mut jumped, mut repeats) = (Vec::new(), 0, false, true);
+    while idx < sections.len() {
+      order.push((idx, repeats));
+      if jumped && has_navigation(idx, &SectionModificationType::Fine) {
+        break;
+      } else if jumped && has_navigation(idx, &SectionModificationType::ToCoda) {
+        match find_navigation(idx + 1..sections.len(), &SectionModificationType::Coda) {
+          Some(coda_idx) => idx = coda_idx,
+          None => break,
+        }
+      } else if !jumped && has_navigation(idx, &SectionModificationType::DaCapo) {
+        repeats = has_navigation(idx, &SectionModificationType::JumpWithRepeats);
+        (idx, jumped) = (0, true);
+      } else if !jumped && has_navigation(idx, &SectionModificationType::DalSegno) {
+        repeats = has_navigation(idx, &SectionModificationType::JumpWithRepeats);
+        idx = find_navigation(0..sections.len(), &SectionModificationType::Segno).unwrap_or_default();
+        jumped = true;
+      } else {
+        idx += 1;
+      }
+    }
+    order
+  }
+
+  fn navigation_order(&self) -> Vec<(usize, bool)> {
+    Self::follow_navigation(
+      &self
+        .content
+        .iter()
+        .map(|item| match item {
+          SectionContent::Section(section) => Some(section),
+          SectionContent::Staff(_) => None,
+        })
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  #[must_use]
+  pub fn performance_order(&self) -> Vec<usize> {
+    self.navigation_order().into_iter().map(|(idx, _)| idx).collect()
+  }
+
   #[must_use]
   pub fn get_section_tempo(&self) -> Option<Tempo> {
     self.iter_modifications().find_map(|item| match item.r#type {
@@ -429,17 +557,25 @@ impl Section {
   }
 
   #[must_use]
-  #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
-  pub fn get_beats(&self, beat_base: &Duration) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration) -> Rational {
+    self.get_beats_with_repeats(*beat_base, true)
+  }
+
+  pub(crate) fn get_beats_with_repeats(&self, beat_base: Duration, repeats: bool) -> Rational {
     let section_beat_base = if let Some(tempo) = self.get_section_tempo() {
       tempo.base_note
     } else {
-      *beat_base
+      beat_base
     };
-    let total_iterations = f64::from(self.get_total_iterations());
-    let (mut beats, mut staff_found) = (0.0, false);
-    for item in &self.content {
-      match item {
+    let last_iteration = self.get_total_iterations().saturating_sub(1);
+    let total_iterations = if repeats {
+      Rational::from(self.get_total_iterations())
+    } else {
+      Rational::ONE
+    };
+    let (mut beats, mut staff_found) = (Rational::ZERO, false);
+    for (idx, content_repeats) in self.navigation_order() {
+      match &self.content[idx] {
         SectionContent::Staff(staff) => {
           // Staves should all have the same duration, so just return the first one
           if !staff_found {
@@ -448,11 +584,14 @@ impl Section {
           }
         }
         SectionContent::Section(section) => {
-          let num_iterations = match section.get_playable_iterations().len() {
+          // Note: Without repeats, only the final iteration is played
+          let playable_iterations = section.get_playable_iterations();
+          let num_iterations = match playable_iterations.len() {
             0 => total_iterations,
-            count => count as f64,
+            _ if !repeats => Rational::from(u8::from(playable_iterations.contains(&last_iteration))),
+            count => Rational::from(u32::try_from(count).unwrap_or(u32::MAX)),
           };
-          beats += section.get_beats(&section_beat_base) * num_iterations;
+          beats += section.get_beats_with_repeats(section_beat_base, repeats && content_repeats) * num_iterations;
           staff_found = false;
         }
       }
@@ -461,14 +600,13 @@ impl Section {
   }
 
   #[must_use]
-  #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+  pub fn get_tempo_map(&self, tempo: &Tempo, options: &TempoCurveOptions) -> TempoMap {
+    TempoMap::from_timeslices(self.iter_timeslices(), tempo, options)
+  }
+
+  #[must_use]
   pub fn get_duration(&self, tempo: &Tempo) -> f64 {
-    let section_bpm = f64::from(if let Some(section_tempo) = self.get_section_tempo() {
-      section_tempo.beats_per_minute
-    } else {
-      tempo.beats_per_minute
-    });
-    self.get_beats(&tempo.base_note) * 60.0 / section_bpm
+    self.get_tempo_map(tempo, &TempoCurveOptions::default()).get_duration()
   }
 
   pub fn remove_item(&mut self, id: usize) -> &mut Self {
@@ -500,6 +638,47 @@ impl Section {
     self
   }
 
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|item| match item {
+      SectionContent::Staff(staff) => staff.apply_spellings(spellings),
+      SectionContent::Section(section) => section.apply_spellings(spellings),
+    });
+  }
+
+  pub fn transpose(
+    &mut self,
+    interval: Interval,
+    mode: TranspositionMode,
+    key: Option<Key>,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    let key = key.unwrap_or_default();
+    self.transpose_and_record_key(
+      interval,
+      mode,
+      key,
+      transposed_key(key, interval, mode),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
+  pub fn transpose_to_key(
+    &mut self,
+    current_key: Key,
+    target_key: Key,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    self.transpose_and_record_key(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      current_key,
+      notated_key(target_key),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
   #[must_use]
   pub fn is_empty(&self) -> bool {
     self.content.is_empty()
@@ -529,12 +708,20 @@ impl Section {
 
   #[must_use]
   pub fn iter_timeslices(&self) -> SectionTimesliceIter<'_> {
+    self.iter_timeslices_with_repeats(true)
+  }
+
+  pub(crate) fn iter_timeslices_with_repeats(&self, repeats: bool) -> SectionTimesliceIter<'_> {
+    // Note: Without repeats, only the final iteration is played
+    let num_iterations = self.get_total_iterations();
     SectionTimesliceIter {
-      iteration: 0,
-      num_iterations: self.get_total_iterations(),
+      iteration: if repeats { 0 } else { num_iterations.saturating_sub(1) },
+      num_iterations,
+      repeats,
       base_duration: Duration::new(DurationType::TwoThousandFortyEighth, 0),
       content: &self.content,
-      content_iterator: self.iter(),
+      content_order: self.navigation_order(),
+      content_index: 0,
       section_iterator: None,
       staff_iterators: Vec::new(),
       modifications: &self.modifications,
@@ -548,6 +735,67 @@ impl Section {
   }
 }
 
+impl Transpose for Section {
+  fn transpose_from_key(&mut self, interval: Interval, mode: TranspositionMode, mut key: Key) -> Key {
+    // Staves are played simultaneously, so they all start from the same key
+    let mut end_key = key;
+    self.iter_mut().for_each(|item| match item {
+      SectionContent::Staff(staff) => end_key = staff.transpose_from_key(interval, mode, key),
+      SectionContent::Section(section) => {
+        key = section.transpose_from_key(interval, mode, end_key);
+        end_key = key;
+      }
+    });
+    end_key
+  }
+
+  fn set_starting_key(&mut self, key: Key) {
+    for (index, item) in self.iter_mut().enumerate() {
+      match item {
+        SectionContent::Staff(staff) => staff.set_starting_key(key),
+        SectionContent::Section(section) => {
+          if index == 0 {
+            section.set_starting_key(key);
+          }
+          break;
+        }
+      }
+    }
+  }
+
+  fn make_accidentals_explicit(&mut self, key: Key, time_signature: TimeSignature) -> BTreeSet<usize> {
+    // Note: Accidentals only carry over within a single staff
+    let mut implicit_note_ids = BTreeSet::new();
+    for staff_name in self.get_staff_names(true) {
+      let staff_section = self.clone_with_single_staff(&staff_name);
+      let (accidentals, note_ids) = make_accidentals_explicit(staff_section.iter_timeslices(), key, time_signature);
+      self.apply_spellings(&accidentals);
+      implicit_note_ids.extend(note_ids);
+    }
+    implicit_note_ids
+  }
+
+  fn omit_implied_accidentals(
+    &mut self,
+    written_key: Key,
+    key: Key,
+    time_signature: TimeSignature,
+    implicit_note_ids: &BTreeSet<usize>,
+  ) {
+    for staff_name in self.get_staff_names(true) {
+      let staff_section = self.clone_with_single_staff(&staff_name);
+      let spellings = omit_implied_accidentals(
+        staff_section.iter_timeslices(),
+        written_key,
+        key,
+        time_signature,
+        implicit_note_ids,
+      );
+      self.apply_spellings(&spellings);
+    }
+  }
+}
+
 impl IntoIterator for Section {
   type Item = SectionContent;
   type IntoIter = alloc::vec::IntoIter<Self::Item>;
@@ -592,11 +840,13 @@ impl PartialEq for Section {
 pub struct SectionTimesliceIter<'a> {
   iteration: u8,
   num_iterations: u8,
+  repeats: bool,
   base_duration: Duration,
   content: &'a [SectionContent],
-  content_iterator: core::slice::Iter<'a, SectionContent>,
+  content_order: Vec<(usize, bool)>,
+  content_index: usize,
   section_iterator: Option<Box<SectionTimesliceIter<'a>>>,
-  staff_iterators: Vec<(f64, StaffTimesliceIter<'a>)>,
+  staff_iterators: Vec<(Rational, StaffTimesliceIter<'a>)>,
   modifications: &'a BTreeSet<SectionModification>,
   processing_staves: bool,
 }
@@ -606,14 +856,14 @@ impl Iterator for SectionTimesliceIter<'_> {
   fn next(&mut self) -> Option<Self::Item> {
     while self.iteration < self.num_iterations || self.processing_staves {
       if self.processing_staves {
-        let mut next_start_time = f64::MAX;
+        let mut next_start_time: Option<Rational> = None;
         let mut timeslice: Option<Timeslice> = None;
         self.staff_iterators.iter_mut().for_each(|(next_time, iterator)| {
-          if next_time.abs() <= 0.000_001 {
+          if next_time.is_zero() {
             if let Some(mut slice) = iterator.next() {
               *next_time = slice.get_beats(&self.base_duration);
-              if *next_time < next_start_time {
-                next_start_time = *next_time;
+              if next_start_time.is_none_or(|start_time| *next_time < start_time) {
+                next_start_time = Some(*next_time);
               }
               if let Some(timeslice) = timeslice.as_mut() {
                 timeslice.combine_with(&mut slice);
@@ -624,13 +874,14 @@ impl Iterator for SectionTimesliceIter<'_> {
                 timeslice = Some(slice);
               }
             }
-          } else if *next_time >= 0.0 && *next_time < next_start_time {
-            next_start_time = *next_time;
+          } else if !next_time.is_negative() && next_start_time.is_none_or(|start_time| *next_time < start_time) {
+            next_start_time = Some(*next_time);
           }
         });
         if timeslice.is_some() {
+          // Iterators with a negative time remaining have no more timeslices to contribute
           self.staff_iterators.iter_mut().for_each(|(next_time, _)| {
-            *next_time -= next_start_time;
+            *next_time = next_start_time.map_or(-Rational::ONE, |start_time| *next_time - start_time);
           });
           return timeslice;
         }
@@ -648,20 +899,21 @@ impl Iterator for SectionTimesliceIter<'_> {
           None => self.section_iterator = None,
         }
       }
-      if let Some(item) = self.content_iterator.next() {
-        match item {
-          SectionContent::Staff(staff) => self.staff_iterators.push((0.0, staff.iter_timeslices())),
+      if let Some(&(idx, repeats)) = self.content_order.get(self.content_index) {
+        self.content_index += 1;
+        match &self.content[idx] {
+          SectionContent::Staff(staff) => self.staff_iterators.push((Rational::ZERO, staff.iter_timeslices())),
           SectionContent::Section(section) => {
             self.processing_staves = !self.staff_iterators.is_empty();
             if section.get_playable_iterations().is_empty()
               || section.get_playable_iterations().contains(&self.iteration)
             {
-              self.section_iterator = Some(Box::new(section.iter_timeslices()));
+              self.section_iterator = Some(Box::new(section.iter_timeslices_with_repeats(self.repeats && repeats)));
             }
           }
         }
       } else {
-        self.content_iterator = self.content.iter();
+        self.content_index = 0;
         self.processing_staves = !self.staff_iterators.is_empty();
         self.iteration += 1;
       }
@@ -699,3 +951,322 @@ impl core::fmt::Display for Section {
     )
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::{KeyMode, KeySignature, TimeSignature};
+  use crate::modification::DirectionType;
+  use crate::note::{IntervalQuality, PitchName};
+  use crate::structure::{Part, PartContent};
+  use crate::temporal::AccidentalResolver;
+
+  fn add_section(parent: &mut Section, pitch: PitchName, navigation: &[SectionModificationType]) {
+    let section = parent.add_section("Section");
+    for modification in navigation {
+      section.add_modification(modification.clone());
+    }
+    section
+      .add_staff("Staff")
+      .add_note(Pitch::new(pitch, 4), Duration::new(DurationType::Quarter, 0), None);
+  }
+
+  #[test]
+  fn test_navigation_order() {
+    let tempo = Tempo::new(Duration::new(DurationType::Quarter, 0), 60);
+    let mut section = Section::new("Dal Segno al Coda");
+    add_section(&mut section, PitchName::C, &[]);
+    add_section(&mut section, PitchName::D, &[SectionModificationType::Segno]);
+    add_section(&mut section, PitchName::E, &[SectionModificationType::ToCoda]);
+    add_section(&mut section, PitchName::F, &[SectionModificationType::DalSegno]);
+    add_section(&mut section, PitchName::G, &[SectionModificationType::Coda]);
+    assert_eq!(section.performance_order(), vec![0, 1, 2, 3, 1, 2, 4]);
+    let pitches: Vec<_> = section
+      .iter_timeslices()
+      .map(|timeslice| timeslice.content[0].note.pitch.name)
+      .collect();
+    assert_eq!(
+      pitches,
+      vec![
+        PitchName::C,
+        PitchName::D,
+        PitchName::E,
+        PitchName::F,
+        PitchName::D,
+        PitchName::E,
+        PitchName::G
+      ]
+    );
+    assert_eq!(section.get_duration(&tempo), 7.0);
+
+    let mut section = Section::new("Da Capo al Fine");
+    add_section(&mut section, PitchName::C, &[SectionModificationType::Fine]);
+    add_section(&mut section, PitchName::D, &[SectionModificationType::DaCapo]);
+    assert_eq!(section.performance_order(), vec![0, 1, 0]);
+    assert_eq!(section.get_duration(&tempo), 3.0);
+  }
+
+  #[test]
+  fn test_unroll() {
+    let tempo = Tempo::new(Duration::new(DurationType::Quarter, 0), 60);
+    let mut section = Section::new("Repeat with Endings");
+    section.add_modification(SectionModificationType::Repeat { num_times: 1 });
+    add_section(&mut section, PitchName::C, &[]);
+    add_section(
+      &mut section,
+      PitchName::D,
+      &[SectionModificationType::OnlyPlay { iterations: vec![0] }],
+    );
+    add_section(
+      &mut section,
+      PitchName::E,
+      &[SectionModificationType::OnlyPlay { iterations: vec![1] }],
+    );
+    let mut original_note_ids = BTreeMap::new();
+    let unrolled = section.unroll(&mut original_note_ids);
+    let original_pitches: Vec<_> = section
+      .iter_timeslices()
+      .map(|timeslice| timeslice.content[0].note.pitch.name)
+      .collect();
+    let unrolled_notes: Vec<_> = unrolled
+      .iter_timeslices()
+      .map(|timeslice| timeslice.content[0].note.clone())
+      .collect();
+    assert_eq!(
+      unrolled_notes.iter().map(|note| note.pitch.name).collect::<Vec<_>>(),
+      original_pitches
+    );
+    assert_eq!(unrolled.get_duration(&tempo), section.get_duration(&tempo));
+    assert_eq!(unrolled.get_total_iterations(), 1);
+    assert_eq!(original_note_ids.len(), 4);
+    assert_eq!(
+      original_note_ids[&unrolled_notes[0].note_id],
+      original_note_ids[&unrolled_notes[2].note_id]
+    );
+    assert_ne!(unrolled_notes[0].note_id, unrolled_notes[2].note_id);
+  }
+
+  #[test]
+  fn test_unroll_matches_playback() {
+    let playback = |section: &Section, original_note_ids: Option<&BTreeMap<usize, usize>>| -> Vec<Vec<_>> {
+      section
+        .iter_timeslices()
+        .map(|timeslice| {
+          timeslice
+            .content
+            .iter()
+            .map(|content| {
+              let note_id = original_note_ids.map_or(content.note.note_id, |ids| ids[&content.note.note_id]);
+              (note_id, content.note.pitch, content.get_beats(&Duration::default()))
+            })
+            .collect()
+        })
+        .collect()
+    };
+    let repeated_with_endings = |parent: &mut Section, navigation: &[SectionModificationType]| {
+      let section = parent.add_section("Repeated");
+      section.add_modification(SectionModificationType::Repeat { num_times: 1 });
+      for modification in navigation {
+        section.add_modification(modification.clone());
+      }
+      add_section(section, PitchName::C, &[]);
+      add_section(
+        section,
+        PitchName::D,
+        &[SectionModificationType::OnlyPlay { iterations: vec![0] }],
+      );
+      add_section(
+        section,
+        PitchName::E,
+        &[SectionModificationType::OnlyPlay { iterations: vec![1] }],
+      );
+    };
+
+    let mut sections = Vec::new();
+    let mut section = Section::new("Repeat");
+    repeated_with_endings(&mut section, &[]);
+    sections.push(section);
+    let mut section = Section::new("Da Capo al Fine");
+    add_section(&mut section, PitchName::C, &[SectionModificationType::Fine]);
+    add_section(&mut section, PitchName::D, &[SectionModificationType::DaCapo]);
+    sections.push(section);
+    let mut section = Section::new("Dal Segno al Coda");
+    add_section(&mut section, PitchName::C, &[]);
+    add_section(&mut section, PitchName::D, &[SectionModificationType::Segno]);
+    add_section(&mut section, PitchName::E, &[SectionModificationType::ToCoda]);
+    add_section(&mut section, PitchName::F, &[SectionModificationType::DalSegno]);
+    add_section(&mut section, PitchName::G, &[SectionModificationType::Coda]);
+    sections.push(section);
+    for jump in [
+      vec![SectionModificationType::DaCapo],
+      vec![
+        SectionModificationType::DaCapo,
+        SectionModificationType::JumpWithRepeats,
+      ],
+    ] {
+      let mut section = Section::new("Da Capo al Fine over Repeat");
+      repeated_with_endings(&mut section, &[SectionModificationType::Fine]);
+      add_section(&mut section, PitchName::F, &jump);
+      sections.push(section);
+    }
+
+    for section in &sections {
+      let mut original_note_ids = BTreeMap::new();
+      let unrolled = section.unroll(&mut original_note_ids);
+      assert_eq!(
+        playback(&unrolled, Some(&original_note_ids)),
+        playback(section, None),
+        "{}",
+        section.get_name()
+      );
+    }
+    assert_eq!(playback(&sections[3], None).len(), 7);
+    assert_eq!(playback(&sections[4], None).len(), 9);
+  }
+
+  #[test]
+  fn test_da_capo_al_fine_over_repeat() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut part = Part::new("Part");
+    let section = part.add_section("Repeated");
+    section.add_modification(SectionModificationType::Repeat { num_times: 1 });
+    section.add_modification(SectionModificationType::Fine);
+    add_section(section, PitchName::C, &[]);
+    add_section(
+      section,
+      PitchName::D,
+      &[SectionModificationType::OnlyPlay { iterations: vec![0] }],
+    );
+    add_section(
+      section,
+      PitchName::E,
+      &[SectionModificationType::OnlyPlay { iterations: vec![1] }],
+    );
+    let section = part.add_section("Da Capo");
+    section.add_modification(SectionModificationType::DaCapo);
+    section
+      .add_staff("Staff")
+      .add_note(Pitch::new(PitchName::F, 4), quarter, None);
+    let pitches = |part: &Part| -> Vec<_> {
+      part
+        .iter_timeslices()
+        .map(|timeslice| timeslice.content[0].note.pitch.name)
+        .collect()
+    };
+    assert_eq!(part.performance_order(), vec![0, 1, 0]);
+    assert_eq!(
+      pitches(&part),
+      vec![
+        PitchName::C,
+        PitchName::D,
+        PitchName::C,
+        PitchName::E,
+        PitchName::F,
+        PitchName::C,
+        PitchName::E,
+      ]
+    );
+    assert_eq!(part.get_beats(&quarter), Rational::from(7_u8));
+
+    let PartContent::Section(section) = part.iter_mut().last().unwrap();
+    section.add_modification(SectionModificationType::JumpWithRepeats);
+    assert_eq!(pitches(&part).len(), 9);
+    assert_eq!(part.get_beats(&quarter), Rational::from(9_u8));
+  }
+
+  #[test]
+  fn test_transpose_carried_accidentals() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut section = Section::new("Section");
+    let treble = section.add_staff("Treble");
+    for accidental in [Some(Accidental::Sharp), None, Some(Accidental::Natural), None, None] {
+      treble.add_note(Pitch::new(PitchName::F, 4), quarter, accidental);
+    }
+    let bass = section.add_staff("Bass");
+    for _ in 0..5 {
+      bass.add_note(Pitch::new(PitchName::F, 4), quarter, None);
+    }
+    section.transpose(
+      Interval::new(IntervalQuality::Major, 2),
+      TranspositionMode::Chromatic,
+      Some(Key::new(KeySignature::C, KeyMode::Major)),
+      None,
+    );
+    let spellings: Vec<_> = section
+      .iter_timeslices()
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| (content.note.pitch.name, content.note.accidental))
+      .collect();
+    assert_eq!(
+      spellings,
+      [
+        (PitchName::G, Accidental::Sharp),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::Natural),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+        (PitchName::G, Accidental::None),
+      ]
+    );
+
+    let time_signature = TimeSignature::new_explicit(3, 4);
+    let sounding_midi_numbers = |section: &Section| -> Vec<_> {
+      AccidentalResolver::new(section.iter_timeslices(), Key::default(), time_signature)
+        .flat_map(|timeslice| timeslice.content)
+        .map(|content| {
+          content
+            .note
+            .midi_number_with_accidental(content.resolved_accidental.unwrap())
+        })
+        .collect()
+    };
+    let mut section = Section::new("Section");
+    let staff = section.add_staff("Treble");
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, Some(Accidental::Sharp));
+    for name in [PitchName::G, PitchName::A, PitchName::F, PitchName::F] {
+      staff.add_note(Pitch::new(name, 4), quarter, None);
+    }
+    assert_eq!(sounding_midi_numbers(&section), [66, 67, 69, 65, 65]);
+    section.transpose(
+      Interval::new(IntervalQuality::Major, 2),
+      TranspositionMode::Chromatic,
+      None,
+      Some(time_signature),
+    );
+    assert_eq!(sounding_midi_numbers(&section), [68, 69, 71, 67, 67]);
+  }
+
+  #[test]
+  fn test_transpose_to_key_with_mode() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let a_minor = Key::new(KeySignature::A, KeyMode::Minor);
+    let mut section = Section::new("Section");
+    let staff = section.add_staff("Staff");
+    for name in [PitchName::E, PitchName::G, PitchName::E] {
+      staff.add_note(Pitch::new(name, 4), quarter, None);
+    }
+    section.transpose_to_key(Key::default(), a_minor, None);
+    let timeslices: Vec<_> = section.iter_timeslices().collect();
+    assert!(timeslices[0]
+      .directions
+      .iter()
+      .any(|direction| direction.r#type == DirectionType::KeyChange { key: a_minor }));
+    let spellings: Vec<_> = timeslices
+      .into_iter()
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| (content.note.pitch, content.note.accidental))
+      .collect();
+    assert_eq!(
+      spellings,
+      [
+        (Pitch::new(PitchName::C, 4), Accidental::Sharp),
+        (Pitch::new(PitchName::E, 4), Accidental::None),
+        (Pitch::new(PitchName::C, 4), Accidental::None),
+      ]
+    );
+  }
+}
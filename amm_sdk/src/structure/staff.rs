@@ -1,12 +1,15 @@
 use super::{
   chord::Chord,
   multivoice::{MultiVoice, MultiVoiceTimesliceIter},
+  notated_key,
   phrase::{Phrase, PhraseContent, PhraseTimesliceIter},
+  transposed_key, Transpose,
 };
-use crate::context::{generate_id, Tempo};
+use crate::context::{generate_id, Key, Tempo, TimeSignature};
 use crate::modification::{Direction, DirectionType};
-use crate::note::{Accidental, Duration, Note, Pitch};
-use crate::temporal::Timeslice;
+use crate::note::{choose_spellings, Accidental, Duration, Interval, Note, Pitch, Rational, TranspositionMode};
+use crate::temporal::{make_accidentals_explicit, omit_implied_accidentals, Timeslice};
+use alloc::collections::{BTreeMap, BTreeSet};
 use amm_internal::amm_prelude::*;
 use amm_macros::{JsonDeserialize, JsonSerialize};
 
@@ -358,7 +361,7 @@ impl Staff {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration) -> Rational {
     self
       .iter()
       .map(|content| match &content {
@@ -366,14 +369,14 @@ impl Staff {
         StaffContent::Chord(chord) => chord.get_beats(beat_base, None),
         StaffContent::Phrase(phrase) => phrase.get_beats(beat_base, None),
         StaffContent::MultiVoice(multivoice) => multivoice.get_beats(beat_base, None),
-        StaffContent::Direction(_) => 0.0,
+        StaffContent::Direction(_) => Rational::ZERO,
       })
       .sum()
   }
 
   #[must_use]
   pub fn get_duration(&self, tempo: &Tempo) -> f64 {
-    self.get_beats(&tempo.base_note) * 60.0 / f64::from(tempo.beats_per_minute)
+    self.get_beats(&tempo.base_note).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
   }
 
   pub fn remove_item(&mut self, id: usize) -> &mut Self {
@@ -418,6 +421,78 @@ impl Staff {
     self
   }
 
+  pub fn transpose(
+    &mut self,
+    interval: Interval,
+    mode: TranspositionMode,
+    key: Option<Key>,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    let key = key.unwrap_or_default();
+    self.transpose_and_record_key(
+      interval,
+      mode,
+      key,
+      transposed_key(key, interval, mode),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
+  pub(crate) fn apply_spellings(&mut self, spellings: &BTreeMap<usize, (Pitch, Accidental)>) {
+    self.iter_mut().for_each(|item| match item {
+      StaffContent::Note(note) => {
+        if let Some((pitch, accidental)) = spellings.get(&note.note_id) {
+          note.pitch = *pitch;
+          note.accidental = *accidental;
+        }
+      }
+      StaffContent::Chord(chord) => chord.apply_spellings(spellings),
+      StaffContent::Phrase(phrase) => phrase.apply_spellings(spellings),
+      StaffContent::MultiVoice(multivoice) => multivoice.apply_spellings(spellings),
+      StaffContent::Direction(_) => (),
+    });
+  }
+
+  pub(crate) fn renumber_notes(&mut self, original_note_ids: &mut BTreeMap<usize, usize>) {
+    self.iter_mut().for_each(|item| match item {
+      StaffContent::Note(note) => {
+        original_note_ids.insert(note.get_id(), note.note_id);
+        note.note_id = note.get_id();
+      }
+      StaffContent::Chord(chord) => chord.renumber_notes(original_note_ids),
+      StaffContent::Phrase(phrase) => phrase.renumber_notes(original_note_ids),
+      StaffContent::MultiVoice(multivoice) => multivoice.renumber_notes(original_note_ids),
+      StaffContent::Direction(_) => (),
+    });
+  }
+
+  pub fn respell(&mut self, key: Option<Key>, time_signature: Option<TimeSignature>) -> &mut Self {
+    let spellings = choose_spellings(
+      self.iter_timeslices(),
+      key.unwrap_or_default(),
+      time_signature.unwrap_or_default(),
+    );
+    self.apply_spellings(&spellings);
+    self
+  }
+
+  pub fn transpose_to_key(
+    &mut self,
+    current_key: Key,
+    target_key: Key,
+    time_signature: Option<TimeSignature>,
+  ) -> &mut Self {
+    self.transpose_and_record_key(
+      current_key.interval_to(&target_key),
+      TranspositionMode::Chromatic,
+      current_key,
+      notated_key(target_key),
+      time_signature.unwrap_or_default(),
+    );
+    self
+  }
+
   #[must_use]
   pub fn num_timeslices(&self) -> usize {
     self
@@ -453,6 +528,72 @@ impl Staff {
   }
 }
 
+impl Transpose for Staff {
+  fn transpose_from_key(&mut self, interval: Interval, mode: TranspositionMode, mut key: Key) -> Key {
+    self.iter_mut().for_each(|item| match item {
+      StaffContent::Note(note) => {
+        note.transpose(interval, mode, Some(key));
+      }
+      StaffContent::Chord(chord) => {
+        chord.transpose(interval, mode, Some(key));
+      }
+      StaffContent::Phrase(phrase) => {
+        phrase.transpose(interval, mode, Some(key));
+      }
+      StaffContent::MultiVoice(multivoice) => {
+        multivoice.transpose(interval, mode, Some(key));
+      }
+      StaffContent::Direction(direction) => {
+        if let DirectionType::KeyChange { key: new_key } = &mut direction.r#type {
+          key = *new_key;
+          if mode == TranspositionMode::Chromatic {
+            new_key.transpose(interval);
+          }
+        }
+      }
+    });
+    key
+  }
+
+  fn set_starting_key(&mut self, key: Key) {
+    if !matches!(
+      self.content.first(),
+      Some(StaffContent::Direction(Direction {
+        r#type: DirectionType::KeyChange { .. },
+        ..
+      }))
+    ) {
+      self.content.insert(
+        0,
+        StaffContent::Direction(Direction::new(DirectionType::KeyChange { key })),
+      );
+    }
+  }
+
+  fn make_accidentals_explicit(&mut self, key: Key, time_signature: TimeSignature) -> BTreeSet<usize> {
+    let (accidentals, implicit_note_ids) = make_accidentals_explicit(self.iter_timeslices(), key, time_signature);
+    self.apply_spellings(&accidentals);
+    implicit_note_ids
+  }
+
+  fn omit_implied_accidentals(
+    &mut self,
+    written_key: Key,
+    key: Key,
+    time_signature: TimeSignature,
+    implicit_note_ids: &BTreeSet<usize>,
+  ) {
+    let spellings = omit_implied_accidentals(
+      self.iter_timeslices(),
+      written_key,
+      key,
+      time_signature,
+      implicit_note_ids,
+    );
+    self.apply_spellings(&spellings);
+  }
+}
+
 impl IntoIterator for Staff {
   type Item = StaffContent;
   type IntoIter = alloc::vec::IntoIter<Self::Item>;
@@ -1,19 +1,32 @@
+mod performance;
+mod realization;
+mod resolver;
+mod tempo_map;
+mod timeline;
 mod timeslice;
 
+pub(crate) use performance::render_performance;
+pub use performance::{PerformanceEvent, PerformanceStyle};
+pub(crate) use realization::realize_part;
+pub use realization::{GlideMode, OrnamentStyle, PitchGlide, RealizationOptions, RealizedNote};
+pub use resolver::AccidentalResolver;
+pub(crate) use resolver::{make_accidentals_explicit, omit_implied_accidentals};
+pub use tempo_map::{TempoCurveOptions, TempoCurveShape, TempoMap, TempoSegment};
+pub use timeline::{Timeline, TimelineEntry};
 pub use timeslice::{PartTimeslice, Timeslice, TimesliceContent, TimesliceContext, TimeslicePhraseDetails};
 
 pub(crate) fn place_and_merge_part_timeslice(
   part_name: &str,
-  timeslices: &mut Vec<(f64, PartTimeslice)>,
+  timeslices: &mut Vec<(crate::note::Rational, PartTimeslice)>,
   slice: Timeslice,
   mut index: usize,
-  curr_time: f64,
-) -> (usize, f64) {
-  let beat_base_note = crate::note::Duration::new(crate::note::DurationType::TwoThousandFortyEighth, 0);
+  curr_time: crate::note::Rational,
+) -> (usize, crate::note::Rational) {
+  let beat_base_note = crate::note::Duration::new(crate::note::DurationType::Whole, 0);
   let slice_duration = slice.get_beats(&beat_base_note);
   if let Some(slice_details) = timeslices.get_mut(index) {
     let (mut slice_time, mut existing_slice) = (slice_details.0, &mut slice_details.1);
-    while curr_time > slice_time && curr_time - slice_time > 0.000_001 {
+    while curr_time > slice_time {
       index += 1;
       (slice_time, existing_slice) = if let Some((start_time, slice)) = timeslices.get_mut(index) {
         (*start_time, slice)
@@ -23,7 +36,7 @@ pub(crate) fn place_and_merge_part_timeslice(
         (*start_time, slice)
       };
     }
-    if (slice_time - curr_time).abs() < 0.000_001 {
+    if slice_time == curr_time {
       existing_slice.add_timeslice(part_name, slice);
     } else {
       timeslices.insert(index, (curr_time, PartTimeslice::from(part_name, slice)));
@@ -0,0 +1,582 @@
+use super::realization::{RealizationOptions, RealizedNote, Realizer};
+use super::{AccidentalResolver, TempoCurveOptions, Timeslice, TimesliceContent};
+use crate::context::{Dynamic, Key, TimeSignature};
+use crate::modification::{DirectionType, NoteModificationType, PhraseModificationType};
+use crate::note::{Duration, Rational};
+use crate::Composition;
+use alloc::{
+  collections::{BTreeMap, BTreeSet},
+  vec::Vec,
+};
+
+/// Options which control how notation is interpreted when rendering a performance.
+///
+/// Articulation lengths are fractions of the written duration of a note during which
+/// it actually sounds, while dynamic changes are expressed on the scale used by
+/// [`Dynamic::value`], from silent at `0.0` to the loudest possible level at `1.0`.
+///
+/// Accented, marcato, and sforzando notes may also be played slightly ahead of or behind
+/// their written time, which moves the onset of the note without moving its release.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PerformanceStyle {
+  /// The dynamic level at the start of every part, until the first dynamic marking.
+  pub initial_dynamic: Dynamic,
+  /// The change in dynamic level over a crescendo or decrescendo without a final dynamic,
+  /// or up to the middle of a hairpin without a maximum dynamic.
+  pub hairpin_change: f32,
+  /// The increase in dynamic level on an accented note.
+  pub accent_boost: f32,
+  /// The increase in dynamic level on a marcato note.
+  pub marcato_boost: f32,
+  /// The increase in dynamic level on a sforzando note.
+  pub sforzando_boost: f32,
+  /// The number of seconds by which an accented note starts ahead of its written time,
+  /// or behind it if negative.
+  pub accent_onset_seconds: f64,
+  /// The number of seconds by which a marcato note starts ahead of its written time,
+  /// or behind it if negative.
+  pub marcato_onset_seconds: f64,
+  /// The number of seconds by which a sforzando note starts ahead of its written time,
+  /// or behind it if negative.
+  pub sforzando_onset_seconds: f64,
+  /// The sounding length of a note without any articulation.
+  pub default_length: f64,
+  /// The sounding length of a note which is slurred to the following note.
+  pub legato_length: f64,
+  /// The sounding length of a tenuto note.
+  pub tenuto_length: f64,
+  /// The sounding length of a detached legato (portato) note.
+  pub detached_legato_length: f64,
+  /// The sounding length of a staccato note.
+  pub staccato_length: f64,
+  /// The sounding length of a staccatissimo note.
+  pub staccatissimo_length: f64,
+  /// The factor by which a note or rest with a fermata is lengthened, delaying every
+  /// following note in the composition.
+  pub fermata_stretch: f64,
+  /// The number of seconds by which a note is cut short before a breath mark or caesura.
+  pub breath_seconds: f64,
+  /// The number of seconds of silence inserted at a caesura, delaying every following
+  /// note in the composition.
+  pub caesura_seconds: f64,
+  /// The options used to realize gradual tempo changes.
+  pub tempo_curves: TempoCurveOptions,
+  /// The options used to realize ornaments, grace notes, and glides.
+  pub realization: RealizationOptions,
+}
+
+impl Default for PerformanceStyle {
+  fn default() -> Self {
+    Self {
+      initial_dynamic: Dynamic::MezzoForte,
+      hairpin_change: 0.15,
+      accent_boost: 0.1,
+      marcato_boost: 0.15,
+      sforzando_boost: 0.25,
+      accent_onset_seconds: 0.01,
+      marcato_onset_seconds: 0.015,
+      sforzando_onset_seconds: 0.02,
+      default_length: 0.9,
+      legato_length: 1.0,
+      tenuto_length: 1.0,
+      detached_legato_length: 0.75,
+      staccato_length: 0.5,
+      staccatissimo_length: 0.25,
+      fermata_stretch: 2.0,
+      breath_seconds: 0.15,
+      caesura_seconds: 0.5,
+      tempo_curves: TempoCurveOptions::default(),
+      realization: RealizationOptions::default(),
+    }
+  }
+}
+
+/// Represents a single sounding note in a rendered performance, ready to be sent to
+/// a MIDI device or synthesizer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PerformanceEvent {
+  /// The realized note being played, including its pitch and exact written timing.
+  pub note: RealizedNote,
+  /// The time in seconds at which the note starts sounding.
+  pub start_seconds: f64,
+  /// The time in seconds at which the note stops sounding.
+  pub end_seconds: f64,
+  /// The dynamic level of the note, from silent at `0.0` to the loudest possible level at `1.0`.
+  pub level: f32,
+  /// The MIDI velocity of the note.
+  pub velocity: u8,
+  glide_seconds: Option<(f64, f64)>,
+}
+
+impl PerformanceEvent {
+  /// Returns the number of seconds for which the note sounds.
+  #[must_use]
+  pub fn get_duration(&self) -> f64 {
+    self.end_seconds - self.start_seconds
+  }
+
+  /// Returns the pitch offset in cents caused by any glide on the note at the given time in seconds.
+  #[must_use]
+  pub fn cents_offset_at(&self, seconds: f64) -> f64 {
+    match (self.note.glide, self.glide_seconds) {
+      (Some(glide), Some((start_seconds, end_seconds))) => {
+        let progress = if end_seconds > start_seconds {
+          ((seconds - start_seconds) / (end_seconds - start_seconds)).clamp(0.0, 1.0)
+        } else if seconds < start_seconds {
+          0.0
+        } else {
+          1.0
+        };
+        f64::from(glide.start_cents) + progress * f64::from(glide.end_cents - glide.start_cents)
+      }
+      _ => 0.0,
+    }
+  }
+
+  /// Returns the pitch of the note in Hertz at the given time in seconds, optionally
+  /// using a custom frequency for A4.
+  #[must_use]
+  pub fn pitch_hz_at(&self, seconds: f64, a4_frequency_hz: Option<f32>) -> f32 {
+    self
+      .note
+      .pitch_hz_with_offset(self.cents_offset_at(seconds), a4_frequency_hz)
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value for the note at the given time in seconds,
+  /// given the pitch bend range of the receiver in semitones.
+  #[must_use]
+  pub fn midi_pitch_bend_at(&self, seconds: f64, bend_range_semitones: u8) -> u16 {
+    self
+      .note
+      .midi_pitch_bend_with_offset(self.cents_offset_at(seconds), bend_range_semitones)
+  }
+}
+
+/// A realized note along with the performance details gathered from its context.
+struct PendingEvent {
+  note: RealizedNote,
+  level: f32,
+  length: f64,
+  /// The number of seconds by which the note starts ahead of its written time.
+  anticipation: f64,
+  breath_after: bool,
+}
+
+/// The dynamic level and phrasing of a written note.
+#[derive(Clone, Copy)]
+struct NoteContext {
+  level: f32,
+  slurred: bool,
+}
+
+/// Points in a part at which every following note in the composition is delayed.
+#[derive(Default)]
+struct TimeMarks {
+  fermatas: Vec<(Rational, Rational)>,
+  caesuras: Vec<Rational>,
+}
+
+/// Returns every note in the given composition as a flat list of performance events
+/// ordered by starting time, interpreted according to the given style.
+pub(crate) fn render_performance(composition: &Composition, style: &PerformanceStyle) -> Vec<PerformanceEvent> {
+  let (tempo_map, mut marks) = (composition.get_tempo_map(&style.tempo_curves), TimeMarks::default());
+  let beat_base = tempo_map.base_note();
+  let (starting_key, starting_time_signature) = (
+    *composition.get_starting_key(),
+    *composition.get_starting_time_signature(),
+  );
+  let mut pending = Vec::new();
+  for part in composition {
+    let performer = PartPerformer::new(part.get_id(), beat_base, style);
+    pending.extend(if part.is_written_pitch() {
+      let mut sounding_part = part.clone();
+      sounding_part.transpose_to_sounding_pitch(part.written_key(starting_key), starting_time_signature);
+      performer.perform(
+        sounding_part.iter_timeslices(),
+        starting_key,
+        starting_time_signature,
+        &mut marks,
+      )
+    } else {
+      performer.perform(
+        part.iter_timeslices(),
+        starting_key,
+        starting_time_signature,
+        &mut marks,
+      )
+    });
+  }
+
+  // Merge overlapping fermatas from different parts so that they only delay the music once
+  marks.fermatas.sort();
+  let mut fermata_seconds: Vec<(f64, f64)> = Vec::new();
+  let mut last_end = Rational::ZERO;
+  for (start, end) in marks.fermatas {
+    let start = start.max(last_end);
+    if end > start {
+      fermata_seconds.push((tempo_map.time_at_beat(start), tempo_map.time_at_beat(end)));
+      last_end = end;
+    }
+  }
+  marks.caesuras.sort();
+  marks.caesuras.dedup();
+  let warp = |beat: Rational, starting: bool| {
+    let seconds = tempo_map.time_at_beat(beat);
+    let stretched: f64 = fermata_seconds
+      .iter()
+      .map(|(start, end)| (seconds.clamp(*start, *end) - start) * (style.fermata_stretch - 1.0))
+      .sum();
+    let caesuras = marks
+      .caesuras
+      .iter()
+      .filter(|caesura| beat > **caesura || (starting && beat == **caesura))
+      .count();
+    seconds + stretched + f64::from(u32::try_from(caesuras).unwrap_or(u32::MAX)) * style.caesura_seconds
+  };
+
+  let mut events: Vec<PerformanceEvent> = pending
+    .into_iter()
+    .map(|event| {
+      let written_seconds = warp(event.note.start_beat, true);
+      let mut sounding_seconds = (warp(event.note.end_beat, false) - written_seconds) * event.length;
+      if event.breath_after {
+        sounding_seconds -= style.breath_seconds.min(sounding_seconds / 2.0);
+      }
+      let end_seconds = written_seconds + sounding_seconds;
+      let start_seconds = (written_seconds - event.anticipation).clamp(0.0, end_seconds);
+      let glide_seconds = event
+        .note
+        .glide
+        .map(|glide| (warp(glide.start_beat, true), warp(glide.end_beat, false)));
+      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+      let velocity = (event.level * 127.0).round().clamp(1.0, 127.0) as u8;
+      PerformanceEvent {
+        note: event.note,
+        start_seconds,
+        end_seconds,
+        level: event.level,
+        velocity,
+        glide_seconds,
+      }
+    })
+    .collect();
+  events.sort_by(|a, b| {
+    a.start_seconds
+      .total_cmp(&b.start_seconds)
+      .then_with(|| a.note.part_id.cmp(&b.note.part_id))
+  });
+  events
+}
+
+struct PartPerformer<'a> {
+  part_id: usize,
+  beat_base: Duration,
+  style: &'a PerformanceStyle,
+  level: f32,
+  contexts: BTreeMap<usize, NoteContext>,
+  breaths: Vec<Rational>,
+}
+
+impl<'a> PartPerformer<'a> {
+  fn new(part_id: usize, beat_base: Duration, style: &'a PerformanceStyle) -> Self {
+    Self {
+      part_id,
+      beat_base,
+      style,
+      level: style.initial_dynamic.value(),
+      contexts: BTreeMap::new(),
+      breaths: Vec::new(),
+    }
+  }
+
+  fn perform(
+    mut self,
+    timeslices: impl Iterator<Item = Timeslice>,
+    starting_key: Key,
+    starting_time_signature: TimeSignature,
+    marks: &mut TimeMarks,
+  ) -> Vec<PendingEvent> {
+    let style = self.style;
+    let mut realizer = Realizer::new(self.part_id, self.beat_base, &style.realization);
+    let mut current_beat = Rational::ZERO;
+    let mut resolver = AccidentalResolver::new(timeslices, starting_key, starting_time_signature);
+    while let Some(timeslice) = resolver.next() {
+      let slice_beats = timeslice.get_beats(&self.beat_base);
+      for direction in &timeslice.directions {
+        match direction.r#type {
+          DirectionType::Dynamic { dynamic } => self.level = dynamic.value(),
+          DirectionType::BreathMark => self.breaths.push(current_beat),
+          DirectionType::Caesura => {
+            self.breaths.push(current_beat);
+            marks.caesuras.push(current_beat);
+          }
+          _ => (),
+        }
+      }
+      if slice_beats.is_positive()
+        && timeslice.content.iter().any(|content| {
+          content
+            .note
+            .iter_modifications()
+            .any(|modification| modification.r#type == NoteModificationType::Fermata)
+        })
+      {
+        marks.fermatas.push((current_beat, current_beat + slice_beats));
+      }
+      let next_level = timeslice
+        .content
+        .iter()
+        .map(|content| self.record_context(content))
+        .fold(None, Option::or);
+      realizer.realize_timeslice(timeslice, &resolver, current_beat);
+      self.level = next_level.unwrap_or(self.level);
+      current_beat += slice_beats;
+    }
+    // Events are only collected once realization has finished, since grace notes may still shorten earlier notes
+    let pending = self.collect_events(&realizer.finish(current_beat));
+    self.finish(pending)
+  }
+
+  /// Stores the dynamic level and phrasing of the given written note, returning the
+  /// dynamic level reached at the end of a crescendo or decrescendo which it completes,
+  /// or set by a dynamic marking attached to the note.
+  #[allow(clippy::cast_precision_loss)]
+  fn record_context(&mut self, content: &TimesliceContent) -> Option<f32> {
+    let (mut level, mut next_level, mut slurred) = (self.level, None, false);
+    for details in &content.phrase_details {
+      let is_last = details.index_in_phrase + 1 >= details.phrase_length;
+      let progress = if details.phrase_length > 1 {
+        details.index_in_phrase as f32 / (details.phrase_length - 1) as f32
+      } else {
+        1.0
+      };
+      for modification in &details.modifications {
+        match modification {
+          PhraseModificationType::Crescendo { final_dynamic }
+          | PhraseModificationType::Decrescendo { final_dynamic } => {
+            let change = if matches!(modification, PhraseModificationType::Crescendo { .. }) {
+              self.style.hairpin_change
+            } else {
+              -self.style.hairpin_change
+            };
+            let target = final_dynamic.map_or(self.level + change, |dynamic| dynamic.value());
+            level = self.level + (target - self.level) * progress;
+            if is_last {
+              next_level = Some(target);
+            }
+          }
+          PhraseModificationType::Hairpin { maximum_dynamic } => {
+            let target = maximum_dynamic.map_or(self.level + self.style.hairpin_change, |dynamic| dynamic.value());
+            level = self.level + (target - self.level) * (1.0 - (2.0 * progress - 1.0).abs());
+          }
+          PhraseModificationType::Legato => slurred |= !is_last,
+          _ => (),
+        }
+      }
+    }
+    if let Some(dynamic) = content
+      .note
+      .iter_modifications()
+      .find_map(|modification| match modification.r#type {
+        NoteModificationType::Dynamic { dynamic } => Some(dynamic),
+        _ => None,
+      })
+    {
+      // Note: A dynamic attached to a note lasts until the next dynamic, unlike a sforzando which is applied as a boost
+      level = dynamic.value();
+      next_level = Some(level);
+    }
+    self.contexts.insert(
+      content.note.note_id,
+      NoteContext {
+        level: level.clamp(0.0, 1.0),
+        slurred,
+      },
+    );
+    next_level
+  }
+
+  /// Applies the articulation and dynamics of the written notes to every realized note in the part.
+  fn collect_events(&self, notes: &[RealizedNote]) -> Vec<PendingEvent> {
+    let onsets: BTreeSet<_> = notes.iter().map(|note| (note.note.note_id, note.start_beat)).collect();
+    let mut pending = Vec::new();
+    for realized in notes {
+      let context = self
+        .contexts
+        .get(&realized.note.note_id)
+        .copied()
+        .unwrap_or(NoteContext {
+          level: self.level,
+          slurred: false,
+        });
+      // Only the final note realized from a written note is articulated, with any ornament notes played connected
+      let is_final =
+        realized.end_beat == realized.start_beat || !onsets.contains(&(realized.note.note_id, realized.end_beat));
+      let has = |mod_type: NoteModificationType| {
+        realized
+          .note
+          .iter_modifications()
+          .any(|modification| modification.r#type == mod_type)
+      };
+      let length = if !is_final {
+        1.0
+      } else if has(NoteModificationType::Staccatissimo) {
+        self.style.staccatissimo_length
+      } else if has(NoteModificationType::Staccato) {
+        self.style.staccato_length
+      } else if has(NoteModificationType::DetachedLegato) {
+        self.style.detached_legato_length
+      } else if has(NoteModificationType::Tenuto) {
+        self.style.tenuto_length
+      } else if context.slurred {
+        self.style.legato_length
+      } else {
+        self.style.default_length
+      };
+      let (boost, anticipation) = if realized.ornamental {
+        (0.0, 0.0)
+      } else if has(NoteModificationType::Sforzando) {
+        (self.style.sforzando_boost, self.style.sforzando_onset_seconds)
+      } else if has(NoteModificationType::Marcato) {
+        (self.style.marcato_boost, self.style.marcato_onset_seconds)
+      } else if has(NoteModificationType::Accent) {
+        (self.style.accent_boost, self.style.accent_onset_seconds)
+      } else {
+        (0.0, 0.0)
+      };
+      pending.push(PendingEvent {
+        note: realized.clone(),
+        level: (context.level + boost).clamp(0.0, 1.0),
+        length,
+        anticipation,
+        breath_after: false,
+      });
+    }
+    pending
+  }
+
+  /// Marks every note which is followed by a breath.
+  fn finish(self, mut pending: Vec<PendingEvent>) -> Vec<PendingEvent> {
+    for event in &mut pending {
+      event.breath_after = self.breaths.contains(&event.note.end_beat);
+    }
+    pending
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::Tempo;
+  use crate::modification::DirectionType;
+  use crate::note::{DurationType, Pitch, PitchName};
+
+  #[test]
+  fn test_performance_rendering() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut composition = Composition::new("Performance", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    staff.add_direction(DirectionType::Dynamic {
+      dynamic: Dynamic::Piano(1),
+    });
+    staff
+      .add_note(Pitch::new(PitchName::C, 5), quarter, None)
+      .add_modification(NoteModificationType::Staccato);
+    staff
+      .add_note(Pitch::new(PitchName::D, 5), quarter, None)
+      .add_modification(NoteModificationType::Accent);
+    let phrase = staff.add_phrase();
+    phrase.add_modification(PhraseModificationType::Crescendo {
+      final_dynamic: Some(Dynamic::Forte(1)),
+    });
+    phrase.add_modification(PhraseModificationType::Legato);
+    phrase.add_note(Pitch::new(PitchName::E, 5), quarter, None);
+    phrase.add_note(Pitch::new(PitchName::F, 5), quarter, None);
+    phrase.add_note(Pitch::new(PitchName::G, 5), quarter, None);
+    staff
+      .add_note(Pitch::new(PitchName::G, 5), quarter, None)
+      .add_modification(NoteModificationType::Fermata);
+    staff.add_direction(DirectionType::BreathMark);
+    staff
+      .add_note(Pitch::new(PitchName::A, 5), quarter, None)
+      .add_modification(NoteModificationType::Tie);
+    staff.add_note(Pitch::new(PitchName::A, 5), quarter, None);
+    staff
+      .add_note(Pitch::new(PitchName::B, 5), quarter, None)
+      .add_modification(NoteModificationType::Dynamic {
+        dynamic: Dynamic::Piano(2),
+      });
+    staff.add_note(Pitch::new(PitchName::C, 6), quarter, None);
+
+    let events = composition.perform(&PerformanceStyle::default());
+    assert_eq!(events.len(), 9);
+    let seconds = |event: &PerformanceEvent| {
+      (
+        (event.start_seconds * 1000.0).round() / 1000.0,
+        (event.end_seconds * 1000.0).round() / 1000.0,
+      )
+    };
+    assert_eq!(seconds(&events[0]), (0.0, 0.5));
+    assert_eq!(events[0].velocity, 51);
+    assert_eq!(events[1].velocity, 64);
+    assert_eq!(seconds(&events[1]), (0.99, 1.9));
+    assert_eq!(seconds(&events[2]), (2.0, 3.0));
+    assert!(events[2].level < events[3].level && events[3].level < events[4].level);
+    assert_eq!(events[4].velocity, 76);
+    assert_eq!(seconds(&events[4]), (4.0, 4.9));
+    assert_eq!(events[5].velocity, 76);
+    assert_eq!(seconds(&events[5]), (5.0, 6.65));
+    assert_eq!(seconds(&events[6]), (7.0, 8.8));
+    assert!((events[7].level - Dynamic::Piano(2).value()).abs() < f32::EPSILON);
+    assert_eq!(events[7].level, events[8].level);
+  }
+
+  #[test]
+  fn test_tied_articulation() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut composition = Composition::new("Ties", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    staff
+      .add_note(Pitch::new(PitchName::C, 5), quarter, None)
+      .add_modification(NoteModificationType::Tie);
+    staff
+      .add_note(Pitch::new(PitchName::C, 5), quarter, None)
+      .add_modification(NoteModificationType::Staccato);
+
+    let events = composition.perform(&PerformanceStyle::default());
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].start_seconds, events[0].end_seconds), (0.0, 1.0));
+  }
+
+  #[test]
+  fn test_grace_notes_before_beat() {
+    let (quarter, eighth) = (
+      Duration::new(DurationType::Quarter, 0),
+      Duration::new(DurationType::Eighth, 0),
+    );
+    let mut composition = Composition::new("Graces", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    staff.add_note(Pitch::new(PitchName::C, 5), quarter, None);
+    staff
+      .add_note(Pitch::new(PitchName::D, 5), eighth, None)
+      .add_modification(NoteModificationType::Grace { acciaccatura: true });
+    staff.add_note(Pitch::new(PitchName::E, 5), quarter, None);
+
+    let mut style = PerformanceStyle::default();
+    style.realization.acciaccatura_before_beat = true;
+    let events = composition.perform(&style);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[1].start_seconds, 0.875);
+    assert!(events[0].end_seconds < events[1].start_seconds);
+    assert_eq!(events[2].start_seconds, 1.0);
+  }
+}
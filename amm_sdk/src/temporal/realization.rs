@@ -0,0 +1,1125 @@
+use super::{AccidentalResolver, Timeslice, TimesliceContent};
+use crate::context::{Key, TimeSignature};
+use crate::modification::{NoteModification, NoteModificationType, PhraseModificationType};
+use crate::note::{Accidental, Duration, DurationType, Note, Pitch, PitchName, Rational, Tuning};
+use crate::structure::Part;
+use alloc::vec::Vec;
+use core::mem;
+
+/// Represents the performance practice used to interpret ornaments.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OrnamentStyle {
+  /// Baroque practice, in which trills start on the auxiliary note and turns start
+  /// on the auxiliary note on the beat.
+  Baroque,
+  /// Classical practice, in which trills start on the principal note and turns are
+  /// played as five notes starting on the principal note.
+  #[default]
+  Classical,
+}
+
+/// Represents the way in which a glissando or portamento between two pitches is realized.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GlideMode {
+  /// A run of notes through every semitone between the two pitches.
+  #[default]
+  Chromatic,
+  /// A run of notes through every pitch of the current key between the two pitches.
+  Diatonic,
+  /// A continuous change in pitch, represented as a [`PitchGlide`] on the starting note.
+  Continuous,
+}
+
+/// Options which control how ornaments, grace notes, and glides are realized into playable notes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RealizationOptions {
+  /// The performance practice used to interpret turns.
+  pub style: OrnamentStyle,
+  /// Whether trills and shakes start on the auxiliary note instead of the principal note.
+  pub trill_starts_on_auxiliary: bool,
+  /// The duration of each note of a trill or shake, which determines the trill speed.
+  pub trill_note: Duration,
+  /// The duration of each auxiliary note in a mordent, turn, or schleifer.
+  pub ornament_note: Duration,
+  /// The duration of each acciaccatura grace note.
+  pub acciaccatura_note: Duration,
+  /// Whether acciaccaturas are played before the beat, stealing time from the preceding
+  /// note, instead of on the beat, stealing time from the principal note.
+  pub acciaccatura_before_beat: bool,
+  /// The way in which glissandos are realized.
+  pub glissando_mode: GlideMode,
+  /// The way in which portamentos are realized.
+  pub portamento_mode: GlideMode,
+  /// The fraction of the duration of a note which is taken up by a glissando or portamento,
+  /// at the end of the note when leaving it or at the start of the note when arriving at it.
+  pub glide_fraction: Rational,
+  /// The number of semitones covered by a glissando or portamento which has no written
+  /// pitch at its other end.
+  pub indefinite_glide_semitones: u8,
+}
+
+impl RealizationOptions {
+  /// Creates a new set of realization options following the conventions of the given style.
+  #[must_use]
+  pub const fn new(style: OrnamentStyle) -> Self {
+    Self {
+      style,
+      trill_starts_on_auxiliary: matches!(style, OrnamentStyle::Baroque),
+      trill_note: Duration::new(DurationType::ThirtySecond, 0),
+      ornament_note: Duration::new(DurationType::ThirtySecond, 0),
+      acciaccatura_note: Duration::new(DurationType::ThirtySecond, 0),
+      acciaccatura_before_beat: false,
+      glissando_mode: GlideMode::Chromatic,
+      portamento_mode: GlideMode::Continuous,
+      glide_fraction: Rational::new(1, 2),
+      indefinite_glide_semitones: 5,
+    }
+  }
+}
+
+impl Default for RealizationOptions {
+  fn default() -> Self {
+    Self::new(OrnamentStyle::default())
+  }
+}
+
+/// Represents a continuous change in the pitch of a [`RealizedNote`], measured in cents
+/// relative to the pitch of the note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchGlide {
+  /// The beat at which the pitch starts to change.
+  pub start_beat: Rational,
+  /// The beat at which the pitch stops changing.
+  pub end_beat: Rational,
+  /// The pitch offset in cents at the start of the glide.
+  pub start_cents: i32,
+  /// The pitch offset in cents at the end of the glide.
+  pub end_cents: i32,
+}
+
+impl PitchGlide {
+  /// Returns the pitch offset in cents at the given beat, interpolated linearly during
+  /// the glide and held constant before and after it.
+  #[must_use]
+  pub fn cents_at(&self, beat: Rational) -> f64 {
+    let progress = if beat <= self.start_beat {
+      Rational::ZERO
+    } else if beat >= self.end_beat {
+      Rational::ONE
+    } else {
+      (beat - self.start_beat) / (self.end_beat - self.start_beat)
+    };
+    f64::from(self.start_cents) + progress.to_f64() * f64::from(self.end_cents - self.start_cents)
+  }
+}
+
+/// Represents a single concretely timed note produced by realizing a part for playback.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealizedNote {
+  /// The beat at which the note starts, in units of the beat base used for realization.
+  pub start_beat: Rational,
+  /// The beat at which the note stops sounding.
+  pub end_beat: Rational,
+  /// The written note from which this note was realized, with its pitch replaced by
+  /// the sounding pitch of any auxiliary note.
+  ///
+  /// A chain of tied notes is realized as the first note of the chain sustained for the
+  /// full tied duration, in which case the tie itself is removed from the note and any
+  /// articulation describing how the note is released is taken from the last note of the chain.
+  pub note: Note,
+  /// The accidental with which the note sounds, taking the key signature and any
+  /// accidentals carried over within the measure into account.
+  pub accidental: Accidental,
+  /// The unique identifier of the part containing the note.
+  pub part_id: usize,
+  /// Whether the note is a grace note or a note generated by realizing an ornament or glide.
+  ///
+  /// The first note of a realized ornament or departing glide carries the onset of the
+  /// written note and is therefore not considered ornamental, while the notes of a glide
+  /// arriving at a written note are treated like grace notes.
+  pub ornamental: bool,
+  /// A continuous change in pitch applied while the note sounds, if any.
+  pub glide: Option<PitchGlide>,
+}
+
+impl RealizedNote {
+  /// Returns the duration of the note in beats.
+  #[must_use]
+  pub fn get_beats(&self) -> Rational {
+    self.end_beat - self.start_beat
+  }
+
+  /// Returns the pitch of the note in MIDI number format.
+  #[must_use]
+  pub fn midi_number(&self) -> u8 {
+    self.note.midi_number_with_accidental(self.accidental)
+  }
+
+  /// Returns the pitch of the note in Hertz, optionally using a custom frequency for A4.
+  #[must_use]
+  pub fn pitch_hz(&self, a4_frequency_hz: Option<f32>) -> f32 {
+    self.note.pitch_hz_with_accidental(self.accidental, a4_frequency_hz)
+  }
+
+  /// Returns the pitch of the note in Hertz in the given tuning, or `None` if the
+  /// note is not mapped by the tuning.
+  #[must_use]
+  pub fn tuned_pitch_hz(&self, tuning: &Tuning) -> Option<f32> {
+    self.note.tuned_pitch_hz_with_accidental(self.accidental, tuning)
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value which corrects the MIDI number of the
+  /// note to its exact pitch, given the pitch bend range of the receiver in semitones.
+  #[must_use]
+  pub fn midi_pitch_bend(&self, bend_range_semitones: u8) -> u16 {
+    self
+      .note
+      .midi_pitch_bend_with_accidental(self.accidental, bend_range_semitones)
+  }
+
+  /// Returns the pitch offset in cents caused by the glide on the note at the given beat.
+  #[must_use]
+  pub fn cents_offset_at(&self, beat: Rational) -> f64 {
+    self.glide.map_or(0.0, |glide| glide.cents_at(beat))
+  }
+
+  /// Returns the pitch of the note in Hertz at the given beat, taking into account any
+  /// glide on the note and optionally using a custom frequency for A4.
+  #[must_use]
+  pub fn pitch_hz_at(&self, beat: Rational, a4_frequency_hz: Option<f32>) -> f32 {
+    self.pitch_hz_with_offset(self.cents_offset_at(beat), a4_frequency_hz)
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value at the given beat, which corrects the MIDI
+  /// number of the note to its exact pitch including any glide on the note.
+  ///
+  /// Glides which exceed the pitch bend range of the receiver are clamped to that range.
+  #[must_use]
+  pub fn midi_pitch_bend_at(&self, beat: Rational, bend_range_semitones: u8) -> u16 {
+    self.midi_pitch_bend_with_offset(self.cents_offset_at(beat), bend_range_semitones)
+  }
+
+  /// Returns the pitch of the note in Hertz when raised by the given number of cents.
+  #[allow(clippy::cast_possible_truncation)]
+  pub(crate) fn pitch_hz_with_offset(&self, cents: f64, a4_frequency_hz: Option<f32>) -> f32 {
+    self.pitch_hz(a4_frequency_hz) * 2f32.powf((cents / 1200.0) as f32)
+  }
+
+  /// Returns the 14-bit MIDI pitch bend value of the note when raised by the given number of cents.
+  #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+  pub(crate) fn midi_pitch_bend_with_offset(&self, cents: f64, bend_range_semitones: u8) -> u16 {
+    let range = f64::from(bend_range_semitones.max(1)) * 100.0;
+    (f64::from(self.midi_pitch_bend(bend_range_semitones)) + 8192.0 * cents / range)
+      .round()
+      .clamp(0.0, 16383.0) as u16
+  }
+}
+
+/// Returns every sounding note in the given part in playback order, with ornaments and
+/// grace notes expanded into concretely timed notes according to the specified options.
+///
+/// Parts stored in written pitch are converted to sounding pitch, in which case the
+/// starting key is taken to be the sounding key.
+pub(crate) fn realize_part(
+  part: &Part,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+  beat_base: Duration,
+  options: &RealizationOptions,
+) -> Vec<RealizedNote> {
+  let realizer = Realizer::new(part.get_id(), beat_base, options);
+  if part.is_written_pitch() {
+    let mut sounding_part = part.clone();
+    sounding_part.transpose_to_sounding_pitch(part.written_key(starting_key), starting_time_signature);
+    realizer.realize(sounding_part.iter_timeslices(), starting_key, starting_time_signature)
+  } else {
+    realizer.realize(part.iter_timeslices(), starting_key, starting_time_signature)
+  }
+}
+
+/// Returns the diatonic neighbor of the given pitch which lies the specified number of
+/// steps away within the given key, along with its accidental in that key.
+fn diatonic_neighbor(pitch: Pitch, steps: i16, key: Key) -> Option<SpelledPitch> {
+  let position = pitch.diatonic_value() + steps;
+  let name = PitchName::from_diatonic_index(position);
+  u8::try_from(position.div_euclid(7))
+    .ok()
+    .map(|octave| (Pitch::new(name, octave), key.accidentals()[name.index()]))
+}
+
+/// Returns the MIDI number of the given pitch when played with the given accidental.
+fn midi_number(pitch: Pitch, accidental: Accidental) -> i16 {
+  69 + i16::from(pitch.value().1) + i16::from(accidental.value())
+}
+
+/// A pitch along with the accidental with which it sounds.
+type SpelledPitch = (Pitch, Accidental);
+
+/// Describes a glissando or portamento which departs from or arrives at a written note.
+struct Glide {
+  mode: GlideMode,
+  departing: bool,
+  /// The MIDI number of the pitch at the other end of the glide.
+  midi_number: i16,
+  /// Whether the pitch at the other end of the glide is a written note, which is then
+  /// not played as part of the glide.
+  written: bool,
+}
+
+/// Describes a tied note whose realization is deferred until the full length of the tie is known.
+struct OpenTie {
+  note: Note,
+  accidental: Accidental,
+  key: Key,
+  glide: Option<Glide>,
+  start_beat: Rational,
+  end_beat: Rational,
+  /// The latest note tied to from the first note in the chain, if any.
+  last_note: Option<Note>,
+  /// Whether the latest note in the chain is itself tied to a following note.
+  continues: bool,
+}
+
+/// Returns whether the given modification describes how a note is released, which a
+/// chain of tied notes takes from its last note.
+fn is_release_articulation(mod_type: NoteModificationType) -> bool {
+  matches!(
+    mod_type,
+    NoteModificationType::Staccato
+      | NoteModificationType::Staccatissimo
+      | NoteModificationType::Spiccato
+      | NoteModificationType::Tenuto
+      | NoteModificationType::DetachedLegato
+  )
+}
+
+/// Returns whether the given note is tied to the following note.
+fn is_tied(note: &Note) -> bool {
+  note
+    .iter_modifications()
+    .any(|modification| modification.r#type == NoteModificationType::Tie)
+}
+
+pub(crate) struct Realizer<'a> {
+  part_id: usize,
+  beat_base: Duration,
+  options: &'a RealizationOptions,
+  notes: Vec<RealizedNote>,
+  pending_graces: Vec<Vec<(Note, Accidental, bool)>>,
+  open_ties: Vec<OpenTie>,
+}
+
+impl<'a> Realizer<'a> {
+  pub(crate) fn new(part_id: usize, beat_base: Duration, options: &'a RealizationOptions) -> Self {
+    Self {
+      part_id,
+      beat_base,
+      options,
+      notes: Vec::new(),
+      pending_graces: Vec::new(),
+      open_ties: Vec::new(),
+    }
+  }
+
+  fn realize(
+    mut self,
+    timeslices: impl Iterator<Item = Timeslice>,
+    starting_key: Key,
+    starting_time_signature: TimeSignature,
+  ) -> Vec<RealizedNote> {
+    let (mut current_beat, mut resolver) = (
+      Rational::ZERO,
+      AccidentalResolver::new(timeslices, starting_key, starting_time_signature),
+    );
+    while let Some(timeslice) = resolver.next() {
+      current_beat += self.realize_timeslice(timeslice, &resolver, current_beat);
+    }
+    let mut notes = self.finish(current_beat);
+    notes.sort_by_key(|note| note.start_beat);
+    notes
+  }
+
+  /// Realizes every note in the given timeslice, which starts at `current_beat` and has
+  /// just been resolved by the given accidental resolver, returning the number of beats
+  /// taken up by the timeslice.
+  ///
+  /// Grace notes are held back until the next timeslice which takes up any time, where
+  /// all grace notes from the same timeslice share a single onset. Tied notes are held
+  /// back until the end of the tie, so that any ornament on the first note is realized
+  /// once across the full tied duration.
+  pub(crate) fn realize_timeslice<I: Iterator<Item = Timeslice>>(
+    &mut self,
+    timeslice: Timeslice,
+    resolver: &AccidentalResolver<I>,
+    current_beat: Rational,
+  ) -> Rational {
+    let (beat_base, key) = (self.beat_base, resolver.current_key());
+    let slice_beats = timeslice.get_beats(&beat_base);
+    let dotted = timeslice
+      .content
+      .iter()
+      .any(|content| !content.note.is_grace_note() && content.note.duration.dots > 0);
+    let (mut principals, mut graces) = (Vec::new(), Vec::new());
+    for content in timeslice.content {
+      let glide = self.find_glide(&content, resolver);
+      let accidental = content.sounding_accidental(Some(key));
+      let beats = content.get_beats(&beat_base);
+      if content.note.is_grace_note() {
+        if !content.note.is_rest() {
+          let acciaccatura = content
+            .note
+            .iter_modifications()
+            .any(|modification| matches!(modification.r#type, NoteModificationType::Grace { acciaccatura: true }));
+          graces.push((content.note, accidental, acciaccatura));
+        }
+      } else if !content.note.is_rest() {
+        principals.push((content.note, accidental, beats, glide));
+      }
+    }
+    if !graces.is_empty() {
+      self.pending_graces.push(graces);
+    }
+    if slice_beats.is_positive() {
+      let mut unmatched = Vec::new();
+      for (note, accidental, beats, glide) in principals {
+        let midi = midi_number(note.pitch, accidental);
+        match self.open_ties.iter_mut().find(|tie| {
+          tie.continues && tie.end_beat == current_beat && midi_number(tie.note.pitch, tie.accidental) == midi
+        }) {
+          Some(tie) => {
+            tie.end_beat = current_beat + beats;
+            tie.continues = is_tied(&note);
+            tie.glide = glide.or(tie.glide.take());
+            tie.last_note = Some(note);
+          }
+          None => unmatched.push((note, accidental, beats, glide)),
+        }
+      }
+      self.resolve_ties(Some(current_beat));
+      let onset = self.realize_graces(current_beat, slice_beats, dotted);
+      for (note, accidental, beats, glide) in unmatched {
+        if current_beat + beats > onset {
+          if is_tied(&note) {
+            self.open_ties.push(OpenTie {
+              note,
+              accidental,
+              key,
+              glide,
+              start_beat: onset,
+              end_beat: current_beat + beats,
+              last_note: None,
+              continues: true,
+            });
+          } else {
+            self.realize_principal(&note, accidental, key, glide.as_ref(), onset, current_beat + beats);
+          }
+        }
+      }
+    }
+    slice_beats
+  }
+
+  fn realize_principal(
+    &mut self,
+    note: &Note,
+    accidental: Accidental,
+    key: Key,
+    glide: Option<&Glide>,
+    start_beat: Rational,
+    end_beat: Rational,
+  ) {
+    match glide {
+      Some(glide) => self.realize_glide(note, accidental, key, glide, start_beat, end_beat),
+      None => self.realize_note(note, accidental, key, start_beat, end_beat),
+    }
+  }
+
+  /// Realizes every tied note whose length is known at the given beat, or every
+  /// remaining tied note if no beat is given.
+  ///
+  /// A tie which is still expecting a continuation at the given beat is broken and
+  /// realized as it stands.
+  fn resolve_ties(&mut self, beat: Option<Rational>) {
+    let (resolved, open): (Vec<_>, Vec<_>) = mem::take(&mut self.open_ties)
+      .into_iter()
+      .partition(|tie| beat.is_none_or(|beat| !tie.continues || tie.end_beat <= beat));
+    self.open_ties = open;
+    for mut tie in resolved {
+      let (continues, replace_release) = (tie.continues, tie.last_note.is_some());
+      let removed_ids: Vec<_> = tie
+        .note
+        .iter_modifications()
+        .filter(|modification| {
+          (!continues && modification.r#type == NoteModificationType::Tie)
+            || (replace_release && is_release_articulation(modification.r#type))
+        })
+        .map(NoteModification::get_id)
+        .collect();
+      for id in removed_ids {
+        tie.note.remove_modification(id);
+      }
+      if let Some(last_note) = &tie.last_note {
+        for modification in last_note.iter_modifications() {
+          if is_release_articulation(modification.r#type) {
+            tie.note.add_modification(modification.r#type);
+          }
+        }
+      }
+      self.realize_principal(
+        &tie.note,
+        tie.accidental,
+        tie.key,
+        tie.glide.as_ref(),
+        tie.start_beat,
+        tie.end_beat,
+      );
+    }
+  }
+
+  /// Realizes any remaining grace notes at the given final beat and returns every
+  /// realized note in the order in which it was realized.
+  pub(crate) fn finish(mut self, end_beat: Rational) -> Vec<RealizedNote> {
+    self.resolve_ties(None);
+    self.realize_graces(end_beat, Rational::ZERO, false);
+    self.notes
+  }
+
+  fn push(&mut self, note: &Note, accidental: Accidental, start_beat: Rational, end_beat: Rational, ornamental: bool) {
+    self.notes.push(RealizedNote {
+      start_beat,
+      end_beat,
+      note: note.clone(),
+      accidental,
+      part_id: self.part_id,
+      ornamental,
+      glide: None,
+    });
+  }
+
+  fn push_auxiliary(
+    &mut self,
+    note: &Note,
+    auxiliary: SpelledPitch,
+    start_beat: Rational,
+    end_beat: Rational,
+    ornamental: bool,
+  ) {
+    let mut auxiliary_note = note.clone();
+    (auxiliary_note.pitch, auxiliary_note.accidental) = auxiliary;
+    self.push(&auxiliary_note, auxiliary.1, start_beat, end_beat, ornamental);
+  }
+
+  /// Realizes the pending grace notes before a principal note of the given length
+  /// starting at `beat`, returning the beat at which the principal note now starts.
+  fn realize_graces(&mut self, beat: Rational, principal_beats: Rational, dotted: bool) -> Rational {
+    if self.pending_graces.is_empty() {
+      return beat;
+    }
+    let graces = mem::take(&mut self.pending_graces);
+    let count = Rational::from(u32::try_from(graces.len()).unwrap_or(u32::MAX));
+    let acciaccatura = graces.iter().flatten().all(|(_, _, acciaccatura)| *acciaccatura);
+    let (start, total, onset) = if !acciaccatura && principal_beats.is_positive() {
+      // Appoggiaturas take half of the principal note, or two thirds if it is dotted
+      let total = principal_beats
+        * if dotted {
+          Rational::new(2, 3)
+        } else {
+          Rational::new(1, 2)
+        };
+      (beat, total, beat + total)
+    } else {
+      let mut total = self.options.acciaccatura_note.beats(self.beat_base.value()) * count;
+      if principal_beats.is_positive() {
+        total = total.min(principal_beats / Rational::from(2_u8));
+      }
+      let preceding_limit = self
+        .notes
+        .iter()
+        .filter(|note| note.end_beat == beat)
+        .map(|note| note.get_beats() / Rational::from(2_u8))
+        .min()
+        .unwrap_or(beat);
+      if self.options.acciaccatura_before_beat && preceding_limit.is_positive() {
+        let total = total.min(preceding_limit);
+        self
+          .notes
+          .iter_mut()
+          .filter(|note| note.end_beat == beat)
+          .for_each(|note| note.end_beat = beat - total);
+        (beat - total, total, beat)
+      } else {
+        (beat, total, beat + total)
+      }
+    };
+    let step = total / count;
+    let mut grace_beat = start;
+    for group in graces {
+      for (note, accidental, _) in group {
+        self.push(&note, accidental, grace_beat, grace_beat + step, true);
+      }
+      grace_beat += step;
+    }
+    if principal_beats.is_positive() {
+      onset
+    } else {
+      beat
+    }
+  }
+
+  /// Plays the given sequence of pitches starting at `start_beat`, each lasting
+  /// `step` beats, with the final pitch sustained until `end_beat`.
+  ///
+  /// A pitch of `None` represents the principal note.
+  #[allow(clippy::too_many_arguments)]
+  fn push_sequence(
+    &mut self,
+    note: &Note,
+    accidental: Accidental,
+    pitches: &[Option<SpelledPitch>],
+    step: Rational,
+    start_beat: Rational,
+    end_beat: Rational,
+    held_before: bool,
+  ) {
+    let mut beat = start_beat;
+    for (index, pitch) in pitches.iter().enumerate() {
+      let next_beat = if index + 1 == pitches.len() {
+        end_beat
+      } else {
+        beat + step
+      };
+      let ornamental = held_before || index > 0;
+      match pitch {
+        Some(auxiliary) => self.push_auxiliary(note, *auxiliary, beat, next_beat, ornamental),
+        None => self.push(note, accidental, beat, next_beat, ornamental),
+      }
+      beat = next_beat;
+    }
+  }
+
+  fn realize_note(&mut self, note: &Note, accidental: Accidental, key: Key, start_beat: Rational, end_beat: Rational) {
+    let length = end_beat - start_beat;
+    let ornament = note
+      .iter_modifications()
+      .find_map(|modification| match modification.r#type {
+        NoteModificationType::Tremolo { .. } => Some(modification.r#type),
+        NoteModificationType::Trill { .. }
+        | NoteModificationType::Shake
+        | NoteModificationType::Mordent { .. }
+        | NoteModificationType::Turn { .. }
+        | NoteModificationType::Haydn
+        | NoteModificationType::Schleifer
+          if !note.is_unpitched() =>
+        {
+          Some(modification.r#type)
+        }
+        _ => None,
+      });
+    let ornament_step = self.options.ornament_note.beats(self.beat_base.value());
+    let neighbor = |steps| diatonic_neighbor(note.pitch, steps, key);
+    match ornament {
+      Some(NoteModificationType::Tremolo { relative_speed }) => {
+        // Each tremolo stroke halves the value of the repeated notes, starting from an eighth note
+        let step = Rational::new(1, 4 << relative_speed.clamp(1, 8)) / self.beat_base.value();
+        let count = usize::try_from((length / step).floor().max(1)).unwrap_or(1);
+        let step = step.min(length / Rational::from(u32::try_from(count).unwrap_or(1)));
+        self.push_sequence(
+          note,
+          accidental,
+          &alloc::vec![None; count],
+          step,
+          start_beat,
+          end_beat,
+          false,
+        );
+      }
+      Some(NoteModificationType::Trill { upper }) => self.realize_trill(
+        note,
+        accidental,
+        neighbor(if upper { 1 } else { -1 }),
+        start_beat,
+        end_beat,
+      ),
+      Some(NoteModificationType::Shake) => self.realize_trill(note, accidental, neighbor(1), start_beat, end_beat),
+      Some(NoteModificationType::Mordent { upper }) => {
+        let pitches = [None, neighbor(if upper { 1 } else { -1 }), None];
+        let step = ornament_step.min(length / Rational::from(3_u8));
+        self.push_sequence(note, accidental, &pitches, step, start_beat, end_beat, false);
+      }
+      Some(NoteModificationType::Schleifer) => {
+        let pitches = [neighbor(-2), neighbor(-1), None];
+        let step = ornament_step.min(length / Rational::from(3_u8));
+        self.push_sequence(note, accidental, &pitches, step, start_beat, end_beat, false);
+      }
+      Some(NoteModificationType::Turn { upper, delayed, .. }) => {
+        let (first, second) = if upper {
+          (neighbor(1), neighbor(-1))
+        } else {
+          (neighbor(-1), neighbor(1))
+        };
+        self.realize_turn(note, accidental, (first, second), delayed, start_beat, end_beat);
+      }
+      Some(NoteModificationType::Haydn) => {
+        self.realize_turn(
+          note,
+          accidental,
+          (neighbor(1), neighbor(-1)),
+          false,
+          start_beat,
+          end_beat,
+        );
+      }
+      _ => self.push(note, accidental, start_beat, end_beat, false),
+    }
+  }
+
+  /// Returns the glissando or portamento which departs from or arrives at the given note, if any.
+  ///
+  /// Glides within a phrase depart from every note but the last towards the following
+  /// note, whose accidental is resolved against the state of the given resolver after
+  /// the current timeslice, while glides on a single note cover a fixed number of semitones.
+  fn find_glide<I: Iterator<Item = Timeslice>>(
+    &self,
+    content: &TimesliceContent,
+    resolver: &AccidentalResolver<I>,
+  ) -> Option<Glide> {
+    let key = resolver.current_key();
+    if content.note.is_rest() || content.note.is_unpitched() || content.note.is_grace_note() {
+      return None;
+    }
+    let phrase_glide = content.phrase_details.iter().find_map(|details| {
+      details
+        .modifications
+        .iter()
+        .find_map(|modification| match modification {
+          PhraseModificationType::Glissando => Some(self.options.glissando_mode),
+          PhraseModificationType::Portamento => Some(self.options.portamento_mode),
+          _ => None,
+        })
+        .filter(|_| details.index_in_phrase + 1 < details.phrase_length && !details.next_pitch.is_rest())
+        .map(|mode| {
+          let accidental = if details.next_accidental == Accidental::None {
+            resolver.implied_accidental(details.next_pitch)
+          } else {
+            details.next_accidental
+          };
+          Glide {
+            mode,
+            departing: true,
+            midi_number: midi_number(details.next_pitch, accidental),
+            written: true,
+          }
+        })
+    });
+    phrase_glide.or_else(|| {
+      let midi_number = midi_number(content.note.pitch, content.sounding_accidental(Some(key)));
+      let semitones = i16::from(self.options.indefinite_glide_semitones);
+      content
+        .note
+        .iter_modifications()
+        .find_map(|modification| match modification.r#type {
+          NoteModificationType::Glissando { from_current, going_up } => {
+            Some((self.options.glissando_mode, from_current, going_up))
+          }
+          NoteModificationType::Portamento { from_current, going_up } => {
+            Some((self.options.portamento_mode, from_current, going_up))
+          }
+          _ => None,
+        })
+        .map(|(mode, from_current, going_up)| Glide {
+          mode,
+          departing: from_current,
+          midi_number: if going_up == from_current {
+            midi_number + semitones
+          } else {
+            midi_number - semitones
+          },
+          written: false,
+        })
+    })
+  }
+
+  /// Returns the pitches played by a glide run from the given pitch towards the given
+  /// MIDI number, excluding the starting pitch and optionally including the final one.
+  fn glide_run(
+    from: SpelledPitch,
+    to_midi_number: i16,
+    mode: GlideMode,
+    key: Key,
+    inclusive: bool,
+  ) -> Vec<SpelledPitch> {
+    let from_midi_number = midi_number(from.0, from.1);
+    let direction = (to_midi_number - from_midi_number).signum();
+    let reached = |midi_number: i16| {
+      if inclusive {
+        (to_midi_number - midi_number) * direction >= 0
+      } else {
+        (to_midi_number - midi_number) * direction > 0
+      }
+    };
+    let mut run = Vec::new();
+    if direction == 0 {
+      return run;
+    }
+    match mode {
+      GlideMode::Chromatic => {
+        let mut next = from_midi_number + direction;
+        while reached(next) {
+          if let Ok(midi @ 1..=127) = u8::try_from(next) {
+            let spelled = Note::from_midi(midi, Duration::default(), Some(key));
+            run.push((spelled.pitch, spelled.sounding_accidental(key.accidentals())));
+          }
+          next += direction;
+        }
+      }
+      GlideMode::Diatonic => {
+        let mut steps = direction;
+        while let Some(neighbor) = diatonic_neighbor(from.0, steps, key).filter(|(pitch, accidental)| {
+          let midi_number = midi_number(*pitch, *accidental);
+          (midi_number - from_midi_number) * direction > 0 && reached(midi_number)
+        }) {
+          run.push(neighbor);
+          steps += direction;
+        }
+      }
+      GlideMode::Continuous => (),
+    }
+    run
+  }
+
+  fn realize_glide(
+    &mut self,
+    note: &Note,
+    accidental: Accidental,
+    key: Key,
+    glide: &Glide,
+    start_beat: Rational,
+    end_beat: Rational,
+  ) {
+    let length = end_beat - start_beat;
+    let glide_beats = length * self.options.glide_fraction.max(Rational::ZERO).min(Rational::ONE);
+    let cents = 100 * i32::from(glide.midi_number - midi_number(note.pitch, accidental));
+    if glide.mode == GlideMode::Continuous {
+      self.push(note, accidental, start_beat, end_beat, false);
+      if let Some(realized) = self.notes.last_mut() {
+        realized.glide = Some(if glide.departing {
+          PitchGlide {
+            start_beat: end_beat - glide_beats,
+            end_beat,
+            start_cents: 0,
+            end_cents: cents,
+          }
+        } else {
+          PitchGlide {
+            start_beat,
+            end_beat: start_beat + glide_beats,
+            start_cents: cents,
+            end_cents: 0,
+          }
+        });
+      }
+      return;
+    }
+    let mut run = Self::glide_run(
+      (note.pitch, accidental),
+      glide.midi_number,
+      glide.mode,
+      key,
+      !glide.written,
+    );
+    if run.is_empty() || !glide_beats.is_positive() {
+      self.push(note, accidental, start_beat, end_beat, false);
+    } else if glide.departing {
+      let count = Rational::from(u32::try_from(run.len()).unwrap_or(u32::MAX));
+      let (held_end, step) = if glide_beats == length {
+        let step = length / (count + Rational::ONE);
+        (start_beat + step, step)
+      } else {
+        (end_beat - glide_beats, glide_beats / count)
+      };
+      self.push(note, accidental, start_beat, held_end, false);
+      let (run_length, mut beat) = (run.len(), held_end);
+      for (index, pitch) in run.into_iter().enumerate() {
+        let next_beat = if index + 1 == run_length { end_beat } else { beat + step };
+        self.push_auxiliary(note, pitch, beat, next_beat, true);
+        beat = next_beat;
+      }
+    } else {
+      // Arriving glides are played from their far end towards the written note
+      run.reverse();
+      let count = Rational::from(u32::try_from(run.len()).unwrap_or(u32::MAX));
+      let glide_beats = if glide_beats == length {
+        length * count / (count + Rational::ONE)
+      } else {
+        glide_beats
+      };
+      let (step, mut beat) = (glide_beats / count, start_beat);
+      for pitch in run {
+        self.push_auxiliary(note, pitch, beat, beat + step, true);
+        beat += step;
+      }
+      self.push(note, accidental, start_beat + glide_beats, end_beat, false);
+    }
+  }
+
+  fn realize_trill(
+    &mut self,
+    note: &Note,
+    accidental: Accidental,
+    auxiliary: Option<SpelledPitch>,
+    start_beat: Rational,
+    end_beat: Rational,
+  ) {
+    // Trills always end on the principal note, so the number of notes depends on the starting note
+    let (length, starts_on_auxiliary) = (end_beat - start_beat, self.options.trill_starts_on_auxiliary);
+    let count = (length / self.options.trill_note.beats(self.beat_base.value())).floor();
+    let count = if starts_on_auxiliary {
+      (count - count % 2).max(2)
+    } else {
+      (count - (1 - count % 2)).max(1)
+    };
+    let pitches: Vec<_> = (0..count)
+      .map(|index| {
+        if (index % 2 == 0) == starts_on_auxiliary {
+          auxiliary
+        } else {
+          None
+        }
+      })
+      .collect();
+    let step = self
+      .options
+      .trill_note
+      .beats(self.beat_base.value())
+      .min(length / Rational::from(count));
+    self.push_sequence(note, accidental, &pitches, step, start_beat, end_beat, false);
+  }
+
+  fn realize_turn(
+    &mut self,
+    note: &Note,
+    accidental: Accidental,
+    (first, second): (Option<SpelledPitch>, Option<SpelledPitch>),
+    delayed: bool,
+    start_beat: Rational,
+    end_beat: Rational,
+  ) {
+    let (length, ornament_step) = (
+      end_beat - start_beat,
+      self.options.ornament_note.beats(self.beat_base.value()),
+    );
+    if delayed {
+      // Delayed turns sustain the principal note before playing the turn at the end of the note
+      let step = ornament_step.min(length / Rational::from(5_u8));
+      let turn_beat = end_beat - step * Rational::from(4_u8);
+      self.push(note, accidental, start_beat, turn_beat, false);
+      self.push_sequence(
+        note,
+        accidental,
+        &[first, None, second, None],
+        step,
+        turn_beat,
+        end_beat,
+        true,
+      );
+    } else if self.options.style == OrnamentStyle::Baroque {
+      let step = ornament_step.min(length / Rational::from(4_u8));
+      self.push_sequence(
+        note,
+        accidental,
+        &[first, None, second, None],
+        step,
+        start_beat,
+        end_beat,
+        false,
+      );
+    } else {
+      let step = ornament_step.min(length / Rational::from(5_u8));
+      self.push_sequence(
+        note,
+        accidental,
+        &[None, first, None, second, None],
+        step,
+        start_beat,
+        end_beat,
+        false,
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::Tempo;
+  use crate::Composition;
+
+  #[test]
+  fn test_ornament_realization() {
+    let (quarter, eighth) = (
+      Duration::new(DurationType::Quarter, 0),
+      Duration::new(DurationType::Eighth, 0),
+    );
+    let mut composition = Composition::new("Ornaments", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    staff
+      .add_note(Pitch::new(PitchName::C, 5), quarter, None)
+      .add_modification(NoteModificationType::Trill { upper: true });
+    staff
+      .add_note(Pitch::new(PitchName::D, 5), eighth, None)
+      .add_modification(NoteModificationType::Grace { acciaccatura: false });
+    staff.add_note(Pitch::new(PitchName::C, 5), quarter, None);
+    staff
+      .add_note(Pitch::new(PitchName::E, 5), eighth, None)
+      .add_modification(NoteModificationType::Grace { acciaccatura: true });
+    staff
+      .add_note(Pitch::new(PitchName::D, 5), quarter, None)
+      .add_modification(NoteModificationType::Mordent { upper: false });
+
+    let notes = composition.realize(&RealizationOptions::default());
+    let pitches: Vec<_> = notes.iter().map(|note| note.note.pitch.name).collect();
+    assert_eq!(
+      pitches,
+      [
+        PitchName::C,
+        PitchName::D,
+        PitchName::C,
+        PitchName::D,
+        PitchName::C,
+        PitchName::D,
+        PitchName::C,
+        PitchName::D,
+        PitchName::C,
+        PitchName::E,
+        PitchName::D,
+        PitchName::C,
+        PitchName::D
+      ]
+    );
+    assert_eq!(notes[6].start_beat, Rational::new(3, 4));
+    assert_eq!(notes[6].end_beat, Rational::ONE);
+    assert!(!notes[0].ornamental && notes[1].ornamental);
+    assert_eq!(
+      (notes[7].start_beat, notes[7].end_beat),
+      (Rational::ONE, Rational::new(3, 2))
+    );
+    assert_eq!(notes[8].start_beat, Rational::new(3, 2));
+    assert!(notes[9].ornamental && !notes[10].ornamental);
+    assert_eq!(notes[10].start_beat, Rational::new(17, 8));
+    assert_eq!(notes[12].end_beat, Rational::from(3_u8));
+
+    let mut options = RealizationOptions::new(OrnamentStyle::Baroque);
+    options.acciaccatura_before_beat = true;
+    let notes = composition.realize(&options);
+    assert_eq!(notes[0].note.pitch, Pitch::new(PitchName::D, 5));
+    assert!(!notes[0].ornamental);
+    assert_eq!(notes[7].note.pitch, Pitch::new(PitchName::C, 5));
+    assert_eq!(notes[10].start_beat, Rational::new(15, 8));
+    assert_eq!(notes[9].end_beat, Rational::new(15, 8));
+    assert_eq!(notes[11].start_beat, Rational::from(2_u8));
+  }
+
+  #[test]
+  fn test_tie_and_grace_chord_realization() {
+    let (quarter, eighth) = (
+      Duration::new(DurationType::Quarter, 0),
+      Duration::new(DurationType::Eighth, 0),
+    );
+    let mut composition = Composition::new("Ties", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    let chord = staff.add_chord();
+    for name in [PitchName::D, PitchName::F] {
+      chord
+        .add_note(Pitch::new(name, 5), eighth, None)
+        .add_modification(NoteModificationType::Grace { acciaccatura: true });
+    }
+    let note = staff.add_note(Pitch::new(PitchName::E, 5), quarter, None);
+    note.add_modification(NoteModificationType::Mordent { upper: false });
+    note.add_modification(NoteModificationType::Tie);
+    staff
+      .add_note(Pitch::new(PitchName::E, 5), quarter, None)
+      .add_modification(NoteModificationType::Mordent { upper: false });
+
+    let notes = composition.realize(&RealizationOptions::default());
+    let pitches: Vec<_> = notes.iter().map(|note| note.note.pitch.name).collect();
+    assert_eq!(
+      pitches,
+      [PitchName::D, PitchName::F, PitchName::E, PitchName::D, PitchName::E]
+    );
+    assert_eq!(notes[0].start_beat, notes[1].start_beat);
+    assert_eq!(notes[1].end_beat, Rational::new(1, 8));
+    assert_eq!(notes[2].start_beat, Rational::new(1, 8));
+    assert_eq!(
+      (notes[4].start_beat, notes[4].end_beat),
+      (Rational::new(3, 8), Rational::from(2_u8))
+    );
+    assert!(!notes[4]
+      .note
+      .iter_modifications()
+      .any(|modification| modification.r#type == NoteModificationType::Tie));
+  }
+
+  #[test]
+  fn test_glide_realization() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut composition = Composition::new("Glides", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    let phrase = staff.add_phrase();
+    phrase.add_modification(PhraseModificationType::Glissando);
+    phrase.add_note(Pitch::new(PitchName::C, 4), quarter, None);
+    phrase.add_note(Pitch::new(PitchName::G, 4), quarter, None);
+    staff
+      .add_note(Pitch::new(PitchName::C, 5), quarter, None)
+      .add_modification(NoteModificationType::Portamento {
+        from_current: false,
+        going_up: true,
+      });
+
+    let notes = composition.realize(&RealizationOptions::default());
+    let midi_numbers: Vec<_> = notes.iter().map(RealizedNote::midi_number).collect();
+    assert_eq!(midi_numbers, [60, 61, 62, 63, 64, 65, 66, 67, 72]);
+    assert_eq!(
+      (notes[0].start_beat, notes[0].end_beat),
+      (Rational::ZERO, Rational::new(1, 2))
+    );
+    assert_eq!(notes[1].start_beat, Rational::new(1, 2));
+    assert_eq!(notes[6].end_beat, Rational::ONE);
+    assert!(notes[6].ornamental && !notes[7].ornamental);
+    let glide = notes[8].glide.unwrap();
+    assert_eq!((glide.start_cents, glide.end_cents), (-500, 0));
+    assert_eq!(notes[8].cents_offset_at(Rational::new(9, 4)), -250.0);
+    assert_eq!(notes[8].midi_pitch_bend_at(Rational::from(3_u8), 2), 8192);
+    assert_eq!(notes[8].midi_pitch_bend_at(Rational::new(9, 4), 12), 6485);
+
+    let mut options = RealizationOptions::default();
+    (options.glissando_mode, options.portamento_mode) = (GlideMode::Diatonic, GlideMode::Diatonic);
+    let notes = composition.realize(&options);
+    let midi_numbers: Vec<_> = notes.iter().map(RealizedNote::midi_number).collect();
+    assert_eq!(midi_numbers, [60, 62, 64, 65, 67, 67, 69, 71, 72]);
+    assert_eq!(notes[5].start_beat, Rational::from(2_u8));
+    assert_eq!(notes[8].start_beat, Rational::new(5, 2));
+
+    let mut composition = Composition::new("Carried Glide", Some(Tempo::new(quarter, 60)), None, None);
+    let staff = composition
+      .add_part("Melody")
+      .add_section("Section")
+      .add_staff("Treble");
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, Some(Accidental::Sharp));
+    let phrase = staff.add_phrase();
+    phrase.add_modification(PhraseModificationType::Glissando);
+    phrase.add_note(Pitch::new(PitchName::C, 4), quarter, None);
+    phrase.add_note(Pitch::new(PitchName::F, 4), quarter, None);
+    let notes = composition.realize(&RealizationOptions::default());
+    let midi_numbers: Vec<_> = notes.iter().map(RealizedNote::midi_number).collect();
+    assert_eq!(midi_numbers, [66, 60, 61, 62, 63, 64, 65, 66]);
+  }
+}
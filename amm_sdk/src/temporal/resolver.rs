@@ -0,0 +1,312 @@
+use super::Timeslice;
+use crate::context::{Key, TimeSignature, TimeSignatureType};
+use crate::modification::{DirectionType, NoteModificationType};
+use crate::note::{Accidental, Duration, DurationType, Note, Pitch, Rational};
+use alloc::collections::{BTreeMap, BTreeSet};
+
+/// Iterator adapter which resolves the sounding accidental of every note in a
+/// sequence of timeslices, storing the result in
+/// [`TimesliceContent::resolved_accidental`](super::TimesliceContent::resolved_accidental).
+///
+/// A note without an explicit accidental sounds with the most recent accidental written
+/// on the same pitch (name and octave) earlier in the same measure, or with the accidental
+/// from the current key signature if there is none. Accidentals stop carrying over at
+/// every barline and key signature change, except on a note which is tied across the
+/// barline from a note of the same pitch. Barlines are placed according to the current
+/// time signature, starting from the first timeslice; with no time signature
+/// ([`TimeSignatureType::None`]), accidentals carry over until the next key or time
+/// signature change.
+pub struct AccidentalResolver<I: Iterator<Item = Timeslice>> {
+  timeslices: I,
+  key: Key,
+  measure_length: Option<Rational>,
+  measure_position: Rational,
+  measure_accidentals: BTreeMap<Pitch, Accidental>,
+  tied_accidentals: BTreeMap<Pitch, Accidental>,
+}
+
+impl<I: Iterator<Item = Timeslice>> AccidentalResolver<I> {
+  /// Creates a new accidental resolver over the given timeslices, which start in
+  /// the specified key and time signature.
+  #[must_use]
+  pub fn new(timeslices: I, starting_key: Key, starting_time_signature: TimeSignature) -> Self {
+    Self {
+      timeslices,
+      key: starting_key,
+      measure_length: measure_length(starting_time_signature),
+      measure_position: Rational::ZERO,
+      measure_accidentals: BTreeMap::new(),
+      tied_accidentals: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the key signature in effect as of the most recently resolved timeslice.
+  #[must_use]
+  pub const fn current_key(&self) -> Key {
+    self.key
+  }
+
+  fn start_new_measure(&mut self) {
+    self.measure_accidentals.clear();
+  }
+
+  /// Applies any key or time signature changes in the timeslice, which always start a new measure.
+  fn apply_directions(&mut self, timeslice: &Timeslice) {
+    for direction in &timeslice.directions {
+      match direction.r#type {
+        DirectionType::KeyChange { key } => {
+          self.key = key;
+          self.measure_position = Rational::ZERO;
+          self.start_new_measure();
+        }
+        DirectionType::TimeSignatureChange { time_signature } => {
+          self.measure_length = measure_length(time_signature);
+          self.measure_position = Rational::ZERO;
+          self.start_new_measure();
+        }
+        _ => (),
+      }
+    }
+  }
+
+  /// Returns the accidental with which a note on the given pitch would sound if
+  /// it were written without an explicit accidental.
+  pub(crate) fn implied_accidental(&self, pitch: Pitch) -> Accidental {
+    self
+      .tied_accidentals
+      .get(&pitch)
+      .or_else(|| self.measure_accidentals.get(&pitch))
+      .copied()
+      .unwrap_or(self.key.accidentals()[pitch.name.index()])
+  }
+
+  /// Returns the sounding accidental of the given note, recording any accidental it
+  /// writes or carries across a tie.
+  fn resolve_note(&mut self, note: &Note, tied_accidentals: &mut BTreeMap<Pitch, Accidental>) -> Accidental {
+    let accidental = if note.accidental == Accidental::None {
+      self.implied_accidental(note.pitch)
+    } else {
+      self.measure_accidentals.insert(note.pitch, note.accidental);
+      note.accidental
+    };
+    if note
+      .iter_modifications()
+      .any(|modification| modification.r#type == NoteModificationType::Tie)
+    {
+      tied_accidentals.insert(note.pitch, accidental);
+    }
+    accidental
+  }
+
+  /// Advances the position within the current measure past the timeslice, starting
+  /// a new measure when necessary.
+  fn finish_timeslice(&mut self, timeslice: &Timeslice, tied_accidentals: BTreeMap<Pitch, Accidental>) {
+    if !timeslice.content.is_empty() {
+      self.tied_accidentals = tied_accidentals;
+    }
+    if let Some(measure_length) = self.measure_length {
+      self.measure_position += timeslice.get_beats(&Duration::new(DurationType::Whole, 0));
+      if self.measure_position >= measure_length {
+        self.measure_position = ((self.measure_position - measure_length) / measure_length).fract() * measure_length;
+        self.start_new_measure();
+      }
+    }
+  }
+}
+
+impl<I: Iterator<Item = Timeslice>> Iterator for AccidentalResolver<I> {
+  type Item = Timeslice;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut timeslice = self.timeslices.next()?;
+    self.apply_directions(&timeslice);
+    let mut tied_accidentals = BTreeMap::new();
+    for content in timeslice
+      .content
+      .iter_mut()
+      .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+    {
+      content.resolved_accidental = Some(self.resolve_note(&content.note, &mut tied_accidentals));
+    }
+    self.finish_timeslice(&timeslice, tied_accidentals);
+    Some(timeslice)
+  }
+}
+
+impl<I: core::iter::FusedIterator<Item = Timeslice>> core::iter::FusedIterator for AccidentalResolver<I> {}
+
+/// Returns the length of a measure in whole notes for the given time signature, if any.
+fn measure_length(time_signature: TimeSignature) -> Option<Rational> {
+  match time_signature.signature {
+    TimeSignatureType::CommonTime | TimeSignatureType::CutTime => Some(Rational::ONE),
+    TimeSignatureType::Explicit if time_signature.numerator > 0 && time_signature.denominator > 0 => {
+      Some(Rational::new(
+        i64::from(time_signature.numerator),
+        i64::from(time_signature.denominator),
+      ))
+    }
+    _ => None,
+  }
+}
+
+/// Returns explicit written accidentals for every pitched note in the given timeslices
+/// whose sounding accidental is carried over from an earlier note rather than implied by
+/// the key signature, along with the identifiers of all notes written without an accidental.
+///
+/// Once these accidentals are applied, every note sounds the same when interpreted against
+/// its key signature alone, so that notes can be transposed or respelled individually.
+pub(crate) fn make_accidentals_explicit(
+  timeslices: impl Iterator<Item = Timeslice>,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+) -> (BTreeMap<usize, (Pitch, Accidental)>, BTreeSet<usize>) {
+  let mut accidentals = BTreeMap::new();
+  let mut implicit_note_ids = BTreeSet::new();
+  let mut resolver = AccidentalResolver::new(timeslices, starting_key, starting_time_signature);
+  while let Some(timeslice) = resolver.next() {
+    let key_accidentals = resolver.current_key().accidentals();
+    for content in timeslice
+      .content
+      .iter()
+      .filter(|content| content.note.accidental == Accidental::None)
+    {
+      if let Some(resolved) = content.resolved_accidental {
+        let note = &content.note;
+        if implicit_note_ids.insert(note.note_id)
+          && resolved.normalized() != key_accidentals[note.pitch.name.index()].normalized()
+        {
+          let accidental = if resolved == Accidental::None {
+            Accidental::Natural
+          } else {
+            resolved
+          };
+          accidentals.insert(note.note_id, (note.pitch, accidental));
+        }
+      }
+    }
+  }
+  (accidentals, implicit_note_ids)
+}
+
+/// Returns the written pitch and accidental of every pitched note in the given timeslices,
+/// whose notes are interpreted against their key signature alone, omitting any accidental
+/// which is implied by the key signature or carried over from an earlier note in the same
+/// measure.
+///
+/// Accidentals are only omitted from notes which are written without one or whose identifiers
+/// are contained in `implicit_note_ids`, so that cautionary accidentals are retained.
+///
+/// Until the first key signature change, notes are read against `written_key` but spelled
+/// against `starting_key`, which allows the key signature to be replaced (e.g., when changing
+/// the mode of a passage) without changing how its notes sound.
+pub(crate) fn omit_implied_accidentals(
+  timeslices: impl Iterator<Item = Timeslice>,
+  written_key: Key,
+  starting_key: Key,
+  starting_time_signature: TimeSignature,
+  implicit_note_ids: &BTreeSet<usize>,
+) -> BTreeMap<usize, (Pitch, Accidental)> {
+  let mut spellings = BTreeMap::new();
+  let mut resolver = AccidentalResolver::new(core::iter::empty(), starting_key, starting_time_signature);
+  let mut written_key = Some(written_key);
+  for mut timeslice in timeslices {
+    resolver.apply_directions(&timeslice);
+    if timeslice
+      .directions
+      .iter()
+      .any(|direction| matches!(direction.r#type, DirectionType::KeyChange { .. }))
+    {
+      written_key = None;
+    }
+    let key_accidentals = written_key.unwrap_or(resolver.key).accidentals();
+    let mut tied_accidentals = BTreeMap::new();
+    for content in timeslice
+      .content
+      .iter_mut()
+      .filter(|content| !content.note.is_rest() && !content.note.is_unpitched())
+    {
+      let note = &mut content.note;
+      let (_, accidental) = *spellings.entry(note.note_id).or_insert_with(|| {
+        let sounding = note.sounding_accidental(key_accidentals);
+        let accidental = if resolver.implied_accidental(note.pitch).normalized() == sounding.normalized()
+          && (note.accidental == Accidental::None || implicit_note_ids.contains(&note.note_id))
+        {
+          Accidental::None
+        } else if note.accidental != Accidental::None {
+          note.accidental
+        } else if sounding == Accidental::None {
+          Accidental::Natural
+        } else {
+          sounding
+        };
+        (note.pitch, accidental)
+      });
+      note.accidental = accidental;
+      resolver.resolve_note(note, &mut tied_accidentals);
+    }
+    resolver.finish_timeslice(&timeslice, tied_accidentals);
+  }
+  spellings
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::KeyMode;
+  use crate::context::KeySignature;
+  use crate::note::PitchName;
+  use crate::structure::Staff;
+
+  fn resolved_midi_numbers(staff: &Staff, key: Key, time_signature: TimeSignature) -> Vec<u8> {
+    AccidentalResolver::new(staff.iter_timeslices(), key, time_signature)
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| content.midi_number(Some(key)))
+      .collect()
+  }
+
+  #[test]
+  fn test_measure_carry_over() {
+    let quarter = Duration::new(DurationType::Quarter, 0);
+    let mut staff = Staff::new("Treble");
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, Some(Accidental::Sharp));
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, None);
+    staff.add_note(Pitch::new(PitchName::F, 5), quarter, None);
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, Some(Accidental::Natural));
+    staff.add_note(Pitch::new(PitchName::F, 4), quarter, None);
+    let time_signature = TimeSignature::new(TimeSignatureType::CommonTime);
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    assert_eq!(resolved_midi_numbers(&staff, key, time_signature), [66, 66, 77, 65, 65]);
+
+    let mut staff = Staff::new("Treble");
+    staff.add_note(Pitch::new(PitchName::B, 4), quarter, Some(Accidental::Natural));
+    staff.add_note(Pitch::new(PitchName::B, 4), quarter, None);
+    staff.add_note(Pitch::new(PitchName::B, 4), quarter, None);
+    let key = Key::new(KeySignature::F, KeyMode::Major);
+    let time_signature = TimeSignature::new_explicit(2, 4);
+    assert_eq!(resolved_midi_numbers(&staff, key, time_signature), [71, 71, 70]);
+  }
+
+  #[test]
+  fn test_ties_and_key_changes() {
+    let half = Duration::new(DurationType::Half, 0);
+    let mut staff = Staff::new("Treble");
+    staff.add_note(Pitch::new(PitchName::C, 5), half, None);
+    staff
+      .add_note(Pitch::new(PitchName::G, 4), half, Some(Accidental::Sharp))
+      .add_modification(NoteModificationType::Tie);
+    staff.add_note(Pitch::new(PitchName::G, 4), half, None);
+    staff.add_note(Pitch::new(PitchName::G, 4), half, None);
+    staff.add_direction(DirectionType::KeyChange {
+      key: Key::new(KeySignature::D, KeyMode::Major),
+    });
+    staff.add_note(Pitch::new(PitchName::C, 5), half, None);
+    let key = Key::new(KeySignature::C, KeyMode::Major);
+    let time_signature = TimeSignature::new(TimeSignatureType::CommonTime);
+    assert_eq!(resolved_midi_numbers(&staff, key, time_signature), [72, 68, 68, 67, 73]);
+    let resolved: Vec<_> = AccidentalResolver::new(staff.iter_timeslices(), key, time_signature)
+      .flat_map(|timeslice| timeslice.content)
+      .map(|content| content.resolved_accidental)
+      .collect();
+    assert_eq!(resolved[4], Some(Accidental::Sharp));
+  }
+}
@@ -0,0 +1,477 @@
+use super::Timeslice;
+use crate::context::Tempo;
+use crate::modification::SectionModificationType;
+use crate::note::{Duration, DurationType, Rational};
+use alloc::vec::Vec;
+
+const MAX_RATIO_DENOMINATOR: i64 = 1000;
+
+/// Represents the shape of a gradual tempo change over time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TempoCurveShape {
+  /// The tempo changes by the same number of beats per minute on every beat.
+  #[default]
+  Linear,
+  /// The tempo changes by the same ratio on every beat.
+  Exponential,
+}
+
+/// Options which control how gradual tempo changes are realized in a [`TempoMap`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoCurveOptions {
+  /// The shape of every gradual tempo change.
+  pub shape: TempoCurveShape,
+  /// The ratio between the final and initial tempo of an accelerando or stringendo
+  /// which is not followed by an explicit tempo.
+  pub accelerando_ratio: f64,
+  /// The ratio between the final and initial tempo of a ritardando or rallentando
+  /// which is not followed by an explicit tempo, also used as the immediate tempo
+  /// reduction of a ritenuto.
+  pub ritardando_ratio: f64,
+}
+
+impl Default for TempoCurveOptions {
+  fn default() -> Self {
+    Self {
+      shape: TempoCurveShape::Linear,
+      accelerando_ratio: 1.25,
+      ritardando_ratio: 0.75,
+    }
+  }
+}
+
+/// Represents a span of beats over which the tempo is either constant or changes
+/// smoothly from a starting to a final tempo.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoSegment {
+  /// The beat at which the segment starts.
+  pub start_beat: Rational,
+  /// The beat at which the segment ends.
+  pub end_beat: Rational,
+  /// The tempo in beats per minute at the start of the segment.
+  pub start_bpm: Rational,
+  /// The tempo in beats per minute at the end of the segment.
+  pub end_bpm: Rational,
+  /// The shape of the tempo change between the start and end of the segment.
+  pub shape: TempoCurveShape,
+}
+
+impl TempoSegment {
+  fn is_constant(&self) -> bool {
+    self.end_bpm == self.start_bpm || self.end_beat <= self.start_beat
+  }
+
+  /// Returns the tempo in beats per minute at the given beat within the segment.
+  #[must_use]
+  pub fn bpm_at(&self, beat: Rational) -> f64 {
+    if self.is_constant() {
+      self.start_bpm.to_f64()
+    } else {
+      let progress =
+        ((beat - self.start_beat) / (self.end_beat - self.start_beat)).clamp(Rational::ZERO, Rational::ONE);
+      match self.shape {
+        TempoCurveShape::Linear => (self.start_bpm + (self.end_bpm - self.start_bpm) * progress).to_f64(),
+        TempoCurveShape::Exponential => {
+          self.start_bpm.to_f64() * (self.end_bpm / self.start_bpm).to_f64().powf(progress.to_f64())
+        }
+      }
+    }
+  }
+
+  /// Returns the number of seconds between the start of the segment and the given
+  /// beat, which may lie beyond the end of the segment for constant tempos.
+  #[must_use]
+  pub fn seconds_until(&self, beat: Rational) -> f64 {
+    let (beats, length) = (beat - self.start_beat, self.end_beat - self.start_beat);
+    if self.is_constant() {
+      (beats * Rational::from(60_u8) / self.start_bpm).to_f64()
+    } else {
+      let (start_bpm, length) = (self.start_bpm.to_f64(), length.to_f64());
+      match self.shape {
+        TempoCurveShape::Linear => {
+          60.0 * length / (self.end_bpm - self.start_bpm).to_f64() * (self.bpm_at(beat) / start_bpm).ln()
+        }
+        TempoCurveShape::Exponential => {
+          let rate = (self.end_bpm / self.start_bpm).to_f64().ln();
+          60.0 * length / (start_bpm * rate) * (1.0 - (-rate * beats.to_f64() / length).exp())
+        }
+      }
+    }
+  }
+
+  /// Returns the beat reached the given number of seconds after the start of the segment.
+  ///
+  /// Since the time in seconds is not exact, neither is the returned beat.
+  #[must_use]
+  pub fn beat_after(&self, seconds: f64) -> f64 {
+    let (start_bpm, end_bpm) = (self.start_bpm.to_f64(), self.end_bpm.to_f64());
+    let length = (self.end_beat - self.start_beat).to_f64();
+    let beats = if self.is_constant() {
+      seconds * start_bpm / 60.0
+    } else {
+      match self.shape {
+        TempoCurveShape::Linear => {
+          let bpm = start_bpm * (seconds / 60.0 * (end_bpm - start_bpm) / length).exp();
+          (bpm - start_bpm) * length / (end_bpm - start_bpm)
+        }
+        TempoCurveShape::Exponential => {
+          let rate = (end_bpm / start_bpm).ln();
+          let remaining = 1.0 - seconds / 60.0 * start_bpm * rate / length;
+          if remaining > 0.0 {
+            -length / rate * remaining.ln()
+          } else {
+            length
+          }
+        }
+      }
+    };
+    self.start_beat.to_f64() + beats.clamp(0.0, length)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TempoChange {
+  Accelerate,
+  Decelerate,
+  Hold,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct TempoSpan {
+  start_beat: Rational,
+  end_beat: Rational,
+  explicit_bpm: Option<Rational>,
+  change: Option<TempoChange>,
+}
+
+/// Maps beats to performance time in seconds, taking into account every explicit
+/// tempo change as well as gradual tempo changes such as accelerandos and ritardandos.
+///
+/// An explicit tempo remains in effect until the next explicit tempo. A gradual tempo
+/// change spans every consecutive timeslice that it is applied to and ends on the
+/// explicit tempo which immediately follows it, or on the current tempo multiplied by
+/// the corresponding ratio in [`TempoCurveOptions`] if there is none, after which the
+/// current tempo resumes. A ritenuto immediately reduces the tempo by the ritardando
+/// ratio for its duration.
+///
+/// All beats are measured in units of the base note of the tempo used to create the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+  base_note: Duration,
+  initial_bpm: Rational,
+  segments: Vec<TempoSegment>,
+}
+
+impl TempoMap {
+  /// Creates a new tempo map with a constant tempo.
+  #[must_use]
+  pub fn new(tempo: &Tempo) -> Self {
+    Self {
+      base_note: tempo.base_note,
+      initial_bpm: Rational::from(tempo.beats_per_minute),
+      segments: Vec::new(),
+    }
+  }
+
+  /// Creates a new tempo map from the tempo details of a sequence of timeslices,
+  /// which start at the given tempo.
+  #[must_use]
+  pub fn from_timeslices(
+    timeslices: impl Iterator<Item = Timeslice>,
+    tempo: &Tempo,
+    options: &TempoCurveOptions,
+  ) -> Self {
+    Self::from_parallel_timeslices([timeslices], tempo, options)
+  }
+
+  /// Creates a new tempo map from the tempo details of several sequences of
+  /// timeslices which are played simultaneously, such as the parts of a composition,
+  /// and which all start at the given tempo.
+  ///
+  /// If the sequences disagree on the tempo at a given beat, the earliest sequence
+  /// takes precedence.
+  #[must_use]
+  pub fn from_parallel_timeslices<I: Iterator<Item = Timeslice>>(
+    sequences: impl IntoIterator<Item = I>,
+    tempo: &Tempo,
+    options: &TempoCurveOptions,
+  ) -> Self {
+    let mut tempo_map = Self::new(tempo);
+    let spans: Vec<Vec<TempoSpan>> = sequences
+      .into_iter()
+      .map(|timeslices| tempo_map.collect_spans(timeslices))
+      .collect();
+    tempo_map.build_segments(&merge_spans(&spans), options);
+    tempo_map
+  }
+
+  fn bpm_in_base_note(&self, tempo: Tempo) -> Rational {
+    Rational::from(tempo.beats_per_minute) * tempo.base_note.value() / self.base_note.value()
+  }
+
+  fn collect_spans(&self, timeslices: impl Iterator<Item = Timeslice>) -> Vec<TempoSpan> {
+    let (mut spans, mut current_beat) = (Vec::new(), Rational::ZERO);
+    for timeslice in timeslices {
+      let beats = timeslice.get_beats(&self.base_note);
+      if beats.is_positive() {
+        let (mut explicit_bpm, mut change) = (None, None);
+        for details in &timeslice.tempo_details {
+          match details {
+            SectionModificationType::TempoExplicit { tempo } => explicit_bpm = Some(self.bpm_in_base_note(*tempo)),
+            SectionModificationType::TempoImplicit { tempo } => {
+              let tempo = Tempo::new(Duration::new(DurationType::Quarter, 0), tempo.value());
+              explicit_bpm = explicit_bpm.or(Some(self.bpm_in_base_note(tempo)));
+            }
+            SectionModificationType::Accelerando | SectionModificationType::Stringendo => {
+              change = Some(TempoChange::Accelerate);
+            }
+            SectionModificationType::Rallentando | SectionModificationType::Ritardando => {
+              change = Some(TempoChange::Decelerate);
+            }
+            SectionModificationType::Ritenuto => change = change.or(Some(TempoChange::Hold)),
+            _ => (),
+          }
+        }
+        spans.push(TempoSpan {
+          start_beat: current_beat,
+          end_beat: current_beat + beats,
+          explicit_bpm,
+          change,
+        });
+      }
+      current_beat += beats;
+    }
+    spans
+  }
+
+  fn build_segments(&mut self, spans: &[TempoSpan], options: &TempoCurveOptions) {
+    let accelerando_ratio = Rational::from_f64(options.accelerando_ratio, MAX_RATIO_DENOMINATOR);
+    let ritardando_ratio = Rational::from_f64(options.ritardando_ratio, MAX_RATIO_DENOMINATOR);
+    let mut current_bpm = self.initial_bpm;
+    for (index, span) in spans.iter().enumerate() {
+      current_bpm = span.explicit_bpm.unwrap_or(current_bpm);
+      let (start_bpm, end_bpm, shape) = match span.change {
+        None => (current_bpm, current_bpm, TempoCurveShape::Linear),
+        Some(TempoChange::Hold) => {
+          let bpm = current_bpm * ritardando_ratio;
+          (bpm, bpm, TempoCurveShape::Linear)
+        }
+        Some(change) => {
+          let ratio = if change == TempoChange::Accelerate {
+            accelerando_ratio
+          } else {
+            ritardando_ratio
+          };
+          let target_bpm = spans
+            .get(index + 1)
+            .filter(|next| next.start_beat == span.end_beat)
+            .and_then(|next| next.explicit_bpm)
+            .filter(|bpm| *bpm != current_bpm)
+            .unwrap_or(current_bpm * ratio);
+          (current_bpm, target_bpm, options.shape)
+        }
+      };
+      match self.segments.last_mut() {
+        Some(last)
+          if span.change.is_none()
+            && last.is_constant()
+            && last.start_bpm == start_bpm
+            && last.end_beat == span.start_beat =>
+        {
+          last.end_beat = span.end_beat;
+        }
+        _ => self.segments.push(TempoSegment {
+          start_beat: span.start_beat,
+          end_beat: span.end_beat,
+          start_bpm,
+          end_bpm,
+          shape,
+        }),
+      }
+    }
+  }
+
+  /// Returns the base note which represents a single beat in the tempo map.
+  #[must_use]
+  pub const fn base_note(&self) -> Duration {
+    self.base_note
+  }
+
+  /// Returns an iterator over the tempo segments in the map, in order.
+  pub fn iter_segments(&self) -> core::slice::Iter<'_, TempoSegment> {
+    self.segments.iter()
+  }
+
+  /// Returns the beat at which the final tempo segment ends.
+  #[must_use]
+  pub fn get_end_beat(&self) -> Rational {
+    self.segments.last().map_or(Rational::ZERO, |segment| segment.end_beat)
+  }
+
+  /// Returns the total duration of all tempo segments in seconds.
+  #[must_use]
+  pub fn get_duration(&self) -> f64 {
+    self.time_at_beat(self.get_end_beat())
+  }
+
+  /// Returns the tempo in beats per minute at the given beat.
+  ///
+  /// Beats past the end of the map continue at its final tempo.
+  #[must_use]
+  pub fn bpm_at_beat(&self, beat: Rational) -> f64 {
+    self
+      .segments
+      .iter()
+      .find(|segment| beat < segment.end_beat)
+      .or(self.segments.last())
+      .map_or(self.initial_bpm.to_f64(), |segment| segment.bpm_at(beat))
+  }
+
+  /// Returns the time in seconds at which the given beat is played.
+  ///
+  /// Beats past the end of the map continue at its final tempo.
+  #[must_use]
+  pub fn time_at_beat(&self, beat: Rational) -> f64 {
+    let beat = beat.max(Rational::ZERO);
+    let mut seconds = 0.0;
+    for segment in &self.segments {
+      if beat <= segment.end_beat {
+        return seconds + segment.seconds_until(beat);
+      }
+      seconds += segment.seconds_until(segment.end_beat);
+    }
+    let final_bpm = self.segments.last().map_or(self.initial_bpm, |segment| segment.end_bpm);
+    seconds + ((beat - self.get_end_beat()) * Rational::from(60_u8) / final_bpm).to_f64()
+  }
+
+  /// Returns the beat which is played at the given time in seconds.
+  ///
+  /// Times past the end of the map continue at its final tempo. Since the time in
+  /// seconds is not exact, neither is the returned beat.
+  #[must_use]
+  pub fn beat_at_time(&self, seconds: f64) -> f64 {
+    let mut remaining = seconds.max(0.0);
+    for segment in &self.segments {
+      let segment_seconds = segment.seconds_until(segment.end_beat);
+      if remaining <= segment_seconds {
+        return segment.beat_after(remaining);
+      }
+      remaining -= segment_seconds;
+    }
+    let final_bpm = self.segments.last().map_or(self.initial_bpm, |segment| segment.end_bpm);
+    self.get_end_beat().to_f64() + remaining * final_bpm.to_f64() / 60.0
+  }
+}
+
+fn merge_spans(sequences: &[Vec<TempoSpan>]) -> Vec<TempoSpan> {
+  // Split the timeline at every span boundary in any sequence, then combine the
+  // tempo details of all spans covering each resulting interval
+  let mut boundaries: Vec<Rational> = sequences
+    .iter()
+    .flatten()
+    .flat_map(|span| [span.start_beat, span.end_beat])
+    .collect();
+  boundaries.sort();
+  boundaries.dedup();
+  let (mut cursors, mut merged) = (vec![0; sequences.len()], Vec::<TempoSpan>::new());
+  for window in boundaries.windows(2) {
+    let (start_beat, end_beat) = (window[0], window[1]);
+    let (mut explicit_bpm, mut change) = (None, None);
+    for (spans, cursor) in sequences.iter().zip(cursors.iter_mut()) {
+      while spans.get(*cursor).is_some_and(|span| span.end_beat <= start_beat) {
+        *cursor += 1;
+      }
+      if let Some(span) = spans.get(*cursor).filter(|span| span.start_beat <= start_beat) {
+        explicit_bpm = explicit_bpm.or(span.explicit_bpm);
+        change = change.or(span.change);
+      }
+    }
+    match merged.last_mut() {
+      Some(last) if last.explicit_bpm == explicit_bpm && last.change == change => last.end_beat = end_beat,
+      _ => merged.push(TempoSpan {
+        start_beat,
+        end_beat,
+        explicit_bpm,
+        change,
+      }),
+    }
+  }
+  merged
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::note::{Pitch, PitchName};
+  use crate::structure::Section;
+
+  const EPSILON: f64 = 0.000_001;
+
+  fn add_section(parent: &mut Section, num_notes: usize, modification: Option<SectionModificationType>) {
+    let section = parent.add_section("Section");
+    if let Some(modification) = modification {
+      section.add_modification(modification);
+    }
+    let staff = section.add_staff("Staff");
+    for _ in 0..num_notes {
+      staff.add_note(
+        Pitch::new(PitchName::C, 4),
+        Duration::new(DurationType::Quarter, 0),
+        None,
+      );
+    }
+  }
+
+  #[test]
+  fn test_tempo_curves() {
+    let tempo = Tempo::new(Duration::new(DurationType::Quarter, 0), 60);
+    let mut section = Section::new("Ritardando");
+    add_section(&mut section, 2, None);
+    add_section(&mut section, 4, Some(SectionModificationType::Ritardando));
+    add_section(
+      &mut section,
+      2,
+      Some(SectionModificationType::TempoExplicit {
+        tempo: Tempo::new(Duration::new(DurationType::Half, 0), 15),
+      }),
+    );
+    let tempo_map = TempoMap::from_timeslices(section.iter_timeslices(), &tempo, &TempoCurveOptions::default());
+    assert_eq!(tempo_map.iter_segments().count(), 3);
+    assert!((tempo_map.bpm_at_beat(Rational::from(4_u8)) - 45.0).abs() < EPSILON);
+    let ritardando_seconds = 4.0 * 60.0 / -30.0 * 0.5f64.ln();
+    assert!((tempo_map.time_at_beat(Rational::from(6_u8)) - (2.0 + ritardando_seconds)).abs() < EPSILON);
+    assert!((tempo_map.get_duration() - (6.0 + ritardando_seconds)).abs() < EPSILON);
+    let mut start_beat = Rational::ZERO;
+    let timeslice_seconds: f64 = section
+      .iter_timeslices()
+      .map(|timeslice| {
+        let seconds = timeslice.get_duration_at(&tempo_map, start_beat);
+        start_beat += timeslice.get_beats(&tempo.base_note);
+        seconds
+      })
+      .sum();
+    assert!((timeslice_seconds - tempo_map.get_duration()).abs() < EPSILON);
+    for beat in [
+      Rational::new(1, 2),
+      Rational::from(3_u8),
+      Rational::new(11, 2),
+      Rational::from(7_u8),
+      Rational::from(9_u8),
+    ] {
+      assert!((tempo_map.beat_at_time(tempo_map.time_at_beat(beat)) - beat.to_f64()).abs() < EPSILON);
+    }
+
+    let options = TempoCurveOptions {
+      shape: TempoCurveShape::Exponential,
+      ..TempoCurveOptions::default()
+    };
+    let mut section = Section::new("Accelerando");
+    add_section(&mut section, 4, Some(SectionModificationType::Accelerando));
+    let tempo_map = TempoMap::from_timeslices(section.iter_timeslices(), &tempo, &options);
+    assert!((tempo_map.bpm_at_beat(Rational::from(4_u8)) - 75.0).abs() < EPSILON);
+    assert!((tempo_map.bpm_at_beat(Rational::from(2_u8)) - 60.0 * 1.25f64.sqrt()).abs() < EPSILON);
+    for beat in [Rational::ONE, Rational::new(5, 2), Rational::from(4_u8)] {
+      assert!((tempo_map.beat_at_time(tempo_map.time_at_beat(beat)) - beat.to_f64()).abs() < EPSILON);
+    }
+  }
+}
@@ -0,0 +1,390 @@
+use super::{TempoCurveOptions, TempoMap};
+use crate::modification::SectionModificationType;
+use crate::note::Rational;
+use crate::structure::{
+  ChordContent, MultiVoiceContent, Part, PartContent, Phrase, PhraseContent, Section, SectionContent, StaffContent,
+};
+use crate::Composition;
+use alloc::{
+  collections::{BTreeMap, BTreeSet},
+  vec::Vec,
+};
+use core::cmp::Ordering;
+
+const EPSILON: f64 = 0.000_001;
+const BUCKET_SECONDS: f64 = 1.0;
+
+/// Represents a single sounding note within a [`Timeline`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimelineEntry {
+  /// The beat at which the note starts, in units of the base note of the composition tempo.
+  pub start_beat: Rational,
+  /// The beat at which the note stops sounding.
+  pub end_beat: Rational,
+  /// The time in seconds at which the note starts.
+  pub start_seconds: f64,
+  /// The time in seconds at which the note stops sounding.
+  pub end_seconds: f64,
+  /// The timeslice identifier of the note, which is shared by every playback of the
+  /// same written note (see [`Note::note_id`](crate::note::Note::note_id)).
+  pub note_id: usize,
+  /// The unique identifier of the part containing the note.
+  pub part_id: usize,
+  /// The unique identifier of the staff containing the note.
+  pub staff_id: usize,
+}
+
+/// Precomputed index of every sounding note in a composition in playback order,
+/// supporting fast queries by performance time.
+///
+/// Entries are sorted by their starting time, so that all notes starting within a given
+/// window can be found with a binary search instead of walking the composition. Each
+/// entry is additionally listed in every fixed-length time bucket that it overlaps, so
+/// that the notes sounding at a given time can be found without scanning back over
+/// earlier entries. The index does not track changes to the composition automatically:
+/// after editing a part, call [`refresh_part`](Self::refresh_part) to bring the index
+/// up to date.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline {
+  options: TempoCurveOptions,
+  tempo_map: TempoMap,
+  part_tempos: BTreeMap<usize, PartTempo>,
+  entries: Vec<TimelineEntry>,
+  buckets: Vec<Vec<TimelineEntry>>,
+}
+
+/// The tempo directions within a single part, along with the beat at which it ends,
+/// which together determine the contribution of the part to the tempo map.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct PartTempo {
+  directions: Vec<(Rational, Rational, BTreeSet<SectionModificationType>)>,
+  end_beat: Rational,
+}
+
+impl Timeline {
+  /// Creates a new timeline containing every sounding note in the given composition,
+  /// with gradual tempo changes realized according to the specified options.
+  #[must_use]
+  pub fn new(composition: &Composition, options: &TempoCurveOptions) -> Self {
+    let mut timeline = Self {
+      options: *options,
+      tempo_map: composition.get_tempo_map(options),
+      part_tempos: BTreeMap::new(),
+      entries: Vec::new(),
+      buckets: Vec::new(),
+    };
+    timeline.index_composition(composition);
+    timeline
+  }
+
+  fn index_composition(&mut self, composition: &Composition) {
+    self.part_tempos.clear();
+    self.entries.clear();
+    self.buckets.clear();
+    let mut entries = Vec::new();
+    for part in composition {
+      let (part_entries, part_tempo) = self.index_part(part);
+      entries.extend(part_entries);
+      self.part_tempos.insert(part.get_id(), part_tempo);
+    }
+    self.insert_entries(entries);
+  }
+
+  fn index_part(&self, part: &Part) -> (Vec<TimelineEntry>, PartTempo) {
+    let mut staff_ids = BTreeMap::new();
+    part
+      .iter()
+      .for_each(|PartContent::Section(section)| index_section_staves(section, &mut staff_ids));
+    let beat_base = self.tempo_map.base_note();
+    let (mut entries, mut part_tempo, mut current_beat) = (Vec::new(), PartTempo::default(), Rational::ZERO);
+    for timeslice in part.iter_timeslices() {
+      let beats = timeslice.get_beats(&beat_base);
+      for content in timeslice.content.iter().filter(|content| !content.note.is_rest()) {
+        let end_beat = current_beat + content.get_beats(&beat_base);
+        entries.push(TimelineEntry {
+          start_beat: current_beat,
+          end_beat,
+          start_seconds: self.tempo_map.time_at_beat(current_beat),
+          end_seconds: self.tempo_map.time_at_beat(end_beat),
+          note_id: content.note.note_id,
+          part_id: part.get_id(),
+          staff_id: staff_ids.get(&content.note.note_id).copied().unwrap_or_default(),
+        });
+      }
+      if beats.is_positive() && !timeslice.tempo_details.is_empty() {
+        part_tempo
+          .directions
+          .push((current_beat, current_beat + beats, timeslice.tempo_details));
+      }
+      current_beat += beats;
+    }
+    part_tempo.end_beat = current_beat;
+    (entries, part_tempo)
+  }
+
+  /// Adds the given entries to the sorted entries and to every bucket they overlap.
+  fn insert_entries(&mut self, mut entries: Vec<TimelineEntry>) {
+    entries.sort_by(compare_entries);
+    for entry in &entries {
+      let buckets = bucket_range(entry);
+      if self.buckets.len() < buckets.end {
+        self.buckets.resize_with(buckets.end, Vec::new);
+      }
+      for bucket in &mut self.buckets[buckets] {
+        let index = bucket.partition_point(|other| compare_entries(other, entry).is_lt());
+        bucket.insert(index, *entry);
+      }
+    }
+    // Note: Both runs are already sorted, so the stable sort merges them in linear time
+    self.entries.extend(entries);
+    self.entries.sort_by(compare_entries);
+  }
+
+  /// Removes every entry belonging to the given part from the sorted entries and from
+  /// the buckets they overlap.
+  fn remove_entries(&mut self, part_id: usize) {
+    let mut buckets = BTreeSet::new();
+    self.entries.retain(|entry| {
+      if entry.part_id == part_id {
+        buckets.extend(bucket_range(entry));
+      }
+      entry.part_id != part_id
+    });
+    for index in buckets {
+      if let Some(bucket) = self.buckets.get_mut(index) {
+        bucket.retain(|entry| entry.part_id != part_id);
+      }
+    }
+    while self.buckets.last().is_some_and(Vec::is_empty) {
+      self.buckets.pop();
+    }
+  }
+
+  /// Re-indexes all notes in the part with the given identifier after it has been
+  /// edited, leaving the entries for all other parts untouched.
+  ///
+  /// If the part no longer exists in the composition, its entries are removed. The
+  /// existing tempo map is reused unless the edit changed the tempo directions in the
+  /// part or the length of the composition, in which case the tempo map is rebuilt and,
+  /// if it changed, every part is re-indexed, since the performance times of all notes
+  /// may have moved.
+  pub fn refresh_part(&mut self, composition: &Composition, part_id: usize) {
+    let part = composition.get_part(part_id);
+    let (entries, part_tempo) = part.map(|part| self.index_part(part)).unwrap_or_default();
+    let previous_end_beat = self.end_beat();
+    let previous_tempo = self.part_tempos.remove(&part_id).unwrap_or_default();
+    let mut tempo_changed = previous_tempo.directions != part_tempo.directions;
+    if part.is_some() {
+      self.part_tempos.insert(part_id, part_tempo);
+    }
+    tempo_changed |= self.end_beat() != previous_end_beat;
+    if tempo_changed {
+      let tempo_map = composition.get_tempo_map(&self.options);
+      if tempo_map != self.tempo_map {
+        self.tempo_map = tempo_map;
+        self.index_composition(composition);
+        return;
+      }
+    }
+    self.remove_entries(part_id);
+    self.insert_entries(entries);
+  }
+
+  fn end_beat(&self) -> Rational {
+    self
+      .part_tempos
+      .values()
+      .map(|part_tempo| part_tempo.end_beat)
+      .max()
+      .unwrap_or_default()
+  }
+
+  /// Returns the tempo map used to convert beats into seconds.
+  #[must_use]
+  pub const fn get_tempo_map(&self) -> &TempoMap {
+    &self.tempo_map
+  }
+
+  /// Returns the time in seconds at which the last note stops sounding.
+  #[must_use]
+  pub fn get_duration(&self) -> f64 {
+    self.entries.iter().map(|entry| entry.end_seconds).fold(0.0, f64::max)
+  }
+
+  /// Returns every note which is sounding at the given time in seconds.
+  pub fn sounding_at(&self, seconds: f64) -> impl Iterator<Item = &TimelineEntry> {
+    let bucket = if seconds < 0.0 {
+      None
+    } else {
+      self.buckets.get(bucket_index(seconds))
+    };
+    bucket
+      .into_iter()
+      .flatten()
+      .filter(move |entry| entry.start_seconds <= seconds + EPSILON && entry.end_seconds > seconds + EPSILON)
+  }
+
+  /// Returns every note which starts at or after `start_seconds` and before `end_seconds`.
+  #[must_use]
+  pub fn starting_between(&self, start_seconds: f64, end_seconds: f64) -> &[TimelineEntry] {
+    let start = self
+      .entries
+      .partition_point(|entry| entry.start_seconds < start_seconds - EPSILON);
+    let end = self
+      .entries
+      .partition_point(|entry| entry.start_seconds < end_seconds - EPSILON)
+      .max(start);
+    &self.entries[start..end]
+  }
+
+  /// Returns the number of entries in the timeline.
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns whether the timeline contains no entries.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns an iterator over all entries in the timeline, ordered by starting time.
+  pub fn iter(&self) -> core::slice::Iter<'_, TimelineEntry> {
+    self.entries.iter()
+  }
+}
+
+impl<'a> IntoIterator for &'a Timeline {
+  type Item = &'a TimelineEntry;
+  type IntoIter = core::slice::Iter<'a, TimelineEntry>;
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn bucket_index(seconds: f64) -> usize {
+  (seconds.max(0.0) / BUCKET_SECONDS) as usize
+}
+
+fn bucket_range(entry: &TimelineEntry) -> core::ops::Range<usize> {
+  bucket_index(entry.start_seconds - EPSILON)..bucket_index(entry.end_seconds + EPSILON) + 1
+}
+
+fn compare_entries(a: &TimelineEntry, b: &TimelineEntry) -> Ordering {
+  a.start_seconds
+    .total_cmp(&b.start_seconds)
+    .then_with(|| (a.part_id, a.staff_id, a.note_id).cmp(&(b.part_id, b.staff_id, b.note_id)))
+}
+
+fn index_section_staves(section: &Section, staff_ids: &mut BTreeMap<usize, usize>) {
+  section.iter().for_each(|content| match content {
+    SectionContent::Staff(staff) => staff.iter().for_each(|content| match content {
+      StaffContent::Note(note) => {
+        staff_ids.insert(note.note_id, staff.get_id());
+      }
+      StaffContent::Chord(chord) => chord.iter().for_each(|ChordContent::Note(note)| {
+        staff_ids.insert(note.note_id, staff.get_id());
+      }),
+      StaffContent::Phrase(phrase) => index_phrase_notes(phrase, staff.get_id(), staff_ids),
+      StaffContent::MultiVoice(multivoice) => multivoice
+        .iter()
+        .for_each(|MultiVoiceContent::Phrase(phrase)| index_phrase_notes(phrase, staff.get_id(), staff_ids)),
+      StaffContent::Direction(_) => (),
+    }),
+    SectionContent::Section(section) => index_section_staves(section, staff_ids),
+  });
+}
+
+fn index_phrase_notes(phrase: &Phrase, staff_id: usize, staff_ids: &mut BTreeMap<usize, usize>) {
+  phrase.iter().for_each(|content| match content {
+    PhraseContent::Note(note) => {
+      staff_ids.insert(note.note_id, staff_id);
+    }
+    PhraseContent::Chord(chord) => chord.iter().for_each(|ChordContent::Note(note)| {
+      staff_ids.insert(note.note_id, staff_id);
+    }),
+    PhraseContent::Phrase(phrase) => index_phrase_notes(phrase, staff_id, staff_ids),
+    PhraseContent::MultiVoice(multivoice) => multivoice
+      .iter()
+      .for_each(|MultiVoiceContent::Phrase(phrase)| index_phrase_notes(phrase, staff_id, staff_ids)),
+  });
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::context::Tempo;
+  use crate::modification::SectionModificationType;
+  use crate::note::{Duration, DurationType, Pitch, PitchName};
+
+  #[test]
+  fn test_timeline_queries() {
+    let (quarter, half) = (
+      Duration::new(DurationType::Quarter, 0),
+      Duration::new(DurationType::Half, 0),
+    );
+    let mut composition = Composition::new("Timeline", Some(Tempo::new(quarter, 60)), None, None);
+    let melody = composition.add_part("Melody");
+    let melody_id = melody.get_id();
+    let section = melody.add_section("Section");
+    let section_id = section.get_id();
+    let staff = section.add_staff("Treble");
+    let staff_id = staff.get_id();
+    staff.add_note(Pitch::new(PitchName::C, 5), quarter, None);
+    staff.add_note(Pitch::new(PitchName::D, 5), quarter, None);
+    let first_note_id = staff.add_note(Pitch::new(PitchName::E, 5), quarter, None).get_id();
+    let bass = composition.add_part("Bass");
+    let bass_id = bass.get_id();
+    let staff = bass.add_section("Section").add_staff("Bass");
+    staff.add_note(Pitch::new(PitchName::C, 3), half, None);
+    staff.add_note(Pitch::new(PitchName::G, 2), half, None);
+
+    let mut timeline = composition.get_timeline();
+    assert_eq!(timeline.len(), 5);
+    assert_eq!(timeline.get_duration(), 4.0);
+    let sounding: Vec<_> = timeline.sounding_at(1.5).collect();
+    assert_eq!(sounding.len(), 2);
+    assert!(sounding
+      .iter()
+      .any(|entry| entry.part_id == melody_id && entry.staff_id == staff_id));
+    assert_eq!(timeline.sounding_at(3.5).count(), 1);
+    assert_eq!(timeline.starting_between(0.0, 2.0).len(), 3);
+
+    composition
+      .get_part_mut(melody_id)
+      .and_then(|part| part.get_note_mut(first_note_id))
+      .unwrap()
+      .duration = half;
+    timeline.refresh_part(&composition, melody_id);
+    assert_eq!(timeline, composition.get_timeline());
+    assert_eq!(timeline.sounding_at(3.5).count(), 2);
+    assert_eq!(timeline.sounding_at(-1.0).count(), 0);
+    assert_eq!(timeline.sounding_at(4.0).count(), 0);
+
+    composition
+      .get_section_mut(section_id)
+      .unwrap()
+      .add_modification(SectionModificationType::TempoExplicit {
+        tempo: Tempo::new(quarter, 120),
+      });
+    timeline.refresh_part(&composition, melody_id);
+    assert_eq!(timeline, composition.get_timeline());
+    assert_eq!(timeline.get_duration(), 2.0);
+    assert_eq!(timeline.sounding_at(1.5).count(), 2);
+
+    composition
+      .get_staff_mut(staff_id)
+      .unwrap()
+      .add_note(Pitch::new(PitchName::F, 5), half, None);
+    timeline.refresh_part(&composition, melody_id);
+    assert_eq!(timeline, composition.get_timeline());
+    assert_eq!(timeline.get_duration(), 3.0);
+
+    composition.remove_item(bass_id);
+    timeline.refresh_part(&composition, bass_id);
+    assert_eq!(timeline, composition.get_timeline());
+    assert_eq!(timeline.len(), 4);
+  }
+}
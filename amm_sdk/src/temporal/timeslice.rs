@@ -1,6 +1,7 @@
+use super::TempoMap;
 use crate::context::{Key, Tempo, TimeSignature};
 use crate::modification::{Direction, PhraseModificationType, SectionModificationType};
-use crate::note::{Accidental, Duration, Note, Pitch};
+use crate::note::{Accidental, Duration, Note, Pitch, Rational, Tuning};
 use alloc::{collections::BTreeMap, vec::Vec};
 use amm_internal::amm_prelude::*;
 
@@ -43,6 +44,7 @@ impl core::fmt::Display for TimeslicePhraseDetails {
 pub struct TimesliceContent {
   pub note: Note,
   pub phrase_details: Vec<TimeslicePhraseDetails>,
+  pub resolved_accidental: Option<Accidental>,
 }
 
 impl TimesliceContent {
@@ -51,6 +53,7 @@ impl TimesliceContent {
     Self {
       note,
       phrase_details: Vec::new(),
+      resolved_accidental: None,
     }
   }
 
@@ -66,13 +69,50 @@ impl TimesliceContent {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration) -> f64 {
+  pub fn sounding_accidental(&self, key: Option<Key>) -> Accidental {
+    self
+      .resolved_accidental
+      .unwrap_or_else(|| self.note.sounding_accidental(key.unwrap_or_default().accidentals()))
+  }
+
+  #[must_use]
+  pub fn midi_number(&self, key: Option<Key>) -> u8 {
+    self.note.midi_number_with_accidental(self.sounding_accidental(key))
+  }
+
+  #[must_use]
+  pub fn pitch_hz(&self, key: Option<Key>, a4_frequency_hz: Option<f32>) -> f32 {
+    self
+      .note
+      .pitch_hz_with_accidental(self.sounding_accidental(key), a4_frequency_hz)
+  }
+
+  #[must_use]
+  pub fn tuned_pitch_hz(&self, key: Option<Key>, tuning: &Tuning) -> Option<f32> {
+    if self.note.is_rest() {
+      None
+    } else {
+      self
+        .note
+        .tuned_pitch_hz_with_accidental(self.sounding_accidental(key), tuning)
+    }
+  }
+
+  #[must_use]
+  pub fn midi_pitch_bend(&self, key: Option<Key>, bend_range_semitones: u8) -> u16 {
+    self
+      .note
+      .midi_pitch_bend_with_accidental(self.sounding_accidental(key), bend_range_semitones)
+  }
+
+  #[must_use]
+  pub fn get_beats(&self, beat_base: &Duration) -> Rational {
     self.note.get_beats(
       beat_base,
       self.phrase_details.iter().find_map(|detail| {
         detail.modifications.iter().find_map(|modification| match modification {
-          PhraseModificationType::Tuplet { num_beats, into_beats } => {
-            Some(f64::from(*into_beats) / f64::from(*num_beats))
+          PhraseModificationType::Tuplet { num_beats, into_beats } if *num_beats > 0 => {
+            Some(Rational::new(i64::from(*into_beats), i64::from(*num_beats)))
           }
           _ => None,
         })
@@ -80,9 +120,17 @@ impl TimesliceContent {
     )
   }
 
+  /// Returns the duration in seconds at a constant tempo, ignoring any tempo changes.
   #[must_use]
+  #[deprecated(note = "ignores tempo changes; use `get_duration_at` with a `TempoMap` instead")]
   pub fn get_duration(&self, tempo: &Tempo) -> f64 {
-    self.get_beats(&tempo.base_note) * 60.0 / f64::from(tempo.beats_per_minute)
+    self.get_beats(&tempo.base_note).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
+  }
+
+  #[must_use]
+  pub fn get_duration_at(&self, tempo_map: &TempoMap, start_beat: Rational) -> f64 {
+    let end_beat = start_beat + self.get_beats(&tempo_map.base_note());
+    tempo_map.time_at_beat(end_beat) - tempo_map.time_at_beat(start_beat)
   }
 
   #[must_use]
@@ -145,10 +193,12 @@ impl Timeslice {
   }
 
   pub fn add_tempo_details(&mut self, tempo_details: &SectionModificationType) -> &mut Self {
-    if !matches!(
-      tempo_details,
-      SectionModificationType::OnlyPlay { .. } | SectionModificationType::Repeat { .. }
-    ) {
+    if !tempo_details.is_navigation()
+      && !matches!(
+        tempo_details,
+        SectionModificationType::OnlyPlay { .. } | SectionModificationType::Repeat { .. }
+      )
+    {
       self.tempo_details.insert(tempo_details.clone());
     }
     self
@@ -163,7 +213,7 @@ impl Timeslice {
   }
 
   #[must_use]
-  pub fn get_beats(&self, beat_base: &Duration) -> f64 {
+  pub fn get_beats(&self, beat_base: &Duration) -> Rational {
     self
       .content
       .iter()
@@ -174,13 +224,21 @@ impl Timeslice {
           Some(element.get_beats(beat_base))
         }
       })
-      .reduce(f64::min)
+      .min()
       .unwrap_or_default()
   }
 
+  /// Returns the duration in seconds at a constant tempo, ignoring any tempo changes.
   #[must_use]
+  #[deprecated(note = "ignores tempo changes; use `get_duration_at` with a `TempoMap` instead")]
   pub fn get_duration(&self, tempo: &Tempo) -> f64 {
-    self.get_beats(&tempo.base_note) * 60.0 / f64::from(tempo.beats_per_minute)
+    self.get_beats(&tempo.base_note).to_f64() * 60.0 / f64::from(tempo.beats_per_minute)
+  }
+
+  #[must_use]
+  pub fn get_duration_at(&self, tempo_map: &TempoMap, start_beat: Rational) -> f64 {
+    let end_beat = start_beat + self.get_beats(&tempo_map.base_note());
+    tempo_map.time_at_beat(end_beat) - tempo_map.time_at_beat(start_beat)
   }
 }
 